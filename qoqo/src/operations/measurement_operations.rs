@@ -94,6 +94,23 @@ struct PragmaGetPauliProduct {
     circuit: Circuit,
 }
 
+#[wrap(Operate, OperatePragma)]
+/// This PRAGMA measurement operation returns the amplitudes of selected computational basis states.
+///
+/// Each bitstring selects one basis state, where entry i of the bitstring is the value of qubit i.
+/// The amplitudes are written into the complex readout register in the order of the bitstrings.
+///
+/// Args:
+///     readout (string): The name of the classical readout register.
+///     bitstrings (list[list[bool]]): The bitstrings of the basis states for which the amplitudes are returned.
+///     circuit (Optional[Circuit]): The measurement preparation Circuit, applied on a copy of the register before measurement.
+///
+struct PragmaGetAmplitudes {
+    readout: String,
+    bitstrings: Vec<Vec<bool>>,
+    circuit: Option<Circuit>,
+}
+
 #[wrap(Operate, OperatePragma)]
 /// This PRAGMA measurement operation returns a measurement record for N repeated measurements.
 ///
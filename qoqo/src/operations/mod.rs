@@ -71,6 +71,7 @@ use pyo3::prelude::*;
 ///    PragmaGetDensityMatrix
 ///    PragmaGetOccupationProbability
 ///    PragmaGetPauliProduct
+///    PragmaGetAmplitudes
 ///    PragmaRepeatedMeasurement
 ///    PragmaSetNumberOfMeasurements
 ///    PragmaSetStateVector
@@ -135,6 +136,7 @@ pub fn operations(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PragmaGetDensityMatrixWrapper>()?;
     m.add_class::<PragmaGetOccupationProbabilityWrapper>()?;
     m.add_class::<PragmaGetPauliProductWrapper>()?;
+    m.add_class::<PragmaGetAmplitudesWrapper>()?;
     m.add_class::<PragmaRepeatedMeasurementWrapper>()?;
     m.add_class::<PragmaSetNumberOfMeasurementsWrapper>()?;
     m.add_class::<PragmaSetStateVectorWrapper>()?;
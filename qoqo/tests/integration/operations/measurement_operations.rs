@@ -60,6 +60,7 @@ fn new_circuit(py: Python) -> &PyCell<CircuitWrapper> {
 /// Test readout() input/function
 #[test_case(Operation::from(MeasureQubit::new(0, String::from("ro"), 1)); "MeasureQubit")]
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())); "PragmaGetPauliProduct")]
@@ -95,6 +96,7 @@ fn test_pyo3_qubit_mapping(input_measurement: Operation, operation_name: &str) {
 
 /// Test circuit() input/function
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())); "PragmaGetPauliProduct")]
@@ -168,6 +170,7 @@ fn test_pyo3_input_pragmarepeatedmeasurements_input() {
 
 /// Test involved_qubits function for Pragmas with All
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaRepeatedMeasurement::new(String::from("ro"), 2, Some(create_qubit_mapping()))); "PragmaRepeatedMeasurement")]
@@ -201,6 +204,7 @@ fn test_pyo3_involved_qubits_0(input_definition: Operation) {
 /// Test format and repr functions
 #[test_case(Operation::from(MeasureQubit::new(0, String::from("ro"), 1)), "MeasureQubit { qubit: 0, readout: \"ro\", readout_index: 1 }"; "MeasureQubit")]
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), None)), "PragmaGetStateVector { readout: \"ro\", circuit: None }"; "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], None)), "PragmaGetAmplitudes { readout: \"ro\", bitstrings: [[true, false]], circuit: None }"; "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), None)), "PragmaGetDensityMatrix { readout: \"ro\", circuit: None }"; "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), None)), "PragmaGetOccupationProbability { readout: \"ro\", circuit: None }"; "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), Circuit::default())), "PragmaGetPauliProduct { qubit_paulis: {0: 1}, readout: \"ro\", circuit: Circuit { definitions: [], operations: [], _roqoqo_version: RoqoqoVersion } }"; "PragmaGetPauliProduct")]
@@ -221,6 +225,7 @@ fn test_pyo3_format_repr(input_measurement: Operation, format_repr: &str) {
 /// Test copy and deepcopy functions
 #[test_case(Operation::from(MeasureQubit::new(0, String::from("ro"), 1)); "MeasureQubit")]
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())); "PragmaGetPauliProduct")]
@@ -254,6 +259,7 @@ fn test_pyo3_copy_deepcopy(input_measurement: Operation) {
 
 /// Test tags function
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))), "PragmaGetStateVector"; "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))), "PragmaGetAmplitudes"; "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))), "PragmaGetDensityMatrix"; "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))), "PragmaGetOccupationProbability"; "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())), "PragmaGetPauliProduct"; "PragmaGetPauliProduct")]
@@ -290,6 +296,7 @@ fn test_pyo3_tags() {
 /// Test hqslang function
 #[test_case(Operation::from(MeasureQubit::new(0, String::from("ro"), 1)), String::from("MeasureQubit"); "MeasureQubit")]
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))), String::from("PragmaGetStateVector"); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))), String::from("PragmaGetAmplitudes"); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))), String::from("PragmaGetDensityMatrix"); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))), String::from("PragmaGetOccupationProbability"); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())), String::from("PragmaGetPauliProduct"); "PragmaGetPauliProduct")]
@@ -307,6 +314,7 @@ fn test_pyo3_hqslang(input_measurement: Operation, hqslang_param: String) {
 /// Test is_parametrized function
 #[test_case(Operation::from(MeasureQubit::new(0, String::from("ro"), 1)); "MeasureQubit")]
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())); "PragmaGetPauliProduct")]
@@ -328,6 +336,7 @@ fn test_pyo3_is_parametrized(input_measurement: Operation) {
 /// Test substitute_parameters function
 #[test_case(Operation::from(MeasureQubit::new(0, String::from("ro"), 0)); "MeasureQubit")]
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())); "PragmaGetPauliProduct")]
@@ -363,6 +372,7 @@ fn test_pyo3_substitute_parameters(input_measurement: Operation) {
 /// Test substitute_parameters() causing an error `not-a-real-number`
 #[test_case(Operation::from(MeasureQubit::new(0, String::from("ro"), 0)); "MeasureQubit")]
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))); "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))); "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))); "PragmaGetDensityMatrix")]
 #[test_case(Operation::from(PragmaGetOccupationProbability::new(String::from("ro"), Some(create_circuit()))); "PragmaGetOccupationProbability")]
 #[test_case(Operation::from(PragmaGetPauliProduct::new(create_qubit_mapping(), String::from("ro"), create_circuit())); "PragmaGetPauliProduct")]
@@ -386,6 +396,9 @@ fn test_pyo3_substitute_params_error(input_operation: Operation) {
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))),
             Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(circuit_remapped())));
             "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))),
+            Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![false, false, true]], Some(circuit_remapped())));
+            "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))),
             Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(circuit_remapped())));
             "PragmaGetDensityMatrix")]
@@ -446,6 +459,9 @@ fn test_pyo3_remapqubits_error(input_operation: Operation) {
 #[test_case(Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(create_circuit()))),
             Operation::from(PragmaGetStateVector::new(String::from("ro"), Some(circuit_remapped())));
             "PragmaGetStateVector")]
+#[test_case(Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], Some(create_circuit()))),
+            Operation::from(PragmaGetAmplitudes::new(String::from("ro"), vec![vec![false, false, true]], Some(circuit_remapped())));
+            "PragmaGetAmplitudes")]
 #[test_case(Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(create_circuit()))),
             Operation::from(PragmaGetDensityMatrix::new(String::from("ro"), Some(circuit_remapped())));
             "PragmaGetDensityMatrix")]
@@ -566,6 +582,51 @@ fn test_pyo3_new_get_statevector() {
     })
 }
 
+/// Test PragmaGetAmplitudes new() function
+#[test]
+fn test_pyo3_new_get_amplitudes() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let operation = py.get_type::<PragmaGetAmplitudesWrapper>();
+        let new_op = operation
+            .call1((
+                "ro".to_string(),
+                vec![vec![true, false]],
+                Option::<CircuitWrapper>::None,
+            ))
+            .unwrap()
+            .cast_as::<PyCell<PragmaGetAmplitudesWrapper>>()
+            .unwrap();
+
+        let input_definition = Operation::from(PragmaGetAmplitudes::new(
+            String::from("ro"),
+            vec![vec![true, false]],
+            None,
+        ));
+        let copy_param = convert_operation_to_pyobject(input_definition).unwrap();
+        let comparison_copy =
+            bool::extract(new_op.call_method1("__eq__", (copy_param,)).unwrap()).unwrap();
+        assert!(comparison_copy);
+
+        let meas_wrapper = new_op.extract::<PragmaGetAmplitudesWrapper>().unwrap();
+        let new_op_diff = operation
+            .call1(("ro2".to_string(), vec![vec![true]], new_circuit(py)))
+            .unwrap()
+            .cast_as::<PyCell<PragmaGetAmplitudesWrapper>>()
+            .unwrap();
+        let meas_wrapper_diff = new_op_diff.extract::<PragmaGetAmplitudesWrapper>().unwrap();
+        let helper_ne: bool = meas_wrapper_diff != meas_wrapper;
+        assert!(helper_ne);
+        let helper_eq: bool = meas_wrapper == meas_wrapper.clone();
+        assert!(helper_eq);
+
+        assert_eq!(
+            format!("{:?}", meas_wrapper),
+            "PragmaGetAmplitudesWrapper { internal: PragmaGetAmplitudes { readout: \"ro\", bitstrings: [[true, false]], circuit: None } }"
+        );
+    })
+}
+
 /// Test PragmaGetDensityMatrix new() function
 #[test]
 fn test_pyo3_new_get_density_matrix() {
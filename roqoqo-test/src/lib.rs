@@ -16,6 +16,15 @@
 //! operations should be tested.
 //! This crate provides helper functions that can be used to test the implementation
 //! of the interface of the
+//!
+//! The [MockBackend] records the circuits it receives and returns scripted output registers,
+//! to test code that runs circuits and measurements on roqoqo backends.
+//! The [MockHttpServer] provides the REST job interface of the roqoqo `HttpBackend` on localhost,
+//! to test HTTP clients without a real service.
 
+mod mock_backend;
+pub use mock_backend::MockBackend;
+mod mock_http_server;
+pub use mock_http_server::MockHttpServer;
 mod stochastic_gate_test;
 pub use stochastic_gate_test::*;
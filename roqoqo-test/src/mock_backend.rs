@@ -0,0 +1,181 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use roqoqo::backends::{EvaluatingBackend, RegisterResult};
+use roqoqo::operations::{Define, Operate, Operation};
+use roqoqo::registers::{BitOutputRegister, ComplexOutputRegister, FloatOutputRegister};
+use roqoqo::{Circuit, RoqoqoBackendError};
+
+/// Mock backend recording the received circuits and returning scripted output registers.
+///
+/// The backend does not simulate the circuits. Every call of
+/// [EvaluatingBackend::run_circuit_iterator] records the received operations and returns
+///
+/// 1. the result scripted for the number of the call with [MockBackend::add_call_result], if any,
+/// 2. an [RoqoqoBackendError::OperationNotInBackend] error if the circuit contains an operation
+///    marked as unsupported with [MockBackend::add_unsupported_operation],
+/// 3. otherwise the output registers scripted by readout name for every output register defined
+///    in the circuit. Output registers without scripted values are returned empty.
+///
+/// Clones of a MockBackend share the recorded circuits and the scripted results,
+/// so that a clone can be passed by value (e.g. to [roqoqo::QuantumProgram::run])
+/// and the recorded circuits can be inspected with the original afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+/// Shared state of a [MockBackend].
+#[derive(Debug, Default)]
+struct MockState {
+    recorded_circuits: Vec<Circuit>,
+    call_results: HashMap<usize, RegisterResult>,
+    bit_registers: HashMap<String, BitOutputRegister>,
+    float_registers: HashMap<String, FloatOutputRegister>,
+    complex_registers: HashMap<String, ComplexOutputRegister>,
+    unsupported_operations: HashSet<&'static str>,
+}
+
+impl MockBackend {
+    /// Creates a new MockBackend without scripted results.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the shared state, a panic in another thread does not invalidate the recording.
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the circuits received by the backend in the order of the calls.
+    pub fn recorded_circuits(&self) -> Vec<Circuit> {
+        self.state().recorded_circuits.clone()
+    }
+
+    /// Returns the number of circuits received by the backend.
+    pub fn number_of_calls(&self) -> usize {
+        self.state().recorded_circuits.len()
+    }
+
+    /// Removes all recorded circuits, the next call is counted as call 0 again.
+    pub fn clear_recorded_circuits(&self) {
+        self.state().recorded_circuits.clear();
+    }
+
+    /// Scripts the result of one call of the backend.
+    ///
+    /// The result is returned once, when the call with the given number (starting at 0) is made.
+    /// Scripting an `Err` simulates a failure of the backend with any [RoqoqoBackendError] variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `call` - The number of the call returning the result.
+    /// * `result` - The result returned by the call.
+    pub fn add_call_result(&self, call: usize, result: RegisterResult) {
+        self.state().call_results.insert(call, result);
+    }
+
+    /// Scripts the values of a bit output register returned whenever the register is defined as output.
+    ///
+    /// # Arguments
+    ///
+    /// * `readout` - The name of the register.
+    /// * `register` - The values of the register.
+    pub fn add_bit_register(&self, readout: &str, register: BitOutputRegister) {
+        self.state()
+            .bit_registers
+            .insert(readout.to_string(), register);
+    }
+
+    /// Scripts the values of a float output register returned whenever the register is defined as output.
+    ///
+    /// # Arguments
+    ///
+    /// * `readout` - The name of the register.
+    /// * `register` - The values of the register.
+    pub fn add_float_register(&self, readout: &str, register: FloatOutputRegister) {
+        self.state()
+            .float_registers
+            .insert(readout.to_string(), register);
+    }
+
+    /// Scripts the values of a complex output register returned whenever the register is defined as output.
+    ///
+    /// # Arguments
+    ///
+    /// * `readout` - The name of the register.
+    /// * `register` - The values of the register.
+    pub fn add_complex_register(&self, readout: &str, register: ComplexOutputRegister) {
+        self.state()
+            .complex_registers
+            .insert(readout.to_string(), register);
+    }
+
+    /// Marks an operation as not supported, circuits containing it fail with OperationNotInBackend.
+    ///
+    /// # Arguments
+    ///
+    /// * `hqslang` - The hqslang name of the operation.
+    pub fn add_unsupported_operation(&self, hqslang: &'static str) {
+        self.state().unsupported_operations.insert(hqslang);
+    }
+}
+
+impl EvaluatingBackend for MockBackend {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let circuit: Circuit = circuit.cloned().collect();
+        let mut state = self.state();
+        let call = state.recorded_circuits.len();
+        state.recorded_circuits.push(circuit.clone());
+        if let Some(result) = state.call_results.remove(&call) {
+            return result;
+        }
+        if let Some(operation) = circuit
+            .iter()
+            .find(|operation| state.unsupported_operations.contains(operation.hqslang()))
+        {
+            return Err(RoqoqoBackendError::OperationNotInBackend {
+                backend: "MockBackend",
+                hqslang: operation.hqslang(),
+            });
+        }
+        let mut bit_registers: HashMap<String, BitOutputRegister> = HashMap::new();
+        let mut float_registers: HashMap<String, FloatOutputRegister> = HashMap::new();
+        let mut complex_registers: HashMap<String, ComplexOutputRegister> = HashMap::new();
+        for operation in circuit.iter() {
+            match operation {
+                Operation::DefinitionBit(def) if *def.is_output() => {
+                    let register = state.bit_registers.get(def.name()).cloned();
+                    bit_registers.insert(def.name().clone(), register.unwrap_or_default());
+                }
+                Operation::DefinitionFloat(def) if *def.is_output() => {
+                    let register = state.float_registers.get(def.name()).cloned();
+                    float_registers.insert(def.name().clone(), register.unwrap_or_default());
+                }
+                Operation::DefinitionComplex(def) if *def.is_output() => {
+                    let register = state.complex_registers.get(def.name()).cloned();
+                    complex_registers.insert(def.name().clone(), register.unwrap_or_default());
+                }
+                _ => (),
+            }
+        }
+        Ok((bit_registers, float_registers, complex_registers))
+    }
+}
@@ -0,0 +1,290 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use roqoqo::backends::{EvaluatingBackend, JobBackend, LocalJobBackend};
+use roqoqo::Circuit;
+
+/// Local HTTP server providing the REST job interface expected by the roqoqo `HttpBackend`.
+///
+/// The server listens on a free port of localhost and executes the submitted circuits with a
+/// [LocalJobBackend], so that the number of status polls before a job completes can be configured
+/// with [LocalJobBackend::set_polls_until_completed]. It answers the routes
+///
+/// * `POST /jobs` - submits the JSON-serialized circuit in the body and returns `{"job_id": "<id>"}`,
+/// * `GET /jobs/<id>` - returns the JSON-serialized `JobStatus` of the job,
+/// * `GET /jobs/<id>/result` - returns the output registers of the job,
+/// * `DELETE /jobs/<id>` - cancels the job.
+///
+/// Requests are handled one after another. To test error handling of clients, the server can require
+/// an access token, delay its responses and answer all requests with a fixed status code.
+/// The server is stopped when it is dropped.
+#[derive(Debug)]
+pub struct MockHttpServer {
+    address: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Configuration and recorded requests of a [MockHttpServer].
+#[derive(Debug, Default)]
+struct ServerState {
+    access_token: Option<String>,
+    response_delay: Duration,
+    forced_status: Option<u16>,
+    requests: Vec<String>,
+}
+
+/// Request received by a [MockHttpServer].
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+impl MockHttpServer {
+    /// Starts a new MockHttpServer executing jobs with a [LocalJobBackend].
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The job backend executing the submitted circuits.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MockHttpServer)` - The running server.
+    /// * `Err(io::Error)` - No local port could be opened.
+    pub fn start<B>(backend: LocalJobBackend<B>) -> io::Result<Self>
+    where
+        B: EvaluatingBackend + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(ServerState::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_state = state.clone();
+        let thread_running = running.clone();
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if !thread_running.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    // A failed connection only affects the client that opened it.
+                    let _ = handle_connection(stream, &backend, &thread_state);
+                }
+            }
+        });
+        Ok(Self {
+            address,
+            state,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the URL of the server, used as the endpoint of the client.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Sets the bearer token that requests need to provide.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_token` - The required token (None to accept unauthenticated requests).
+    pub fn set_access_token(&self, access_token: Option<String>) {
+        lock(&self.state).access_token = access_token;
+    }
+
+    /// Sets the time the server waits before answering a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `response_delay` - The new delay of the responses.
+    pub fn set_response_delay(&self, response_delay: Duration) {
+        lock(&self.state).response_delay = response_delay;
+    }
+
+    /// Answers all requests with a fixed HTTP status code instead of handling them.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status code of all responses (None to handle requests normally).
+    pub fn set_forced_status(&self, status: Option<u16>) {
+        lock(&self.state).forced_status = status;
+    }
+
+    /// Returns the method and path of all received requests, for example `"GET /jobs/0"`.
+    pub fn requests(&self) -> Vec<String> {
+        lock(&self.state).requests.clone()
+    }
+}
+
+impl Drop for MockHttpServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        // Wakes up the server thread waiting for the next connection.
+        let _ = TcpStream::connect(self.address);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Locks the shared state, a panic in another thread does not invalidate the configuration.
+fn lock(state: &Mutex<ServerState>) -> MutexGuard<'_, ServerState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Reads a request from a connection, handles it and writes the response.
+fn handle_connection<B>(
+    stream: TcpStream,
+    backend: &LocalJobBackend<B>,
+    state: &Mutex<ServerState>,
+) -> io::Result<()>
+where
+    B: EvaluatingBackend,
+{
+    let request = read_request(&stream)?;
+    let (delay, forced_status, access_token) = {
+        let mut state = lock(state);
+        state
+            .requests
+            .push(format!("{} {}", request.method, request.path));
+        (
+            state.response_delay,
+            state.forced_status,
+            state.access_token.clone(),
+        )
+    };
+    thread::sleep(delay);
+    let (status, body) = match (forced_status, access_token) {
+        (Some(status), _) => (status, format!("\"Forced status {}\"", status)),
+        (None, Some(token))
+            if request.authorization.as_deref() != Some(&format!("Bearer {}", token)) =>
+        {
+            (401, "\"Missing or invalid access token\"".to_string())
+        }
+        _ => respond(&request, backend),
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Reads the request line, the relevant headers and the body of a request.
+fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let mut authorization = None;
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_lowercase().as_str() {
+                "authorization" => authorization = Some(value.trim().to_string()),
+                "content-length" => content_length = value.trim().parse().unwrap_or_default(),
+                _ => (),
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        path,
+        authorization,
+        body,
+    })
+}
+
+/// Handles a request with the job backend and returns the status code and the JSON body.
+fn respond<B>(request: &Request, backend: &LocalJobBackend<B>) -> (u16, String)
+where
+    B: EvaluatingBackend,
+{
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let job = segments.get(1).and_then(|job| job.parse::<usize>().ok());
+    if job.map(|job| job >= backend.number_of_jobs()) == Some(true) {
+        return (404, "\"Unknown job\"".to_string());
+    }
+    let result = match (request.method.as_str(), segments.as_slice(), job) {
+        ("POST", ["jobs"], None) => match serde_json::from_slice::<Circuit>(&request.body) {
+            Ok(circuit) => backend
+                .submit_circuit(&circuit)
+                .map(|job| serde_json::json!({ "job_id": job.to_string() })),
+            Err(error) => return (400, json_string(&error.to_string())),
+        },
+        ("GET", ["jobs", _], Some(job)) => backend
+            .job_status(&job)
+            .map(|status| serde_json::json!(status)),
+        ("GET", ["jobs", _, "result"], Some(job)) => {
+            backend.fetch_result(&job).map(|(bits, floats, complex)| {
+                serde_json::json!({
+                    "bit_registers": bits,
+                    "float_registers": floats,
+                    "complex_registers": complex,
+                })
+            })
+        }
+        ("DELETE", ["jobs", _], Some(job)) => {
+            backend.cancel_job(&job).map(|_| serde_json::Value::Null)
+        }
+        _ => return (404, "\"Unknown route\"".to_string()),
+    };
+    match result {
+        Ok(value) => (200, value.to_string()),
+        Err(error) => (409, json_string(&error.to_string())),
+    }
+}
+
+/// Serializes a message as a JSON string.
+fn json_string(msg: &str) -> String {
+    serde_json::Value::String(msg.to_string()).to_string()
+}
+
+/// Returns the reason phrase of the status codes used by the server.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Status",
+    }
+}
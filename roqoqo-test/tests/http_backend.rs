@@ -0,0 +1,248 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    EvaluatingBackend, HttpBackend, HttpJobBackend, JobBackend, JobStatus, LocalJobBackend,
+    StatevectorSimulator, DEFAULT_REQUEST_TIMEOUT,
+};
+use roqoqo::measurements::ClassicalRegister;
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use roqoqo_test::MockHttpServer;
+use std::time::Duration;
+
+fn bell_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += DefinitionComplex::new("amplitudes".to_string(), 2, true);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaGetAmplitudes::new(
+        "amplitudes".to_string(),
+        vec![vec![false, false], vec![true, true]],
+        None,
+    );
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 20, None);
+    circuit
+}
+
+fn measurement() -> ClassicalRegister {
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += Hadamard::new(0);
+    let mut circuits = Vec::new();
+    for qubit in 0..2 {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new(format!("ro_{}", qubit), 2, true);
+        circuit += PauliX::new(qubit);
+        circuit += PragmaRepeatedMeasurement::new(format!("ro_{}", qubit), 10, None);
+        circuits.push(circuit);
+    }
+    ClassicalRegister {
+        constant_circuit: Some(constant_circuit),
+        circuits,
+    }
+}
+
+fn simulator() -> StatevectorSimulator {
+    let mut simulator = StatevectorSimulator::new(2);
+    simulator.set_random_seed(Some(7));
+    simulator
+}
+
+fn server(polls_until_completed: usize) -> MockHttpServer {
+    let mut backend = LocalJobBackend::new(simulator());
+    backend.set_polls_until_completed(polls_until_completed);
+    MockHttpServer::start(backend).unwrap()
+}
+
+fn http_backend(server: &MockHttpServer) -> HttpBackend {
+    let mut backend = HttpBackend::new(HttpJobBackend::new(&server.url()));
+    backend.set_poll_interval(Duration::from_millis(1));
+    backend
+}
+
+/// Test the getters and setters of the HttpJobBackend
+#[test]
+fn test_http_job_backend_settings() {
+    let mut backend = HttpJobBackend::new("http://localhost:8080/api/");
+    assert_eq!(backend.endpoint(), "http://localhost:8080/api");
+    assert_eq!(backend.access_token(), None);
+    assert_eq!(backend.request_timeout(), DEFAULT_REQUEST_TIMEOUT);
+    backend.set_access_token(Some("secret".to_string()));
+    backend.set_request_timeout(Duration::from_secs(1));
+    assert_eq!(backend.access_token(), Some("secret"));
+    assert_eq!(backend.request_timeout(), Duration::from_secs(1));
+    assert!(!format!("{:?}", backend).contains("secret"));
+}
+
+/// Test that circuits run on the server return the same registers as the simulator
+#[test]
+fn test_run_circuit() {
+    let server = server(2);
+    let backend = http_backend(&server);
+    assert_eq!(
+        backend.run_circuit(&bell_circuit()),
+        simulator().run_circuit(&bell_circuit())
+    );
+    assert_eq!(
+        server.requests(),
+        vec![
+            "POST /jobs",
+            "GET /jobs/0",
+            "GET /jobs/0",
+            "GET /jobs/0/result"
+        ]
+    );
+}
+
+/// Test that all circuits of a measurement are submitted as jobs
+#[test]
+fn test_run_measurement() {
+    let server = server(1);
+    let backend = http_backend(&server);
+    assert_eq!(
+        backend.run_measurement_registers(&measurement()),
+        simulator().run_measurement_registers(&measurement())
+    );
+    let requests = server.requests();
+    assert_eq!(&requests[..2], &["POST /jobs", "POST /jobs"]);
+}
+
+/// Test the job interface of the HttpJobBackend
+#[test]
+fn test_job_lifecycle() {
+    let server = server(2);
+    let backend = HttpJobBackend::new(&server.url());
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(job, "0");
+    assert_eq!(backend.job_status(&job), Ok(JobStatus::Queued));
+    assert!(matches!(
+        backend.fetch_result(&job),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+    assert_eq!(backend.job_status(&job), Ok(JobStatus::Completed));
+    assert!(backend.fetch_result(&job).is_ok());
+
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(backend.cancel_job(&job), Ok(()));
+    assert_eq!(backend.job_status(&job), Ok(JobStatus::Cancelled));
+    assert!(matches!(
+        backend.job_status(&"5".to_string()),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
+
+/// Test that errors of failed jobs are returned
+#[test]
+fn test_failed_job() {
+    let server = MockHttpServer::start(LocalJobBackend::new(StatevectorSimulator::new(1))).unwrap();
+    let backend = http_backend(&server);
+    let job = backend.backend().submit_circuit(&bell_circuit()).unwrap();
+    assert!(matches!(
+        backend.backend().job_status(&job),
+        Ok(JobStatus::Failed { .. })
+    ));
+    assert!(matches!(
+        backend.run_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
+
+/// Test that the access token is sent and missing tokens are reported
+#[test]
+fn test_access_token() {
+    let server = server(0);
+    server.set_access_token(Some("secret".to_string()));
+    let mut backend = http_backend(&server);
+    assert!(matches!(
+        backend.run_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::MissingAuthentification { .. })
+    ));
+
+    let mut job_backend = HttpJobBackend::new(&server.url());
+    job_backend.set_access_token(Some("wrong".to_string()));
+    backend = HttpBackend::new(job_backend.clone());
+    assert!(matches!(
+        backend.run_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::MissingAuthentification { .. })
+    ));
+
+    job_backend.set_access_token(Some("secret".to_string()));
+    backend = HttpBackend::new(job_backend);
+    assert_eq!(
+        backend.run_circuit(&bell_circuit()),
+        simulator().run_circuit(&bell_circuit())
+    );
+}
+
+/// Test that HTTP status codes are mapped onto backend errors
+#[test]
+fn test_status_errors() {
+    let server = server(0);
+    let backend = http_backend(&server);
+    for (status, expected) in [
+        (403, "MissingAuthentification"),
+        (408, "Timeout"),
+        (504, "Timeout"),
+        (500, "NetworkError"),
+        (503, "NetworkError"),
+        (400, "GenericError"),
+    ] {
+        server.set_forced_status(Some(status));
+        let error = backend.run_circuit(&bell_circuit()).unwrap_err();
+        assert!(
+            format!("{:?}", error).starts_with(expected),
+            "status {} returned {:?}",
+            status,
+            error
+        );
+    }
+}
+
+/// Test that slow responses and unreachable servers are reported
+#[test]
+fn test_timeout_and_network_error() {
+    let server = server(0);
+    server.set_response_delay(Duration::from_millis(500));
+    let mut job_backend = HttpJobBackend::new(&server.url());
+    job_backend.set_request_timeout(Duration::from_millis(50));
+    let backend = HttpBackend::new(job_backend);
+    assert!(matches!(
+        backend.run_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::Timeout { .. })
+    ));
+
+    let url = server.url();
+    drop(server);
+    let backend = HttpBackend::new(HttpJobBackend::new(&url));
+    assert!(matches!(
+        backend.run_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::NetworkError { .. })
+    ));
+}
+
+/// Test that the BlockingJobBackend timeout cancels jobs that do not finish
+#[test]
+fn test_polling_timeout() {
+    let server = server(usize::MAX);
+    let mut backend = http_backend(&server);
+    backend.set_timeout(Some(Duration::from_millis(20)));
+    assert!(matches!(
+        backend.run_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::Timeout { .. })
+    ));
+    assert_eq!(
+        backend.backend().job_status(&"0".to_string()),
+        Ok(JobStatus::Cancelled)
+    );
+    assert!(server.requests().contains(&"DELETE /jobs/0".to_string()));
+}
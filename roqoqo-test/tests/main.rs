@@ -10,5 +10,11 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod http_backend;
+#[cfg(test)]
+mod mock_backend;
+#[cfg(test)]
+mod simulation_server;
 #[cfg(test)]
 mod stochastic_gate_test;
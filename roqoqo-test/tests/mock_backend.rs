@@ -0,0 +1,220 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use nalgebra::Complex;
+use qoqo_calculator::{CalculatorError, CalculatorFloat};
+use roqoqo::backends::EvaluatingBackend;
+use roqoqo::measurements::{BasisRotation, BasisRotationInput, ClassicalRegister};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, QuantumProgram, RoqoqoBackendError, RoqoqoError};
+use roqoqo_test::MockBackend;
+use std::collections::HashMap;
+
+fn circuit() -> Circuit {
+    circuit_with_angle("angle".into())
+}
+
+fn circuit_with_angle(angle: CalculatorFloat) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += DefinitionFloat::new("float".to_string(), 1, true);
+    circuit += DefinitionComplex::new("complex".to_string(), 1, true);
+    circuit += DefinitionBit::new("internal".to_string(), 1, false);
+    circuit += RotateX::new(0, angle);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit
+}
+
+/// Test that circuits are recorded and scripted registers are returned by readout name
+#[test]
+fn test_recording_and_readout_registers() {
+    let backend = MockBackend::new();
+    backend.add_bit_register("ro", vec![vec![true, false], vec![false, true]]);
+    backend.add_float_register("float", vec![vec![0.5]]);
+    let (bit_registers, float_registers, complex_registers) =
+        backend.run_circuit(&circuit()).unwrap();
+    assert_eq!(
+        bit_registers["ro"],
+        vec![vec![true, false], vec![false, true]]
+    );
+    assert!(!bit_registers.contains_key("internal"));
+    assert_eq!(float_registers["float"], vec![vec![0.5]]);
+    assert!(complex_registers["complex"].is_empty());
+
+    backend.add_complex_register("complex", vec![vec![Complex::new(0.0, 1.0)]]);
+    let (_, _, complex_registers) = backend.run_circuit(&circuit()).unwrap();
+    assert_eq!(
+        complex_registers["complex"],
+        vec![vec![Complex::new(0.0, 1.0)]]
+    );
+
+    assert_eq!(backend.number_of_calls(), 2);
+    assert_eq!(backend.recorded_circuits(), vec![circuit(), circuit()]);
+    backend.clear_recorded_circuits();
+    assert_eq!(backend.number_of_calls(), 0);
+}
+
+/// Test that clones share the scripted results and the recorded circuits
+#[test]
+fn test_clones_share_state() {
+    let backend = MockBackend::new();
+    let clone = backend.clone();
+    clone.add_bit_register("ro", vec![vec![true, true]]);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit()).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true, true]]);
+    assert_eq!(clone.recorded_circuits(), vec![circuit()]);
+    clone.clear_recorded_circuits();
+    assert_eq!(backend.number_of_calls(), 0);
+}
+
+/// Test that scripted results are returned in call order and only once
+#[test]
+fn test_call_results() {
+    let backend = MockBackend::new();
+    let mut scripted_registers = HashMap::new();
+    scripted_registers.insert("scripted".to_string(), vec![vec![true]]);
+    backend.add_call_result(
+        1,
+        Ok((scripted_registers.clone(), HashMap::new(), HashMap::new())),
+    );
+
+    let (bit_registers, _, _) = backend.run_circuit(&circuit()).unwrap();
+    assert!(bit_registers["ro"].is_empty());
+    let (bit_registers, _, _) = backend.run_circuit(&circuit()).unwrap();
+    assert_eq!(bit_registers, scripted_registers);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit()).unwrap();
+    assert!(bit_registers.contains_key("ro"));
+}
+
+/// Test that failures with every variant of RoqoqoBackendError can be simulated
+#[test]
+fn test_failures() {
+    let errors = vec![
+        RoqoqoBackendError::OperationNotInBackend {
+            backend: "MockBackend",
+            hqslang: "RotateX",
+        },
+        RoqoqoBackendError::MissingAuthentification {
+            msg: "token".to_string(),
+        },
+        RoqoqoBackendError::NetworkError {
+            msg: "network".to_string(),
+        },
+        RoqoqoBackendError::Timeout {
+            msg: "timeout".to_string(),
+        },
+        RoqoqoBackendError::FileAlreadyExists {
+            path: "file".to_string(),
+        },
+        RoqoqoBackendError::GenericError {
+            msg: "generic".to_string(),
+        },
+        RoqoqoBackendError::RoqoqoError(RoqoqoError::UnitaryMatrixErrror {
+            alpha_r: 1.0,
+            alpha_i: 0.0,
+            beta_r: 1.0,
+            beta_i: 0.0,
+            norm: 2.0,
+        }),
+        RoqoqoBackendError::CalculatorError(CalculatorError::DivisionByZero),
+    ];
+    let number_errors = errors.len();
+    let backend = MockBackend::new();
+    let mut expected = Vec::new();
+    for (call, error) in errors.into_iter().enumerate() {
+        expected.push(format!("{:?}", error));
+        backend.add_call_result(call, Err(error));
+    }
+    for expected_error in expected.iter() {
+        let error = backend.run_circuit(&circuit()).unwrap_err();
+        assert_eq!(&format!("{:?}", error), expected_error);
+    }
+    assert_eq!(backend.number_of_calls(), number_errors);
+    assert!(backend.run_circuit(&circuit()).is_ok());
+}
+
+/// Test that unsupported operations fail with OperationNotInBackend
+#[test]
+fn test_unsupported_operation() {
+    let backend = MockBackend::new();
+    backend.add_unsupported_operation("MeasureQubit");
+    assert_eq!(
+        backend.run_circuit(&circuit()),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "MockBackend",
+            hqslang: "MeasureQubit"
+        })
+    );
+    assert_eq!(backend.number_of_calls(), 1);
+}
+
+/// Test recording the circuits a QuantumProgram sends to the backend
+#[test]
+fn test_quantum_program_circuits() {
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += Hadamard::new(1);
+    let measurement = ClassicalRegister {
+        constant_circuit: Some(constant_circuit.clone()),
+        circuits: vec![circuit(), circuit()],
+    };
+    let program = QuantumProgram::ClassicalRegister {
+        measurement,
+        input_parameter_names: vec!["angle".to_string()],
+    };
+    let backend = MockBackend::new();
+    backend.add_bit_register("ro", vec![vec![true, true]]);
+    let (bit_registers, _, _) = program.run_registers(backend.clone(), &[0.5]).unwrap();
+    assert_eq!(
+        bit_registers["ro"],
+        vec![vec![true, true], vec![true, true]]
+    );
+
+    let mut expected = constant_circuit;
+    expected += circuit_with_angle(0.5.into());
+    assert_eq!(
+        backend.recorded_circuits(),
+        vec![expected.clone(), expected]
+    );
+}
+
+/// Test evaluating expectation values from scripted registers
+#[test]
+fn test_quantum_program_expectation_values() {
+    let mut input = BasisRotationInput::new(2, false);
+    let index = input.add_pauli_product("ro".to_string(), vec![0]).unwrap();
+    let mut linear = HashMap::new();
+    linear.insert(index, 2.0);
+    input.add_linear_exp_val("z_0".to_string(), linear).unwrap();
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 4, None);
+    let program = QuantumProgram::BasisRotation {
+        measurement: BasisRotation {
+            constant_circuit: None,
+            circuits: vec![circuit],
+            input,
+        },
+        input_parameter_names: vec![],
+    };
+    let backend = MockBackend::new();
+    backend.add_bit_register(
+        "ro",
+        vec![
+            vec![false, false],
+            vec![false, true],
+            vec![true, false],
+            vec![false, false],
+        ],
+    );
+    let result = program.run(backend, &[]).unwrap().unwrap();
+    assert!((result["z_0"] - 1.0).abs() < 1e-10);
+}
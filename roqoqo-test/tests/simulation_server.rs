@@ -0,0 +1,352 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    EvaluatingBackend, HttpBackend, HttpJobBackend, JobBackend, JobStatus, RegisterResult,
+    StatevectorSimulator,
+};
+use roqoqo::measurements::{CheatedBasisRotation, CheatedBasisRotationInput, ClassicalRegister};
+use roqoqo::operations::*;
+use roqoqo::server::{JobOutput, MeasurementJob, ProgramJob, SimulationServer};
+use roqoqo::{Circuit, QuantumProgram, RoqoqoBackendError};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn bell_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 20, None);
+    circuit
+}
+
+fn classical_register() -> ClassicalRegister {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro_1".to_string(), 2, true);
+    circuit += PauliX::new(1);
+    circuit += PragmaRepeatedMeasurement::new("ro_1".to_string(), 10, None);
+    ClassicalRegister {
+        constant_circuit: None,
+        circuits: vec![bell_circuit(), circuit],
+    }
+}
+
+fn cheated_program() -> QuantumProgram {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("z".to_string(), 1, true);
+    circuit += RotateX::new(0, "theta".into());
+    let mut qubit_paulis = HashMap::new();
+    qubit_paulis.insert(0, 3);
+    circuit += PragmaGetPauliProduct::new(qubit_paulis, "z".to_string(), Circuit::new());
+    let mut input = CheatedBasisRotationInput::new();
+    let index = input.add_pauli_product("z".to_string());
+    let mut linear = HashMap::new();
+    linear.insert(index, 2.0);
+    input
+        .add_linear_exp_val("two_z".to_string(), linear)
+        .unwrap();
+    QuantumProgram::CheatedBasisRotation {
+        measurement: CheatedBasisRotation {
+            constant_circuit: None,
+            circuits: vec![circuit],
+            input,
+        },
+        input_parameter_names: vec!["theta".to_string()],
+    }
+}
+
+fn simulator() -> StatevectorSimulator {
+    let mut simulator = StatevectorSimulator::new(2);
+    simulator.set_random_seed(Some(11));
+    simulator
+}
+
+fn server() -> SimulationServer {
+    SimulationServer::start(simulator(), "127.0.0.1:0", 2, None).unwrap()
+}
+
+/// Submits a job to a route of the server and returns the job ID.
+fn submit(server: &SimulationServer, route: &str, job: serde_json::Value) -> String {
+    let response: serde_json::Value = ureq::post(&format!("{}/{}", server.url(), route))
+        .send_json(job)
+        .unwrap()
+        .into_json()
+        .unwrap();
+    response["job_id"].as_str().unwrap().to_string()
+}
+
+/// Polls a job until it has finished and returns its status.
+fn wait_for_job(server: &SimulationServer, job: &str) -> JobStatus {
+    loop {
+        let status: JobStatus = ureq::get(&format!("{}/jobs/{}", server.url(), job))
+            .call()
+            .unwrap()
+            .into_json()
+            .unwrap();
+        if status.is_finished() {
+            return status;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Fetches the output of a completed job.
+fn fetch_output(server: &SimulationServer, job: &str) -> JobOutput {
+    ureq::get(&format!("{}/jobs/{}/result", server.url(), job))
+        .call()
+        .unwrap()
+        .into_json()
+        .unwrap()
+}
+
+/// Test that circuits submitted by the HttpBackend return the registers of the local backend
+#[test]
+fn test_circuit_jobs() {
+    let server = server();
+    let mut backend = HttpBackend::new(HttpJobBackend::new(&server.url()));
+    backend.set_poll_interval(Duration::from_millis(1));
+    assert_eq!(
+        backend.run_circuit(&bell_circuit()),
+        simulator().run_circuit(&bell_circuit())
+    );
+    assert_eq!(server.number_jobs(), 1);
+    assert_eq!(server.number_queued_jobs(), 0);
+}
+
+/// Test that measurements returning registers are run on the server
+#[test]
+fn test_measurement_job() {
+    let server = server();
+    let job = submit(
+        &server,
+        "measurements",
+        serde_json::json!(MeasurementJob::ClassicalRegister(classical_register())),
+    );
+    assert_eq!(wait_for_job(&server, &job), JobStatus::Completed);
+    let expected = simulator()
+        .run_measurement_registers(&classical_register())
+        .unwrap();
+    assert_eq!(fetch_output(&server, &job), JobOutput::from(expected));
+}
+
+/// Test that quantum programs are run with the submitted parameters
+#[test]
+fn test_program_job() {
+    let server = server();
+    let job = submit(
+        &server,
+        "programs",
+        serde_json::json!(ProgramJob {
+            program: cheated_program(),
+            parameters: vec![PI / 3.0],
+        }),
+    );
+    assert_eq!(wait_for_job(&server, &job), JobStatus::Completed);
+    match fetch_output(&server, &job) {
+        JobOutput::ExpectationValues { expectation_values } => {
+            assert!((expectation_values["two_z"] - 1.0).abs() < 1e-10);
+        }
+        output => panic!("Unexpected output {:?}", output),
+    }
+
+    let job = submit(
+        &server,
+        "programs",
+        serde_json::json!(ProgramJob {
+            program: cheated_program(),
+            parameters: vec![],
+        }),
+    );
+    assert!(matches!(
+        wait_for_job(&server, &job),
+        JobStatus::Failed { .. }
+    ));
+    assert!(matches!(
+        ureq::get(&format!("{}/jobs/{}/result", server.url(), job)).call(),
+        Err(ureq::Error::Status(409, _))
+    ));
+}
+
+/// Test that finished jobs are removed and unknown jobs, routes and bodies are rejected
+#[test]
+fn test_delete_and_invalid_requests() {
+    let server = server();
+    let backend = HttpJobBackend::new(&server.url());
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(wait_for_job(&server, &job), JobStatus::Completed);
+    assert_eq!(backend.cancel_job(&job), Ok(()));
+    assert_eq!(server.number_jobs(), 0);
+    assert!(matches!(
+        backend.job_status(&job),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+
+    let url = server.url();
+    assert!(matches!(
+        ureq::post(&format!("{}/jobs", url)).send_string("not a circuit"),
+        Err(ureq::Error::Status(400, _))
+    ));
+    assert!(matches!(
+        ureq::get(&format!("{}/circuits", url)).call(),
+        Err(ureq::Error::Status(404, _))
+    ));
+}
+
+/// Test that the server requires the access token
+#[test]
+fn test_access_token() {
+    let server =
+        SimulationServer::start(simulator(), "127.0.0.1:0", 1, Some("secret".to_string())).unwrap();
+    let mut job_backend = HttpJobBackend::new(&server.url());
+    assert!(matches!(
+        job_backend.submit_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::MissingAuthentification { .. })
+    ));
+    job_backend.set_access_token(Some("secret".to_string()));
+    assert!(job_backend.submit_circuit(&bell_circuit()).is_ok());
+
+    server.set_access_token(None);
+    assert!(HttpJobBackend::new(&server.url())
+        .submit_circuit(&bell_circuit())
+        .is_ok());
+}
+
+/// Sends the head of a request without its body and returns the status line of the response.
+fn send_head(server: &SimulationServer, head: &str) -> String {
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    stream.write_all(head.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response.lines().next().unwrap_or_default().to_string()
+}
+
+/// Test that too large and unauthorized requests are refused without reading their body
+#[test]
+fn test_refused_before_body() {
+    let server = server();
+    server.set_max_body_size(100);
+    assert!(matches!(
+        ureq::post(&format!("{}/jobs", server.url())).send_json(serde_json::json!(bell_circuit())),
+        Err(ureq::Error::Status(413, _))
+    ));
+    assert_eq!(
+        send_head(
+            &server,
+            "POST /jobs HTTP/1.1\r\nContent-Length: 1000000000000\r\n\r\n"
+        ),
+        "HTTP/1.1 413 Payload Too Large"
+    );
+
+    server.set_access_token(Some("secret".to_string()));
+    assert_eq!(
+        send_head(&server, "POST /jobs HTTP/1.1\r\nContent-Length: 50\r\n\r\n"),
+        "HTTP/1.1 401 Unauthorized"
+    );
+    assert_eq!(server.number_jobs(), 0);
+}
+
+/// Test that only the most recently finished jobs are kept
+#[test]
+fn test_finished_job_eviction() {
+    let server = server();
+    server.set_max_finished_jobs(2);
+    let backend = HttpJobBackend::new(&server.url());
+    let jobs: Vec<String> = (0..3)
+        .map(|_| {
+            let job = backend.submit_circuit(&bell_circuit()).unwrap();
+            assert_eq!(wait_for_job(&server, &job), JobStatus::Completed);
+            job
+        })
+        .collect();
+    assert_eq!(server.number_jobs(), 2);
+    assert!(backend.job_status(&jobs[0]).is_err());
+    assert!(backend.fetch_result(&jobs[2]).is_ok());
+
+    server.set_max_finished_jobs(0);
+    assert_eq!(server.number_jobs(), 0);
+}
+
+/// Backend panicking on circuits containing a PauliZ gate and simulating all other circuits
+#[derive(Debug, Clone)]
+struct PanickingBackend;
+
+impl EvaluatingBackend for PanickingBackend {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        if operations
+            .iter()
+            .any(|operation| operation.hqslang() == "PauliZ")
+        {
+            panic!("PauliZ is not supported");
+        }
+        simulator().run_circuit_iterator(operations.into_iter())
+    }
+}
+
+/// Test that a panicking backend fails the job without stopping the worker
+#[test]
+fn test_panicking_job() {
+    let server = SimulationServer::start(PanickingBackend, "127.0.0.1:0", 1, None).unwrap();
+    let backend = HttpJobBackend::new(&server.url());
+    let mut circuit = bell_circuit();
+    circuit += PauliZ::new(0);
+    let job = backend.submit_circuit(&circuit).unwrap();
+    match wait_for_job(&server, &job) {
+        JobStatus::Failed { msg } => assert!(msg.contains("PauliZ is not supported")),
+        status => panic!("Unexpected status {:?}", status),
+    }
+
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(wait_for_job(&server, &job), JobStatus::Completed);
+}
+
+/// Test that jobs are refused while the queue is full
+#[test]
+fn test_queue_limit() {
+    let server = server();
+    server.set_max_queued_jobs(0);
+    let backend = HttpJobBackend::new(&server.url());
+    assert!(matches!(
+        backend.submit_circuit(&bell_circuit()),
+        Err(RoqoqoBackendError::NetworkError { .. })
+    ));
+    assert_eq!(
+        send_head(&server, "POST /jobs HTTP/1.1\r\nContent-Length: 50\r\n\r\n"),
+        "HTTP/1.1 503 Service Unavailable"
+    );
+    assert_eq!(server.number_jobs(), 0);
+
+    server.set_max_queued_jobs(1);
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(wait_for_job(&server, &job), JobStatus::Completed);
+}
+
+/// Test that a client that does not finish its request does not block other clients
+#[test]
+fn test_slow_client() {
+    let server = server();
+    let mut slow_client = TcpStream::connect(server.address()).unwrap();
+    slow_client.write_all(b"POST /jobs HTTP/1.1\r\n").unwrap();
+    let start = Instant::now();
+    let backend = HttpJobBackend::new(&server.url());
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(wait_for_job(&server, &job), JobStatus::Completed);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
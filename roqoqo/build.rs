@@ -228,6 +228,9 @@ impl<'ast> Visit<'ast> for Visitor {
                 if trait_name.as_str() == "OperatePragmaNoiseProba" {
                     self.pragma_noise_proba_operations.push(id.clone());
                 }
+                if trait_name.as_str() == "OperateConstantGate" {
+                    self.constant_gate_operations.push(id.clone());
+                }
                 if trait_name.as_str() == "OperateMultiQubitGate" {
                     self.multi_qubit_gate_operations.push(id);
                 }
@@ -246,6 +249,24 @@ const SOURCE_FILES: &[&str] = &[
     "src/operations/define_operations.rs",
 ];
 
+/// Operations added after the serialization format of the operation enums was published.
+///
+/// The variants of the generated enums follow the order of the structs in the source files and
+/// bincode serializes a variant by its index. To keep the indices of existing operations stable,
+/// the variants of these operations are moved to the end of every enum, in the order of this list.
+/// New operations are appended to this list.
+const APPENDED_OPERATIONS: &[&str] = &["PragmaGetAmplitudes"];
+
+/// Moves the appended operations to the end of the identifiers of an enum.
+fn move_appended_operations_to_end(identifiers: &mut Vec<Ident>) {
+    for name in APPENDED_OPERATIONS {
+        if let Some(position) = identifiers.iter().position(|id| id == name) {
+            let id = identifiers.remove(position);
+            identifiers.push(id);
+        }
+    }
+}
+
 fn main() {
     // create a visitor that will go through source code and collect the identifiers of structs that belong ad variants
     // in the Operation enum, those that belong in the SingleQubitGateOperationEnum and so on
@@ -257,6 +278,24 @@ fn main() {
         let syntax_tree: File = syn::parse2(code).unwrap();
         vis.visit_file(&syntax_tree);
     }
+    for identifiers in [
+        &mut vis.operations,
+        &mut vis.single_qubit_operations,
+        &mut vis.two_qubit_operations,
+        &mut vis.multi_qubit_operations,
+        &mut vis.pragma_operations,
+        &mut vis.pragma_noise_operations,
+        &mut vis.pragma_noise_proba_operations,
+        &mut vis.gate_operations,
+        &mut vis.rotations,
+        &mut vis.definitions,
+        &mut vis.constant_gate_operations,
+        &mut vis.single_qubit_gate_operations,
+        &mut vis.two_qubit_gate_operations,
+        &mut vis.multi_qubit_gate_operations,
+    ] {
+        move_appended_operations_to_end(identifiers);
+    }
 
     // Construct TokenStreams for variants of operation enum
     let operations_quotes = vis.operations.into_iter().map(|v| {
@@ -0,0 +1,382 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::simulator_helpers::{
+    apply_matrix, basis_states_to_bits, check_qubits, create_rng, dense_dimension, gate_qubits,
+    number_of_repetitions, pauli_matrix, sample_basis_states, SimulationRegisters,
+};
+use crate::backends::{CachedSimulatorState, CachingBackend, EvaluatingBackend, RegisterResult};
+use crate::operations::{
+    GateOperation, Operate, OperateGate, OperatePragmaNoise, OperateSingleQubit, Operation,
+    PragmaNoiseOperation, SingleQubitOperation,
+};
+use crate::{Circuit, RoqoqoBackendError};
+use ndarray::{Array1, Array2};
+use num_complex::Complex64;
+use qoqo_calculator::CalculatorFloat;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::convert::TryFrom;
+
+/// Pure-Rust density matrix simulator implementing [EvaluatingBackend].
+///
+/// The simulator represents the quantum register by its full density matrix of dimension 2^N x 2^N,
+/// stored as a vector in row-major order. Gate operations are applied via their unitary matrices
+/// and the noise PRAGMAs [crate::operations::PragmaDamping], [crate::operations::PragmaDepolarising],
+/// [crate::operations::PragmaDephasing], [crate::operations::PragmaRandomNoise] and
+/// [crate::operations::PragmaGeneralNoise] are applied via their superoperators.
+/// A [crate::operations::PragmaBoostNoise] multiplies the gate time of all following noise PRAGMAs
+/// with its noise coefficient.
+///
+/// Supported measurement operations are [crate::operations::MeasureQubit],
+/// [crate::operations::PragmaRepeatedMeasurement], [crate::operations::PragmaGetDensityMatrix],
+/// [crate::operations::PragmaGetOccupationProbability] and [crate::operations::PragmaGetPauliProduct].
+/// Since the simulated state is in general mixed, [crate::operations::PragmaGetStateVector]
+/// is not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DensityMatrixSimulator {
+    /// The number of qubits of the simulated quantum register.
+    number_qubits: usize,
+    /// Optional seed of the random number generator used for measurements.
+    random_seed: Option<u64>,
+}
+
+impl DensityMatrixSimulator {
+    /// Creates a new DensityMatrixSimulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_qubits` - The number of qubits of the simulated quantum register.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new DensityMatrixSimulator.
+    pub fn new(number_qubits: usize) -> Self {
+        Self {
+            number_qubits,
+            random_seed: None,
+        }
+    }
+
+    /// Returns the number of qubits of the simulated quantum register.
+    pub fn number_qubits(&self) -> usize {
+        self.number_qubits
+    }
+
+    /// Returns the seed of the random number generator.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets the seed of the random number generator.
+    ///
+    /// When a seed is set, every run of a circuit produces the same measurement results.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_seed` - The seed of the random number generator (None for a random seed).
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.random_seed = random_seed;
+    }
+
+    /// Returns the dimension 2^N of the Hilbert space of the quantum register.
+    fn dimension(&self) -> usize {
+        1_usize << self.number_qubits
+    }
+
+    /// Returns the vectorized density matrix of the |0...0> state.
+    ///
+    /// Fails if the density matrix of the number of qubits is too large to be simulated.
+    fn initial_state(&self) -> Result<Array1<Complex64>, RoqoqoBackendError> {
+        let size = dense_dimension(self.number_qubits.saturating_mul(2))?;
+        let mut rho: Array1<Complex64> = Array1::zeros(size);
+        rho[0] = Complex64::new(1.0, 0.0);
+        Ok(rho)
+    }
+
+    /// Applies a single operation to the density matrix and the classical registers.
+    fn apply_operation(
+        &self,
+        operation: &Operation,
+        rho: &mut Array1<Complex64>,
+        registers: &mut SimulationRegisters,
+        noise_coefficient: &mut CalculatorFloat,
+        rng: &mut StdRng,
+    ) -> Result<(), RoqoqoBackendError> {
+        if registers.define(operation) {
+            return Ok(());
+        }
+        check_qubits(operation, self.number_qubits)?;
+        let tags = operation.tags();
+        if tags.contains(&"GateOperation") {
+            let matrix = GateOperation::try_from(operation.clone())?.unitary_matrix()?;
+            self.apply_unitary(rho, &matrix, &gate_qubits(operation)?);
+            return Ok(());
+        }
+        if tags.contains(&"PragmaNoiseOperation") {
+            let noise = PragmaNoiseOperation::try_from(operation.clone())?;
+            let superoperator = noise.powercf(noise_coefficient.clone()).superoperator()?;
+            let superoperator = superoperator.mapv(|x| Complex64::new(x, 0.0));
+            let qubit = *SingleQubitOperation::try_from(operation.clone())?.qubit();
+            apply_matrix(rho, &superoperator, &[qubit + self.number_qubits, qubit]);
+            return Ok(());
+        }
+        match operation {
+            Operation::PragmaBoostNoise(op) => {
+                *noise_coefficient = op.noise_coefficient().clone();
+                Ok(())
+            }
+            Operation::MeasureQubit(op) => {
+                let probability_one = self.probability_one(rho, *op.qubit());
+                let result = rng.gen::<f64>() < probability_one;
+                self.project(rho, *op.qubit(), result);
+                registers.set_bit(op.readout(), *op.readout_index(), result)
+            }
+            Operation::PragmaActiveReset(op) => {
+                let weight_one = self.probability_one(rho, *op.qubit());
+                let weight_zero = 1.0 - weight_one;
+                let mut excited = rho.clone();
+                self.project(rho, *op.qubit(), false);
+                self.project(&mut excited, *op.qubit(), true);
+                let pauli_x = pauli_matrix(1)?;
+                self.apply_unitary(&mut excited, &pauli_x, &[*op.qubit()]);
+                rho.zip_mut_with(&excited, |a, b| *a = *a * weight_zero + *b * weight_one);
+                Ok(())
+            }
+            Operation::PragmaRepeatedMeasurement(op) => {
+                let length = registers.bit_register_length(op.readout())?;
+                let rows = basis_states_to_bits(
+                    sample_basis_states(&self.diagonal(rho), *op.number_measurements(), rng),
+                    length,
+                    self.number_qubits,
+                    op.qubit_mapping(),
+                )?;
+                registers.append_repeated_measurement(op.readout(), rows);
+                Ok(())
+            }
+            Operation::PragmaGetDensityMatrix(op) => {
+                let tmp_rho = self.prepared_copy(
+                    rho,
+                    op.circuit().as_ref(),
+                    registers,
+                    noise_coefficient,
+                    rng,
+                )?;
+                registers
+                    .complex_registers
+                    .insert(op.readout().clone(), tmp_rho.to_vec());
+                Ok(())
+            }
+            Operation::PragmaGetOccupationProbability(op) => {
+                let tmp_rho = self.prepared_copy(
+                    rho,
+                    op.circuit().as_ref(),
+                    registers,
+                    noise_coefficient,
+                    rng,
+                )?;
+                registers
+                    .float_registers
+                    .insert(op.readout().clone(), self.diagonal(&tmp_rho));
+                Ok(())
+            }
+            Operation::PragmaGetPauliProduct(op) => {
+                let mut tmp_rho =
+                    self.prepared_copy(rho, Some(op.circuit()), registers, noise_coefficient, rng)?;
+                for (qubit, pauli) in op.qubit_paulis().iter() {
+                    apply_matrix(
+                        &mut tmp_rho,
+                        &pauli_matrix(*pauli)?,
+                        &[qubit + self.number_qubits],
+                    );
+                }
+                let value: f64 = self.diagonal(&tmp_rho).iter().sum();
+                registers
+                    .float_registers
+                    .insert(op.readout().clone(), vec![value]);
+                Ok(())
+            }
+            Operation::PragmaSetDensityMatrix(op) => {
+                let density_matrix: &Array2<Complex64> = op.density_matrix();
+                if density_matrix.dim() != (self.dimension(), self.dimension()) {
+                    return Err(RoqoqoBackendError::GenericError {
+                        msg: format!(
+                            "Shape {:?} of the density matrix does not match the dimension {} of the simulated quantum register",
+                            density_matrix.dim(),
+                            self.dimension()
+                        ),
+                    });
+                }
+                rho.iter_mut()
+                    .zip(density_matrix.iter())
+                    .for_each(|(a, b)| *a = *b);
+                Ok(())
+            }
+            Operation::PragmaSetNumberOfMeasurements(_)
+            | Operation::PragmaGlobalPhase(_)
+            | Operation::PragmaRepeatGate(_)
+            | Operation::PragmaOverrotation(_)
+            | Operation::PragmaStopParallelBlock(_)
+            | Operation::PragmaSleep(_)
+            | Operation::PragmaStartDecompositionBlock(_)
+            | Operation::PragmaStopDecompositionBlock(_) => Ok(()),
+            _ => Err(RoqoqoBackendError::OperationNotInBackend {
+                backend: "DensityMatrixSimulator",
+                hqslang: operation.hqslang(),
+            }),
+        }
+    }
+
+    /// Applies a unitary to the density matrix: rho -> U rho U^dagger.
+    fn apply_unitary(
+        &self,
+        rho: &mut Array1<Complex64>,
+        matrix: &Array2<Complex64>,
+        qubits: &[usize],
+    ) {
+        let row_qubits: Vec<usize> = qubits.iter().map(|q| q + self.number_qubits).collect();
+        apply_matrix(rho, matrix, &row_qubits);
+        apply_matrix(rho, &matrix.mapv(|x| x.conj()), qubits);
+    }
+
+    /// Returns the diagonal of the density matrix (the occupation probabilities).
+    fn diagonal(&self, rho: &Array1<Complex64>) -> Vec<f64> {
+        let dimension = self.dimension();
+        (0..dimension)
+            .map(|index| rho[index * dimension + index].re)
+            .collect()
+    }
+
+    /// Returns the probability to measure a qubit in the excited state.
+    fn probability_one(&self, rho: &Array1<Complex64>, qubit: usize) -> f64 {
+        self.diagonal(rho)
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| (index >> qubit) & 1 == 1)
+            .map(|(_, probability)| probability)
+            .sum()
+    }
+
+    /// Projects the density matrix onto a measurement result of a qubit and renormalizes it.
+    fn project(&self, rho: &mut Array1<Complex64>, qubit: usize, result: bool) {
+        let row_mask = 1_usize << (qubit + self.number_qubits);
+        let column_mask = 1_usize << qubit;
+        for (index, value) in rho.iter_mut().enumerate() {
+            if (index & row_mask != 0) != result || (index & column_mask != 0) != result {
+                *value = Complex64::new(0.0, 0.0);
+            }
+        }
+        let trace: f64 = self.diagonal(rho).iter().sum();
+        if trace > 0.0 {
+            rho.mapv_inplace(|x| x / trace);
+        }
+    }
+
+    /// Returns a copy of the density matrix with the optional preparation circuit applied.
+    fn prepared_copy(
+        &self,
+        rho: &Array1<Complex64>,
+        circuit: Option<&crate::Circuit>,
+        registers: &mut SimulationRegisters,
+        noise_coefficient: &mut CalculatorFloat,
+        rng: &mut StdRng,
+    ) -> Result<Array1<Complex64>, RoqoqoBackendError> {
+        let mut tmp_rho = rho.clone();
+        if let Some(circuit) = circuit {
+            for operation in circuit.iter() {
+                self.apply_operation(operation, &mut tmp_rho, registers, noise_coefficient, rng)?;
+            }
+        }
+        Ok(tmp_rho)
+    }
+}
+
+impl EvaluatingBackend for DensityMatrixSimulator {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        self.run_from_state(
+            &self.initial_state()?,
+            &SimulationRegisters::new(),
+            &operations,
+        )
+    }
+}
+
+impl CachingBackend for DensityMatrixSimulator {
+    type CachedState = CachedSimulatorState;
+
+    fn prepare_constant_circuit(
+        &self,
+        constant_circuit: Option<&Circuit>,
+    ) -> Result<CachedSimulatorState, RoqoqoBackendError> {
+        let mut rng = create_rng(self.random_seed);
+        let mut noise_coefficient = CalculatorFloat::from(1.0);
+        CachedSimulatorState::prepare(
+            constant_circuit,
+            self.initial_state()?,
+            |operation, rho, registers| {
+                self.apply_operation(operation, rho, registers, &mut noise_coefficient, &mut rng)
+            },
+        )
+    }
+
+    fn run_circuit_from_cached_state(
+        &self,
+        cached_state: &CachedSimulatorState,
+        circuit: &Circuit,
+    ) -> RegisterResult {
+        match cached_state.state.as_ref() {
+            Some(rho) => {
+                let operations: Vec<&Operation> = circuit.iter().collect();
+                self.run_from_state(rho, &cached_state.registers, &operations)
+            }
+            None => self.run_circuit_iterator(
+                cached_state
+                    .constant_operations
+                    .iter()
+                    .chain(circuit.iter()),
+            ),
+        }
+    }
+}
+
+impl DensityMatrixSimulator {
+    /// Executes operations repeatedly, starting every run from the given density matrix and registers.
+    fn run_from_state(
+        &self,
+        initial_rho: &Array1<Complex64>,
+        initial_registers: &SimulationRegisters,
+        operations: &[&Operation],
+    ) -> RegisterResult {
+        let mut rng = create_rng(self.random_seed);
+        let mut registers = initial_registers.clone();
+        for _ in 0..number_of_repetitions(operations) {
+            let mut rho = initial_rho.clone();
+            let mut noise_coefficient = CalculatorFloat::from(1.0);
+            registers.reset_run(initial_registers);
+            for operation in operations.iter() {
+                self.apply_operation(
+                    operation,
+                    &mut rho,
+                    &mut registers,
+                    &mut noise_coefficient,
+                    &mut rng,
+                )?;
+            }
+            registers.finish_run();
+        }
+        registers.into_output()
+    }
+}
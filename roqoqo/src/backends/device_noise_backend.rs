@@ -0,0 +1,91 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::devices::{insert_device_noise, Device};
+use crate::operations::Operation;
+use crate::{Circuit, RoqoqoBackendError};
+
+/// Wrapper around an [EvaluatingBackend] that adds the decoherence noise of a [Device] to circuits.
+///
+/// Before a circuit is passed to the wrapped backend, [crate::devices::insert_device_noise]
+/// inserts [crate::operations::PragmaGeneralNoise] operations for the gate times and idle times
+/// of the qubits on the device. The wrapped backend needs to support noise PRAGMAs,
+/// for example the [crate::backends::DensityMatrixSimulator].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceNoiseBackend<B, D>
+where
+    B: EvaluatingBackend,
+    D: Device,
+{
+    /// The wrapped backend running the noisy circuits.
+    backend: B,
+    /// The device providing the gate times and decoherence rates.
+    device: D,
+}
+
+impl<B, D> DeviceNoiseBackend<B, D>
+where
+    B: EvaluatingBackend,
+    D: Device,
+{
+    /// Creates a new DeviceNoiseBackend.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend running the noisy circuits.
+    /// * `device` - The device providing the gate times and decoherence rates.
+    pub fn new(backend: B, device: D) -> Self {
+        Self { backend, device }
+    }
+
+    /// Returns a reference to the wrapped backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns a reference to the device providing the noise model.
+    pub fn device(&self) -> &D {
+        &self.device
+    }
+
+    /// Returns the circuit with the noise of the device that is passed to the wrapped backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit the noise is inserted into.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Circuit)` - The circuit with the inserted noise PRAGMAs.
+    /// * `Err(RoqoqoBackendError)` - The circuit cannot be executed on the device.
+    pub fn noisy_circuit(&self, circuit: &Circuit) -> Result<Circuit, RoqoqoBackendError> {
+        insert_device_noise(&self.device, circuit)
+    }
+}
+
+impl<B, D> EvaluatingBackend for DeviceNoiseBackend<B, D>
+where
+    B: EvaluatingBackend,
+    D: Device,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let mut collected = Circuit::new();
+        for operation in circuit {
+            collected += operation.clone();
+        }
+        self.backend.run_circuit(&self.noisy_circuit(&collected)?)
+    }
+}
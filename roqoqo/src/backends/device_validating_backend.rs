@@ -0,0 +1,115 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::devices::{validate_circuit, Device, DeviceViolation};
+use crate::measurements::Measure;
+use crate::operations::Operation;
+use crate::RoqoqoBackendError;
+
+/// Wrapper around an [EvaluatingBackend] that only runs circuits executable on a [Device].
+///
+/// Before a circuit is passed to the wrapped backend, all operations are checked with
+/// [crate::devices::validate_circuit]. Circuits violating the constraints of the device
+/// are refused with [RoqoqoBackendError::DeviceValidationFailed] listing all violations.
+/// For measurements all circuits are checked before the first circuit is run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceValidatingBackend<B, D>
+where
+    B: EvaluatingBackend,
+    D: Device,
+{
+    /// The wrapped backend running valid circuits.
+    backend: B,
+    /// The device the circuits are checked against.
+    device: D,
+}
+
+impl<B, D> DeviceValidatingBackend<B, D>
+where
+    B: EvaluatingBackend,
+    D: Device,
+{
+    /// Creates a new DeviceValidatingBackend.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend running valid circuits.
+    /// * `device` - The device the circuits are checked against.
+    pub fn new(backend: B, device: D) -> Self {
+        Self { backend, device }
+    }
+
+    /// Returns a reference to the wrapped backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns a reference to the device the circuits are checked against.
+    pub fn device(&self) -> &D {
+        &self.device
+    }
+
+    /// Checks the operations of a circuit against the device.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The iterator over operations that is checked (corresponds to a circuit).
+    ///
+    /// # Returns
+    ///
+    /// `Vec<DeviceViolation>` - All violations of the device constraints, empty for a valid circuit.
+    pub fn validate<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> Vec<DeviceViolation> {
+        validate_circuit(&self.device, circuit)
+    }
+
+    /// Returns an error listing the violations if there are any.
+    fn check(&self, violations: Vec<DeviceViolation>) -> Result<(), RoqoqoBackendError> {
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(RoqoqoBackendError::DeviceValidationFailed { violations })
+        }
+    }
+}
+
+impl<B, D> EvaluatingBackend for DeviceValidatingBackend<B, D>
+where
+    B: EvaluatingBackend,
+    D: Device,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        self.check(self.validate(operations.iter().copied()))?;
+        self.backend.run_circuit_iterator(operations.into_iter())
+    }
+
+    fn run_measurement_registers<T>(&self, measurement: &T) -> RegisterResult
+    where
+        T: Measure,
+    {
+        for circuit in measurement.circuits() {
+            let violations = match measurement.constant_circuit() {
+                Some(x) => self.validate(x.iter().chain(circuit.iter())),
+                None => self.validate(circuit.iter()),
+            };
+            self.check(violations)?;
+        }
+        self.backend.run_measurement_registers(measurement)
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{BlockingJobBackend, JobBackend, JobStatus, RegisterResult};
+use crate::operations::Operation;
+use crate::registers::{BitOutputRegister, ComplexOutputRegister, FloatOutputRegister};
+use crate::{Circuit, RoqoqoBackendError};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// Default maximum time for a single HTTP request of the [HttpJobBackend].
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Blocking [crate::backends::EvaluatingBackend] running circuits as jobs of a REST service.
+///
+/// The [BlockingJobBackend] polls the jobs of the wrapped [HttpJobBackend] until they have finished.
+pub type HttpBackend = BlockingJobBackend<HttpJobBackend>;
+
+/// Job submitted to a REST service, as returned by `POST {endpoint}/jobs`.
+#[derive(serde::Deserialize)]
+struct SubmittedJob {
+    job_id: String,
+}
+
+/// Output registers of a completed job, as returned by `GET {endpoint}/jobs/{job_id}/result`.
+#[derive(serde::Deserialize)]
+struct JobResult {
+    bit_registers: HashMap<String, BitOutputRegister>,
+    float_registers: HashMap<String, FloatOutputRegister>,
+    complex_registers: HashMap<String, ComplexOutputRegister>,
+}
+
+/// [JobBackend] submitting circuits to a REST service as JSON.
+///
+/// The service is expected to provide the following routes relative to the endpoint:
+///
+/// * `POST jobs` - Submits the JSON-serialized [Circuit] in the body, returns `{"job_id": "<id>"}`.
+/// * `GET jobs/<id>` - Returns the JSON-serialized [JobStatus] of the job.
+/// * `GET jobs/<id>/result` - Returns the output registers of a completed job as
+///   `{"bit_registers": {...}, "float_registers": {...}, "complex_registers": {...}}`.
+/// * `DELETE jobs/<id>` - Cancels the job.
+///
+/// When an access token is set, it is sent as a bearer token in the `Authorization` header.
+/// Failed requests are mapped onto [RoqoqoBackendError]:
+///
+/// * status 401 and 403 - [RoqoqoBackendError::MissingAuthentification],
+/// * status 408 and 504 and requests exceeding the request timeout - [RoqoqoBackendError::Timeout],
+/// * other 5xx status codes and failed connections - [RoqoqoBackendError::NetworkError],
+/// * all other failures - [RoqoqoBackendError::GenericError].
+#[derive(Clone)]
+pub struct HttpJobBackend {
+    /// URL of the REST service without trailing slash.
+    endpoint: String,
+    /// Optional bearer token authenticating the requests.
+    access_token: Option<String>,
+    /// Maximum time for a single request.
+    request_timeout: Duration,
+    /// HTTP agent sending the requests.
+    agent: ureq::Agent,
+}
+
+impl fmt::Debug for HttpJobBackend {
+    // The access token is not printed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpJobBackend")
+            .field("endpoint", &self.endpoint)
+            .field(
+                "access_token",
+                &self.access_token.as_ref().map(|_| "<hidden>"),
+            )
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+impl HttpJobBackend {
+    /// Creates a new HttpJobBackend without access token.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The URL of the REST service, for example `http://localhost:8080/api`.
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            access_token: None,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            agent: create_agent(DEFAULT_REQUEST_TIMEOUT),
+        }
+    }
+
+    /// Returns the URL of the REST service.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Returns the access token sent with every request.
+    pub fn access_token(&self) -> Option<&str> {
+        self.access_token.as_deref()
+    }
+
+    /// Sets the access token sent with every request.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_token` - The new bearer token (None to send unauthenticated requests).
+    pub fn set_access_token(&mut self, access_token: Option<String>) {
+        self.access_token = access_token;
+    }
+
+    /// Returns the maximum time for a single request.
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    /// Sets the maximum time for a single request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_timeout` - The new maximum time for a single request.
+    pub fn set_request_timeout(&mut self, request_timeout: Duration) {
+        self.request_timeout = request_timeout;
+        self.agent = create_agent(request_timeout);
+    }
+
+    /// Creates a request to a route of the service including the authorization header.
+    fn request(&self, method: &str, route: &str) -> ureq::Request {
+        let request = self
+            .agent
+            .request(method, &format!("{}/{}", self.endpoint, route));
+        match &self.access_token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+
+    /// Deserializes the JSON body of a successful response.
+    fn read_json<T>(
+        &self,
+        response: Result<ureq::Response, ureq::Error>,
+    ) -> Result<T, RoqoqoBackendError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = response.map_err(|error| self.convert_error(error))?;
+        let url = response.get_url().to_string();
+        response.into_json().map_err(|error| match error.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => RoqoqoBackendError::Timeout {
+                msg: format!("Reading response of {} timed out: {}", url, error),
+            },
+            _ => RoqoqoBackendError::GenericError {
+                msg: format!("Invalid response of {}: {}", url, error),
+            },
+        })
+    }
+
+    /// Maps a failed request onto a [RoqoqoBackendError].
+    fn convert_error(&self, error: ureq::Error) -> RoqoqoBackendError {
+        match error {
+            ureq::Error::Status(status, response) => {
+                let url = response.get_url().to_string();
+                let msg = format!(
+                    "Request to {} failed with status {}: {}",
+                    url,
+                    status,
+                    response.into_string().unwrap_or_default()
+                );
+                match status {
+                    401 | 403 => RoqoqoBackendError::MissingAuthentification { msg },
+                    408 | 504 => RoqoqoBackendError::Timeout { msg },
+                    500..=599 => RoqoqoBackendError::NetworkError { msg },
+                    _ => RoqoqoBackendError::GenericError { msg },
+                }
+            }
+            ureq::Error::Transport(transport) => {
+                let timed_out = transport
+                    .source()
+                    .and_then(|source| source.downcast_ref::<io::Error>())
+                    .map(|error| {
+                        matches!(
+                            error.kind(),
+                            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+                        )
+                    })
+                    .unwrap_or(false);
+                let msg = format!("Request to {} failed: {}", self.endpoint, transport);
+                if timed_out {
+                    RoqoqoBackendError::Timeout { msg }
+                } else {
+                    RoqoqoBackendError::NetworkError { msg }
+                }
+            }
+        }
+    }
+}
+
+/// Creates the HTTP agent with a timeout for every request.
+fn create_agent(request_timeout: Duration) -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(request_timeout).build()
+}
+
+impl JobBackend for HttpJobBackend {
+    type JobHandle = String;
+
+    fn submit_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> Result<String, RoqoqoBackendError> {
+        let circuit: Circuit = circuit.cloned().collect();
+        let job: SubmittedJob = self.read_json(self.request("POST", "jobs").send_json(&circuit))?;
+        Ok(job.job_id)
+    }
+
+    fn job_status(&self, job: &String) -> Result<JobStatus, RoqoqoBackendError> {
+        self.read_json(self.request("GET", &format!("jobs/{}", job)).call())
+    }
+
+    fn fetch_result(&self, job: &String) -> RegisterResult {
+        let result: JobResult =
+            self.read_json(self.request("GET", &format!("jobs/{}/result", job)).call())?;
+        Ok((
+            result.bit_registers,
+            result.float_registers,
+            result.complex_registers,
+        ))
+    }
+
+    fn cancel_job(&self, job: &String) -> Result<(), RoqoqoBackendError> {
+        self.request("DELETE", &format!("jobs/{}", job))
+            .call()
+            .map(|_| ())
+            .map_err(|error| self.convert_error(error))
+    }
+}
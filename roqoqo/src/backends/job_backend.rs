@@ -0,0 +1,488 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{EvaluatingBackend, OutputRegisters, RegisterResult};
+use crate::measurements::Measure;
+use crate::operations::Operation;
+use crate::{Circuit, RoqoqoBackendError};
+use std::fmt::Debug;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default time between two status polls of the [BlockingJobBackend].
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Status of a job submitted to a [JobBackend].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum JobStatus {
+    /// The job is waiting in the queue of the backend.
+    Queued,
+    /// The job is being executed.
+    Running,
+    /// The job has finished and its results can be fetched.
+    Completed,
+    /// The job has failed, fetching its results returns the error.
+    Failed {
+        /// Error message of the failed job.
+        msg: String,
+    },
+    /// The job has been cancelled before it finished.
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Returns true when the job will not change its status anymore.
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+/// Trait for backends that execute circuits as jobs, typically on remote quantum hardware.
+///
+/// Instead of blocking until a circuit has been executed like [EvaluatingBackend],
+/// a job backend returns a handle as soon as a circuit has been submitted.
+/// The handle is used to poll the status of the job and to fetch or cancel its results,
+/// so that many jobs can be kept in flight at the same time.
+/// Any job backend can be used as an [EvaluatingBackend] by wrapping it in a [BlockingJobBackend].
+pub trait JobBackend {
+    /// Handle identifying a submitted job, for example the job ID of a remote service.
+    type JobHandle: Clone + Debug;
+
+    /// Submits the operations obtained from an iterator over operations as a job.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The iterator over operations that is submitted (corresponds to a circuit).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self::JobHandle)` - The handle of the submitted job.
+    /// * `Err(RoqoqoBackendError)` - The job could not be submitted.
+    fn submit_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> Result<Self::JobHandle, RoqoqoBackendError>;
+
+    /// Returns the current status of a job.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The handle of the job.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(JobStatus)` - The status of the job.
+    /// * `Err(RoqoqoBackendError)` - The status could not be obtained.
+    fn job_status(&self, job: &Self::JobHandle) -> Result<JobStatus, RoqoqoBackendError>;
+
+    /// Fetches the output registers of a job.
+    ///
+    /// Returns an error when the job has not completed yet, has been cancelled or has failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The handle of the job.
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the circuit of the job.
+    fn fetch_result(&self, job: &Self::JobHandle) -> RegisterResult;
+
+    /// Cancels a job that has not finished yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The handle of the job.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The job has been cancelled or had already finished.
+    /// * `Err(RoqoqoBackendError)` - The job could not be cancelled.
+    fn cancel_job(&self, job: &Self::JobHandle) -> Result<(), RoqoqoBackendError>;
+
+    /// Submits a circuit as a job.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit that is submitted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self::JobHandle)` - The handle of the submitted job.
+    /// * `Err(RoqoqoBackendError)` - The job could not be submitted.
+    fn submit_circuit(&self, circuit: &Circuit) -> Result<Self::JobHandle, RoqoqoBackendError> {
+        self.submit_circuit_iterator(circuit.iter())
+    }
+
+    /// Submits all circuits corresponding to one measurement as separate jobs.
+    ///
+    /// The constant circuit of the measurement is prepended to every circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement that is submitted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Self::JobHandle>)` - The handles of the jobs in the order of the measurement circuits.
+    /// * `Err(RoqoqoBackendError)` - A job could not be submitted.
+    fn submit_measurement<T>(
+        &self,
+        measurement: &T,
+    ) -> Result<Vec<Self::JobHandle>, RoqoqoBackendError>
+    where
+        T: Measure,
+    {
+        measurement
+            .circuits()
+            .map(|circuit| match measurement.constant_circuit() {
+                Some(x) => self.submit_circuit_iterator(x.iter().chain(circuit.iter())),
+                None => self.submit_circuit_iterator(circuit.iter()),
+            })
+            .collect()
+    }
+
+    /// Fetches the output registers of several jobs and combines them in the order of the handles.
+    ///
+    /// # Arguments
+    ///
+    /// * `jobs` - The handles of the jobs, for example returned by [JobBackend::submit_measurement].
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The combined output registers of all jobs.
+    fn fetch_measurement_registers(&self, jobs: &[Self::JobHandle]) -> RegisterResult {
+        OutputRegisters::from_results(jobs.iter().map(|job| self.fetch_result(job)))
+    }
+}
+
+/// Adapter using a [JobBackend] as a blocking [EvaluatingBackend].
+///
+/// Every circuit is submitted as a job and the status of the job is polled until it has finished.
+/// All circuits of a measurement are submitted before waiting for the first result,
+/// so that the jobs of a measurement are in flight at the same time.
+/// When the optional timeout is exceeded, the unfinished jobs are cancelled
+/// and a [RoqoqoBackendError::Timeout] is returned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockingJobBackend<B>
+where
+    B: JobBackend,
+{
+    /// The wrapped job backend.
+    backend: B,
+    /// Time between two status polls.
+    poll_interval: Duration,
+    /// Maximum time to wait for the jobs of one circuit or measurement.
+    timeout: Option<Duration>,
+}
+
+impl<B> BlockingJobBackend<B>
+where
+    B: JobBackend,
+{
+    /// Creates a new BlockingJobBackend without timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The job backend executing the circuits.
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout: None,
+        }
+    }
+
+    /// Returns a reference to the wrapped job backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the time between two status polls.
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Sets the time between two status polls.
+    ///
+    /// # Arguments
+    ///
+    /// * `poll_interval` - The new time between two status polls.
+    pub fn set_poll_interval(&mut self, poll_interval: Duration) {
+        self.poll_interval = poll_interval;
+    }
+
+    /// Returns the maximum time to wait for the jobs of one circuit or measurement.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the maximum time to wait for the jobs of one circuit or measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The new timeout (None to wait indefinitely).
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Polls a job until it has finished and fetches its results.
+    fn wait_for_job(&self, job: &B::JobHandle, deadline: Option<Instant>) -> RegisterResult {
+        loop {
+            match self.backend.job_status(job)? {
+                JobStatus::Queued | JobStatus::Running => (),
+                JobStatus::Cancelled => {
+                    return Err(RoqoqoBackendError::GenericError {
+                        msg: format!("Job {:?} has been cancelled", job),
+                    })
+                }
+                JobStatus::Completed | JobStatus::Failed { .. } => {
+                    return self.backend.fetch_result(job)
+                }
+            }
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    return Err(RoqoqoBackendError::Timeout {
+                        msg: format!("Job {:?} did not finish in {:?}", job, self.timeout),
+                    });
+                }
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Waits for several jobs in order and combines their results, cancelling all jobs on failure.
+    fn wait_for_jobs(&self, jobs: &[B::JobHandle]) -> RegisterResult {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let mut registers = OutputRegisters::default();
+        for job in jobs.iter() {
+            match self.wait_for_job(job, deadline) {
+                Ok(result) => registers.append(result),
+                Err(error) => {
+                    for job in jobs.iter() {
+                        // Cancelling is best effort, the original error is more informative.
+                        let _ = self.backend.cancel_job(job);
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        Ok(registers.into())
+    }
+}
+
+impl<B> EvaluatingBackend for BlockingJobBackend<B>
+where
+    B: JobBackend,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let job = self.backend.submit_circuit_iterator(circuit)?;
+        self.wait_for_jobs(&[job])
+    }
+
+    fn run_measurement_registers<T>(&self, measurement: &T) -> RegisterResult
+    where
+        T: Measure,
+    {
+        let jobs = self.backend.submit_measurement(measurement)?;
+        self.wait_for_jobs(&jobs)
+    }
+}
+
+/// In-process stand-in for a remote [JobBackend].
+///
+/// Executes the submitted circuits with a wrapped [EvaluatingBackend] in the current process.
+/// To test code polling remote backends, a job is only executed after its status
+/// has been polled a configurable number of times and stays queued until then.
+/// The results of a job can be fetched once.
+#[derive(Debug)]
+pub struct LocalJobBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// The backend executing the circuits of the jobs.
+    backend: B,
+    /// Number of status polls before a job is executed.
+    polls_until_completed: usize,
+    /// All jobs submitted to the backend, the job handle is the index in this vector.
+    jobs: Mutex<Vec<LocalJob>>,
+}
+
+/// Job of a [LocalJobBackend].
+#[derive(Debug)]
+struct LocalJob {
+    /// Operations of the circuit, removed when the job is executed.
+    operations: Vec<Operation>,
+    /// Number of status polls left before the job is executed.
+    remaining_polls: usize,
+    /// Current status of the job.
+    status: JobStatus,
+    /// Results of the finished job, `None` before execution or once fetched.
+    result: Option<RegisterResult>,
+}
+
+impl<B> LocalJobBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// Creates a new LocalJobBackend executing jobs directly on submission.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend executing the circuits of the jobs.
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            polls_until_completed: 0,
+            jobs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a reference to the backend executing the circuits of the jobs.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the number of status polls before a job is executed.
+    pub fn polls_until_completed(&self) -> usize {
+        self.polls_until_completed
+    }
+
+    /// Sets the number of status polls before a job is executed.
+    ///
+    /// Only affects jobs submitted afterwards. With zero polls a job is executed on submission.
+    ///
+    /// # Arguments
+    ///
+    /// * `polls_until_completed` - The new number of status polls before a job is executed.
+    pub fn set_polls_until_completed(&mut self, polls_until_completed: usize) {
+        self.polls_until_completed = polls_until_completed;
+    }
+
+    /// Returns the number of jobs submitted to the backend.
+    pub fn number_of_jobs(&self) -> usize {
+        self.jobs.lock().map(|jobs| jobs.len()).unwrap_or_default()
+    }
+
+    /// Locks the jobs of the backend.
+    fn lock_jobs(&self) -> Result<MutexGuard<'_, Vec<LocalJob>>, RoqoqoBackendError> {
+        self.jobs
+            .lock()
+            .map_err(|_| RoqoqoBackendError::GenericError {
+                msg: "Jobs of LocalJobBackend are poisoned".to_string(),
+            })
+    }
+
+    /// Executes the circuit of a job and returns the final status of the job and its results.
+    fn execute(&self, operations: &[Operation]) -> (JobStatus, RegisterResult) {
+        let result = self.backend.run_circuit_iterator(operations.iter());
+        let status = match &result {
+            Ok(_) => JobStatus::Completed,
+            Err(error) => JobStatus::Failed {
+                msg: error.to_string(),
+            },
+        };
+        (status, result)
+    }
+}
+
+/// Returns the job corresponding to a handle of a [LocalJobBackend].
+fn get_job(jobs: &mut [LocalJob], job: usize) -> Result<&mut LocalJob, RoqoqoBackendError> {
+    jobs.get_mut(job)
+        .ok_or_else(|| RoqoqoBackendError::GenericError {
+            msg: format!("Unknown job {}", job),
+        })
+}
+
+impl<B> JobBackend for LocalJobBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    type JobHandle = usize;
+
+    fn submit_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> Result<usize, RoqoqoBackendError> {
+        let mut job = LocalJob {
+            operations: circuit.cloned().collect(),
+            remaining_polls: self.polls_until_completed,
+            status: JobStatus::Queued,
+            result: None,
+        };
+        if job.remaining_polls == 0 {
+            let (status, result) = self.execute(&job.operations);
+            job.operations.clear();
+            job.status = status;
+            job.result = Some(result);
+        }
+        let mut jobs = self.lock_jobs()?;
+        jobs.push(job);
+        Ok(jobs.len() - 1)
+    }
+
+    fn job_status(&self, job: &usize) -> Result<JobStatus, RoqoqoBackendError> {
+        let operations = {
+            let mut jobs = self.lock_jobs()?;
+            let local_job = get_job(&mut jobs, *job)?;
+            if local_job.status != JobStatus::Queued {
+                return Ok(local_job.status.clone());
+            }
+            local_job.remaining_polls -= 1;
+            if local_job.remaining_polls > 0 {
+                return Ok(JobStatus::Queued);
+            }
+            local_job.status = JobStatus::Running;
+            std::mem::take(&mut local_job.operations)
+        };
+        // The jobs are not locked during the execution, so other jobs can be polled meanwhile
+        let (status, result) = self.execute(&operations);
+        let mut jobs = self.lock_jobs()?;
+        let local_job = get_job(&mut jobs, *job)?;
+        if local_job.status == JobStatus::Running {
+            local_job.status = status;
+            local_job.result = Some(result);
+        }
+        Ok(local_job.status.clone())
+    }
+
+    fn fetch_result(&self, job: &usize) -> RegisterResult {
+        let mut jobs = self.lock_jobs()?;
+        let local_job = get_job(&mut jobs, *job)?;
+        match local_job.status {
+            JobStatus::Completed | JobStatus::Failed { .. } => {
+                local_job.result.take().unwrap_or_else(|| {
+                    Err(RoqoqoBackendError::GenericError {
+                        msg: format!("Results of job {} have already been fetched", job),
+                    })
+                })
+            }
+            _ => Err(RoqoqoBackendError::GenericError {
+                msg: format!("Job {} has no results, status {:?}", job, local_job.status),
+            }),
+        }
+    }
+
+    fn cancel_job(&self, job: &usize) -> Result<(), RoqoqoBackendError> {
+        let mut jobs = self.lock_jobs()?;
+        let local_job = get_job(&mut jobs, *job)?;
+        if !local_job.status.is_finished() {
+            local_job.status = JobStatus::Cancelled;
+        }
+        Ok(())
+    }
+}
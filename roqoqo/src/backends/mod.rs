@@ -0,0 +1,444 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Traits defining the standard form of roqoqo backends.
+//!
+//! roqoqo can be used to implement interfaces and backends to quantum hardware, quantum simulators and other software packages.
+//! While roqoqo does not require a certain design for general interfaces or backends,
+//! roqoqo::backends provides a trait for implementing backends that produce measurement results which can be evaluated to
+//! expectation values.
+//! This trait is supposed to be implemented for backends connecting to quantum simulators or to real quantum hardware devices.
+//!
+//! Note: The following backends are implemented in roqoqo and supported by HQS Quantum Simulations GmbH.
+//!
+//! Evaluated backends:
+//! * `aqt`, ( <https://github.com/HQSquantumsimulations/qoqo_aqt> ),
+//! * `mock` ( <https://github.com/HQSquantumsimulations/qoqo_mock> ),
+//! * `quest` ( <https://github.com/HQSquantumsimulations/qoqo-quest> ).
+//!
+//! Other backends:
+//! * `qasm` ( <https://github.com/HQSquantumsimulations/qoqo_qasm> ).
+//!
+//! In addition roqoqo ships simple pure-Rust simulator backends that can be used
+//! to run and test roqoqo programs without an external simulator:
+//! * [StatevectorSimulator] - A noiseless statevector simulator.
+//! * [DensityMatrixSimulator] - A density matrix simulator applying the noise PRAGMAs.
+//! * [StabilizerSimulator] - A Clifford tableau simulator for circuits with many qubits.
+//! * [MpsSimulator] - A matrix-product-state simulator for weakly entangled qubit chains.
+//! * [TrajectorySimulator] - A quantum-trajectory simulator unravelling the noise PRAGMAs.
+//! * [TensorNetworkSimulator] - A tensor-network simulator returning amplitudes of wide, shallow circuits.
+//!
+//! Backends for remote quantum hardware that queue circuits as jobs can implement [JobBackend]
+//! and be used as an [EvaluatingBackend] through the [BlockingJobBackend] adapter.
+//! [LocalJobBackend] is an in-process stand-in job backend for testing.
+//! With the `http` feature, `HttpBackend` runs circuits as jobs of a REST service.
+//!
+//! [DeviceValidatingBackend] refuses to run circuits that cannot be executed on a [crate::devices::Device]
+//! and [DeviceNoiseBackend] adds the decoherence noise of a device to the circuits it runs.
+//! [ReadoutErrorBackend] adds the readout errors of a [crate::measurements::ReadoutErrorModel] to the measured bits.
+//! [ObservedBackend] notifies a [BackendObserver] before and after every circuit and measurement,
+//! [LoggingObserver] and [SummaryObserver] log the runs and collect a [RunSummary].
+//! [ResourceEstimationBackend] does not run circuits but estimates the shots, gates and time needed on a device.
+//! [RetryBackend] retries circuits failing with transient errors such as timeouts according to a [RetryPolicy].
+//! With the `serialize` feature, [ResultCachingBackend] wraps any [EvaluatingBackend] and
+//! caches the results of repeatedly executed circuits in memory and optionally on disk.
+
+use std::collections::HashMap;
+
+#[doc(hidden)]
+mod density_matrix_simulator;
+mod simulator_helpers;
+pub use density_matrix_simulator::*;
+pub use simulator_helpers::CachedSimulatorState;
+#[doc(hidden)]
+mod device_noise_backend;
+pub use device_noise_backend::*;
+#[doc(hidden)]
+mod device_validating_backend;
+pub use device_validating_backend::*;
+#[doc(hidden)]
+#[cfg(feature = "http")]
+mod http_backend;
+#[cfg(feature = "http")]
+pub use http_backend::*;
+#[doc(hidden)]
+mod job_backend;
+pub use job_backend::*;
+#[doc(hidden)]
+mod mps_simulator;
+pub use mps_simulator::*;
+#[doc(hidden)]
+mod observed_backend;
+pub use observed_backend::*;
+#[doc(hidden)]
+mod readout_error_backend;
+pub use readout_error_backend::*;
+#[doc(hidden)]
+mod resource_estimation_backend;
+pub use resource_estimation_backend::*;
+#[cfg(feature = "serialize")]
+#[doc(hidden)]
+mod result_caching_backend;
+#[cfg(feature = "serialize")]
+pub use result_caching_backend::*;
+#[doc(hidden)]
+mod retry_backend;
+pub use retry_backend::*;
+#[doc(hidden)]
+mod stabilizer_simulator;
+pub use stabilizer_simulator::*;
+#[doc(hidden)]
+mod statevector_simulator;
+pub use statevector_simulator::*;
+#[doc(hidden)]
+mod tensor_network_simulator;
+pub use tensor_network_simulator::*;
+#[doc(hidden)]
+mod trajectory_simulator;
+pub use trajectory_simulator::*;
+
+use crate::operations::Operation;
+use crate::registers::{BitOutputRegister, ComplexOutputRegister, FloatOutputRegister};
+use crate::Circuit;
+use crate::{
+    measurements::{Measure, MeasureExpectationValues},
+    RoqoqoBackendError,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Result of functions running a full circuit and producing output registers.
+pub type RegisterResult = Result<
+    (
+        HashMap<String, BitOutputRegister>,
+        HashMap<String, FloatOutputRegister>,
+        HashMap<String, ComplexOutputRegister>,
+    ),
+    RoqoqoBackendError,
+>;
+
+/// Trait for Backends that can evaluate measurements to expectation values.
+pub trait EvaluatingBackend: Sized {
+    /// Runs a circuit with the backend.
+    ///
+    /// A circuit is passed to the backend and executed.
+    /// During execution values are written to and read from classical registers
+    /// ([crate::registers::BitRegister], [crate::registers::FloatRegister] and [crate::registers::ComplexRegister]).
+    /// To produce sufficient statistics for evaluating expectationg values,
+    /// circuits have to be run multiple times.
+    /// The results of each repetition are concatenated in OutputRegisters
+    /// ([crate::registers::BitOutputRegister], [crate::registers::FloatOutputRegister] and [crate::registers::ComplexOutputRegister]).  
+    ///
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the evaluated circuits.
+    fn run_circuit(&self, circuit: &Circuit) -> RegisterResult {
+        self.run_circuit_iterator(circuit.iter())
+    }
+
+    /// Runs each operation obtained from an iterator over operations on the backend.
+    ///
+    /// An iterator over operations is passed to the backend and executed.
+    /// During execution values are written to and read from classical registers
+    /// ([crate::registers::BitRegister], [crate::registers::FloatRegister] and [crate::registers::ComplexRegister]).
+    /// To produce sufficient statistics for evaluating expectationg values,
+    /// circuits have to be run multiple times.
+    /// The results of each repetition are concatenated in OutputRegisters
+    /// ([crate::registers::BitOutputRegister], [crate::registers::FloatOutputRegister] and [crate::registers::ComplexOutputRegister]).  
+    ///
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The iterator over operations that is run on the backend (corresponds to a circuit).
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the evaluated circuits.
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult;
+
+    /// Runs all circuits corresponding to one measurement with the backend.
+    ///
+    /// An expectation value measurement in general involves several circuits.
+    /// Each circuit is passes to the backend and executed separately.
+    /// During execution values are written to and read from classical registers
+    /// ([crate::registers::BitRegister], [crate::registers::FloatRegister] and [crate::registers::ComplexRegister]).
+    /// To produce sufficient statistics for evaluating expectationg values,
+    /// circuits have to be run multiple times.
+    /// The results of each repetition are concatenated in OutputRegisters
+    /// ([crate::registers::BitOutputRegister], [crate::registers::FloatOutputRegister] and [crate::registers::ComplexOutputRegister]).  
+    /// At the end all OutputRegisters are combined in a single HashMap for each type of register.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the evaluated measurement circuits.
+    fn run_measurement_registers<T>(&self, measurement: &T) -> RegisterResult
+    where
+        T: Measure,
+    {
+        let mut registers = OutputRegisters::default();
+        for circuit in measurement.circuits() {
+            let circuit_registers = match measurement.constant_circuit() {
+                Some(x) => self.run_circuit_iterator(x.iter().chain(circuit.iter()))?,
+                None => self.run_circuit_iterator(circuit.iter())?,
+            };
+            registers.append(circuit_registers);
+        }
+        Ok(registers.into())
+    }
+
+    /// Runs all circuits corresponding to one measurement in parallel with the backend.
+    ///
+    /// Thread-parallel variant of [EvaluatingBackend::run_measurement_registers] for backends that
+    /// can be shared between threads. The circuits are dispatched to the rayon thread pool,
+    /// the resulting registers are combined in the order of the circuits of the measurement,
+    /// so that the result does not depend on the order in which the threads finish.
+    /// When several circuits fail, the error of the first failing circuit is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the evaluated measurement circuits.
+    #[cfg(feature = "parallel")]
+    fn run_measurement_registers_parallel<T>(&self, measurement: &T) -> RegisterResult
+    where
+        Self: Sync,
+        T: Measure,
+    {
+        let circuits: Vec<Vec<Operation>> = measurement
+            .circuits()
+            .map(|circuit| match measurement.constant_circuit() {
+                Some(x) => x.iter().chain(circuit.iter()).cloned().collect(),
+                None => circuit.iter().cloned().collect(),
+            })
+            .collect();
+        let results: Vec<RegisterResult> = circuits
+            .into_par_iter()
+            .map(|operations| self.run_circuit_iterator(operations.iter()))
+            .collect();
+        OutputRegisters::from_results(results)
+    }
+
+    /// Evaluates expectation values of a measurement with the backend, running its circuits in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Option<HashMap<String, f64>>)` - The HashMap of measurement results.
+    /// `Err(RoqoqoBackendError)` - The measurement run failed.
+    #[cfg(feature = "parallel")]
+    fn run_measurement_parallel<T>(
+        &self,
+        measurement: &T,
+    ) -> Result<Option<HashMap<String, f64>>, RoqoqoBackendError>
+    where
+        Self: Sync,
+        T: MeasureExpectationValues,
+    {
+        let (bit_registers, float_registers, complex_registers) =
+            self.run_measurement_registers_parallel(measurement)?;
+        Ok(measurement.evaluate(bit_registers, float_registers, complex_registers)?)
+    }
+
+    /// Evaluates expectation values of a measurement with the backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Option<HashMap<String, f64>>)` - The HashMap of measurement results.
+    /// `Err(RoqoqoBackendError)` - The measurement run failed.
+    fn run_measurement<T>(
+        &self,
+        measurement: &T,
+    ) -> Result<Option<HashMap<String, f64>>, RoqoqoBackendError>
+    where
+        T: MeasureExpectationValues,
+    {
+        let (bit_registers, float_registers, complex_registers) =
+            self.run_measurement_registers(measurement)?;
+        Ok(measurement.evaluate(bit_registers, float_registers, complex_registers)?)
+    }
+}
+
+/// Trait for backends that can cache their state after the constant circuit of a measurement.
+///
+/// All circuits of a measurement start with the same constant circuit.
+/// Instead of executing the constant circuit again for every measurement circuit,
+/// backends implementing this trait execute it once in [CachingBackend::prepare_constant_circuit]
+/// and run every measurement circuit starting from the cached state.
+pub trait CachingBackend: EvaluatingBackend {
+    /// The state of the backend after executing a constant circuit.
+    type CachedState;
+
+    /// Executes the constant circuit of a measurement and returns the resulting state.
+    ///
+    /// # Arguments
+    ///
+    /// * `constant_circuit` - The constant circuit of the measurement (None if there is none).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self::CachedState)` - The state of the backend after the constant circuit.
+    /// * `Err(RoqoqoBackendError)` - The constant circuit could not be executed.
+    fn prepare_constant_circuit(
+        &self,
+        constant_circuit: Option<&Circuit>,
+    ) -> Result<Self::CachedState, RoqoqoBackendError>;
+
+    /// Runs a circuit starting from a cached state.
+    ///
+    /// The result is the same as running the constant circuit used to prepare the cached state
+    /// followed by `circuit` with [EvaluatingBackend::run_circuit_iterator].
+    ///
+    /// # Arguments
+    ///
+    /// * `cached_state` - The state after the constant circuit.
+    /// * `circuit` - The circuit that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the evaluated circuit.
+    fn run_circuit_from_cached_state(
+        &self,
+        cached_state: &Self::CachedState,
+        circuit: &Circuit,
+    ) -> RegisterResult;
+
+    /// Runs all circuits corresponding to one measurement, executing the constant circuit only once.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the evaluated measurement circuits.
+    fn run_measurement_registers_cached<T>(&self, measurement: &T) -> RegisterResult
+    where
+        T: Measure,
+    {
+        let cached_state =
+            self.prepare_constant_circuit(measurement.constant_circuit().as_ref())?;
+        let mut registers = OutputRegisters::default();
+        for circuit in measurement.circuits() {
+            registers.append(self.run_circuit_from_cached_state(&cached_state, circuit)?);
+        }
+        Ok(registers.into())
+    }
+
+    /// Runs all circuits corresponding to one measurement in parallel from a cached constant circuit state.
+    ///
+    /// Combines [CachingBackend::run_measurement_registers_cached] with the parallel execution of
+    /// [EvaluatingBackend::run_measurement_registers_parallel]. The registers are combined in the
+    /// order of the circuits of the measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement that is run on the backend.
+    ///
+    /// # Returns
+    ///
+    /// `RegisterResult` - The output registers written by the evaluated measurement circuits.
+    #[cfg(feature = "parallel")]
+    fn run_measurement_registers_cached_parallel<T>(&self, measurement: &T) -> RegisterResult
+    where
+        Self: Sync,
+        Self::CachedState: Sync,
+        T: Measure,
+    {
+        let cached_state =
+            self.prepare_constant_circuit(measurement.constant_circuit().as_ref())?;
+        let circuits: Vec<Circuit> = measurement.circuits().cloned().collect();
+        let results: Vec<RegisterResult> = circuits
+            .into_par_iter()
+            .map(|circuit| self.run_circuit_from_cached_state(&cached_state, &circuit))
+            .collect();
+        OutputRegisters::from_results(results)
+    }
+}
+
+/// Output registers of a successfully executed circuit.
+pub(crate) type Registers = (
+    HashMap<String, BitOutputRegister>,
+    HashMap<String, FloatOutputRegister>,
+    HashMap<String, ComplexOutputRegister>,
+);
+
+/// Output registers of several circuits combined into one HashMap per register type.
+#[derive(Debug, Default)]
+pub(crate) struct OutputRegisters {
+    bit_registers: HashMap<String, BitOutputRegister>,
+    float_registers: HashMap<String, FloatOutputRegister>,
+    complex_registers: HashMap<String, ComplexOutputRegister>,
+}
+
+impl OutputRegisters {
+    /// Appends the output registers of one circuit to the combined registers.
+    fn append(&mut self, (bit_registers, float_registers, complex_registers): Registers) {
+        for (key, mut val) in bit_registers.into_iter() {
+            self.bit_registers.entry(key).or_default().append(&mut val);
+        }
+        for (key, mut val) in float_registers.into_iter() {
+            self.float_registers
+                .entry(key)
+                .or_default()
+                .append(&mut val);
+        }
+        for (key, mut val) in complex_registers.into_iter() {
+            self.complex_registers
+                .entry(key)
+                .or_default()
+                .append(&mut val);
+        }
+    }
+
+    /// Combines the results of several circuits in order, returning the first error.
+    pub(crate) fn from_results(
+        results: impl IntoIterator<Item = RegisterResult>,
+    ) -> RegisterResult {
+        let mut registers = OutputRegisters::default();
+        for result in results.into_iter() {
+            registers.append(result?);
+        }
+        Ok(registers.into())
+    }
+}
+
+impl From<OutputRegisters> for Registers {
+    fn from(registers: OutputRegisters) -> Self {
+        (
+            registers.bit_registers,
+            registers.float_registers,
+            registers.complex_registers,
+        )
+    }
+}
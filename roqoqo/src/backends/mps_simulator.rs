@@ -0,0 +1,687 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::simulator_helpers::{
+    check_qubit_mapping, check_qubits, create_rng, number_of_repetitions, pauli_matrix,
+    SimulationRegisters,
+};
+use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::operations::{
+    Operate, OperateGate, OperateSingleQubit, OperateTwoQubit, Operation, SingleQubitGateOperation,
+    TwoQubitGateOperation,
+};
+use crate::registers::{BitOutputRegister, BitRegister};
+use crate::RoqoqoBackendError;
+use nalgebra::DMatrix;
+use ndarray::{Array1, Array2, Array3};
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Name of the float output register the MpsSimulator writes the accumulated truncation error to.
+pub const MPS_TRUNCATION_ERROR_REGISTER: &str = "mps_truncation_error";
+
+/// Maximum number of qubits of the dense vectors and matrices constructed from the state.
+const MAX_DENSE_QUBITS: usize = 24;
+
+/// Matrix-product-state simulator implementing [EvaluatingBackend].
+///
+/// The simulator represents the state of a chain of qubits as a matrix product state,
+/// where qubit `i` is site `i` of the chain. Single-qubit gates are applied locally.
+/// Two-qubit gates are applied to neighbouring sites and the resulting two-site tensor
+/// is split with a singular value decomposition. Singular values are discarded as long as
+/// their summed squares (the discarded weight) stay below the truncation threshold,
+/// and at most `max_bond_dimension` singular values are kept.
+/// Two-qubit gates between qubits that are not neighbours are applied by moving one
+/// of the qubits next to the other with SWAP operations and moving it back afterwards.
+///
+/// The accumulated discarded weight of a run is written to the float output register
+/// [MPS_TRUNCATION_ERROR_REGISTER], alongside the registers defined in the circuit.
+/// Circuits defining a register with this name are refused.
+///
+/// Supported measurement operations are [crate::operations::MeasureQubit],
+/// [crate::operations::PragmaRepeatedMeasurement] and [crate::operations::PragmaGetPauliProduct].
+/// [crate::operations::PragmaGetStateVector], [crate::operations::PragmaGetDensityMatrix] and
+/// [crate::operations::PragmaGetOccupationProbability] construct the full state vector and are
+/// therefore only usable for small numbers of qubits: they return an error if the state vector
+/// (or for the density matrix, the matrix) would have more than 2^24 entries.
+/// Multi-qubit gates and noise PRAGMAs are not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MpsSimulator {
+    /// The number of qubits of the simulated quantum register.
+    number_qubits: usize,
+    /// The maximum bond dimension kept after a two-qubit gate.
+    max_bond_dimension: usize,
+    /// The maximum weight of the singular values discarded after a two-qubit gate.
+    truncation_threshold: f64,
+    /// Optional seed of the random number generator used for measurements.
+    random_seed: Option<u64>,
+}
+
+impl MpsSimulator {
+    /// Creates a new MpsSimulator.
+    ///
+    /// The maximum bond dimension defaults to 64 and the truncation threshold to 1e-12.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_qubits` - The number of qubits of the simulated quantum register.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new MpsSimulator.
+    pub fn new(number_qubits: usize) -> Self {
+        Self {
+            number_qubits,
+            max_bond_dimension: 64,
+            truncation_threshold: 1e-12,
+            random_seed: None,
+        }
+    }
+
+    /// Returns the number of qubits of the simulated quantum register.
+    pub fn number_qubits(&self) -> usize {
+        self.number_qubits
+    }
+
+    /// Returns the maximum bond dimension kept after a two-qubit gate.
+    pub fn max_bond_dimension(&self) -> usize {
+        self.max_bond_dimension
+    }
+
+    /// Sets the maximum bond dimension kept after a two-qubit gate.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_bond_dimension` - The new maximum bond dimension (at least 1).
+    pub fn set_max_bond_dimension(&mut self, max_bond_dimension: usize) {
+        self.max_bond_dimension = max_bond_dimension.max(1);
+    }
+
+    /// Returns the maximum weight of the singular values discarded after a two-qubit gate.
+    pub fn truncation_threshold(&self) -> f64 {
+        self.truncation_threshold
+    }
+
+    /// Sets the maximum weight of the singular values discarded after a two-qubit gate.
+    ///
+    /// # Arguments
+    ///
+    /// * `truncation_threshold` - The new truncation threshold.
+    pub fn set_truncation_threshold(&mut self, truncation_threshold: f64) {
+        self.truncation_threshold = truncation_threshold;
+    }
+
+    /// Returns the seed of the random number generator.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets the seed of the random number generator.
+    ///
+    /// When a seed is set, every run of a circuit produces the same measurement results.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_seed` - The seed of the random number generator (None for a random seed).
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.random_seed = random_seed;
+    }
+
+    /// Applies a single operation to the matrix product state and the classical registers.
+    fn apply_operation(
+        &self,
+        operation: &Operation,
+        mps: &mut MatrixProductState,
+        registers: &mut SimulationRegisters,
+        rng: &mut StdRng,
+    ) -> Result<(), RoqoqoBackendError> {
+        if registers.define(operation) {
+            return Ok(());
+        }
+        check_qubits(operation, self.number_qubits)?;
+        let tags = operation.tags();
+        if tags.contains(&"SingleQubitGateOperation") {
+            let gate = SingleQubitGateOperation::try_from(operation.clone())?;
+            mps.apply_single_qubit_gate(&gate.unitary_matrix()?, *gate.qubit());
+            return Ok(());
+        }
+        if tags.contains(&"TwoQubitGateOperation") {
+            let gate = TwoQubitGateOperation::try_from(operation.clone())?;
+            mps.apply_two_qubit_gate(
+                &gate.unitary_matrix()?,
+                *gate.control(),
+                *gate.target(),
+                self.max_bond_dimension,
+                self.truncation_threshold,
+            );
+            return Ok(());
+        }
+        match operation {
+            Operation::MeasureQubit(op) => {
+                let result = rng.gen::<f64>() < mps.probability_one(*op.qubit());
+                mps.project(*op.qubit(), result);
+                registers.set_bit(op.readout(), *op.readout_index(), result)
+            }
+            Operation::PragmaActiveReset(op) => {
+                let result = rng.gen::<f64>() < mps.probability_one(*op.qubit());
+                mps.project(*op.qubit(), result);
+                if result {
+                    mps.apply_single_qubit_gate(&pauli_matrix(1)?, *op.qubit());
+                }
+                Ok(())
+            }
+            Operation::PragmaRepeatedMeasurement(op) => {
+                check_qubit_mapping(op.qubit_mapping(), self.number_qubits)?;
+                let length = registers.bit_register_length(op.readout())?;
+                let mut sampling_mps = mps.clone();
+                sampling_mps.move_center(0);
+                let rows: BitOutputRegister = (0..*op.number_measurements())
+                    .map(|_| {
+                        let sample = sampling_mps.sample(rng);
+                        let mut row: BitRegister = vec![false; length];
+                        match op.qubit_mapping() {
+                            Some(mapping) => {
+                                for (qubit, index) in mapping.iter() {
+                                    if *index < length {
+                                        row[*index] = sample[*qubit];
+                                    }
+                                }
+                            }
+                            None => {
+                                for (bit, result) in row.iter_mut().zip(sample.iter()) {
+                                    *bit = *result;
+                                }
+                            }
+                        }
+                        row
+                    })
+                    .collect();
+                registers.append_repeated_measurement(op.readout(), rows);
+                Ok(())
+            }
+            Operation::PragmaGetPauliProduct(op) => {
+                let tmp_mps = self.prepared_copy(mps, Some(op.circuit()), registers, rng)?;
+                let mut operators: HashMap<usize, Array2<Complex64>> = HashMap::new();
+                for (qubit, pauli) in op.qubit_paulis().iter() {
+                    operators.insert(*qubit, pauli_matrix(*pauli)?);
+                }
+                registers.float_registers.insert(
+                    op.readout().clone(),
+                    vec![tmp_mps.expectation_value(&operators)],
+                );
+                Ok(())
+            }
+            Operation::PragmaGetStateVector(op) => {
+                let tmp_mps = self.prepared_copy(mps, op.circuit().as_ref(), registers, rng)?;
+                registers
+                    .complex_registers
+                    .insert(op.readout().clone(), tmp_mps.to_statevector()?.to_vec());
+                Ok(())
+            }
+            Operation::PragmaGetDensityMatrix(op) => {
+                if 2 * self.number_qubits > MAX_DENSE_QUBITS {
+                    return Err(dense_size_error(2 * self.number_qubits));
+                }
+                let tmp_mps = self.prepared_copy(mps, op.circuit().as_ref(), registers, rng)?;
+                let state = tmp_mps.to_statevector()?;
+                let density_matrix: Vec<Complex64> = state
+                    .iter()
+                    .flat_map(|row| state.iter().map(move |column| row * column.conj()))
+                    .collect();
+                registers
+                    .complex_registers
+                    .insert(op.readout().clone(), density_matrix);
+                Ok(())
+            }
+            Operation::PragmaGetOccupationProbability(op) => {
+                let tmp_mps = self.prepared_copy(mps, op.circuit().as_ref(), registers, rng)?;
+                registers.float_registers.insert(
+                    op.readout().clone(),
+                    tmp_mps
+                        .to_statevector()?
+                        .iter()
+                        .map(|x| x.norm_sqr())
+                        .collect(),
+                );
+                Ok(())
+            }
+            Operation::PragmaSetNumberOfMeasurements(_)
+            | Operation::PragmaGlobalPhase(_)
+            | Operation::PragmaRepeatGate(_)
+            | Operation::PragmaOverrotation(_)
+            | Operation::PragmaBoostNoise(_)
+            | Operation::PragmaStopParallelBlock(_)
+            | Operation::PragmaSleep(_)
+            | Operation::PragmaStartDecompositionBlock(_)
+            | Operation::PragmaStopDecompositionBlock(_) => Ok(()),
+            _ => Err(RoqoqoBackendError::OperationNotInBackend {
+                backend: "MpsSimulator",
+                hqslang: operation.hqslang(),
+            }),
+        }
+    }
+
+    /// Returns a copy of the matrix product state with the optional preparation circuit applied.
+    fn prepared_copy(
+        &self,
+        mps: &MatrixProductState,
+        circuit: Option<&crate::Circuit>,
+        registers: &mut SimulationRegisters,
+        rng: &mut StdRng,
+    ) -> Result<MatrixProductState, RoqoqoBackendError> {
+        let mut tmp_mps = mps.clone();
+        if let Some(circuit) = circuit {
+            for operation in circuit.iter() {
+                self.apply_operation(operation, &mut tmp_mps, registers, rng)?;
+            }
+        }
+        Ok(tmp_mps)
+    }
+}
+
+impl EvaluatingBackend for MpsSimulator {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        let mut rng = create_rng(self.random_seed);
+        let mut registers = SimulationRegisters::new();
+        let mut truncation_errors: Vec<Vec<f64>> = Vec::new();
+        for _ in 0..number_of_repetitions(&operations) {
+            let mut mps = MatrixProductState::new(self.number_qubits);
+            for operation in operations.iter() {
+                self.apply_operation(operation, &mut mps, &mut registers, &mut rng)?;
+            }
+            registers.finish_run();
+            truncation_errors.push(vec![mps.truncation_error]);
+        }
+        let (bit_registers, mut float_registers, complex_registers) = registers.into_output()?;
+        if bit_registers.contains_key(MPS_TRUNCATION_ERROR_REGISTER)
+            || float_registers.contains_key(MPS_TRUNCATION_ERROR_REGISTER)
+            || complex_registers.contains_key(MPS_TRUNCATION_ERROR_REGISTER)
+        {
+            return Err(RoqoqoBackendError::GenericError {
+                msg: format!(
+                    "Output register {} of the MpsSimulator clashes with a register of the circuit",
+                    MPS_TRUNCATION_ERROR_REGISTER
+                ),
+            });
+        }
+        float_registers.insert(MPS_TRUNCATION_ERROR_REGISTER.to_string(), truncation_errors);
+        Ok((bit_registers, float_registers, complex_registers))
+    }
+}
+
+/// A matrix product state in mixed canonical form.
+///
+/// Every tensor has the shape (left bond, physical, right bond). All tensors left of
+/// the orthogonality center are left-orthonormal and all tensors right of it are right-orthonormal.
+#[derive(Debug, Clone)]
+struct MatrixProductState {
+    /// The tensors of the sites of the chain.
+    tensors: Vec<Array3<Complex64>>,
+    /// The site of the orthogonality center.
+    center: usize,
+    /// The accumulated weight of the discarded singular values.
+    truncation_error: f64,
+}
+
+impl MatrixProductState {
+    /// Creates the matrix product state of the |0...0> state.
+    fn new(number_qubits: usize) -> Self {
+        let mut tensor: Array3<Complex64> = Array3::zeros((1, 2, 1));
+        tensor[(0, 0, 0)] = Complex64::new(1.0, 0.0);
+        Self {
+            tensors: vec![tensor; number_qubits],
+            center: 0,
+            truncation_error: 0.0,
+        }
+    }
+
+    /// Moves the orthogonality center to the given site.
+    fn move_center(&mut self, site: usize) {
+        while self.center < site {
+            let tensor = &self.tensors[self.center];
+            let (left, _, right) = tensor.dim();
+            let matrix = Array2::from_shape_fn((left * 2, right), |(row, column)| {
+                tensor[(row / 2, row % 2, column)]
+            });
+            let (u, singular_values, v_t) = svd(&matrix);
+            let kept = number_nonzero(&singular_values);
+            self.tensors[self.center] =
+                Array3::from_shape_fn((left, 2, kept), |(l, p, k)| u[(l * 2 + p, k)]);
+            let remainder =
+                Array2::from_shape_fn((kept, right), |(k, r)| v_t[(k, r)] * singular_values[k]);
+            let next = &self.tensors[self.center + 1];
+            self.tensors[self.center + 1] = contract_left(&remainder, next);
+            self.center += 1;
+        }
+        while self.center > site {
+            let tensor = &self.tensors[self.center];
+            let (left, _, right) = tensor.dim();
+            let matrix = Array2::from_shape_fn((left, 2 * right), |(row, column)| {
+                tensor[(row, column / right, column % right)]
+            });
+            let (u, singular_values, v_t) = svd(&matrix);
+            let kept = number_nonzero(&singular_values);
+            self.tensors[self.center] =
+                Array3::from_shape_fn((kept, 2, right), |(k, p, r)| v_t[(k, p * right + r)]);
+            let remainder =
+                Array2::from_shape_fn((left, kept), |(l, k)| u[(l, k)] * singular_values[k]);
+            let previous = &self.tensors[self.center - 1];
+            self.tensors[self.center - 1] = contract_right(previous, &remainder);
+            self.center -= 1;
+        }
+    }
+
+    /// Applies a single-qubit gate to a site.
+    fn apply_single_qubit_gate(&mut self, matrix: &Array2<Complex64>, site: usize) {
+        let tensor = &self.tensors[site];
+        self.tensors[site] = Array3::from_shape_fn(tensor.dim(), |(l, p, r)| {
+            matrix[(p, 0)] * tensor[(l, 0, r)] + matrix[(p, 1)] * tensor[(l, 1, r)]
+        });
+    }
+
+    /// Applies a two-qubit gate, moving the target next to the control with SWAPs if necessary.
+    ///
+    /// The control qubit corresponds to the most significant bit of the gate matrix.
+    fn apply_two_qubit_gate(
+        &mut self,
+        matrix: &Array2<Complex64>,
+        control: usize,
+        target: usize,
+        max_bond_dimension: usize,
+        truncation_threshold: f64,
+    ) {
+        let swap = swap_matrix();
+        if control < target {
+            for site in (control + 1..target).rev() {
+                self.apply_two_site_gate(&swap, site, max_bond_dimension, truncation_threshold);
+            }
+            self.apply_two_site_gate(matrix, control, max_bond_dimension, truncation_threshold);
+            for site in control + 1..target {
+                self.apply_two_site_gate(&swap, site, max_bond_dimension, truncation_threshold);
+            }
+        } else {
+            let flipped = Array2::from_shape_fn((4, 4), |(row, column)| {
+                matrix[((row % 2) * 2 + row / 2, (column % 2) * 2 + column / 2)]
+            });
+            for site in target..control - 1 {
+                self.apply_two_site_gate(&swap, site, max_bond_dimension, truncation_threshold);
+            }
+            self.apply_two_site_gate(
+                &flipped,
+                control - 1,
+                max_bond_dimension,
+                truncation_threshold,
+            );
+            for site in (target..control - 1).rev() {
+                self.apply_two_site_gate(&swap, site, max_bond_dimension, truncation_threshold);
+            }
+        }
+    }
+
+    /// Applies a two-site gate to the sites `site` and `site + 1` and truncates the bond.
+    ///
+    /// The qubit on `site` corresponds to the most significant bit of the gate matrix.
+    fn apply_two_site_gate(
+        &mut self,
+        matrix: &Array2<Complex64>,
+        site: usize,
+        max_bond_dimension: usize,
+        truncation_threshold: f64,
+    ) {
+        self.move_center(site);
+        let first = &self.tensors[site];
+        let second = &self.tensors[site + 1];
+        let (left, _, bond) = first.dim();
+        let right = second.dim().2;
+        let mut theta: Array2<Complex64> = Array2::zeros((left * 2, 2 * right));
+        for l in 0..left {
+            for r in 0..right {
+                let mut local = [Complex64::new(0.0, 0.0); 4];
+                for (index, value) in local.iter_mut().enumerate() {
+                    for k in 0..bond {
+                        *value += first[(l, index / 2, k)] * second[(k, index % 2, r)];
+                    }
+                }
+                for row in 0..4 {
+                    let value: Complex64 = (0..4)
+                        .map(|column| matrix[(row, column)] * local[column])
+                        .sum();
+                    theta[(l * 2 + row / 2, (row % 2) * right + r)] = value;
+                }
+            }
+        }
+        let (u, singular_values, v_t) = svd(&theta);
+        let total_weight: f64 = singular_values.iter().map(|s| s * s).sum();
+        let mut kept = singular_values.len();
+        let mut discarded_weight = 0.0;
+        while kept > 1 {
+            let weight = singular_values[kept - 1].powi(2) / total_weight;
+            if discarded_weight + weight > truncation_threshold {
+                break;
+            }
+            discarded_weight += weight;
+            kept -= 1;
+        }
+        while kept > max_bond_dimension {
+            discarded_weight += singular_values[kept - 1].powi(2) / total_weight;
+            kept -= 1;
+        }
+        self.truncation_error += discarded_weight;
+        let kept_weight: f64 = singular_values[..kept].iter().map(|s| s * s).sum();
+        let normalization = kept_weight.sqrt();
+        self.tensors[site] = Array3::from_shape_fn((left, 2, kept), |(l, p, k)| u[(l * 2 + p, k)]);
+        self.tensors[site + 1] = Array3::from_shape_fn((kept, 2, right), |(k, p, r)| {
+            v_t[(k, p * right + r)] * singular_values[k] / normalization
+        });
+        self.center = site + 1;
+    }
+
+    /// Returns the probability to measure a qubit in the excited state.
+    fn probability_one(&mut self, site: usize) -> f64 {
+        self.move_center(site);
+        let tensor = &self.tensors[site];
+        let total: f64 = tensor.iter().map(|x| x.norm_sqr()).sum();
+        let excited: f64 = tensor
+            .indexed_iter()
+            .filter(|((_, p, _), _)| *p == 1)
+            .map(|(_, x)| x.norm_sqr())
+            .sum();
+        excited / total
+    }
+
+    /// Projects a qubit onto a measurement result and renormalizes the state.
+    fn project(&mut self, site: usize, result: bool) {
+        self.move_center(site);
+        let tensor = &mut self.tensors[site];
+        for ((_, p, _), value) in tensor.indexed_iter_mut() {
+            if (p == 1) != result {
+                *value = Complex64::new(0.0, 0.0);
+            }
+        }
+        let norm: f64 = tensor.iter().map(|x| x.norm_sqr()).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            tensor.mapv_inplace(|x| x / norm);
+        }
+    }
+
+    /// Samples a computational basis state, the orthogonality center must be on site 0.
+    ///
+    /// Returns the measured value of every site.
+    fn sample(&self, rng: &mut StdRng) -> Vec<bool> {
+        let mut boundary: Array1<Complex64> = Array1::from_elem(1, Complex64::new(1.0, 0.0));
+        let mut basis_state: Vec<bool> = Vec::with_capacity(self.tensors.len());
+        for tensor in self.tensors.iter() {
+            let (left, _, right) = tensor.dim();
+            let candidates: Vec<Array1<Complex64>> = (0..2)
+                .map(|p| {
+                    Array1::from_shape_fn(right, |r| {
+                        (0..left).map(|l| boundary[l] * tensor[(l, p, r)]).sum()
+                    })
+                })
+                .collect();
+            let weights: Vec<f64> = candidates
+                .iter()
+                .map(|candidate| candidate.iter().map(|x| x.norm_sqr()).sum())
+                .collect();
+            let result = rng.gen::<f64>() * (weights[0] + weights[1]) >= weights[0];
+            let weight = if result { weights[1] } else { weights[0] };
+            boundary = candidates[result as usize].mapv(|x| x / weight.sqrt());
+            basis_state.push(result);
+        }
+        basis_state
+    }
+
+    /// Returns the expectation value of a product of single-site operators.
+    fn expectation_value(&self, operators: &HashMap<usize, Array2<Complex64>>) -> f64 {
+        let mut environment: Array2<Complex64> =
+            Array2::from_elem((1, 1), Complex64::new(1.0, 0.0));
+        let mut norm: Array2<Complex64> = Array2::from_elem((1, 1), Complex64::new(1.0, 0.0));
+        let identity = pauli_matrix(0).unwrap_or_else(|_| Array2::eye(2));
+        for (site, tensor) in self.tensors.iter().enumerate() {
+            let operator = operators.get(&site).unwrap_or(&identity);
+            environment = transfer(&environment, tensor, operator);
+            norm = transfer(&norm, tensor, &identity);
+        }
+        (environment[(0, 0)] / norm[(0, 0)]).re
+    }
+
+    /// Contracts the matrix product state into the full state vector.
+    ///
+    /// Fails if the state vector would have more than 2^[MAX_DENSE_QUBITS] entries.
+    fn to_statevector(&self) -> Result<Array1<Complex64>, RoqoqoBackendError> {
+        if self.tensors.len() > MAX_DENSE_QUBITS {
+            return Err(dense_size_error(self.tensors.len()));
+        }
+        // Rows: basis states of the already contracted sites, columns: open right bond
+        let mut state: Array2<Complex64> = Array2::from_elem((1, 1), Complex64::new(1.0, 0.0));
+        for (site, tensor) in self.tensors.iter().enumerate() {
+            let (left, _, right) = tensor.dim();
+            let dimension = state.nrows();
+            state = Array2::from_shape_fn((dimension * 2, right), |(index, r)| {
+                let previous = index & ((1 << site) - 1);
+                let p = index >> site;
+                (0..left)
+                    .map(|l| state[(previous, l)] * tensor[(l, p, r)])
+                    .sum()
+            });
+        }
+        Ok(state.column(0).to_owned())
+    }
+}
+
+/// Returns the error for dense outputs with too many qubits.
+fn dense_size_error(number_qubits: usize) -> RoqoqoBackendError {
+    RoqoqoBackendError::GenericError {
+        msg: format!(
+            "Dense output of {} qubits exceeds the maximum of {} qubits of the MpsSimulator",
+            number_qubits, MAX_DENSE_QUBITS
+        ),
+    }
+}
+
+/// Returns the singular value decomposition of a matrix with singular values in descending order.
+fn svd(matrix: &Array2<Complex64>) -> (Array2<Complex64>, Vec<f64>, Array2<Complex64>) {
+    let (rows, columns) = matrix.dim();
+    let decomposition =
+        DMatrix::from_fn(rows, columns, |row, column| matrix[(row, column)]).svd(true, true);
+    let singular_values: Vec<f64> = decomposition.singular_values.iter().copied().collect();
+    let rank = singular_values.len();
+    // Both matrices are computed because compute_u and compute_v are set
+    let u = decomposition
+        .u
+        .unwrap_or_else(|| DMatrix::zeros(rows, rank));
+    let v_t = decomposition
+        .v_t
+        .unwrap_or_else(|| DMatrix::zeros(rank, columns));
+    (
+        Array2::from_shape_fn((rows, rank), |(row, k)| u[(row, k)]),
+        singular_values,
+        Array2::from_shape_fn((rank, columns), |(k, column)| v_t[(k, column)]),
+    )
+}
+
+/// Returns the number of singular values that are not numerically zero (at least one).
+fn number_nonzero(singular_values: &[f64]) -> usize {
+    let largest = singular_values.first().copied().unwrap_or(0.0);
+    singular_values
+        .iter()
+        .filter(|s| **s > 1e-14 * largest)
+        .count()
+        .max(1)
+}
+
+/// Contracts a matrix into the left bond of a tensor.
+fn contract_left(matrix: &Array2<Complex64>, tensor: &Array3<Complex64>) -> Array3<Complex64> {
+    let (_, _, right) = tensor.dim();
+    let (rows, inner) = matrix.dim();
+    Array3::from_shape_fn((rows, 2, right), |(k, p, r)| {
+        (0..inner).map(|l| matrix[(k, l)] * tensor[(l, p, r)]).sum()
+    })
+}
+
+/// Contracts a matrix into the right bond of a tensor.
+fn contract_right(tensor: &Array3<Complex64>, matrix: &Array2<Complex64>) -> Array3<Complex64> {
+    let (left, _, inner) = tensor.dim();
+    let columns = matrix.ncols();
+    Array3::from_shape_fn((left, 2, columns), |(l, p, k)| {
+        (0..inner).map(|r| tensor[(l, p, r)] * matrix[(r, k)]).sum()
+    })
+}
+
+/// Propagates an environment <psi|O|psi> through one site with a single-site operator.
+fn transfer(
+    environment: &Array2<Complex64>,
+    tensor: &Array3<Complex64>,
+    operator: &Array2<Complex64>,
+) -> Array2<Complex64> {
+    let (left, _, right) = tensor.dim();
+    // Apply the operator to the ket tensor
+    let ket = Array3::from_shape_fn((left, 2, right), |(l, p, r)| {
+        operator[(p, 0)] * tensor[(l, 0, r)] + operator[(p, 1)] * tensor[(l, 1, r)]
+    });
+    let mut result: Array2<Complex64> = Array2::zeros((right, right));
+    for a in 0..left {
+        for b in 0..left {
+            let value = environment[(a, b)];
+            if value == Complex64::new(0.0, 0.0) {
+                continue;
+            }
+            for p in 0..2 {
+                for r1 in 0..right {
+                    let bra = tensor[(a, p, r1)].conj() * value;
+                    for r2 in 0..right {
+                        result[(r1, r2)] += bra * ket[(b, p, r2)];
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Returns the matrix of the SWAP gate.
+fn swap_matrix() -> Array2<Complex64> {
+    Array2::from_shape_fn((4, 4), |(row, column)| {
+        if column == (row % 2) * 2 + row / 2 {
+            Complex64::new(1.0, 0.0)
+        } else {
+            Complex64::new(0.0, 0.0)
+        }
+    })
+}
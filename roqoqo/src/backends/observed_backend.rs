@@ -0,0 +1,557 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::measurements::Measure;
+use crate::operations::{Operate, Operation};
+use crate::{QuantumProgram, RoqoqoBackendError};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Statistics of the operations of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CircuitStatistics {
+    /// Total number of operations in the circuit.
+    pub number_operations: usize,
+    /// Number of operations by their hqslang name.
+    pub operation_counts: HashMap<String, usize>,
+    /// Number of executions of the circuit.
+    ///
+    /// The largest number of measurements set by a [crate::operations::PragmaRepeatedMeasurement]
+    /// or [crate::operations::PragmaSetNumberOfMeasurements], one if the circuit contains neither.
+    pub number_shots: usize,
+}
+
+impl CircuitStatistics {
+    /// Collects the statistics of the operations obtained from an iterator over operations.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The iterator over operations (corresponds to a circuit).
+    pub fn from_operations<'a>(circuit: impl IntoIterator<Item = &'a Operation>) -> Self {
+        let mut statistics = CircuitStatistics::default();
+        let mut number_shots = None;
+        for operation in circuit {
+            statistics.number_operations += 1;
+            *statistics
+                .operation_counts
+                .entry(operation.hqslang().to_string())
+                .or_insert(0) += 1;
+            let shots = match operation {
+                Operation::PragmaRepeatedMeasurement(op) => Some(*op.number_measurements()),
+                Operation::PragmaSetNumberOfMeasurements(op) => Some(*op.number_measurements()),
+                _ => None,
+            };
+            number_shots = number_shots.max(shots);
+        }
+        statistics.number_shots = number_shots.unwrap_or(1);
+        statistics
+    }
+}
+
+/// Trait for observers notified about the execution of circuits, measurements and quantum programs.
+///
+/// All methods do nothing by default, so that observers only implement the events they need.
+/// The circuit events are only sent for circuits run on their own. Measurements are run as a whole
+/// by the observed backend, so the measurement events carry the statistics of their circuits.
+/// Observers are notified by an [ObservedBackend] and by [QuantumProgram::run_observed]
+/// and [QuantumProgram::run_registers_observed].
+pub trait BackendObserver {
+    /// Called before a circuit is run.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistics` - The statistics of the circuit.
+    fn before_circuit(&self, statistics: &CircuitStatistics) {
+        let _ = statistics;
+    }
+
+    /// Called after a circuit has been run.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistics` - The statistics of the circuit.
+    /// * `duration` - The wall time of the run.
+    /// * `error` - The error of a failed run, None if the run succeeded.
+    fn after_circuit(
+        &self,
+        statistics: &CircuitStatistics,
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        let _ = (statistics, duration, error);
+    }
+
+    /// Called before the circuits of a measurement are run.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuits` - The statistics of the circuits of the measurement, each including the constant circuit.
+    fn before_measurement(&self, circuits: &[CircuitStatistics]) {
+        let _ = circuits;
+    }
+
+    /// Called after the circuits of a measurement have been run.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuits` - The statistics of the circuits of the measurement, each including the constant circuit.
+    /// * `duration` - The wall time of the measurement.
+    /// * `error` - The error of a failed measurement, None if the measurement succeeded.
+    fn after_measurement(
+        &self,
+        circuits: &[CircuitStatistics],
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        let _ = (circuits, duration, error);
+    }
+
+    /// Called before a quantum program is run.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The quantum program.
+    /// * `parameters` - The parameters the program is run with.
+    fn before_program(&self, program: &QuantumProgram, parameters: &[f64]) {
+        let _ = (program, parameters);
+    }
+
+    /// Called after a quantum program has been run.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The quantum program.
+    /// * `duration` - The wall time of the run.
+    /// * `error` - The error of a failed run, None if the run succeeded.
+    fn after_program(
+        &self,
+        program: &QuantumProgram,
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        let _ = (program, duration, error);
+    }
+}
+
+impl<O> BackendObserver for &O
+where
+    O: BackendObserver + ?Sized,
+{
+    fn before_circuit(&self, statistics: &CircuitStatistics) {
+        (**self).before_circuit(statistics)
+    }
+
+    fn after_circuit(
+        &self,
+        statistics: &CircuitStatistics,
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        (**self).after_circuit(statistics, duration, error)
+    }
+
+    fn before_measurement(&self, circuits: &[CircuitStatistics]) {
+        (**self).before_measurement(circuits)
+    }
+
+    fn after_measurement(
+        &self,
+        circuits: &[CircuitStatistics],
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        (**self).after_measurement(circuits, duration, error)
+    }
+
+    fn before_program(&self, program: &QuantumProgram, parameters: &[f64]) {
+        (**self).before_program(program, parameters)
+    }
+
+    fn after_program(
+        &self,
+        program: &QuantumProgram,
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        (**self).after_program(program, duration, error)
+    }
+}
+
+/// Wrapper around an [EvaluatingBackend] notifying a [BackendObserver] about every run.
+///
+/// Measurements are passed on to [EvaluatingBackend::run_measurement_registers] of the wrapped
+/// backend, so that its way of running measurements (e.g. retries, parallel execution or cached
+/// constant circuits) is kept. The observer is therefore notified before and after the whole
+/// measurement with the statistics of its circuits, and not about each circuit of the measurement.
+/// Circuits run on their own are reported with their individual wall time.
+/// To observe several backends with the same observer, pass a reference to the observer.
+#[derive(Debug, Clone)]
+pub struct ObservedBackend<B, O>
+where
+    B: EvaluatingBackend,
+    O: BackendObserver,
+{
+    /// The wrapped backend running the circuits.
+    backend: B,
+    /// The observer notified about the runs.
+    observer: O,
+}
+
+impl<B, O> ObservedBackend<B, O>
+where
+    B: EvaluatingBackend,
+    O: BackendObserver,
+{
+    /// Creates a new ObservedBackend.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend running the circuits.
+    /// * `observer` - The observer notified about the runs.
+    pub fn new(backend: B, observer: O) -> Self {
+        Self { backend, observer }
+    }
+
+    /// Returns a reference to the wrapped backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns a reference to the observer.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+}
+
+impl<B, O> EvaluatingBackend for ObservedBackend<B, O>
+where
+    B: EvaluatingBackend,
+    O: BackendObserver,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        let statistics = CircuitStatistics::from_operations(operations.iter().copied());
+        self.observer.before_circuit(&statistics);
+        let start = Instant::now();
+        let result = self.backend.run_circuit_iterator(operations.into_iter());
+        self.observer
+            .after_circuit(&statistics, start.elapsed(), result.as_ref().err());
+        result
+    }
+
+    fn run_measurement_registers<T>(&self, measurement: &T) -> RegisterResult
+    where
+        T: Measure,
+    {
+        let circuits: Vec<CircuitStatistics> = measurement
+            .circuits()
+            .map(|circuit| match measurement.constant_circuit() {
+                Some(x) => CircuitStatistics::from_operations(x.iter().chain(circuit.iter())),
+                None => CircuitStatistics::from_operations(circuit.iter()),
+            })
+            .collect();
+        self.observer.before_measurement(&circuits);
+        let start = Instant::now();
+        let result = self.backend.run_measurement_registers(measurement);
+        self.observer
+            .after_measurement(&circuits, start.elapsed(), result.as_ref().err());
+        result
+    }
+}
+
+/// Observer writing one line per event in the `key=value` (logfmt) format.
+///
+/// For example, a finished circuit is logged as
+/// `event=circuit_finished operations=5 shots=100 duration_s=0.000512 status=ok`.
+/// Errors writing the log are ignored.
+pub struct LoggingObserver {
+    /// The target the log lines are written to.
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl fmt::Debug for LoggingObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggingObserver").finish()
+    }
+}
+
+impl LoggingObserver {
+    /// Creates a new LoggingObserver writing to a target.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The target the log lines are written to, for example a file.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Creates a new LoggingObserver writing to the standard error.
+    pub fn stderr() -> Self {
+        Self::new(io::stderr())
+    }
+
+    /// Writes a log line.
+    fn log(&self, line: String) {
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = writeln!(writer, "{}", line);
+        let _ = writer.flush();
+    }
+}
+
+/// Returns the status and error fields of a log line.
+fn status(error: Option<&RoqoqoBackendError>) -> String {
+    match error {
+        Some(error) => format!("status=error error={:?}", error.to_string()),
+        None => "status=ok".to_string(),
+    }
+}
+
+impl BackendObserver for LoggingObserver {
+    fn before_circuit(&self, statistics: &CircuitStatistics) {
+        self.log(format!(
+            "event=circuit_started operations={} shots={}",
+            statistics.number_operations, statistics.number_shots
+        ));
+    }
+
+    fn after_circuit(
+        &self,
+        statistics: &CircuitStatistics,
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        self.log(format!(
+            "event=circuit_finished operations={} shots={} duration_s={:.6} {}",
+            statistics.number_operations,
+            statistics.number_shots,
+            duration.as_secs_f64(),
+            status(error)
+        ));
+    }
+
+    fn before_measurement(&self, circuits: &[CircuitStatistics]) {
+        self.log(format!(
+            "event=measurement_started circuits={}",
+            circuits.len()
+        ));
+        for (index, statistics) in circuits.iter().enumerate() {
+            self.log(format!(
+                "event=measurement_circuit index={} operations={} shots={}",
+                index, statistics.number_operations, statistics.number_shots
+            ));
+        }
+    }
+
+    fn after_measurement(
+        &self,
+        circuits: &[CircuitStatistics],
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        self.log(format!(
+            "event=measurement_finished circuits={} duration_s={:.6} {}",
+            circuits.len(),
+            duration.as_secs_f64(),
+            status(error)
+        ));
+    }
+
+    fn before_program(&self, program: &QuantumProgram, parameters: &[f64]) {
+        self.log(format!(
+            "event=program_started program={} parameters={:?}",
+            program, parameters
+        ));
+    }
+
+    fn after_program(
+        &self,
+        program: &QuantumProgram,
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        self.log(format!(
+            "event=program_finished program={} duration_s={:.6} {}",
+            program,
+            duration.as_secs_f64(),
+            status(error)
+        ));
+    }
+}
+
+/// Summary of the runs collected by a [SummaryObserver].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RunSummary {
+    /// Number of circuits that were run, including the circuits of measurements.
+    pub number_circuits: usize,
+    /// Number of circuits run on their own that failed.
+    pub failed_circuits: usize,
+    /// Number of measurements that were run.
+    pub number_measurements: usize,
+    /// Number of measurements that failed.
+    pub failed_measurements: usize,
+    /// Number of quantum programs that were run.
+    pub number_programs: usize,
+    /// Total number of operations of all circuits.
+    pub number_operations: usize,
+    /// Number of operations of all circuits by their hqslang name.
+    pub operation_counts: HashMap<String, usize>,
+    /// Total number of shots of all circuits.
+    pub number_shots: usize,
+    /// Total wall time of all circuit and measurement runs.
+    pub circuit_time: Duration,
+    /// Total wall time of all runs, counting nested runs (the measurement of a program) once.
+    pub wall_time: Duration,
+}
+
+impl RunSummary {
+    /// Adds the operations and shots of a circuit to the summary.
+    fn add_circuit(&mut self, statistics: &CircuitStatistics) {
+        self.number_circuits += 1;
+        self.number_operations += statistics.number_operations;
+        for (name, count) in statistics.operation_counts.iter() {
+            *self.operation_counts.entry(name.clone()).or_insert(0) += count;
+        }
+        self.number_shots += statistics.number_shots;
+    }
+}
+
+/// Observer collecting a [RunSummary] of all runs.
+///
+/// Clones of a SummaryObserver share the summary.
+#[derive(Debug, Clone, Default)]
+pub struct SummaryObserver {
+    state: Arc<Mutex<SummaryState>>,
+}
+
+/// Shared state of a [SummaryObserver].
+#[derive(Debug, Default)]
+struct SummaryState {
+    summary: RunSummary,
+    /// Number of runs that have started but not finished.
+    depth: usize,
+    /// Start of the outermost unfinished run.
+    start: Option<Instant>,
+}
+
+impl SummaryState {
+    /// Records the start of a run.
+    fn start(&mut self) {
+        if self.depth == 0 {
+            self.start = Some(Instant::now());
+        }
+        self.depth += 1;
+    }
+
+    /// Records the end of a run, adding the wall time when the outermost run finishes.
+    fn finish(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            if let Some(start) = self.start.take() {
+                self.summary.wall_time += start.elapsed();
+            }
+        }
+    }
+}
+
+impl SummaryObserver {
+    /// Creates a new SummaryObserver with an empty summary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the summary of all runs since the creation or the last reset.
+    pub fn summary(&self) -> RunSummary {
+        self.state().summary.clone()
+    }
+
+    /// Resets the summary.
+    pub fn reset(&self) {
+        *self.state() = SummaryState::default();
+    }
+
+    /// Locks the shared state, a panic in another thread does not invalidate the summary.
+    fn state(&self) -> MutexGuard<'_, SummaryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl BackendObserver for SummaryObserver {
+    fn before_circuit(&self, _statistics: &CircuitStatistics) {
+        self.state().start();
+    }
+
+    fn after_circuit(
+        &self,
+        statistics: &CircuitStatistics,
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        let mut state = self.state();
+        state.summary.add_circuit(statistics);
+        if error.is_some() {
+            state.summary.failed_circuits += 1;
+        }
+        state.summary.circuit_time += duration;
+        state.finish();
+    }
+
+    fn before_measurement(&self, _circuits: &[CircuitStatistics]) {
+        self.state().start();
+    }
+
+    fn after_measurement(
+        &self,
+        circuits: &[CircuitStatistics],
+        duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        let mut state = self.state();
+        for statistics in circuits.iter() {
+            state.summary.add_circuit(statistics);
+        }
+        state.summary.number_measurements += 1;
+        if error.is_some() {
+            state.summary.failed_measurements += 1;
+        }
+        state.summary.circuit_time += duration;
+        state.finish();
+    }
+
+    fn before_program(&self, _program: &QuantumProgram, _parameters: &[f64]) {
+        self.state().start();
+    }
+
+    fn after_program(
+        &self,
+        _program: &QuantumProgram,
+        _duration: Duration,
+        _error: Option<&RoqoqoBackendError>,
+    ) {
+        let mut state = self.state();
+        state.summary.number_programs += 1;
+        state.finish();
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::simulator_helpers::create_rng;
+use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::measurements::ReadoutErrorModel;
+use crate::operations::Operation;
+
+/// Wrapper around an [EvaluatingBackend] that adds readout errors to the measured bit registers.
+///
+/// The readouts of all bit registers returned by the wrapped backend are replaced by readouts
+/// sampled from the confusion matrices of a [ReadoutErrorModel].
+/// The qubit indices of the model are interpreted as the indices of the bits in the registers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadoutErrorBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// The wrapped backend returning error-free readouts.
+    backend: B,
+    /// The readout errors applied to the bit registers.
+    readout_error_model: ReadoutErrorModel,
+    /// Seed of the random number generator sampling the readout errors (None for a random seed).
+    random_seed: Option<u64>,
+}
+
+impl<B> ReadoutErrorBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// Creates a new ReadoutErrorBackend.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend returning error-free readouts.
+    /// * `readout_error_model` - The readout errors applied to the bit registers.
+    pub fn new(backend: B, readout_error_model: ReadoutErrorModel) -> Self {
+        Self {
+            backend,
+            readout_error_model,
+            random_seed: None,
+        }
+    }
+
+    /// Returns a reference to the wrapped backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns a reference to the readout error model.
+    pub fn readout_error_model(&self) -> &ReadoutErrorModel {
+        &self.readout_error_model
+    }
+
+    /// Returns the seed of the random number generator sampling the readout errors.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets the seed of the random number generator sampling the readout errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_seed` - The seed of the random number generator (None for a random seed).
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.random_seed = random_seed;
+    }
+}
+
+impl<B> EvaluatingBackend for ReadoutErrorBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let (mut bit_registers, float_registers, complex_registers) =
+            self.backend.run_circuit_iterator(circuit)?;
+        let mut rng = create_rng(self.random_seed);
+        // Registers are processed in sorted order so that a fixed seed gives reproducible results
+        let mut names: Vec<String> = bit_registers.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            if let Some(register) = bit_registers.get_mut(&name) {
+                self.readout_error_model
+                    .apply_to_register(register, &mut rng);
+            }
+        }
+        Ok((bit_registers, float_registers, complex_registers))
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{CircuitStatistics, EvaluatingBackend, RegisterResult};
+use crate::devices::{circuit_duration, Device};
+use crate::operations::{
+    Define, MultiQubitGateOperation, Operation, SingleQubitGateOperation, TwoQubitGateOperation,
+};
+use crate::registers::{BitOutputRegister, ComplexOutputRegister, FloatOutputRegister};
+use num_complex::Complex64;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Resources accumulated by a [ResourceEstimationBackend].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResourceEstimate {
+    /// Number of circuits that were run.
+    pub number_circuits: usize,
+    /// Total number of shots of all circuits (see [CircuitStatistics::number_shots]).
+    pub number_shots: usize,
+    /// Number of operations in the circuits.
+    pub number_operations: usize,
+    /// Number of single-qubit gates in the circuits.
+    pub number_single_qubit_gates: usize,
+    /// Number of two-qubit gates in the circuits.
+    pub number_two_qubit_gates: usize,
+    /// Number of multi-qubit gates in the circuits.
+    pub number_multi_qubit_gates: usize,
+    /// Estimated time in seconds to run all shots of all circuits on the device.
+    pub estimated_time: f64,
+}
+
+/// Dry-run backend estimating the resources needed to run circuits on a [Device].
+///
+/// The backend does not simulate the circuits. For every circuit it accumulates the number of
+/// shots set by the measurement PRAGMAs and the number of gates in a [ResourceEstimate].
+/// The estimated time of a circuit is its duration on the device (see [crate::devices::circuit_duration])
+/// multiplied by its number of shots, overheads such as resetting the qubits are not included.
+/// Circuits that cannot be executed on the device are refused with
+/// [crate::RoqoqoBackendError::DeviceValidationFailed] and do not change the estimate.
+///
+/// For every output register defined in a circuit a register filled with zeros is returned:
+/// bit registers contain one row per shot, float and complex registers contain a single row,
+/// all with the length of the register definition. Measurements can therefore be evaluated,
+/// even though the resulting expectation values carry no information.
+///
+/// Clones of a ResourceEstimationBackend share the estimate,
+/// so that a clone can be passed by value (e.g. to [crate::QuantumProgram::run]).
+#[derive(Debug, Clone)]
+pub struct ResourceEstimationBackend<D>
+where
+    D: Device,
+{
+    /// The device providing the gate times.
+    device: D,
+    /// The shared resource estimate.
+    estimate: Arc<Mutex<ResourceEstimate>>,
+}
+
+impl<D> ResourceEstimationBackend<D>
+where
+    D: Device,
+{
+    /// Creates a new ResourceEstimationBackend with an empty estimate.
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The device providing the gate times.
+    pub fn new(device: D) -> Self {
+        Self {
+            device,
+            estimate: Arc::new(Mutex::new(ResourceEstimate::default())),
+        }
+    }
+
+    /// Returns a reference to the device providing the gate times.
+    pub fn device(&self) -> &D {
+        &self.device
+    }
+
+    /// Returns the resources accumulated since the creation or the last reset.
+    pub fn estimate(&self) -> ResourceEstimate {
+        self.lock_estimate().clone()
+    }
+
+    /// Resets the accumulated resources.
+    pub fn reset_estimate(&self) {
+        *self.lock_estimate() = ResourceEstimate::default();
+    }
+
+    /// Locks the shared estimate, a panic in another thread does not invalidate the estimate.
+    fn lock_estimate(&self) -> MutexGuard<'_, ResourceEstimate> {
+        self.estimate
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<D> EvaluatingBackend for ResourceEstimationBackend<D>
+where
+    D: Device,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        let duration = circuit_duration(&self.device, operations.iter().copied())?;
+        let statistics = CircuitStatistics::from_operations(operations.iter().copied());
+
+        let mut bit_registers: HashMap<String, BitOutputRegister> = HashMap::new();
+        let mut float_registers: HashMap<String, FloatOutputRegister> = HashMap::new();
+        let mut complex_registers: HashMap<String, ComplexOutputRegister> = HashMap::new();
+        let mut single_qubit_gates = 0;
+        let mut two_qubit_gates = 0;
+        let mut multi_qubit_gates = 0;
+        for operation in operations {
+            match operation {
+                Operation::DefinitionBit(def) if *def.is_output() => {
+                    bit_registers.insert(
+                        def.name().clone(),
+                        vec![vec![false; *def.length()]; statistics.number_shots],
+                    );
+                }
+                Operation::DefinitionFloat(def) if *def.is_output() => {
+                    float_registers.insert(def.name().clone(), vec![vec![0.0; *def.length()]]);
+                }
+                Operation::DefinitionComplex(def) if *def.is_output() => {
+                    complex_registers.insert(
+                        def.name().clone(),
+                        vec![vec![Complex64::new(0.0, 0.0); *def.length()]],
+                    );
+                }
+                _ => {
+                    if SingleQubitGateOperation::try_from(operation.clone()).is_ok() {
+                        single_qubit_gates += 1;
+                    } else if TwoQubitGateOperation::try_from(operation.clone()).is_ok() {
+                        two_qubit_gates += 1;
+                    } else if MultiQubitGateOperation::try_from(operation.clone()).is_ok() {
+                        multi_qubit_gates += 1;
+                    }
+                }
+            }
+        }
+
+        let mut estimate = self.lock_estimate();
+        estimate.number_circuits += 1;
+        estimate.number_shots += statistics.number_shots;
+        estimate.number_operations += statistics.number_operations;
+        estimate.number_single_qubit_gates += single_qubit_gates;
+        estimate.number_two_qubit_gates += two_qubit_gates;
+        estimate.number_multi_qubit_gates += multi_qubit_gates;
+        estimate.estimated_time += duration * statistics.number_shots as f64;
+        Ok((bit_registers, float_registers, complex_registers))
+    }
+}
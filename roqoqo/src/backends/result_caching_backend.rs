@@ -0,0 +1,625 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{EvaluatingBackend, RegisterResult, Registers};
+use crate::operations::Operation;
+use crate::RoqoqoBackendError;
+use serde::ser::{self, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Hit and miss statistics of a [ResultCachingBackend].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStatistics {
+    /// Number of circuits whose results were found in the in-memory or on-disk cache.
+    pub hits: usize,
+    /// Number of hits that were loaded from the on-disk cache.
+    pub disk_hits: usize,
+    /// Number of circuits that had to be executed by the wrapped backend.
+    pub misses: usize,
+}
+
+/// Wrapper around an [EvaluatingBackend] caching the results of executed circuits.
+///
+/// Circuits are identified by a stable content hash of their operations (see [content_hash]).
+/// When a circuit with the same operations is run again, the cached output registers are
+/// returned without executing the circuit on the wrapped backend.
+/// Only successful runs are cached, errors of the wrapped backend are returned unchanged.
+/// In addition to the in-memory cache, results can be stored in a cache directory
+/// to reuse them between program runs. A cache directory should only be shared between
+/// backends that return the same results for the same circuits.
+///
+/// Clones of a ResultCachingBackend share the in-memory cache and the statistics,
+/// so that a clone can be passed by value (e.g. to [crate::QuantumProgram::run])
+/// without losing the cached results.
+#[derive(Debug, Clone)]
+pub struct ResultCachingBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// The wrapped backend executing circuits that are not cached.
+    backend: B,
+    /// Optional directory of the on-disk cache.
+    cache_directory: Option<PathBuf>,
+    /// Shared in-memory cache and statistics.
+    state: Arc<Mutex<CacheState>>,
+}
+
+/// Shared state of a [ResultCachingBackend].
+#[derive(Debug, Default)]
+struct CacheState {
+    /// Cached entries by content hash, several entries share a hash only on hash collisions.
+    entries: HashMap<u64, Vec<CacheEntry>>,
+    statistics: CacheStatistics,
+}
+
+/// Cached operations and output registers of one circuit.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    operations: Vec<Operation>,
+    registers: Registers,
+}
+
+impl CacheState {
+    /// Returns the cached output registers of a circuit.
+    fn get(&self, hash: u64, operations: &[Operation]) -> Option<Registers> {
+        self.entries
+            .get(&hash)?
+            .iter()
+            .find(|entry| entry.operations == operations)
+            .map(|entry| entry.registers.clone())
+    }
+
+    /// Adds output registers to the cache.
+    fn insert(&mut self, hash: u64, entry: CacheEntry) {
+        let entries = self.entries.entry(hash).or_default();
+        if !entries.iter().any(|x| x.operations == entry.operations) {
+            entries.push(entry);
+        }
+    }
+}
+
+impl<B> ResultCachingBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// Creates a new ResultCachingBackend with an empty in-memory cache and no cache directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend executing circuits that are not cached.
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            cache_directory: None,
+            state: Arc::new(Mutex::new(CacheState::default())),
+        }
+    }
+
+    /// Returns a reference to the wrapped backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the directory of the on-disk cache.
+    pub fn cache_directory(&self) -> Option<&Path> {
+        self.cache_directory.as_deref()
+    }
+
+    /// Sets the directory of the on-disk cache.
+    ///
+    /// The directory is created when the first result is stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache_directory` - The new cache directory (None to only cache in memory).
+    pub fn set_cache_directory(&mut self, cache_directory: Option<PathBuf>) {
+        self.cache_directory = cache_directory;
+    }
+
+    /// Returns the hit and miss statistics of the cache.
+    pub fn statistics(&self) -> CacheStatistics {
+        self.state().statistics
+    }
+
+    /// Resets the hit and miss statistics of the cache.
+    pub fn reset_statistics(&self) {
+        self.state().statistics = CacheStatistics::default();
+    }
+
+    /// Removes all results from the in-memory cache, the on-disk cache is not changed.
+    pub fn clear_memory_cache(&self) {
+        self.state().entries.clear();
+    }
+
+    /// Locks the shared state, a panic in another thread does not invalidate the cache.
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the path of the cache file of a content hash.
+    fn cache_file(&self, hash: u64) -> Option<PathBuf> {
+        self.cache_directory
+            .as_ref()
+            .map(|directory| directory.join(format!("{:016x}.bin", hash)))
+    }
+
+    /// Loads the output registers of a circuit from the on-disk cache.
+    ///
+    /// Unreadable files and files of other circuits with the same hash are treated as misses.
+    fn read_cache_file(&self, hash: u64, operations: &[Operation]) -> Option<CacheEntry> {
+        let bytes = fs::read(self.cache_file(hash)?).ok()?;
+        let entry: CacheEntry = bincode::deserialize(&bytes).ok()?;
+        (entry.operations == operations).then(|| entry)
+    }
+
+    /// Stores the output registers of a circuit in the on-disk cache.
+    fn write_cache_file(&self, hash: u64, entry: &CacheEntry) -> Result<(), RoqoqoBackendError> {
+        let path = match self.cache_file(hash) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let to_error = |error: &dyn fmt::Display| RoqoqoBackendError::GenericError {
+            msg: format!("Could not write cache file {}: {}", path.display(), error),
+        };
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| to_error(&error))?;
+        }
+        let bytes = bincode::serialize(entry).map_err(|error| to_error(&error))?;
+        // Writing to a temporary file first prevents other processes from reading partial files.
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, bytes).map_err(|error| to_error(&error))?;
+        fs::rename(&temporary_path, &path).map_err(|error| to_error(&error))
+    }
+}
+
+impl<B> EvaluatingBackend for ResultCachingBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<Operation> = circuit.cloned().collect();
+        let hash = content_hash(operations.iter())?;
+        {
+            let mut state = self.state();
+            if let Some(registers) = state.get(hash, &operations) {
+                state.statistics.hits += 1;
+                return Ok(registers);
+            }
+        }
+        if let Some(entry) = self.read_cache_file(hash, &operations) {
+            let registers = entry.registers.clone();
+            let mut state = self.state();
+            state.insert(hash, entry);
+            state.statistics.hits += 1;
+            state.statistics.disk_hits += 1;
+            return Ok(registers);
+        }
+        self.state().statistics.misses += 1;
+        let registers = self.backend.run_circuit_iterator(operations.iter())?;
+        let entry = CacheEntry {
+            operations,
+            registers: registers.clone(),
+        };
+        self.write_cache_file(hash, &entry)?;
+        self.state().insert(hash, entry);
+        Ok(registers)
+    }
+}
+
+/// Returns a stable content hash of a sequence of operations.
+///
+/// The hash only depends on the serialized content of the operations and not on the process,
+/// platform or the iteration order of HashMaps inside operations (e.g. the qubit mapping of
+/// [crate::operations::PragmaRepeatedMeasurement]), so it can be used to identify circuits
+/// between program runs. Enum variants (e.g. the operation type) are hashed by name, so adding
+/// or reordering variants does not change the hash. Circuits nested in operations include the
+/// roqoqo version in their serialization, so the hash of such operations changes between
+/// roqoqo versions.
+///
+/// # Arguments
+///
+/// * `operations` - The iterator over operations that is hashed (corresponds to a circuit).
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The 64-bit FNV-1a hash of the canonical serialization of the operations.
+/// * `Err(RoqoqoBackendError::GenericError)` - An operation could not be serialized.
+pub fn content_hash<'a>(
+    operations: impl Iterator<Item = &'a Operation>,
+) -> Result<u64, RoqoqoBackendError> {
+    let mut hasher = ContentHasher::new();
+    for operation in operations {
+        operation
+            .serialize(&mut hasher)
+            .map_err(|error| RoqoqoBackendError::GenericError {
+                msg: format!("Could not hash operation: {}", error.0),
+            })?;
+    }
+    Ok(hasher.state)
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Tags written before every serialized value, so that the encoding is prefix-free.
+const TAG_BOOL: u8 = 1;
+const TAG_SIGNED: u8 = 2;
+const TAG_UNSIGNED: u8 = 3;
+const TAG_FLOAT: u8 = 4;
+const TAG_CHAR: u8 = 5;
+const TAG_STR: u8 = 6;
+const TAG_BYTES: u8 = 7;
+const TAG_NONE: u8 = 8;
+const TAG_SOME: u8 = 9;
+const TAG_UNIT: u8 = 10;
+const TAG_VARIANT: u8 = 11;
+const TAG_SEQUENCE: u8 = 12;
+const TAG_MAP: u8 = 13;
+const TAG_END: u8 = 14;
+
+/// serde Serializer feeding a canonical encoding of a value into an FNV-1a hash.
+///
+/// Entries of maps are hashed separately and combined in sorted order.
+#[derive(Debug)]
+struct ContentHasher {
+    state: u64,
+}
+
+/// Error of the [ContentHasher], only produced by custom Serialize implementations.
+#[derive(Debug)]
+struct HashError(String);
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for HashError {}
+
+impl ser::Error for HashError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        HashError(msg.to_string())
+    }
+}
+
+impl ContentHasher {
+    fn new() -> Self {
+        Self {
+            state: FNV_OFFSET_BASIS,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= u64::from(*byte);
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_tagged(&mut self, tag: u8, bytes: &[u8]) {
+        self.write(&[tag]);
+        self.write(bytes);
+    }
+
+    fn write_length(&mut self, tag: u8, length: usize) {
+        self.write_tagged(tag, &(length as u64).to_le_bytes());
+    }
+
+    // Variants are identified by name and not by index,
+    // so that adding or reordering enum variants does not change the hash.
+    fn write_variant(&mut self, variant: &str) {
+        self.write_length(TAG_VARIANT, variant.len());
+        self.write(variant.as_bytes());
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut ContentHasher {
+    type Ok = ();
+    type Error = HashError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = MapHasher<'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), HashError> {
+        self.write_tagged(TAG_BOOL, &[u8::from(v)]);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), HashError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), HashError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), HashError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), HashError> {
+        self.write_tagged(TAG_SIGNED, &v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), HashError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), HashError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), HashError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), HashError> {
+        self.write_tagged(TAG_UNSIGNED, &v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), HashError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), HashError> {
+        self.write_tagged(TAG_FLOAT, &v.to_bits().to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), HashError> {
+        self.write_tagged(TAG_CHAR, &u32::from(v).to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), HashError> {
+        self.write_length(TAG_STR, v.len());
+        self.write(v.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), HashError> {
+        self.write_length(TAG_BYTES, v.len());
+        self.write(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), HashError> {
+        self.write(&[TAG_NONE]);
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), HashError> {
+        self.write(&[TAG_SOME]);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), HashError> {
+        self.write(&[TAG_UNIT]);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), HashError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), HashError> {
+        self.write_variant(variant);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        self.write_variant(variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, HashError> {
+        self.write(&[TAG_SEQUENCE]);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, HashError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self, HashError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, HashError> {
+        self.write_variant(variant);
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapHasher<'a>, HashError> {
+        Ok(MapHasher {
+            parent: self,
+            entries: Vec::new(),
+            current: ContentHasher::new(),
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self, HashError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, HashError> {
+        self.write_variant(variant);
+        self.serialize_seq(Some(len))
+    }
+}
+
+impl ser::SerializeSeq for &mut ContentHasher {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.write(&[TAG_END]);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut ContentHasher {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut ContentHasher {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut ContentHasher {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeStruct for &mut ContentHasher {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for &mut ContentHasher {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Serializer of maps hashing every entry separately to make the hash independent of the entry order.
+#[derive(Debug)]
+struct MapHasher<'a> {
+    parent: &'a mut ContentHasher,
+    entries: Vec<u64>,
+    current: ContentHasher,
+}
+
+impl ser::SerializeMap for MapHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), HashError> {
+        self.current = ContentHasher::new();
+        key.serialize(&mut self.current)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut self.current)?;
+        self.entries.push(self.current.state);
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<(), HashError> {
+        self.entries.sort_unstable();
+        self.parent.write_length(TAG_MAP, self.entries.len());
+        for entry in self.entries.iter() {
+            self.parent.write(&entry.to_le_bytes());
+        }
+        Ok(())
+    }
+}
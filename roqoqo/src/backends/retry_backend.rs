@@ -0,0 +1,281 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{EvaluatingBackend, OutputRegisters, RegisterResult, Registers};
+use crate::measurements::Measure;
+use crate::operations::Operation;
+use crate::RoqoqoBackendError;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+/// Policy for retrying circuits that failed with a transient error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts to run a circuit, including the first attempt.
+    pub max_attempts: usize,
+    /// Waiting time before the first retry.
+    pub initial_backoff: Duration,
+    /// Factor by which the waiting time increases after each retry.
+    pub backoff_factor: f64,
+    /// Upper limit of the waiting time between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Creates a policy with three attempts and a backoff starting at 100 ms that doubles up to 10 s.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            backoff_factor: 2.0,
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the waiting time before a retry.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The number of the retry, starting at 0 for the first retry.
+    ///
+    /// # Returns
+    ///
+    /// `Duration` - The initial backoff multiplied by the backoff factor for each previous retry, limited by the maximum backoff.
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let seconds =
+            self.initial_backoff.as_secs_f64() * self.backoff_factor.powi(retry.min(1000) as i32);
+        if seconds.is_finite() && seconds < self.max_backoff.as_secs_f64() {
+            Duration::from_secs_f64(seconds.max(0.0))
+        } else {
+            self.max_backoff
+        }
+    }
+
+    /// Returns true if a circuit failing with the error is run again.
+    ///
+    /// Timeouts and network errors are considered transient, all other errors are returned immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error returned by the backend.
+    pub fn is_retryable(&self, error: &RoqoqoBackendError) -> bool {
+        matches!(
+            error,
+            RoqoqoBackendError::Timeout { .. } | RoqoqoBackendError::NetworkError { .. }
+        )
+    }
+}
+
+/// Summary of the runs and retries of a [RetryBackend].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetrySummary {
+    /// Number of circuits that were run successfully (possibly after retries).
+    pub successful_runs: usize,
+    /// Number of circuits that failed after all attempts or with an error that is not retried.
+    pub failed_runs: usize,
+    /// Total number of retries of failed attempts.
+    pub retries: usize,
+    /// Number of measurement circuits whose registers were kept from a previously failed measurement.
+    pub reused_circuits: usize,
+}
+
+/// Wrapper around an [EvaluatingBackend] that retries circuits failing with transient errors.
+///
+/// Circuits failing with [RoqoqoBackendError::Timeout] or [RoqoqoBackendError::NetworkError]
+/// are run again after an exponentially increasing waiting time until the maximum number
+/// of attempts of the [RetryPolicy] is reached.
+///
+/// The circuits of a measurement are retried individually, so that the registers of circuits that
+/// already finished are not lost. When a circuit of a measurement still fails, the error is returned
+/// and the registers of the finished circuits are kept. Running the same measurement again
+/// only runs the remaining circuits, running a different measurement discards the kept registers.
+///
+/// Clones of a RetryBackend share the kept registers and the [RetrySummary],
+/// so that a clone can be passed by value (e.g. to [crate::QuantumProgram::run]).
+#[derive(Debug, Clone)]
+pub struct RetryBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// The wrapped backend running the circuits.
+    backend: B,
+    /// The policy for retrying failed circuits.
+    policy: RetryPolicy,
+    /// Shared summary and kept registers.
+    state: Arc<Mutex<RetryState>>,
+}
+
+/// Shared state of a [RetryBackend].
+#[derive(Debug, Default)]
+struct RetryState {
+    summary: RetrySummary,
+    /// Circuits and finished registers of the last failed measurement.
+    partial_measurement: Option<PartialMeasurement>,
+}
+
+/// Circuits of a failed measurement and the registers of the circuits that finished.
+#[derive(Debug)]
+struct PartialMeasurement {
+    /// The full circuits of the measurement (including the constant circuit).
+    circuits: Vec<Vec<Operation>>,
+    /// The registers of the first circuits of the measurement.
+    registers: Vec<Registers>,
+}
+
+impl<B> RetryBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    /// Creates a new RetryBackend.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend running the circuits.
+    /// * `policy` - The policy for retrying failed circuits.
+    pub fn new(backend: B, policy: RetryPolicy) -> Self {
+        Self {
+            backend,
+            policy,
+            state: Arc::new(Mutex::new(RetryState::default())),
+        }
+    }
+
+    /// Returns a reference to the wrapped backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the policy for retrying failed circuits.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Sets the policy for retrying failed circuits.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The new retry policy.
+    pub fn set_policy(&mut self, policy: RetryPolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the summary of runs and retries.
+    pub fn summary(&self) -> RetrySummary {
+        self.state().summary
+    }
+
+    /// Resets the summary of runs and retries.
+    pub fn reset_summary(&self) {
+        self.state().summary = RetrySummary::default();
+    }
+
+    /// Returns the number of circuits whose registers are kept from the last failed measurement.
+    pub fn number_kept_circuits(&self) -> usize {
+        self.state()
+            .partial_measurement
+            .as_ref()
+            .map(|partial| partial.registers.len())
+            .unwrap_or_default()
+    }
+
+    /// Discards the registers kept from the last failed measurement.
+    pub fn clear_kept_registers(&self) {
+        self.state().partial_measurement = None;
+    }
+
+    /// Locks the shared state, a panic in another thread does not invalidate the kept registers.
+    fn state(&self) -> MutexGuard<'_, RetryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Runs a circuit and retries it according to the policy.
+    fn run_with_retries(&self, operations: &[&Operation]) -> Result<Registers, RoqoqoBackendError> {
+        let mut retry = 0;
+        loop {
+            match self
+                .backend
+                .run_circuit_iterator(operations.iter().copied())
+            {
+                Ok(registers) => {
+                    self.state().summary.successful_runs += 1;
+                    return Ok(registers);
+                }
+                Err(error)
+                    if self.policy.is_retryable(&error) && retry + 1 < self.policy.max_attempts =>
+                {
+                    thread::sleep(self.policy.backoff(retry));
+                    self.state().summary.retries += 1;
+                    retry += 1;
+                }
+                Err(error) => {
+                    self.state().summary.failed_runs += 1;
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+impl<B> EvaluatingBackend for RetryBackend<B>
+where
+    B: EvaluatingBackend,
+{
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        self.run_with_retries(&operations)
+    }
+
+    fn run_measurement_registers<T>(&self, measurement: &T) -> RegisterResult
+    where
+        T: Measure,
+    {
+        let circuits: Vec<Vec<Operation>> = measurement
+            .circuits()
+            .map(|circuit| match measurement.constant_circuit() {
+                Some(x) => x.iter().chain(circuit.iter()).cloned().collect(),
+                None => circuit.iter().cloned().collect(),
+            })
+            .collect();
+        let mut registers = {
+            let mut state = self.state();
+            match state.partial_measurement.take() {
+                Some(partial) if partial.circuits == circuits => {
+                    state.summary.reused_circuits += partial.registers.len();
+                    partial.registers
+                }
+                _ => Vec::new(),
+            }
+        };
+        for circuit in circuits.iter().skip(registers.len()) {
+            let operations: Vec<&Operation> = circuit.iter().collect();
+            match self.run_with_retries(&operations) {
+                Ok(circuit_registers) => registers.push(circuit_registers),
+                Err(error) => {
+                    self.state().partial_measurement = Some(PartialMeasurement {
+                        circuits,
+                        registers,
+                    });
+                    return Err(error);
+                }
+            }
+        }
+        OutputRegisters::from_results(registers.into_iter().map(Ok))
+    }
+}
@@ -0,0 +1,577 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Internal helper functions shared by the simulator backends of roqoqo.
+
+use crate::backends::RegisterResult;
+use crate::operations::{
+    Define, InvolveQubits, InvolvedQubits, MultiQubitGateOperation, Operate, OperateMultiQubit,
+    OperateSingleQubit, OperateTwoQubit, Operation, SingleQubitGateOperation,
+    TwoQubitGateOperation,
+};
+use crate::registers::{
+    BitOutputRegister, BitRegister, ComplexOutputRegister, ComplexRegister, FloatOutputRegister,
+    FloatRegister,
+};
+use crate::Circuit;
+use crate::RoqoqoBackendError;
+use ndarray::{Array1, Array2};
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+/// Classical registers written during the simulation of a circuit.
+///
+/// Keeps track of the registers of a single run of a circuit and of the
+/// output registers collecting the results of all repetitions.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SimulationRegisters {
+    /// Bit registers of the current run.
+    pub(crate) bit_registers: HashMap<String, BitRegister>,
+    /// Float registers of the current run.
+    pub(crate) float_registers: HashMap<String, FloatRegister>,
+    /// Complex registers of the current run.
+    pub(crate) complex_registers: HashMap<String, ComplexRegister>,
+    /// Collected bit output registers.
+    pub(crate) bit_registers_output: HashMap<String, BitOutputRegister>,
+    /// Collected float output registers.
+    pub(crate) float_registers_output: HashMap<String, FloatOutputRegister>,
+    /// Collected complex output registers.
+    pub(crate) complex_registers_output: HashMap<String, ComplexOutputRegister>,
+    /// Names of the registers that are defined as outputs.
+    output_names: HashSet<String>,
+    /// Names of bit registers whose output has been written directly by a repeated measurement.
+    repeated_measurement_registers: HashSet<String>,
+}
+
+impl SimulationRegisters {
+    /// Creates new empty SimulationRegisters.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a definition operation, (re-)initializing the corresponding register.
+    ///
+    /// # Returns
+    ///
+    /// * `true` - The operation was a definition and has been applied.
+    /// * `false` - The operation is not a definition.
+    pub(crate) fn define(&mut self, operation: &Operation) -> bool {
+        match operation {
+            Operation::DefinitionBit(def) => {
+                self.bit_registers
+                    .insert(def.name().clone(), vec![false; *def.length()]);
+                if *def.is_output() {
+                    self.output_names.insert(def.name().clone());
+                    self.bit_registers_output
+                        .entry(def.name().clone())
+                        .or_default();
+                }
+                true
+            }
+            Operation::DefinitionFloat(def) => {
+                self.float_registers
+                    .insert(def.name().clone(), vec![0.0; *def.length()]);
+                if *def.is_output() {
+                    self.output_names.insert(def.name().clone());
+                    self.float_registers_output
+                        .entry(def.name().clone())
+                        .or_default();
+                }
+                true
+            }
+            Operation::DefinitionComplex(def) => {
+                self.complex_registers.insert(
+                    def.name().clone(),
+                    vec![Complex64::new(0.0, 0.0); *def.length()],
+                );
+                if *def.is_output() {
+                    self.output_names.insert(def.name().clone());
+                    self.complex_registers_output
+                        .entry(def.name().clone())
+                        .or_default();
+                }
+                true
+            }
+            Operation::DefinitionUsize(_) | Operation::InputSymbolic(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Resets the registers of the current run to those of `initial`.
+    pub(crate) fn reset_run(&mut self, initial: &SimulationRegisters) {
+        self.bit_registers = initial.bit_registers.clone();
+        self.float_registers = initial.float_registers.clone();
+        self.complex_registers = initial.complex_registers.clone();
+    }
+
+    /// Writes a single bit into a bit register.
+    pub(crate) fn set_bit(
+        &mut self,
+        readout: &str,
+        index: usize,
+        value: bool,
+    ) -> Result<(), RoqoqoBackendError> {
+        let register = self.bit_registers.get_mut(readout).ok_or_else(|| {
+            RoqoqoBackendError::GenericError {
+                msg: format!("Bit register {} has not been defined", readout),
+            }
+        })?;
+        if index >= register.len() {
+            return Err(RoqoqoBackendError::GenericError {
+                msg: format!(
+                    "Index {} exceeds length {} of bit register {}",
+                    index,
+                    register.len(),
+                    readout
+                ),
+            });
+        }
+        register[index] = value;
+        Ok(())
+    }
+
+    /// Reads a single bit from a bit register.
+    pub(crate) fn get_bit(&self, readout: &str, index: usize) -> Result<bool, RoqoqoBackendError> {
+        let register =
+            self.bit_registers
+                .get(readout)
+                .ok_or_else(|| RoqoqoBackendError::GenericError {
+                    msg: format!("Bit register {} has not been defined", readout),
+                })?;
+        register
+            .get(index)
+            .copied()
+            .ok_or_else(|| RoqoqoBackendError::GenericError {
+                msg: format!(
+                    "Index {} exceeds length {} of bit register {}",
+                    index,
+                    register.len(),
+                    readout
+                ),
+            })
+    }
+
+    /// Returns the length of a defined bit register.
+    pub(crate) fn bit_register_length(&self, readout: &str) -> Result<usize, RoqoqoBackendError> {
+        self.bit_registers
+            .get(readout)
+            .map(|register| register.len())
+            .ok_or_else(|| RoqoqoBackendError::GenericError {
+                msg: format!("Bit register {} has not been defined", readout),
+            })
+    }
+
+    /// Appends the rows of a repeated measurement to the output of a bit register.
+    pub(crate) fn append_repeated_measurement(
+        &mut self,
+        readout: &str,
+        mut rows: BitOutputRegister,
+    ) {
+        self.repeated_measurement_registers
+            .insert(readout.to_string());
+        self.bit_registers_output
+            .entry(readout.to_string())
+            .or_default()
+            .append(&mut rows);
+    }
+
+    /// Appends the registers of the current run to the output registers.
+    ///
+    /// Bit registers that have been written by a repeated measurement are skipped.
+    pub(crate) fn finish_run(&mut self) {
+        for name in self.output_names.iter() {
+            if let Some(register) = self.bit_registers.get(name) {
+                if !self.repeated_measurement_registers.contains(name) {
+                    self.bit_registers_output
+                        .entry(name.clone())
+                        .or_default()
+                        .push(register.clone());
+                }
+            }
+            if let Some(register) = self.float_registers.get(name) {
+                self.float_registers_output
+                    .entry(name.clone())
+                    .or_default()
+                    .push(register.clone());
+            }
+            if let Some(register) = self.complex_registers.get(name) {
+                self.complex_registers_output
+                    .entry(name.clone())
+                    .or_default()
+                    .push(register.clone());
+            }
+        }
+    }
+
+    /// Returns the collected output registers.
+    pub(crate) fn into_output(self) -> RegisterResult {
+        Ok((
+            self.bit_registers_output,
+            self.float_registers_output,
+            self.complex_registers_output,
+        ))
+    }
+}
+
+/// State of a simulator after the constant circuit of a measurement.
+///
+/// Used as [crate::backends::CachingBackend::CachedState] of the simulators.
+/// The state is only cached when the constant circuit acts deterministically,
+/// otherwise the constant circuit is stored and executed again before every circuit.
+#[derive(Debug, Clone)]
+pub struct CachedSimulatorState {
+    /// The state vector or vectorized density matrix after the constant circuit (None if not cached).
+    pub(crate) state: Option<Array1<Complex64>>,
+    /// The classical registers defined by the constant circuit.
+    pub(crate) registers: SimulationRegisters,
+    /// The operations of the constant circuit when the state is not cached.
+    pub(crate) constant_operations: Vec<Operation>,
+}
+
+impl CachedSimulatorState {
+    /// Returns true when the state after the constant circuit has been cached.
+    pub fn is_prepared(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Executes a constant circuit on an initial state if all of its operations are deterministic.
+    ///
+    /// # Arguments
+    ///
+    /// * `constant_circuit` - The constant circuit that is executed.
+    /// * `initial_state` - The state before the constant circuit.
+    /// * `apply_operation` - Applies one operation to the state and the registers.
+    pub(crate) fn prepare<F>(
+        constant_circuit: Option<&Circuit>,
+        mut initial_state: Array1<Complex64>,
+        mut apply_operation: F,
+    ) -> Result<Self, RoqoqoBackendError>
+    where
+        F: FnMut(
+            &Operation,
+            &mut Array1<Complex64>,
+            &mut SimulationRegisters,
+        ) -> Result<(), RoqoqoBackendError>,
+    {
+        let mut registers = SimulationRegisters::new();
+        let operations: Vec<&Operation> = constant_circuit
+            .map(|circuit| circuit.iter().collect())
+            .unwrap_or_default();
+        if !operations
+            .iter()
+            .all(|operation| is_deterministic(operation))
+        {
+            return Ok(Self {
+                state: None,
+                registers,
+                constant_operations: operations.into_iter().cloned().collect(),
+            });
+        }
+        for operation in operations {
+            apply_operation(operation, &mut initial_state, &mut registers)?;
+        }
+        Ok(Self {
+            state: Some(initial_state),
+            registers,
+            constant_operations: Vec::new(),
+        })
+    }
+}
+
+/// Returns true when an operation acts on a simulator state without randomness or side effects on later runs.
+///
+/// Measurements, conditional operations, resets and PRAGMAs changing the number of
+/// repetitions or the noise of later operations are not deterministic in this sense.
+fn is_deterministic(operation: &Operation) -> bool {
+    let tags = operation.tags();
+    tags.contains(&"GateOperation")
+        || tags.contains(&"Definition")
+        || tags.contains(&"PragmaNoiseOperation")
+        || matches!(
+            operation,
+            Operation::PragmaGlobalPhase(_)
+                | Operation::PragmaSetStateVector(_)
+                | Operation::PragmaSetDensityMatrix(_)
+                | Operation::PragmaRepeatGate(_)
+                | Operation::PragmaOverrotation(_)
+                | Operation::PragmaStopParallelBlock(_)
+                | Operation::PragmaSleep(_)
+                | Operation::PragmaStartDecompositionBlock(_)
+                | Operation::PragmaStopDecompositionBlock(_)
+        )
+}
+
+/// Returns the random number generator used for one run of a simulator.
+pub(crate) fn create_rng(random_seed: Option<u64>) -> StdRng {
+    match random_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Returns the number of repetitions of a circuit requested by a PragmaSetNumberOfMeasurements.
+pub(crate) fn number_of_repetitions(operations: &[&Operation]) -> usize {
+    operations
+        .iter()
+        .filter_map(|op| match op {
+            Operation::PragmaSetNumberOfMeasurements(x) => Some(*x.number_measurements()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(1)
+}
+
+/// Returns the number of shots needed to execute a circuit shot by shot.
+///
+/// This is the largest number of repetitions requested by a PragmaSetNumberOfMeasurements
+/// or by the number of measurements of a PragmaRepeatedMeasurement.
+pub(crate) fn number_of_shots(operations: &[&Operation]) -> usize {
+    operations
+        .iter()
+        .filter_map(|op| match op {
+            Operation::PragmaRepeatedMeasurement(x) => Some(*x.number_measurements()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(1)
+        .max(number_of_repetitions(operations))
+}
+
+/// Returns the part of `total` measurements that is carried out in run `run` of `number_runs`.
+///
+/// The measurements are distributed as evenly as possible, earlier runs receive the remainder.
+pub(crate) fn distributed_shots(total: usize, run: usize, number_runs: usize) -> usize {
+    total / number_runs + usize::from(run < total % number_runs)
+}
+
+/// Checks that all qubits involved in an operation are smaller than the number of qubits.
+pub(crate) fn check_qubits(
+    operation: &Operation,
+    number_qubits: usize,
+) -> Result<(), RoqoqoBackendError> {
+    if let InvolvedQubits::Set(qubits) = operation.involved_qubits() {
+        if let Some(qubit) = qubits.iter().find(|q| **q >= number_qubits) {
+            return Err(RoqoqoBackendError::GenericError {
+                msg: format!(
+                    "Operation {} acts on qubit {} but the backend only has {} qubits",
+                    operation.hqslang(),
+                    qubit,
+                    number_qubits
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Applies a matrix to a subset of the qubits of a state vector.
+///
+/// The first qubit in `qubits` corresponds to the most significant bit of the row and column
+/// indices of `matrix`, which is the convention used by the unitary matrices of roqoqo gates.
+/// In the state vector qubit `i` corresponds to bit `i` of the index.
+pub(crate) fn apply_matrix(
+    state: &mut Array1<Complex64>,
+    matrix: &Array2<Complex64>,
+    qubits: &[usize],
+) {
+    let number_local_qubits = qubits.len();
+    let local_dimension = 1_usize << number_local_qubits;
+    let offsets: Vec<usize> = (0..local_dimension)
+        .map(|local_index| {
+            qubits
+                .iter()
+                .enumerate()
+                .filter(|(position, _)| {
+                    (local_index >> (number_local_qubits - 1 - position)) & 1 == 1
+                })
+                .map(|(_, qubit)| 1_usize << qubit)
+                .sum()
+        })
+        .collect();
+    let mask: usize = qubits.iter().map(|qubit| 1_usize << qubit).sum();
+    let mut buffer: Vec<Complex64> = vec![Complex64::new(0.0, 0.0); local_dimension];
+    for base in 0..state.len() {
+        if base & mask != 0 {
+            continue;
+        }
+        for (value, offset) in buffer.iter_mut().zip(offsets.iter()) {
+            *value = state[base | offset];
+        }
+        for (row, offset) in offsets.iter().enumerate() {
+            let mut value = Complex64::new(0.0, 0.0);
+            for (column, amplitude) in buffer.iter().enumerate() {
+                value += matrix[(row, column)] * amplitude;
+            }
+            state[base | offset] = value;
+        }
+    }
+}
+
+/// Returns the matrix of a single-qubit Pauli operator (0: identity, 1: X, 2: Y, 3: Z).
+pub(crate) fn pauli_matrix(pauli: usize) -> Result<Array2<Complex64>, RoqoqoBackendError> {
+    let zero = Complex64::new(0.0, 0.0);
+    let one = Complex64::new(1.0, 0.0);
+    let i = Complex64::new(0.0, 1.0);
+    match pauli {
+        0 => Ok(ndarray::array![[one, zero], [zero, one]]),
+        1 => Ok(ndarray::array![[zero, one], [one, zero]]),
+        2 => Ok(ndarray::array![[zero, -i], [i, zero]]),
+        3 => Ok(ndarray::array![[one, zero], [zero, -one]]),
+        _ => Err(RoqoqoBackendError::GenericError {
+            msg: format!(
+                "Pauli index {} is not valid, allowed values are 0, 1, 2 and 3",
+                pauli
+            ),
+        }),
+    }
+}
+
+/// Samples computational basis states from a list of occupation probabilities.
+pub(crate) fn sample_basis_states(
+    probabilities: &[f64],
+    number_samples: usize,
+    rng: &mut StdRng,
+) -> Vec<usize> {
+    let mut cumulative: Vec<f64> = Vec::with_capacity(probabilities.len());
+    let mut sum = 0.0;
+    for probability in probabilities {
+        sum += probability.max(0.0);
+        cumulative.push(sum);
+    }
+    (0..number_samples)
+        .map(|_| {
+            let random: f64 = rng.gen::<f64>() * sum;
+            cumulative
+                .partition_point(|value| *value <= random)
+                .min(probabilities.len() - 1)
+        })
+        .collect()
+}
+
+/// Checks that all qubits of the qubit mapping of a measurement exist in the backend.
+pub(crate) fn check_qubit_mapping(
+    qubit_mapping: &Option<HashMap<usize, usize>>,
+    number_qubits: usize,
+) -> Result<(), RoqoqoBackendError> {
+    if let Some(qubit) = qubit_mapping
+        .iter()
+        .flat_map(|mapping| mapping.keys())
+        .find(|qubit| **qubit >= number_qubits)
+    {
+        return Err(RoqoqoBackendError::GenericError {
+            msg: format!(
+                "Qubit mapping of measurement contains qubit {} but the backend only has {} qubits",
+                qubit, number_qubits
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Converts sampled basis states into the rows of a bit register using the optional qubit mapping.
+///
+/// Fails if the qubit mapping contains a qubit the backend does not have.
+pub(crate) fn basis_states_to_bits(
+    basis_states: Vec<usize>,
+    register_length: usize,
+    number_qubits: usize,
+    qubit_mapping: &Option<HashMap<usize, usize>>,
+) -> Result<BitOutputRegister, RoqoqoBackendError> {
+    check_qubit_mapping(qubit_mapping, number_qubits)?;
+    Ok(basis_states
+        .into_iter()
+        .map(|basis_state| {
+            let mut bits: BitRegister = vec![false; register_length];
+            match qubit_mapping {
+                Some(mapping) => {
+                    for (qubit, index) in mapping.iter() {
+                        if *index < register_length {
+                            bits[*index] = (basis_state >> qubit) & 1 == 1;
+                        }
+                    }
+                }
+                None => {
+                    for (qubit, bit) in bits.iter_mut().enumerate().take(number_qubits) {
+                        *bit = (basis_state >> qubit) & 1 == 1;
+                    }
+                }
+            }
+            bits
+        })
+        .collect())
+}
+
+/// Returns the dimension 2^N of the Hilbert space of a dense simulation of N qubits.
+///
+/// Fails if a dense state of the qubits cannot be stored in memory.
+pub(crate) fn dense_dimension(number_qubits: usize) -> Result<usize, RoqoqoBackendError> {
+    (number_qubits < usize::BITS as usize)
+        .then(|| 1_usize << number_qubits)
+        .filter(|dimension| {
+            dimension
+                .checked_mul(std::mem::size_of::<Complex64>())
+                .map_or(false, |bytes| bytes <= isize::MAX as usize)
+        })
+        .ok_or_else(|| RoqoqoBackendError::GenericError {
+            msg: format!(
+                "A dense state of {} qubits is too large to be simulated",
+                number_qubits
+            ),
+        })
+}
+
+/// Returns the qubits a gate operation acts on, ordered by significance in its unitary matrix.
+pub(crate) fn gate_qubits(operation: &Operation) -> Result<Vec<usize>, RoqoqoBackendError> {
+    let tags = operation.tags();
+    if tags.contains(&"SingleQubitGateOperation") {
+        let gate = SingleQubitGateOperation::try_from(operation.clone())?;
+        Ok(vec![*gate.qubit()])
+    } else if tags.contains(&"TwoQubitGateOperation") {
+        let gate = TwoQubitGateOperation::try_from(operation.clone())?;
+        Ok(vec![*gate.control(), *gate.target()])
+    } else if tags.contains(&"MultiQubitGateOperation") {
+        let gate = MultiQubitGateOperation::try_from(operation.clone())?;
+        Ok(gate.qubits().clone())
+    } else {
+        Err(RoqoqoBackendError::GenericError {
+            msg: format!("Operation {} is not a gate operation", operation.hqslang()),
+        })
+    }
+}
+
+/// Returns Kraus operators of a single-qubit channel given by its superoperator.
+///
+/// The superoperator acts on the density matrix in row-major vector form.
+/// The Kraus operators are obtained from the eigendecomposition of the Choi matrix,
+/// eigenvalues that are not positive are discarded.
+pub(crate) fn kraus_operators(superoperator: &Array2<f64>) -> Vec<Array2<Complex64>> {
+    // Reshuffle the superoperator S[(a,b),(c,d)] into the Choi matrix C[(a,c),(b,d)]
+    let choi = nalgebra::DMatrix::from_fn(4, 4, |row, column| {
+        let (a, c) = (row / 2, row % 2);
+        let (b, d) = (column / 2, column % 2);
+        Complex64::new(superoperator[(a * 2 + b, c * 2 + d)], 0.0)
+    });
+    let decomposition = choi.symmetric_eigen();
+    decomposition
+        .eigenvalues
+        .iter()
+        .enumerate()
+        .filter(|(_, eigenvalue)| **eigenvalue > 1e-14)
+        .map(|(index, eigenvalue)| {
+            let vector = decomposition.eigenvectors.column(index);
+            Array2::from_shape_fn((2, 2), |(a, c)| vector[a * 2 + c] * eigenvalue.sqrt())
+        })
+        .collect()
+}
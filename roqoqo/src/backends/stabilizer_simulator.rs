@@ -0,0 +1,490 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::simulator_helpers::{
+    check_qubit_mapping, check_qubits, create_rng, gate_qubits, number_of_repetitions,
+    pauli_matrix, SimulationRegisters,
+};
+use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::operations::{
+    GateOperation, MultiQubitGateOperation, Operate, OperateGate, OperateMultiQubitGate,
+    OperateSingleQubit, Operation,
+};
+use crate::registers::{BitOutputRegister, BitRegister};
+use crate::RoqoqoBackendError;
+use ndarray::Array2;
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::convert::TryFrom;
+
+/// Tolerance used when checking whether a gate is a Clifford gate.
+const CLIFFORD_TOLERANCE: f64 = 1e-6;
+
+/// Stabilizer (Clifford tableau) simulator implementing [EvaluatingBackend].
+///
+/// The simulator uses the tableau algorithm of Aaronson and Gottesman
+/// (Phys. Rev. A 70, 052328 (2004)) and can simulate circuits with thousands of qubits
+/// as long as they only contain Clifford gates.
+///
+/// A gate is accepted when its unitary matrix maps every Pauli operator onto a Pauli operator,
+/// which includes for example [crate::operations::Hadamard], [crate::operations::SGate],
+/// [crate::operations::CNOT], [crate::operations::SWAP] and rotations with an angle that is a
+/// multiple of π/2. All other gates return [RoqoqoBackendError::OperationNotInBackend].
+/// Multi-qubit gates (e.g. [crate::operations::MultiQubitMS]) are decomposed into
+/// their circuit of one- and two-qubit gates first, which need to be Clifford gates.
+///
+/// Supported measurement operations are [crate::operations::MeasureQubit],
+/// [crate::operations::PragmaRepeatedMeasurement] and [crate::operations::PragmaActiveReset].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StabilizerSimulator {
+    /// The number of qubits of the simulated quantum register.
+    number_qubits: usize,
+    /// Optional seed of the random number generator used for measurements.
+    random_seed: Option<u64>,
+}
+
+impl StabilizerSimulator {
+    /// Creates a new StabilizerSimulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_qubits` - The number of qubits of the simulated quantum register.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new StabilizerSimulator.
+    pub fn new(number_qubits: usize) -> Self {
+        Self {
+            number_qubits,
+            random_seed: None,
+        }
+    }
+
+    /// Returns the number of qubits of the simulated quantum register.
+    pub fn number_qubits(&self) -> usize {
+        self.number_qubits
+    }
+
+    /// Returns the seed of the random number generator.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets the seed of the random number generator.
+    ///
+    /// When a seed is set, every run of a circuit produces the same measurement results.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_seed` - The seed of the random number generator (None for a random seed).
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.random_seed = random_seed;
+    }
+
+    /// Applies a single operation to the tableau and the classical registers.
+    fn apply_operation(
+        &self,
+        operation: &Operation,
+        tableau: &mut Tableau,
+        registers: &mut SimulationRegisters,
+        rng: &mut StdRng,
+    ) -> Result<(), RoqoqoBackendError> {
+        if registers.define(operation) {
+            return Ok(());
+        }
+        check_qubits(operation, self.number_qubits)?;
+        if operation.tags().contains(&"MultiQubitGateOperation") {
+            let circuit = MultiQubitGateOperation::try_from(operation.clone())?.circuit();
+            for decomposed in circuit.iter() {
+                self.apply_operation(decomposed, tableau, registers, rng)
+                    .map_err(|error| match error {
+                        RoqoqoBackendError::OperationNotInBackend { backend, .. } => {
+                            RoqoqoBackendError::OperationNotInBackend {
+                                backend,
+                                hqslang: operation.hqslang(),
+                            }
+                        }
+                        error => error,
+                    })?;
+            }
+            return Ok(());
+        }
+        if operation.tags().contains(&"GateOperation") {
+            let matrix = GateOperation::try_from(operation.clone())?.unitary_matrix()?;
+            let qubits = gate_qubits(operation)?;
+            let clifford = LocalClifford::from_unitary(&matrix, qubits.len()).ok_or(
+                RoqoqoBackendError::OperationNotInBackend {
+                    backend: "StabilizerSimulator",
+                    hqslang: operation.hqslang(),
+                },
+            )?;
+            tableau.apply_clifford(&clifford, &qubits);
+            return Ok(());
+        }
+        match operation {
+            Operation::MeasureQubit(op) => {
+                let result = tableau.measure(*op.qubit(), rng);
+                registers.set_bit(op.readout(), *op.readout_index(), result)
+            }
+            Operation::PragmaActiveReset(op) => {
+                if tableau.measure(*op.qubit(), rng) {
+                    tableau.apply_pauli_x(*op.qubit());
+                }
+                Ok(())
+            }
+            Operation::PragmaRepeatedMeasurement(op) => {
+                check_qubit_mapping(op.qubit_mapping(), self.number_qubits)?;
+                let length = registers.bit_register_length(op.readout())?;
+                let measured_qubits: Vec<(usize, usize)> = match op.qubit_mapping() {
+                    Some(mapping) => mapping
+                        .iter()
+                        .filter(|(_, index)| **index < length)
+                        .map(|(qubit, index)| (*qubit, *index))
+                        .collect(),
+                    None => (0..length.min(self.number_qubits))
+                        .map(|qubit| (qubit, qubit))
+                        .collect(),
+                };
+                let mut rows: BitOutputRegister = Vec::with_capacity(*op.number_measurements());
+                for _ in 0..*op.number_measurements() {
+                    let mut tmp_tableau = tableau.clone();
+                    let mut row: BitRegister = vec![false; length];
+                    for (qubit, index) in measured_qubits.iter() {
+                        row[*index] = tmp_tableau.measure(*qubit, rng);
+                    }
+                    rows.push(row);
+                }
+                registers.append_repeated_measurement(op.readout(), rows);
+                Ok(())
+            }
+            Operation::PragmaSetNumberOfMeasurements(_)
+            | Operation::PragmaGlobalPhase(_)
+            | Operation::PragmaRepeatGate(_)
+            | Operation::PragmaStopParallelBlock(_)
+            | Operation::PragmaSleep(_)
+            | Operation::PragmaStartDecompositionBlock(_)
+            | Operation::PragmaStopDecompositionBlock(_) => Ok(()),
+            _ => Err(RoqoqoBackendError::OperationNotInBackend {
+                backend: "StabilizerSimulator",
+                hqslang: operation.hqslang(),
+            }),
+        }
+    }
+}
+
+impl EvaluatingBackend for StabilizerSimulator {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        let mut rng = create_rng(self.random_seed);
+        let mut registers = SimulationRegisters::new();
+        for _ in 0..number_of_repetitions(&operations) {
+            let mut tableau = Tableau::new(self.number_qubits);
+            for operation in operations.iter() {
+                self.apply_operation(operation, &mut tableau, &mut registers, &mut rng)?;
+            }
+            registers.finish_run();
+        }
+        registers.into_output()
+    }
+}
+
+/// A Pauli product i^phase * prod_j X_j^x_j Z_j^z_j on a few local qubits stored as bitmasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LocalPauli {
+    /// Exponent of the prefactor i^phase (modulo 4).
+    phase: u32,
+    /// Bitmask of the X components.
+    x: usize,
+    /// Bitmask of the Z components.
+    z: usize,
+}
+
+impl LocalPauli {
+    /// Multiplies two Pauli products, commuting the Z parts of self past the X parts of other.
+    fn mul(self, other: LocalPauli) -> LocalPauli {
+        LocalPauli {
+            phase: (self.phase + other.phase + 2 * (self.z & other.x).count_ones()) % 4,
+            x: self.x ^ other.x,
+            z: self.z ^ other.z,
+        }
+    }
+}
+
+/// The action of a Clifford gate on the Pauli operators of the qubits it acts on.
+///
+/// For every local Pauli operator (given by its X and Z bitmasks) the table contains
+/// the bitmasks of the conjugated Pauli operator and whether its sign is flipped.
+#[derive(Debug, Clone)]
+struct LocalClifford {
+    /// Lookup table indexed by (x << number_local_qubits) | z.
+    table: Vec<(usize, usize, bool)>,
+    /// Number of qubits the gate acts on.
+    number_local_qubits: usize,
+}
+
+impl LocalClifford {
+    /// Creates the conjugation table of a unitary matrix, returns None if it is not a Clifford gate.
+    ///
+    /// The local qubit `j` corresponds to the j-th most significant bit of the matrix index
+    /// and to bit `j` of the local bitmasks.
+    fn from_unitary(matrix: &Array2<Complex64>, number_local_qubits: usize) -> Option<Self> {
+        let dimension = 1_usize << number_local_qubits;
+        let adjoint = matrix.t().mapv(|x| x.conj());
+        let mut images_x: Vec<LocalPauli> = Vec::with_capacity(number_local_qubits);
+        let mut images_z: Vec<LocalPauli> = Vec::with_capacity(number_local_qubits);
+        for qubit in 0..number_local_qubits {
+            for (pauli, images) in [(1, &mut images_x), (3, &mut images_z)] {
+                let mut codes = vec![0; number_local_qubits];
+                codes[qubit] = pauli;
+                let conjugated = matrix.dot(&local_pauli_matrix(&codes)).dot(&adjoint);
+                images.push(pauli_from_matrix(&conjugated, number_local_qubits)?);
+            }
+        }
+        let mut table = Vec::with_capacity(dimension * dimension);
+        for x in 0..dimension {
+            for z in 0..dimension {
+                let mut image = LocalPauli {
+                    phase: (x & z).count_ones() % 4,
+                    x: 0,
+                    z: 0,
+                };
+                for qubit in 0..number_local_qubits {
+                    if (x >> qubit) & 1 == 1 {
+                        image = image.mul(images_x[qubit]);
+                    }
+                    if (z >> qubit) & 1 == 1 {
+                        image = image.mul(images_z[qubit]);
+                    }
+                }
+                let number_y = (image.x & image.z).count_ones();
+                let sign_flip = (image.phase + 4 - number_y % 4) % 4 == 2;
+                table.push((image.x, image.z, sign_flip));
+            }
+        }
+        Some(Self {
+            table,
+            number_local_qubits,
+        })
+    }
+}
+
+/// Returns the matrix of a Pauli product given by Pauli codes (0: I, 1: X, 2: Y, 3: Z).
+///
+/// The first code corresponds to the most significant qubit.
+fn local_pauli_matrix(codes: &[usize]) -> Array2<Complex64> {
+    let mut matrix: Array2<Complex64> = Array2::from_elem((1, 1), Complex64::new(1.0, 0.0));
+    for code in codes {
+        // The codes are always valid, so the Pauli matrix always exists
+        let pauli = pauli_matrix(*code).unwrap_or_else(|_| Array2::zeros((2, 2)));
+        matrix = ndarray::linalg::kron(&matrix, &pauli);
+    }
+    matrix
+}
+
+/// Returns the Pauli product i^phase * X^x Z^z equal to a matrix, if it exists.
+///
+/// The column of the basis state 0 determines the X part and the prefactor,
+/// the columns of the single-qubit basis states determine the Z part.
+fn pauli_from_matrix(matrix: &Array2<Complex64>, number_local_qubits: usize) -> Option<LocalPauli> {
+    // The local qubit j corresponds to the j-th most significant bit of the matrix index
+    let to_index = |mask: usize| -> usize {
+        (0..number_local_qubits)
+            .filter(|qubit| (mask >> qubit) & 1 == 1)
+            .map(|qubit| 1 << (number_local_qubits - 1 - qubit))
+            .sum()
+    };
+    let is_close = |a: Complex64, b: Complex64| (a - b).norm() < CLIFFORD_TOLERANCE;
+    let x_index = (0..matrix.nrows()).find(|row| matrix[[*row, 0]].norm() > 0.5)?;
+    let prefactor = matrix[[x_index, 0]];
+    let phase = (0..4).find(|phase| is_close(prefactor, Complex64::i().powu(*phase)))?;
+    // The bit reversal of the index is its own inverse
+    let x = to_index(x_index);
+    let mut z = 0;
+    for qubit in 0..number_local_qubits {
+        let column = to_index(1 << qubit);
+        let ratio = matrix[[column ^ x_index, column]] / prefactor;
+        if is_close(ratio, Complex64::new(-1.0, 0.0)) {
+            z |= 1 << qubit;
+        } else if !is_close(ratio, Complex64::new(1.0, 0.0)) {
+            return None;
+        }
+    }
+    let z_index = to_index(z);
+    let matches = matrix.indexed_iter().all(|((row, column), value)| {
+        let expected = if row == column ^ x_index {
+            match (z_index & column).count_ones() % 2 {
+                0 => prefactor,
+                _ => -prefactor,
+            }
+        } else {
+            Complex64::new(0.0, 0.0)
+        };
+        is_close(*value, expected)
+    });
+    matches.then(|| LocalPauli { phase, x, z })
+}
+
+/// A single row of the stabilizer tableau: a Hermitian Pauli product with a sign.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TableauRow {
+    /// Bit-packed X components.
+    x: Vec<u64>,
+    /// Bit-packed Z components.
+    z: Vec<u64>,
+    /// Whether the sign of the Pauli product is negative.
+    sign: bool,
+}
+
+impl TableauRow {
+    /// Creates the identity row.
+    fn new(number_words: usize) -> Self {
+        Self {
+            x: vec![0; number_words],
+            z: vec![0; number_words],
+            sign: false,
+        }
+    }
+
+    /// Returns the X and Z bit of a qubit.
+    fn get(&self, qubit: usize) -> (bool, bool) {
+        let (word, bit) = (qubit / 64, qubit % 64);
+        (
+            (self.x[word] >> bit) & 1 == 1,
+            (self.z[word] >> bit) & 1 == 1,
+        )
+    }
+
+    /// Sets the X and Z bit of a qubit.
+    fn set(&mut self, qubit: usize, x: bool, z: bool) {
+        let (word, bit) = (qubit / 64, qubit % 64);
+        self.x[word] = (self.x[word] & !(1 << bit)) | ((x as u64) << bit);
+        self.z[word] = (self.z[word] & !(1 << bit)) | ((z as u64) << bit);
+    }
+
+    /// Multiplies the row from the left with another row (the rowsum operation of the tableau algorithm).
+    fn left_multiply(&mut self, other: &TableauRow) {
+        // Sum of the exponents of i picked up when multiplying the single-qubit Pauli operators
+        let mut exponent: i64 = 0;
+        for word in 0..self.x.len() {
+            let (x1, z1) = (other.x[word], other.z[word]);
+            let (x2, z2) = (self.x[word], self.z[word]);
+            let plus = (x1 & z1 & !x2 & z2) | (x1 & !z1 & x2 & z2) | (!x1 & z1 & x2 & !z2);
+            let minus = (x1 & z1 & x2 & !z2) | (x1 & !z1 & !x2 & z2) | (!x1 & z1 & x2 & z2);
+            exponent += plus.count_ones() as i64 - minus.count_ones() as i64;
+            self.x[word] ^= x1;
+            self.z[word] ^= z1;
+        }
+        exponent += 2 * (self.sign as i64) + 2 * (other.sign as i64);
+        self.sign = exponent.rem_euclid(4) == 2;
+    }
+}
+
+/// The stabilizer tableau of Aaronson and Gottesman.
+///
+/// Rows 0..N are the destabilizers and rows N..2N the stabilizers of the state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tableau {
+    /// The number of qubits.
+    number_qubits: usize,
+    /// The destabilizer and stabilizer rows.
+    rows: Vec<TableauRow>,
+}
+
+impl Tableau {
+    /// Creates the tableau of the |0...0> state.
+    fn new(number_qubits: usize) -> Self {
+        let number_words = (number_qubits + 63) / 64;
+        let mut rows = vec![TableauRow::new(number_words); 2 * number_qubits];
+        for qubit in 0..number_qubits {
+            rows[qubit].set(qubit, true, false);
+            rows[qubit + number_qubits].set(qubit, false, true);
+        }
+        Self {
+            number_qubits,
+            rows,
+        }
+    }
+
+    /// Conjugates all rows with a Clifford gate acting on the given qubits.
+    fn apply_clifford(&mut self, clifford: &LocalClifford, qubits: &[usize]) {
+        let number_local_qubits = clifford.number_local_qubits;
+        for row in self.rows.iter_mut() {
+            let mut local_x = 0;
+            let mut local_z = 0;
+            for (local_qubit, qubit) in qubits.iter().enumerate() {
+                let (x, z) = row.get(*qubit);
+                local_x |= (x as usize) << local_qubit;
+                local_z |= (z as usize) << local_qubit;
+            }
+            if local_x == 0 && local_z == 0 {
+                continue;
+            }
+            let (new_x, new_z, sign_flip) =
+                clifford.table[(local_x << number_local_qubits) | local_z];
+            for (local_qubit, qubit) in qubits.iter().enumerate() {
+                row.set(
+                    *qubit,
+                    (new_x >> local_qubit) & 1 == 1,
+                    (new_z >> local_qubit) & 1 == 1,
+                );
+            }
+            row.sign ^= sign_flip;
+        }
+    }
+
+    /// Applies a PauliX gate, flipping the sign of all rows anticommuting with X.
+    fn apply_pauli_x(&mut self, qubit: usize) {
+        for row in self.rows.iter_mut() {
+            if row.get(qubit).1 {
+                row.sign = !row.sign;
+            }
+        }
+    }
+
+    /// Measures a qubit in the Z-basis and updates the tableau.
+    fn measure(&mut self, qubit: usize, rng: &mut StdRng) -> bool {
+        let n = self.number_qubits;
+        let pivot = (n..2 * n).find(|row| self.rows[*row].get(qubit).0);
+        match pivot {
+            Some(pivot) => {
+                // Random outcome
+                let pivot_row = self.rows[pivot].clone();
+                for (index, row) in self.rows.iter_mut().enumerate() {
+                    if index != pivot && row.get(qubit).0 {
+                        row.left_multiply(&pivot_row);
+                    }
+                }
+                let result: bool = rng.gen();
+                let mut new_row = TableauRow::new(pivot_row.x.len());
+                new_row.set(qubit, false, true);
+                new_row.sign = result;
+                self.rows[pivot - n] = self.rows[pivot].clone();
+                self.rows[pivot] = new_row;
+                result
+            }
+            None => {
+                // Deterministic outcome
+                let mut scratch = TableauRow::new(self.rows[0].x.len());
+                for index in 0..n {
+                    if self.rows[index].get(qubit).0 {
+                        scratch.left_multiply(&self.rows[index + n]);
+                    }
+                }
+                scratch.sign
+            }
+        }
+    }
+}
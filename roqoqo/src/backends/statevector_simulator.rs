@@ -0,0 +1,514 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::simulator_helpers::{
+    apply_matrix, basis_states_to_bits, check_qubits, create_rng, dense_dimension,
+    distributed_shots, gate_qubits, number_of_repetitions, number_of_shots, pauli_matrix,
+    sample_basis_states, SimulationRegisters,
+};
+use crate::backends::{CachedSimulatorState, CachingBackend, EvaluatingBackend, RegisterResult};
+use crate::operations::{
+    GateOperation, Operate, OperateGate, OperateSingleQubit, Operation, PragmaRepeatedMeasurement,
+};
+use crate::{Circuit, RoqoqoBackendError};
+use ndarray::Array1;
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Pure-Rust statevector simulator implementing [EvaluatingBackend].
+///
+/// The simulator represents the quantum register by its full state vector of dimension 2^N,
+/// where qubit `i` corresponds to bit `i` of the index of the state vector.
+/// Every gate operation is applied via its unitary matrix.
+/// Supported measurement operations are [crate::operations::MeasureQubit],
+/// [crate::operations::PragmaRepeatedMeasurement], [crate::operations::PragmaGetStateVector],
+/// [crate::operations::PragmaGetDensityMatrix], [crate::operations::PragmaGetOccupationProbability],
+/// [crate::operations::PragmaGetPauliProduct] and [crate::operations::PragmaGetAmplitudes].
+/// The circuit of a [crate::operations::PragmaConditional] is executed when the condition bit,
+/// written by a previous [crate::operations::MeasureQubit] of the same run, is set.
+/// Noise PRAGMAs are not supported and return [RoqoqoBackendError::OperationNotInBackend].
+///
+/// When the circuit contains a [crate::operations::PragmaSetNumberOfMeasurements]
+/// the whole circuit is executed the given number of times.
+///
+/// By default all measurements of a [crate::operations::PragmaRepeatedMeasurement] are sampled
+/// from the state of a single run, so they share the outcomes of any mid-circuit measurement of
+/// that run. In shot-resolved mode (see [StatevectorSimulator::set_shot_resolved]) the whole
+/// circuit is instead executed once per shot, re-running every state collapse and classical
+/// condition. Each shot then contributes exactly one row to every bit output register, so the
+/// rows of different registers belong to the same shot and feed-forward protocols like quantum
+/// teleportation produce correct per-shot results.
+///
+/// # Example
+///
+/// ```
+/// use roqoqo::backends::{EvaluatingBackend, StatevectorSimulator};
+/// use roqoqo::operations::{DefinitionBit, Hadamard, CNOT, PragmaRepeatedMeasurement};
+/// use roqoqo::Circuit;
+///
+/// let mut circuit = Circuit::new();
+/// circuit += DefinitionBit::new("ro".to_string(), 2, true);
+/// circuit += Hadamard::new(0);
+/// circuit += CNOT::new(0, 1);
+/// circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, None);
+///
+/// let backend = StatevectorSimulator::new(2);
+/// let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+/// assert_eq!(bit_registers["ro"].len(), 100);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatevectorSimulator {
+    /// The number of qubits of the simulated quantum register.
+    number_qubits: usize,
+    /// Optional seed of the random number generator used for measurements.
+    random_seed: Option<u64>,
+    /// Whether the circuit is executed once per shot.
+    shot_resolved: bool,
+}
+
+impl StatevectorSimulator {
+    /// Creates a new StatevectorSimulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_qubits` - The number of qubits of the simulated quantum register.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new StatevectorSimulator.
+    pub fn new(number_qubits: usize) -> Self {
+        Self {
+            number_qubits,
+            random_seed: None,
+            shot_resolved: false,
+        }
+    }
+
+    /// Returns the number of qubits of the simulated quantum register.
+    pub fn number_qubits(&self) -> usize {
+        self.number_qubits
+    }
+
+    /// Returns the seed of the random number generator.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets the seed of the random number generator.
+    ///
+    /// When a seed is set, every run of a circuit produces the same measurement results.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_seed` - The seed of the random number generator (None for a random seed).
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.random_seed = random_seed;
+    }
+
+    /// Returns whether the circuit is executed once per shot.
+    pub fn shot_resolved(&self) -> bool {
+        self.shot_resolved
+    }
+
+    /// Sets whether the circuit is executed once per shot.
+    ///
+    /// In shot-resolved mode the number of shots is the largest number of measurements requested
+    /// by a [crate::operations::PragmaSetNumberOfMeasurements] or a
+    /// [crate::operations::PragmaRepeatedMeasurement]. Every shot starts from the |0...0> state
+    /// and every [crate::operations::PragmaRepeatedMeasurement] samples at most one row per shot.
+    ///
+    /// # Arguments
+    ///
+    /// * `shot_resolved` - True to execute the circuit once per shot.
+    pub fn set_shot_resolved(&mut self, shot_resolved: bool) {
+        self.shot_resolved = shot_resolved;
+    }
+
+    /// Returns the state vector of the quantum register initialized in the |0...0> state.
+    ///
+    /// Fails if the state vector of the number of qubits is too large to be simulated.
+    pub(crate) fn initial_state(&self) -> Result<Array1<Complex64>, RoqoqoBackendError> {
+        let mut state: Array1<Complex64> = Array1::zeros(dense_dimension(self.number_qubits)?);
+        state[0] = Complex64::new(1.0, 0.0);
+        Ok(state)
+    }
+
+    /// Applies a single operation to the state vector and the classical registers.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation that is applied.
+    /// * `state` - The state vector of the quantum register.
+    /// * `registers` - The classical registers of the current run.
+    /// * `rng` - The random number generator used for measurements.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The operation has been applied.
+    /// * `Err(RoqoqoBackendError)` - The operation is not supported or could not be applied.
+    pub(crate) fn apply_operation(
+        &self,
+        operation: &Operation,
+        state: &mut Array1<Complex64>,
+        registers: &mut SimulationRegisters,
+        rng: &mut StdRng,
+    ) -> Result<(), RoqoqoBackendError> {
+        if registers.define(operation) {
+            return Ok(());
+        }
+        check_qubits(operation, self.number_qubits)?;
+        if operation.tags().contains(&"GateOperation") {
+            let matrix = GateOperation::try_from(operation.clone())?.unitary_matrix()?;
+            apply_matrix(state, &matrix, &gate_qubits(operation)?);
+            return Ok(());
+        }
+        match operation {
+            Operation::MeasureQubit(op) => {
+                let result = measure_qubit(state, *op.qubit(), rng);
+                registers.set_bit(op.readout(), *op.readout_index(), result)
+            }
+            Operation::PragmaActiveReset(op) => {
+                if measure_qubit(state, *op.qubit(), rng) {
+                    flip_qubit(state, *op.qubit());
+                }
+                Ok(())
+            }
+            Operation::PragmaRepeatedMeasurement(op) => {
+                self.repeated_measurement(op, *op.number_measurements(), state, registers, rng)
+            }
+            Operation::PragmaConditional(op) => {
+                if registers.get_bit(op.condition_register(), *op.condition_index())? {
+                    for conditional_operation in op.circuit().iter() {
+                        self.apply_operation(conditional_operation, state, registers, rng)?;
+                    }
+                }
+                Ok(())
+            }
+            Operation::PragmaGetStateVector(op) => {
+                let tmp_state = self.prepared_copy(state, op.circuit().as_ref(), registers, rng)?;
+                registers
+                    .complex_registers
+                    .insert(op.readout().clone(), tmp_state.to_vec());
+                Ok(())
+            }
+            Operation::PragmaGetDensityMatrix(op) => {
+                let tmp_state = self.prepared_copy(state, op.circuit().as_ref(), registers, rng)?;
+                let density_matrix: Vec<Complex64> = tmp_state
+                    .iter()
+                    .flat_map(|row| tmp_state.iter().map(move |column| row * column.conj()))
+                    .collect();
+                registers
+                    .complex_registers
+                    .insert(op.readout().clone(), density_matrix);
+                Ok(())
+            }
+            Operation::PragmaGetAmplitudes(op) => {
+                let tmp_state = self.prepared_copy(state, op.circuit().as_ref(), registers, rng)?;
+                let amplitudes = op
+                    .bitstrings()
+                    .iter()
+                    .map(|bitstring| {
+                        if bitstring.len() > self.number_qubits {
+                            return Err(RoqoqoBackendError::GenericError {
+                                msg: format!(
+                                    "Length {} of the bitstring exceeds the number of qubits {}",
+                                    bitstring.len(),
+                                    self.number_qubits
+                                ),
+                            });
+                        }
+                        let index: usize = bitstring
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, bit)| **bit)
+                            .map(|(qubit, _)| 1_usize << qubit)
+                            .sum();
+                        Ok(tmp_state[index])
+                    })
+                    .collect::<Result<Vec<Complex64>, RoqoqoBackendError>>()?;
+                registers
+                    .complex_registers
+                    .insert(op.readout().clone(), amplitudes);
+                Ok(())
+            }
+            Operation::PragmaGetOccupationProbability(op) => {
+                let tmp_state = self.prepared_copy(state, op.circuit().as_ref(), registers, rng)?;
+                registers.float_registers.insert(
+                    op.readout().clone(),
+                    tmp_state.iter().map(|x| x.norm_sqr()).collect(),
+                );
+                Ok(())
+            }
+            Operation::PragmaGetPauliProduct(op) => {
+                let tmp_state = self.prepared_copy(state, Some(op.circuit()), registers, rng)?;
+                let value = pauli_product_expectation_value(&tmp_state, op.qubit_paulis())?;
+                registers
+                    .float_registers
+                    .insert(op.readout().clone(), vec![value]);
+                Ok(())
+            }
+            Operation::PragmaSetStateVector(op) => {
+                if op.statevector().len() != state.len() {
+                    return Err(RoqoqoBackendError::GenericError {
+                        msg: format!(
+                            "Length {} of the state vector does not match the dimension {} of the simulated quantum register",
+                            op.statevector().len(),
+                            state.len()
+                        ),
+                    });
+                }
+                state.assign(op.statevector());
+                Ok(())
+            }
+            Operation::PragmaGlobalPhase(op) => {
+                let phase = Complex64::from_polar(1.0, *op.phase().float()?);
+                state.mapv_inplace(|x| x * phase);
+                Ok(())
+            }
+            Operation::PragmaSetNumberOfMeasurements(_)
+            | Operation::PragmaRepeatGate(_)
+            | Operation::PragmaOverrotation(_)
+            | Operation::PragmaBoostNoise(_)
+            | Operation::PragmaStopParallelBlock(_)
+            | Operation::PragmaSleep(_)
+            | Operation::PragmaStartDecompositionBlock(_)
+            | Operation::PragmaStopDecompositionBlock(_) => Ok(()),
+            _ => Err(RoqoqoBackendError::OperationNotInBackend {
+                backend: "StatevectorSimulator",
+                hqslang: operation.hqslang(),
+            }),
+        }
+    }
+
+    /// Samples rows of a repeated measurement from the state vector without collapsing it.
+    fn repeated_measurement(
+        &self,
+        operation: &PragmaRepeatedMeasurement,
+        number_measurements: usize,
+        state: &Array1<Complex64>,
+        registers: &mut SimulationRegisters,
+        rng: &mut StdRng,
+    ) -> Result<(), RoqoqoBackendError> {
+        let length = registers.bit_register_length(operation.readout())?;
+        let probabilities: Vec<f64> = state.iter().map(|x| x.norm_sqr()).collect();
+        let rows = basis_states_to_bits(
+            sample_basis_states(&probabilities, number_measurements, rng),
+            length,
+            self.number_qubits,
+            operation.qubit_mapping(),
+        )?;
+        registers.append_repeated_measurement(operation.readout(), rows);
+        Ok(())
+    }
+
+    /// Applies an operation during one shot of a shot-resolved run.
+    ///
+    /// Repeated measurements, also inside conditional circuits, only sample the rows belonging
+    /// to the current shot.
+    fn apply_operation_in_shot(
+        &self,
+        operation: &Operation,
+        shot: usize,
+        number_shots: usize,
+        state: &mut Array1<Complex64>,
+        registers: &mut SimulationRegisters,
+        rng: &mut StdRng,
+    ) -> Result<(), RoqoqoBackendError> {
+        match operation {
+            Operation::PragmaRepeatedMeasurement(op) => {
+                let number_measurements =
+                    distributed_shots(*op.number_measurements(), shot, number_shots);
+                self.repeated_measurement(op, number_measurements, state, registers, rng)
+            }
+            Operation::PragmaConditional(op) => {
+                if registers.get_bit(op.condition_register(), *op.condition_index())? {
+                    for conditional_operation in op.circuit().iter() {
+                        self.apply_operation_in_shot(
+                            conditional_operation,
+                            shot,
+                            number_shots,
+                            state,
+                            registers,
+                            rng,
+                        )?;
+                    }
+                }
+                Ok(())
+            }
+            _ => self.apply_operation(operation, state, registers, rng),
+        }
+    }
+
+    /// Returns a copy of the state vector with the optional preparation circuit applied.
+    fn prepared_copy(
+        &self,
+        state: &Array1<Complex64>,
+        circuit: Option<&crate::Circuit>,
+        registers: &mut SimulationRegisters,
+        rng: &mut StdRng,
+    ) -> Result<Array1<Complex64>, RoqoqoBackendError> {
+        let mut tmp_state = state.clone();
+        if let Some(circuit) = circuit {
+            for operation in circuit.iter() {
+                self.apply_operation(operation, &mut tmp_state, registers, rng)?;
+            }
+        }
+        Ok(tmp_state)
+    }
+}
+
+impl EvaluatingBackend for StatevectorSimulator {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        self.run_from_state(
+            &self.initial_state()?,
+            &SimulationRegisters::new(),
+            &operations,
+        )
+    }
+}
+
+impl CachingBackend for StatevectorSimulator {
+    type CachedState = CachedSimulatorState;
+
+    fn prepare_constant_circuit(
+        &self,
+        constant_circuit: Option<&Circuit>,
+    ) -> Result<CachedSimulatorState, RoqoqoBackendError> {
+        let mut rng = create_rng(self.random_seed);
+        CachedSimulatorState::prepare(
+            constant_circuit,
+            self.initial_state()?,
+            |operation, state, registers| {
+                self.apply_operation(operation, state, registers, &mut rng)
+            },
+        )
+    }
+
+    fn run_circuit_from_cached_state(
+        &self,
+        cached_state: &CachedSimulatorState,
+        circuit: &Circuit,
+    ) -> RegisterResult {
+        match cached_state.state.as_ref() {
+            Some(state) => {
+                let operations: Vec<&Operation> = circuit.iter().collect();
+                self.run_from_state(state, &cached_state.registers, &operations)
+            }
+            None => self.run_circuit_iterator(
+                cached_state
+                    .constant_operations
+                    .iter()
+                    .chain(circuit.iter()),
+            ),
+        }
+    }
+}
+
+impl StatevectorSimulator {
+    /// Executes operations repeatedly, starting every run from the given state and registers.
+    fn run_from_state(
+        &self,
+        initial_state: &Array1<Complex64>,
+        initial_registers: &SimulationRegisters,
+        operations: &[&Operation],
+    ) -> RegisterResult {
+        let mut rng = create_rng(self.random_seed);
+        let mut registers = initial_registers.clone();
+        if self.shot_resolved {
+            let number_shots = number_of_shots(operations);
+            for shot in 0..number_shots {
+                let mut state = initial_state.clone();
+                registers.reset_run(initial_registers);
+                for operation in operations.iter() {
+                    self.apply_operation_in_shot(
+                        operation,
+                        shot,
+                        number_shots,
+                        &mut state,
+                        &mut registers,
+                        &mut rng,
+                    )?;
+                }
+                registers.finish_run();
+            }
+            return registers.into_output();
+        }
+        for _ in 0..number_of_repetitions(operations) {
+            let mut state = initial_state.clone();
+            registers.reset_run(initial_registers);
+            for operation in operations.iter() {
+                self.apply_operation(operation, &mut state, &mut registers, &mut rng)?;
+            }
+            registers.finish_run();
+        }
+        registers.into_output()
+    }
+}
+
+/// Measures a single qubit in the Z-basis and collapses the state vector.
+pub(crate) fn measure_qubit(state: &mut Array1<Complex64>, qubit: usize, rng: &mut StdRng) -> bool {
+    let mask = 1_usize << qubit;
+    let probability_one: f64 = state
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| index & mask != 0)
+        .map(|(_, x)| x.norm_sqr())
+        .sum();
+    let result = rng.gen::<f64>() < probability_one;
+    let norm = if result {
+        probability_one.sqrt()
+    } else {
+        (1.0 - probability_one).sqrt()
+    };
+    for (index, value) in state.iter_mut().enumerate() {
+        if (index & mask != 0) == result {
+            *value /= norm;
+        } else {
+            *value = Complex64::new(0.0, 0.0);
+        }
+    }
+    result
+}
+
+/// Flips a single qubit of the state vector (applies PauliX).
+pub(crate) fn flip_qubit(state: &mut Array1<Complex64>, qubit: usize) {
+    let mask = 1_usize << qubit;
+    for index in 0..state.len() {
+        if index & mask == 0 {
+            state.swap(index, index | mask);
+        }
+    }
+}
+
+/// Returns the expectation value of a Pauli product for a state vector.
+pub(crate) fn pauli_product_expectation_value(
+    state: &Array1<Complex64>,
+    qubit_paulis: &HashMap<usize, usize>,
+) -> Result<f64, RoqoqoBackendError> {
+    let mut tmp_state = state.clone();
+    for (qubit, pauli) in qubit_paulis.iter() {
+        apply_matrix(&mut tmp_state, &pauli_matrix(*pauli)?, &[*qubit]);
+    }
+    Ok(state
+        .iter()
+        .zip(tmp_state.iter())
+        .map(|(a, b)| a.conj() * b)
+        .sum::<Complex64>()
+        .re)
+}
@@ -0,0 +1,438 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::simulator_helpers::{check_qubits, gate_qubits, SimulationRegisters};
+use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::operations::{
+    GateOperation, MultiQubitGateOperation, Operate, OperateGate, OperateMultiQubit,
+    OperateMultiQubitGate, Operation,
+};
+use crate::RoqoqoBackendError;
+use ndarray::Array2;
+use num_complex::Complex64;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+/// Default for the largest number of indices of a tensor created during a contraction.
+pub const TENSOR_NETWORK_DEFAULT_MAX_RANK: usize = 28;
+
+/// Tensor-network amplitude simulator implementing [EvaluatingBackend].
+///
+/// The simulator does not store the state of the quantum register. Instead the circuit is
+/// converted into a network of tensors, one for the initial |0> state of every qubit and one
+/// for every gate (built from its unitary matrix). Multi-qubit gates acting on more than two
+/// qubits are replaced by their decomposition into two-qubit gates
+/// (see [crate::operations::OperateMultiQubitGate::circuit]).
+///
+/// A [crate::operations::PragmaGetAmplitudes] closes the network with the requested bitstrings
+/// and contracts it to a single amplitude per bitstring, using a greedy contraction order that
+/// always contracts the pair of tensors reducing the total size of the network the most.
+/// For shallow circuits the tensors stay small, so that amplitudes of circuits with far more
+/// qubits than a statevector simulator can hold are accessible.
+/// When an intermediate tensor would have more than
+/// [TensorNetworkSimulator::max_tensor_rank] indices, a [RoqoqoBackendError::GenericError] is returned.
+///
+/// Besides gates, definitions and [crate::operations::PragmaGlobalPhase] only PRAGMAs without
+/// effect on the quantum state are supported; measurements return
+/// [RoqoqoBackendError::OperationNotInBackend].
+///
+/// # Example
+///
+/// ```
+/// use roqoqo::backends::{EvaluatingBackend, TensorNetworkSimulator};
+/// use roqoqo::operations::{DefinitionComplex, Hadamard, CNOT, PragmaGetAmplitudes};
+/// use roqoqo::Circuit;
+///
+/// let mut circuit = Circuit::new();
+/// circuit += DefinitionComplex::new("amplitudes".to_string(), 2, true);
+/// circuit += Hadamard::new(0);
+/// for qubit in 0..49 {
+///     circuit += CNOT::new(qubit, qubit + 1);
+/// }
+/// circuit += PragmaGetAmplitudes::new(
+///     "amplitudes".to_string(),
+///     vec![vec![false; 50], vec![true; 50]],
+///     None,
+/// );
+///
+/// let backend = TensorNetworkSimulator::new(50);
+/// let (_, _, complex_registers) = backend.run_circuit(&circuit).unwrap();
+/// assert!((complex_registers["amplitudes"][0][1].re - 0.5_f64.sqrt()).abs() < 1e-10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorNetworkSimulator {
+    /// The number of qubits of the simulated quantum register.
+    number_qubits: usize,
+    /// The largest number of indices of a tensor created during a contraction.
+    max_tensor_rank: usize,
+}
+
+impl TensorNetworkSimulator {
+    /// Creates a new TensorNetworkSimulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_qubits` - The number of qubits of the simulated quantum register.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new TensorNetworkSimulator.
+    pub fn new(number_qubits: usize) -> Self {
+        Self {
+            number_qubits,
+            max_tensor_rank: TENSOR_NETWORK_DEFAULT_MAX_RANK,
+        }
+    }
+
+    /// Returns the number of qubits of the simulated quantum register.
+    pub fn number_qubits(&self) -> usize {
+        self.number_qubits
+    }
+
+    /// Returns the largest number of indices of a tensor created during a contraction.
+    pub fn max_tensor_rank(&self) -> usize {
+        self.max_tensor_rank
+    }
+
+    /// Sets the largest number of indices of a tensor created during a contraction.
+    ///
+    /// A tensor with `r` indices holds 2^r complex numbers, the limit therefore bounds
+    /// the memory used by the simulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_tensor_rank` - The new largest number of indices of a tensor.
+    pub fn set_max_tensor_rank(&mut self, max_tensor_rank: usize) {
+        self.max_tensor_rank = max_tensor_rank;
+    }
+
+    /// Applies a single operation to the tensor network and the classical registers.
+    fn apply_operation(
+        &self,
+        operation: &Operation,
+        network: &mut TensorNetwork,
+        registers: &mut SimulationRegisters,
+    ) -> Result<(), RoqoqoBackendError> {
+        if registers.define(operation) {
+            return Ok(());
+        }
+        check_qubits(operation, self.number_qubits)?;
+        if operation.tags().contains(&"MultiQubitGateOperation") {
+            let gate = MultiQubitGateOperation::try_from(operation.clone())?;
+            if gate.qubits().len() > 2 {
+                for decomposed_operation in gate.circuit().iter() {
+                    self.apply_operation(decomposed_operation, network, registers)?;
+                }
+                return Ok(());
+            }
+        }
+        if operation.tags().contains(&"GateOperation") {
+            let matrix = GateOperation::try_from(operation.clone())?.unitary_matrix()?;
+            network.add_gate(&matrix, &gate_qubits(operation)?);
+            return Ok(());
+        }
+        match operation {
+            Operation::PragmaGetAmplitudes(op) => {
+                let mut tmp_network = network.clone();
+                if let Some(circuit) = op.circuit() {
+                    for circuit_operation in circuit.iter() {
+                        self.apply_operation(circuit_operation, &mut tmp_network, registers)?;
+                    }
+                }
+                let amplitudes = op
+                    .bitstrings()
+                    .iter()
+                    .map(|bitstring| tmp_network.amplitude(bitstring, self.max_tensor_rank))
+                    .collect::<Result<Vec<Complex64>, RoqoqoBackendError>>()?;
+                registers
+                    .complex_registers
+                    .insert(op.readout().clone(), amplitudes);
+                Ok(())
+            }
+            Operation::PragmaGlobalPhase(op) => {
+                network.global_factor *= Complex64::from_polar(1.0, *op.phase().float()?);
+                Ok(())
+            }
+            Operation::PragmaSetNumberOfMeasurements(_)
+            | Operation::PragmaRepeatGate(_)
+            | Operation::PragmaOverrotation(_)
+            | Operation::PragmaBoostNoise(_)
+            | Operation::PragmaStopParallelBlock(_)
+            | Operation::PragmaSleep(_)
+            | Operation::PragmaStartDecompositionBlock(_)
+            | Operation::PragmaStopDecompositionBlock(_) => Ok(()),
+            _ => Err(RoqoqoBackendError::OperationNotInBackend {
+                backend: "TensorNetworkSimulator",
+                hqslang: operation.hqslang(),
+            }),
+        }
+    }
+}
+
+impl EvaluatingBackend for TensorNetworkSimulator {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        // The amplitudes are deterministic, the circuit is therefore only executed once
+        let mut registers = SimulationRegisters::new();
+        let mut network = TensorNetwork::new(self.number_qubits);
+        for operation in circuit {
+            self.apply_operation(operation, &mut network, &mut registers)?;
+        }
+        registers.finish_run();
+        registers.into_output()
+    }
+}
+
+/// A dense tensor where every index has dimension 2.
+#[derive(Debug, Clone, PartialEq)]
+struct Tensor {
+    /// The labels of the indices, the first index is the most significant one in `data`.
+    indices: Vec<usize>,
+    /// The entries of the tensor in row-major order.
+    data: Vec<Complex64>,
+}
+
+impl Tensor {
+    /// Contracts two tensors over all indices they share.
+    ///
+    /// The indices of the result are the free indices of `self` followed by those of `other`.
+    fn contract(&self, other: &Tensor) -> Tensor {
+        let shared: Vec<usize> = self
+            .indices
+            .iter()
+            .filter(|index| other.indices.contains(index))
+            .copied()
+            .collect();
+        let free_self: Vec<usize> = self
+            .indices
+            .iter()
+            .filter(|index| !shared.contains(index))
+            .copied()
+            .collect();
+        let free_other: Vec<usize> = other
+            .indices
+            .iter()
+            .filter(|index| !shared.contains(index))
+            .copied()
+            .collect();
+        let offsets_self = self.offsets(&free_self);
+        let offsets_other = other.offsets(&free_other);
+        let shared_self = self.offsets(&shared);
+        let shared_other = other.offsets(&shared);
+        let mut data: Vec<Complex64> = Vec::with_capacity(offsets_self.len() * offsets_other.len());
+        for offset_self in offsets_self.iter() {
+            for offset_other in offsets_other.iter() {
+                data.push(
+                    shared_self
+                        .iter()
+                        .zip(shared_other.iter())
+                        .map(|(a, b)| self.data[offset_self + a] * other.data[offset_other + b])
+                        .sum(),
+                );
+            }
+        }
+        let mut indices = free_self;
+        indices.extend(free_other);
+        Tensor { indices, data }
+    }
+
+    /// Returns the positions in `data` of all value combinations of a subset of the indices.
+    ///
+    /// The first index of the subset is the most significant one in the enumeration.
+    fn offsets(&self, subset: &[usize]) -> Vec<usize> {
+        let rank = self.indices.len();
+        let strides: Vec<usize> = subset
+            .iter()
+            .map(|label| {
+                let position = self
+                    .indices
+                    .iter()
+                    .position(|index| index == label)
+                    .expect("Index is part of the tensor");
+                1_usize << (rank - 1 - position)
+            })
+            .collect();
+        (0..1_usize << subset.len())
+            .map(|combination| {
+                strides
+                    .iter()
+                    .enumerate()
+                    .filter(|(position, _)| (combination >> (subset.len() - 1 - position)) & 1 == 1)
+                    .map(|(_, stride)| stride)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Tensor network representing a circuit applied to the |0...0> state.
+#[derive(Debug, Clone)]
+struct TensorNetwork {
+    /// The tensors of the network.
+    tensors: Vec<Tensor>,
+    /// The open index of every qubit wire.
+    wires: Vec<usize>,
+    /// The label used for the next new index.
+    next_index: usize,
+    /// A global factor of the amplitudes, e.g. from a global phase.
+    global_factor: Complex64,
+}
+
+impl TensorNetwork {
+    /// Creates a network of all qubits in the |0> state.
+    fn new(number_qubits: usize) -> Self {
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        Self {
+            tensors: (0..number_qubits)
+                .map(|qubit| Tensor {
+                    indices: vec![qubit],
+                    data: vec![one, zero],
+                })
+                .collect(),
+            wires: (0..number_qubits).collect(),
+            next_index: number_qubits,
+            global_factor: one,
+        }
+    }
+
+    /// Adds a gate tensor to the network.
+    ///
+    /// The first qubit in `qubits` corresponds to the most significant bit of the matrix indices.
+    fn add_gate(&mut self, matrix: &Array2<Complex64>, qubits: &[usize]) {
+        let mut indices: Vec<usize> = Vec::with_capacity(2 * qubits.len());
+        for _ in qubits.iter() {
+            indices.push(self.next_index);
+            self.next_index += 1;
+        }
+        for qubit in qubits.iter() {
+            indices.push(self.wires[*qubit]);
+        }
+        for (position, qubit) in qubits.iter().enumerate() {
+            self.wires[*qubit] = indices[position];
+        }
+        self.tensors.push(Tensor {
+            indices,
+            data: matrix.iter().copied().collect(),
+        });
+    }
+
+    /// Returns the amplitude of a computational basis state.
+    ///
+    /// Closes every qubit wire with the basis vector given by the bitstring and contracts the
+    /// network with a greedy contraction order.
+    fn amplitude(
+        &self,
+        bitstring: &[bool],
+        max_tensor_rank: usize,
+    ) -> Result<Complex64, RoqoqoBackendError> {
+        if bitstring.len() > self.wires.len() {
+            return Err(RoqoqoBackendError::GenericError {
+                msg: format!(
+                    "Length {} of the bitstring exceeds the number of qubits {}",
+                    bitstring.len(),
+                    self.wires.len()
+                ),
+            });
+        }
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        let mut tensors = self.tensors.clone();
+        for (qubit, wire) in self.wires.iter().enumerate() {
+            let data = if bitstring.get(qubit).copied().unwrap_or(false) {
+                vec![zero, one]
+            } else {
+                vec![one, zero]
+            };
+            tensors.push(Tensor {
+                indices: vec![*wire],
+                data,
+            });
+        }
+        while tensors.len() > 1 {
+            let (first, second) = greedy_pair(&tensors);
+            let result_rank = contracted_rank(&tensors[first], &tensors[second]);
+            if result_rank > max_tensor_rank {
+                return Err(RoqoqoBackendError::GenericError {
+                    msg: format!(
+                        "Contraction creates a tensor with {} indices, exceeding the maximum of {}",
+                        result_rank, max_tensor_rank
+                    ),
+                });
+            }
+            // Remove the later tensor first so that the position of the earlier one stays valid
+            let second_tensor = tensors.swap_remove(second);
+            let first_tensor = tensors.swap_remove(first);
+            tensors.push(first_tensor.contract(&second_tensor));
+        }
+        let scalar = tensors.pop().map(|tensor| tensor.data[0]).unwrap_or(one);
+        Ok(scalar * self.global_factor)
+    }
+}
+
+/// Returns the number of indices of the contraction of two tensors.
+fn contracted_rank(first: &Tensor, second: &Tensor) -> usize {
+    let number_shared = first
+        .indices
+        .iter()
+        .filter(|index| second.indices.contains(index))
+        .count();
+    first.indices.len() + second.indices.len() - 2 * number_shared
+}
+
+/// Returns the positions (first < second) of the next pair of tensors to contract.
+///
+/// Among all pairs sharing an index the pair is chosen whose contraction reduces the total size
+/// of the network the most, ties are broken by the smaller rank of the result.
+/// When no tensors share an index the two smallest tensors are combined.
+fn greedy_pair(tensors: &[Tensor]) -> (usize, usize) {
+    let mut index_positions: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (position, tensor) in tensors.iter().enumerate() {
+        for index in tensor.indices.iter() {
+            index_positions.entry(*index).or_default().push(position);
+        }
+    }
+    let size = |rank: usize| 2_f64.powi(rank as i32);
+    let mut best: Option<((f64, usize), (usize, usize))> = None;
+    for positions in index_positions.values() {
+        if positions.len() != 2 {
+            continue;
+        }
+        let (first, second) = (
+            positions[0].min(positions[1]),
+            positions[0].max(positions[1]),
+        );
+        let result_rank = contracted_rank(&tensors[first], &tensors[second]);
+        let cost = (
+            size(result_rank)
+                - size(tensors[first].indices.len())
+                - size(tensors[second].indices.len()),
+            result_rank,
+        );
+        if best.map_or(true, |(best_cost, _)| cost < best_cost) {
+            best = Some((cost, (first, second)));
+        }
+    }
+    match best {
+        Some((_, pair)) => pair,
+        None => {
+            let mut positions: Vec<usize> = (0..tensors.len()).collect();
+            positions.sort_by_key(|position| tensors[*position].indices.len());
+            (
+                positions[0].min(positions[1]),
+                positions[0].max(positions[1]),
+            )
+        }
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::simulator_helpers::{
+    apply_matrix, basis_states_to_bits, check_qubits, create_rng, distributed_shots,
+    kraus_operators, number_of_repetitions, sample_basis_states, SimulationRegisters,
+};
+use crate::backends::{EvaluatingBackend, RegisterResult, StatevectorSimulator};
+use crate::operations::{
+    Operate, OperatePragmaNoise, OperateSingleQubit, Operation, PragmaNoiseOperation,
+    SingleQubitOperation,
+};
+use crate::registers::FloatOutputRegister;
+use crate::RoqoqoBackendError;
+use ndarray::Array1;
+use num_complex::Complex64;
+use qoqo_calculator::CalculatorFloat;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+/// Suffix of the float output registers containing the standard error of the mean of a register.
+pub const TRAJECTORY_ERROR_SUFFIX: &str = "_error";
+/// Suffix of the float output registers containing the mean of the bits of a bit register.
+pub const TRAJECTORY_MEAN_SUFFIX: &str = "_mean";
+
+/// Quantum-trajectory (Monte-Carlo wavefunction) simulator implementing [EvaluatingBackend].
+///
+/// The simulator unravels every noise PRAGMA into stochastic quantum jumps on a state vector.
+/// The Kraus operators of a noise PRAGMA are obtained from its superoperator and in every
+/// trajectory one Kraus operator is applied, chosen with the probability of the
+/// corresponding jump. Averaging over trajectories reproduces the results of the
+/// [crate::backends::DensityMatrixSimulator] while only storing a state vector.
+/// A [crate::operations::PragmaBoostNoise] multiplies the gate time of all following noise PRAGMAs
+/// with its noise coefficient. The circuit of a [crate::operations::PragmaConditional] is executed
+/// when the condition bit measured in the current trajectory is set. All other operations are
+/// simulated as in the [StatevectorSimulator].
+///
+/// The circuit is executed once per trajectory (or as often as requested by
+/// [crate::operations::PragmaSetNumberOfMeasurements] if that is larger).
+/// The measurements of a [crate::operations::PragmaRepeatedMeasurement] are distributed over
+/// the trajectories. The results are reported as follows:
+///
+/// * Float registers (e.g. from [crate::operations::PragmaGetOccupationProbability]) contain
+///   the average over the trajectories. The float register `<name>_error` contains the
+///   standard error of the mean of every entry.
+/// * Bit registers contain the measured rows of all trajectories. The float register
+///   `<name>_mean` contains the mean of every bit and `<name>_error` its standard error.
+/// * Complex registers contain one row per trajectory.
+///
+/// A run fails if one of these `<name>_error` or `<name>_mean` registers has the name of a
+/// register defined in the circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrajectorySimulator {
+    /// The number of qubits of the simulated quantum register.
+    number_qubits: usize,
+    /// The number of trajectories that are averaged over.
+    number_trajectories: usize,
+    /// Optional seed of the random number generator used for measurements and jumps.
+    random_seed: Option<u64>,
+}
+
+impl TrajectorySimulator {
+    /// Creates a new TrajectorySimulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_qubits` - The number of qubits of the simulated quantum register.
+    /// * `number_trajectories` - The number of trajectories that are averaged over.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new TrajectorySimulator.
+    pub fn new(number_qubits: usize, number_trajectories: usize) -> Self {
+        Self {
+            number_qubits,
+            number_trajectories: number_trajectories.max(1),
+            random_seed: None,
+        }
+    }
+
+    /// Returns the number of qubits of the simulated quantum register.
+    pub fn number_qubits(&self) -> usize {
+        self.number_qubits
+    }
+
+    /// Returns the number of trajectories that are averaged over.
+    pub fn number_trajectories(&self) -> usize {
+        self.number_trajectories
+    }
+
+    /// Sets the number of trajectories that are averaged over.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_trajectories` - The new number of trajectories (at least 1).
+    pub fn set_number_trajectories(&mut self, number_trajectories: usize) {
+        self.number_trajectories = number_trajectories.max(1);
+    }
+
+    /// Returns the seed of the random number generator.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets the seed of the random number generator.
+    ///
+    /// When a seed is set, every run of a circuit produces the same trajectories and measurement results.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_seed` - The seed of the random number generator (None for a random seed).
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.random_seed = random_seed;
+    }
+
+    /// Applies a single operation in one trajectory.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_operation(
+        &self,
+        statevector_simulator: &StatevectorSimulator,
+        operation: &Operation,
+        state: &mut Array1<Complex64>,
+        registers: &mut SimulationRegisters,
+        noise_coefficient: &mut CalculatorFloat,
+        shots: usize,
+        rng: &mut StdRng,
+    ) -> Result<(), RoqoqoBackendError> {
+        if operation.tags().contains(&"PragmaNoiseOperation") {
+            check_qubits(operation, self.number_qubits)?;
+            let noise = PragmaNoiseOperation::try_from(operation.clone())?;
+            let superoperator = noise.powercf(noise_coefficient.clone()).superoperator()?;
+            let qubit = *SingleQubitOperation::try_from(operation.clone())?.qubit();
+            apply_jump(state, &kraus_operators(&superoperator), qubit, rng);
+            return Ok(());
+        }
+        match operation {
+            Operation::PragmaBoostNoise(op) => {
+                *noise_coefficient = op.noise_coefficient().clone();
+                Ok(())
+            }
+            Operation::PragmaConditional(op) => {
+                if registers.get_bit(op.condition_register(), *op.condition_index())? {
+                    for conditional_operation in op.circuit().iter() {
+                        self.apply_operation(
+                            statevector_simulator,
+                            conditional_operation,
+                            state,
+                            registers,
+                            noise_coefficient,
+                            shots,
+                            rng,
+                        )?;
+                    }
+                }
+                Ok(())
+            }
+            Operation::PragmaRepeatedMeasurement(op) => {
+                let length = registers.bit_register_length(op.readout())?;
+                let probabilities: Vec<f64> = state.iter().map(|x| x.norm_sqr()).collect();
+                let rows = basis_states_to_bits(
+                    sample_basis_states(&probabilities, shots, rng),
+                    length,
+                    self.number_qubits,
+                    op.qubit_mapping(),
+                )?;
+                registers.append_repeated_measurement(op.readout(), rows);
+                Ok(())
+            }
+            _ => statevector_simulator
+                .apply_operation(operation, state, registers, rng)
+                .map_err(|err| match err {
+                    RoqoqoBackendError::OperationNotInBackend { hqslang, .. } => {
+                        RoqoqoBackendError::OperationNotInBackend {
+                            backend: "TrajectorySimulator",
+                            hqslang,
+                        }
+                    }
+                    _ => err,
+                }),
+        }
+    }
+}
+
+impl EvaluatingBackend for TrajectorySimulator {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let operations: Vec<&Operation> = circuit.collect();
+        let statevector_simulator = StatevectorSimulator::new(self.number_qubits);
+        let initial_state = statevector_simulator.initial_state()?;
+        let mut rng = create_rng(self.random_seed);
+        let mut registers = SimulationRegisters::new();
+        let number_runs = self
+            .number_trajectories
+            .max(number_of_repetitions(&operations));
+        for run in 0..number_runs {
+            let mut state = initial_state.clone();
+            let mut noise_coefficient = CalculatorFloat::from(1.0);
+            for operation in operations.iter() {
+                // Distribute the shots of repeated measurements over the runs
+                let shots = match operation {
+                    Operation::PragmaRepeatedMeasurement(op) => {
+                        distributed_shots(*op.number_measurements(), run, number_runs)
+                    }
+                    _ => 0,
+                };
+                self.apply_operation(
+                    &statevector_simulator,
+                    operation,
+                    &mut state,
+                    &mut registers,
+                    &mut noise_coefficient,
+                    shots,
+                    &mut rng,
+                )?;
+            }
+            registers.finish_run();
+        }
+        let (bit_registers, float_registers, complex_registers) = registers.into_output()?;
+        let defined: HashSet<&String> = bit_registers
+            .keys()
+            .chain(float_registers.keys())
+            .chain(complex_registers.keys())
+            .collect();
+        let mut statistics: HashMap<String, FloatOutputRegister> = HashMap::new();
+        let mut insert_statistic = |name: String, row: Vec<f64>| {
+            if defined.contains(&name) || statistics.contains_key(&name) {
+                return Err(RoqoqoBackendError::GenericError {
+                    msg: format!(
+                        "Output register {} of the TrajectorySimulator clashes with a register of the circuit",
+                        name
+                    ),
+                });
+            }
+            statistics.insert(name, vec![row]);
+            Ok(())
+        };
+        let mut float_output: HashMap<String, FloatOutputRegister> = HashMap::new();
+        for (name, rows) in float_registers.iter() {
+            let (mean, error) = mean_and_error(rows);
+            insert_statistic(format!("{}{}", name, TRAJECTORY_ERROR_SUFFIX), error)?;
+            float_output.insert(name.clone(), vec![mean]);
+        }
+        for (name, rows) in bit_registers.iter() {
+            let float_rows: FloatOutputRegister = rows
+                .iter()
+                .map(|row| row.iter().map(|bit| f64::from(u8::from(*bit))).collect())
+                .collect();
+            let (mean, _) = mean_and_error(&float_rows);
+            let number_rows = rows.len().max(1) as f64;
+            let error = mean
+                .iter()
+                .map(|p| (p * (1.0 - p) / number_rows).sqrt())
+                .collect();
+            insert_statistic(format!("{}{}", name, TRAJECTORY_MEAN_SUFFIX), mean)?;
+            insert_statistic(format!("{}{}", name, TRAJECTORY_ERROR_SUFFIX), error)?;
+        }
+        float_output.extend(statistics);
+        Ok((bit_registers, float_output, complex_registers))
+    }
+}
+
+/// Applies one randomly chosen Kraus operator of a single-qubit channel to the state vector.
+fn apply_jump(
+    state: &mut Array1<Complex64>,
+    kraus_operators: &[ndarray::Array2<Complex64>],
+    qubit: usize,
+    rng: &mut StdRng,
+) {
+    let candidates: Vec<Array1<Complex64>> = kraus_operators
+        .iter()
+        .map(|kraus| {
+            let mut candidate = state.clone();
+            apply_matrix(&mut candidate, kraus, &[qubit]);
+            candidate
+        })
+        .collect();
+    let weights: Vec<f64> = candidates
+        .iter()
+        .map(|candidate| candidate.iter().map(|x| x.norm_sqr()).sum())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return;
+    }
+    let mut random = rng.gen::<f64>() * total;
+    let mut chosen = weights.len() - 1;
+    for (index, weight) in weights.iter().enumerate() {
+        if random < *weight {
+            chosen = index;
+            break;
+        }
+        random -= weight;
+    }
+    let norm = weights[chosen].sqrt();
+    *state = candidates[chosen].mapv(|x| x / norm);
+}
+
+/// Returns the element-wise mean and standard error of the mean of a list of rows.
+fn mean_and_error(rows: &[Vec<f64>]) -> (Vec<f64>, Vec<f64>) {
+    let length = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let number_rows = rows.len() as f64;
+    let mut mean = vec![0.0; length];
+    let mut error = vec![0.0; length];
+    if rows.is_empty() {
+        return (mean, error);
+    }
+    for row in rows {
+        for (sum, value) in mean.iter_mut().zip(row.iter()) {
+            *sum += value / number_rows;
+        }
+    }
+    if rows.len() > 1 {
+        for row in rows {
+            for ((sum, value), average) in error.iter_mut().zip(row.iter()).zip(mean.iter()) {
+                *sum += (value - average).powi(2) / (number_rows - 1.0);
+            }
+        }
+        error
+            .iter_mut()
+            .for_each(|variance| *variance = (*variance / number_rows).sqrt());
+    }
+    (mean, error)
+}
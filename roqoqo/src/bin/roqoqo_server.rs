@@ -0,0 +1,155 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Starts a [SimulationServer] running jobs on one of the roqoqo simulators.
+//!
+//! The access token required from clients is read from the `ROQOQO_SERVER_TOKEN`
+//! environment variable, so that it does not show up in the process list.
+
+use roqoqo::backends::{
+    DensityMatrixSimulator, MpsSimulator, StabilizerSimulator, StatevectorSimulator,
+    TrajectorySimulator,
+};
+use roqoqo::server::{
+    SimulationServer, DEFAULT_MAX_BODY_SIZE, DEFAULT_MAX_FINISHED_JOBS, DEFAULT_MAX_QUEUED_JOBS,
+};
+use std::env;
+use std::process;
+
+const USAGE: &str = "Usage: roqoqo-server [OPTIONS]
+
+Options:
+    --address <ADDRESS>    Address the server listens on [default: 127.0.0.1:8080]
+    --backend <BACKEND>    Simulator running the jobs: statevector, density-matrix,
+                           stabilizer, mps or trajectory [default: statevector]
+    --qubits <NUMBER>      Number of qubits of the simulator [default: 10]
+    --trajectories <NUMBER>
+                           Number of trajectories of the trajectory simulator [default: 100]
+    --workers <NUMBER>     Number of jobs run at the same time [default: 1]
+    --seed <SEED>          Random seed of the simulator
+    --max-body-size <BYTES>
+                           Maximum size of request bodies [default: 67108864]
+    --max-finished-jobs <NUMBER>
+                           Number of finished jobs kept on the server [default: 1000]
+    --max-queued-jobs <NUMBER>
+                           Number of jobs waiting in the queue [default: 100]
+    --help                 Prints this message
+
+Environment:
+    ROQOQO_SERVER_TOKEN    Bearer token required from clients";
+
+/// Command line options of the server.
+struct Options {
+    address: String,
+    backend: String,
+    qubits: usize,
+    trajectories: usize,
+    workers: usize,
+    seed: Option<u64>,
+    max_body_size: usize,
+    max_finished_jobs: usize,
+    max_queued_jobs: usize,
+}
+
+/// Parses the command line arguments.
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        address: "127.0.0.1:8080".to_string(),
+        backend: "statevector".to_string(),
+        qubits: 10,
+        trajectories: 100,
+        workers: 1,
+        seed: None,
+        max_body_size: DEFAULT_MAX_BODY_SIZE,
+        max_finished_jobs: DEFAULT_MAX_FINISHED_JOBS,
+        max_queued_jobs: DEFAULT_MAX_QUEUED_JOBS,
+    };
+    while let Some(arg) = args.next() {
+        if arg == "--help" {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value of {}", arg))?;
+        let invalid = |_| format!("Invalid value of {}: {}", arg, value);
+        match arg.as_str() {
+            "--address" => options.address = value.clone(),
+            "--backend" => options.backend = value.clone(),
+            "--qubits" => options.qubits = value.parse().map_err(invalid)?,
+            "--trajectories" => options.trajectories = value.parse().map_err(invalid)?,
+            "--workers" => options.workers = value.parse().map_err(invalid)?,
+            "--seed" => options.seed = Some(value.parse().map_err(invalid)?),
+            "--max-body-size" => options.max_body_size = value.parse().map_err(invalid)?,
+            "--max-finished-jobs" => options.max_finished_jobs = value.parse().map_err(invalid)?,
+            "--max-queued-jobs" => options.max_queued_jobs = value.parse().map_err(invalid)?,
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// Starts the server with the simulator selected by the options.
+fn start(options: &Options) -> Result<SimulationServer, String> {
+    let address = options.address.as_str();
+    let workers = options.workers;
+    let token = env::var("ROQOQO_SERVER_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+    let server = match options.backend.as_str() {
+        "statevector" => {
+            let mut backend = StatevectorSimulator::new(options.qubits);
+            backend.set_random_seed(options.seed);
+            SimulationServer::start(backend, address, workers, token)
+        }
+        "density-matrix" => {
+            let mut backend = DensityMatrixSimulator::new(options.qubits);
+            backend.set_random_seed(options.seed);
+            SimulationServer::start(backend, address, workers, token)
+        }
+        "stabilizer" => {
+            let mut backend = StabilizerSimulator::new(options.qubits);
+            backend.set_random_seed(options.seed);
+            SimulationServer::start(backend, address, workers, token)
+        }
+        "mps" => {
+            let mut backend = MpsSimulator::new(options.qubits);
+            backend.set_random_seed(options.seed);
+            SimulationServer::start(backend, address, workers, token)
+        }
+        "trajectory" => {
+            let mut backend = TrajectorySimulator::new(options.qubits, options.trajectories);
+            backend.set_random_seed(options.seed);
+            SimulationServer::start(backend, address, workers, token)
+        }
+        backend => return Err(format!("Unknown backend {}", backend)),
+    };
+    let server = server.map_err(|error| format!("Could not listen on {}: {}", address, error))?;
+    server.set_max_body_size(options.max_body_size);
+    server.set_max_finished_jobs(options.max_finished_jobs);
+    server.set_max_queued_jobs(options.max_queued_jobs);
+    Ok(server)
+}
+
+fn main() {
+    let server = parse_options(env::args().skip(1)).and_then(|options| start(&options));
+    match server {
+        Ok(server) => {
+            println!("Listening on {}", server.url());
+            server.wait();
+        }
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::operations::{InvolveQubits, InvolvedQubits, Operation};
+use crate::Circuit;
+use std::collections::{BTreeSet, HashMap};
+
+/// Represents the operations of a quantum circuit as a directed acyclic dependency graph.
+///
+/// Every operation of the [Circuit] (not including the definitions) is a node of the graph,
+/// identified by its index in [Circuit::operations]. An operation depends on
+///
+/// * the last previous operation acting on each of its qubits (the qubit wires),
+/// * the last previous operation using each of the classical registers it reads or writes
+///   (e.g. the readout register of [crate::operations::MeasureQubit] or the condition register of
+///   [crate::operations::PragmaConditional]),
+/// * the last previous operation acting on all qubits ([InvolvedQubits::All]).
+///
+/// Operations acting on all qubits are barriers: they depend on all previous operations
+/// that no other operation depends on yet. Since every edge points from an earlier to a later
+/// operation, the node indices are a topological order and [CircuitDag::to_circuit] recovers
+/// the original circuit.
+///
+/// # Example
+///
+/// ```
+/// use roqoqo::{Circuit, CircuitDag};
+/// use roqoqo::operations::{CNOT, Hadamard, PauliX};
+///
+/// let mut circuit = Circuit::new();
+/// circuit += Hadamard::new(0);
+/// circuit += PauliX::new(1);
+/// circuit += CNOT::new(0, 1);
+/// let dag = CircuitDag::from(&circuit);
+/// assert_eq!(dag.front_layer(), vec![0, 1]);
+/// assert_eq!(dag.predecessors(2), Some(&[0, 1][..]));
+/// assert_eq!(dag.to_circuit(), circuit);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircuitDag {
+    /// Definitions of the circuit, not part of the graph.
+    definitions: Vec<Operation>,
+    /// Operations of the circuit, the nodes of the graph.
+    operations: Vec<Operation>,
+    /// Sorted indices of the operations each node depends on.
+    predecessors: Vec<Vec<usize>>,
+    /// Sorted indices of the operations depending on each node.
+    successors: Vec<Vec<usize>>,
+}
+
+impl CircuitDag {
+    /// Returns the number of operations (nodes) in the graph.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns true if the graph does not contain any operations.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Returns the definitions of the circuit.
+    pub fn definitions(&self) -> &Vec<Operation> {
+        &self.definitions
+    }
+
+    /// Returns the operation of a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The index of the node.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Operation)` - The operation of the node.
+    /// * `None` - The node does not exist.
+    pub fn get(&self, node: usize) -> Option<&Operation> {
+        self.operations.get(node)
+    }
+
+    /// Returns the sorted indices of the operations a node directly depends on.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The index of the node.
+    pub fn predecessors(&self, node: usize) -> Option<&[usize]> {
+        self.predecessors.get(node).map(|nodes| nodes.as_slice())
+    }
+
+    /// Returns the sorted indices of the operations directly depending on a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The index of the node.
+    pub fn successors(&self, node: usize) -> Option<&[usize]> {
+        self.successors.get(node).map(|nodes| nodes.as_slice())
+    }
+
+    /// Returns the front layer, the sorted indices of the operations without predecessors.
+    pub fn front_layer(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|node| self.predecessors[*node].is_empty())
+            .collect()
+    }
+
+    /// Returns the operations in layers that can be executed at the same time.
+    ///
+    /// Operations are placed in the earliest possible layer (as soon as possible), the first
+    /// layer is the [CircuitDag::front_layer]. Every operation only depends on operations
+    /// in previous layers.
+    pub fn layers(&self) -> Vec<Vec<usize>> {
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        let mut layer_of_node: Vec<usize> = Vec::with_capacity(self.len());
+        for predecessors in self.predecessors.iter() {
+            let layer = predecessors
+                .iter()
+                .map(|predecessor| layer_of_node[*predecessor] + 1)
+                .max()
+                .unwrap_or(0);
+            if layer == layers.len() {
+                layers.push(Vec::new());
+            }
+            layers[layer].push(layer_of_node.len());
+            layer_of_node.push(layer);
+        }
+        layers
+    }
+
+    /// Returns the number of layers of the graph (see [CircuitDag::layers]).
+    pub fn depth(&self) -> usize {
+        self.layers().len()
+    }
+
+    /// Iterates over the nodes and operations of the graph in topological order.
+    ///
+    /// The order is the order of the operations in the original circuit.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Operation)> {
+        self.operations.iter().enumerate()
+    }
+
+    /// Converts the graph back to a circuit with the definitions and operations in the original order.
+    pub fn to_circuit(&self) -> Circuit {
+        let mut circuit = Circuit::new();
+        for operation in self.definitions.iter().chain(self.operations.iter()) {
+            circuit.add_operation(operation.clone());
+        }
+        circuit
+    }
+}
+
+impl From<&Circuit> for CircuitDag {
+    fn from(circuit: &Circuit) -> Self {
+        let mut builder = DagBuilder::default();
+        for operation in circuit.operations() {
+            builder.add_operation(operation);
+        }
+        CircuitDag {
+            definitions: circuit.definitions().clone(),
+            operations: circuit.operations().clone(),
+            predecessors: builder.predecessors,
+            successors: builder.successors,
+        }
+    }
+}
+
+impl From<Circuit> for CircuitDag {
+    fn from(circuit: Circuit) -> Self {
+        CircuitDag::from(&circuit)
+    }
+}
+
+impl From<CircuitDag> for Circuit {
+    fn from(dag: CircuitDag) -> Self {
+        dag.to_circuit()
+    }
+}
+
+/// Incrementally builds the edges of a [CircuitDag].
+#[derive(Debug, Default)]
+struct DagBuilder {
+    /// Predecessors of the nodes added so far.
+    predecessors: Vec<Vec<usize>>,
+    /// Successors of the nodes added so far.
+    successors: Vec<Vec<usize>>,
+    /// Last node acting on each qubit since the last barrier.
+    last_on_qubit: HashMap<usize, usize>,
+    /// Last node using each classical register since the last barrier.
+    last_on_register: HashMap<String, usize>,
+    /// Last node acting on all qubits.
+    last_barrier: Option<usize>,
+    /// Nodes without successors.
+    open_nodes: BTreeSet<usize>,
+}
+
+impl DagBuilder {
+    /// Adds an operation as the next node and connects it to the nodes it depends on.
+    fn add_operation(&mut self, operation: &Operation) {
+        let node = self.predecessors.len();
+        let mut predecessors: BTreeSet<usize> = BTreeSet::new();
+        let registers = classical_registers(operation);
+        match operation.involved_qubits() {
+            InvolvedQubits::All => {
+                predecessors.extend(self.open_nodes.iter().copied());
+                self.last_on_qubit.clear();
+                self.last_on_register.clear();
+                self.last_barrier = Some(node);
+            }
+            InvolvedQubits::Set(qubits) => {
+                for qubit in qubits {
+                    predecessors.extend(
+                        self.last_on_qubit
+                            .get(&qubit)
+                            .or(self.last_barrier.as_ref()),
+                    );
+                    self.last_on_qubit.insert(qubit, node);
+                }
+            }
+            InvolvedQubits::None => {
+                predecessors.extend(self.last_barrier);
+            }
+        }
+        for register in registers {
+            predecessors.extend(
+                self.last_on_register
+                    .get(&register)
+                    .or(self.last_barrier.as_ref()),
+            );
+            self.last_on_register.insert(register, node);
+        }
+        predecessors.remove(&node);
+        for predecessor in predecessors.iter() {
+            self.successors[*predecessor].push(node);
+            self.open_nodes.remove(predecessor);
+        }
+        self.predecessors.push(predecessors.into_iter().collect());
+        self.successors.push(Vec::new());
+        self.open_nodes.insert(node);
+    }
+}
+
+/// Returns the names of the classical registers an operation (or its sub-circuit) reads or writes.
+fn classical_registers(operation: &Operation) -> Vec<String> {
+    let (register, circuit) = match operation {
+        Operation::MeasureQubit(op) => (op.readout(), None),
+        Operation::PragmaRepeatedMeasurement(op) => (op.readout(), None),
+        Operation::PragmaSetNumberOfMeasurements(op) => (op.readout(), None),
+        Operation::PragmaGetStateVector(op) => (op.readout(), op.circuit().as_ref()),
+        Operation::PragmaGetDensityMatrix(op) => (op.readout(), op.circuit().as_ref()),
+        Operation::PragmaGetOccupationProbability(op) => (op.readout(), op.circuit().as_ref()),
+        Operation::PragmaGetPauliProduct(op) => (op.readout(), Some(op.circuit())),
+        Operation::PragmaGetAmplitudes(op) => (op.readout(), op.circuit().as_ref()),
+        Operation::PragmaConditional(op) => (op.condition_register(), Some(op.circuit())),
+        _ => return Vec::new(),
+    };
+    let mut registers = vec![register.clone()];
+    if let Some(circuit) = circuit {
+        for operation in circuit.iter() {
+            for register in classical_registers(operation) {
+                if !registers.contains(&register) {
+                    registers.push(register);
+                }
+            }
+        }
+    }
+    registers
+}
@@ -38,8 +38,17 @@
 //!
 
 use ndarray::Array2;
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
 
-use crate::RoqoqoBackendError;
+use crate::measurements::ReadoutErrorModel;
+use crate::operations::{
+    InvolveQubits, InvolvedQubits, MultiQubitGateOperation, Operate, OperateMultiQubit,
+    OperateSingleQubit, OperateTwoQubit, Operation, PragmaGeneralNoise, SingleQubitGateOperation,
+    TwoQubitGateOperation,
+};
+use crate::{Circuit, RoqoqoBackendError};
 
 /// Trait for roqoqo devices.
 ///
@@ -125,6 +134,17 @@ pub trait Device {
     /// choice from a list of edges and can be used for applications like routing in quantum algorithms.
     fn two_qubit_edges(&self) -> Vec<(usize, usize)>;
 
+    /// Returns the readout error model of the device.
+    ///
+    /// The model can be used to simulate readout errors with [crate::backends::ReadoutErrorBackend]
+    /// and to mitigate readout errors in a [crate::measurements::BasisRotation] measurement.
+    ///
+    /// For most devices the default behaviour is that the device has no known readout errors
+    /// and the function returns None.
+    fn readout_error_model(&self) -> Option<ReadoutErrorModel> {
+        None
+    }
+
     /// Changes the device topology based on a Pragma operation.
     ///
     /// Specific devices and backends can allow changes to the device topology.
@@ -152,3 +172,359 @@ pub trait Device {
         })
     }
 }
+
+/// Violation of the constraints of a [Device] by an operation of a circuit.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceViolation {
+    /// Index of the operation in the circuit, for operations in nested circuits the index of the enclosing operation.
+    pub index: usize,
+    /// hqslang name of the operation.
+    pub hqslang: String,
+    /// The constraint of the device that is violated.
+    pub kind: DeviceViolationKind,
+}
+
+/// Kind of constraint of a [Device] violated by an operation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeviceViolationKind {
+    /// The operation acts on a qubit that is not part of the device.
+    QubitOutOfRange {
+        /// The qubit the operation acts on.
+        qubit: usize,
+        /// The number of qubits of the device.
+        number_qubits: usize,
+    },
+    /// The gate is not available on the qubits it acts on, the device returns no gate time.
+    GateNotAvailable {
+        /// The qubits the gate acts on.
+        qubits: Vec<usize>,
+    },
+    /// The two-qubit gate acts on a pair of qubits that is not in [Device::two_qubit_edges].
+    QubitsNotConnected {
+        /// The control qubit of the gate.
+        control: usize,
+        /// The target qubit of the gate.
+        target: usize,
+    },
+}
+
+impl fmt::Display for DeviceViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operation {} ({}): ", self.index, self.hqslang)?;
+        match &self.kind {
+            DeviceViolationKind::QubitOutOfRange {
+                qubit,
+                number_qubits,
+            } => write!(
+                f,
+                "qubit {} is not part of the device with {} qubits",
+                qubit, number_qubits
+            ),
+            DeviceViolationKind::GateNotAvailable { qubits } => {
+                write!(f, "gate is not available on qubits {:?}", qubits)
+            }
+            DeviceViolationKind::QubitsNotConnected { control, target } => write!(
+                f,
+                "qubits {} and {} are not connected in the device",
+                control, target
+            ),
+        }
+    }
+}
+
+/// Joins the messages of several violations for the message of [crate::RoqoqoBackendError::DeviceValidationFailed].
+pub(crate) fn join_violations(violations: &[DeviceViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+/// Checks that all operations of a circuit can be executed on a device.
+///
+/// Every operation acting on a defined set of qubits must only act on qubits of the device.
+/// Gate operations must be available on the device, meaning the device returns a gate time
+/// for them, and two-qubit gates must act on a pair of qubits in [Device::two_qubit_edges].
+/// The circuits of [crate::operations::PragmaConditional] operations are checked as well.
+///
+/// # Arguments
+///
+/// * `device` - The device the circuit is checked against.
+/// * `operations` - The iterator over operations that is checked (corresponds to a circuit).
+///
+/// # Returns
+///
+/// `Vec<DeviceViolation>` - All violations of the device constraints, empty for a valid circuit.
+pub fn validate_circuit<'a>(
+    device: &dyn Device,
+    operations: impl Iterator<Item = &'a Operation>,
+) -> Vec<DeviceViolation> {
+    let edges = device.two_qubit_edges();
+    let mut violations: Vec<DeviceViolation> = Vec::new();
+    for (index, operation) in operations.enumerate() {
+        validate_operation(device, &edges, index, operation, &mut violations);
+    }
+    violations
+}
+
+/// Adds the violations of a single operation to the list of violations.
+fn validate_operation(
+    device: &dyn Device,
+    edges: &[(usize, usize)],
+    index: usize,
+    operation: &Operation,
+    violations: &mut Vec<DeviceViolation>,
+) {
+    let mut add_violation = |kind: DeviceViolationKind| {
+        violations.push(DeviceViolation {
+            index,
+            hqslang: operation.hqslang().to_string(),
+            kind,
+        })
+    };
+    if let InvolvedQubits::Set(qubits) = operation.involved_qubits() {
+        let mut qubits: Vec<usize> = qubits.into_iter().collect();
+        qubits.sort_unstable();
+        let out_of_range: Vec<usize> = qubits
+            .into_iter()
+            .filter(|qubit| *qubit >= device.number_qubits())
+            .collect();
+        for qubit in out_of_range.iter() {
+            add_violation(DeviceViolationKind::QubitOutOfRange {
+                qubit: *qubit,
+                number_qubits: device.number_qubits(),
+            });
+        }
+        if !out_of_range.is_empty() {
+            return;
+        }
+    }
+    if let Ok(gate) = SingleQubitGateOperation::try_from(operation.clone()) {
+        if device
+            .single_qubit_gate_time(operation.hqslang(), gate.qubit())
+            .is_none()
+        {
+            add_violation(DeviceViolationKind::GateNotAvailable {
+                qubits: vec![*gate.qubit()],
+            });
+        }
+    } else if let Ok(gate) = TwoQubitGateOperation::try_from(operation.clone()) {
+        let (control, target) = (*gate.control(), *gate.target());
+        if !edges.contains(&(control, target)) && !edges.contains(&(target, control)) {
+            add_violation(DeviceViolationKind::QubitsNotConnected { control, target });
+        } else if device
+            .two_qubit_gate_time(operation.hqslang(), &control, &target)
+            .is_none()
+        {
+            add_violation(DeviceViolationKind::GateNotAvailable {
+                qubits: vec![control, target],
+            });
+        }
+    } else if let Ok(gate) = MultiQubitGateOperation::try_from(operation.clone()) {
+        if device
+            .multi_qubit_gate_time(operation.hqslang(), gate.qubits())
+            .is_none()
+        {
+            add_violation(DeviceViolationKind::GateNotAvailable {
+                qubits: gate.qubits().clone(),
+            });
+        }
+    } else if let Operation::PragmaConditional(conditional) = operation {
+        for nested_operation in conditional.circuit().iter() {
+            validate_operation(device, edges, index, nested_operation, violations);
+        }
+    }
+}
+
+/// Inserts the decoherence noise of a device into a circuit.
+///
+/// The gate operations between two non-gate operations are scheduled in layers,
+/// every gate is placed in the first layer after the last gate acting on one of its qubits.
+/// After each gate a [PragmaGeneralNoise] with the gate time of the gate on the device and the
+/// [Device::qubit_decoherence_rates] is inserted for every qubit the gate acts on.
+/// The duration of a layer is the longest gate time in the layer, all qubits of the circuit that
+/// are idle for part or all of a layer receive a [PragmaGeneralNoise] for the idle time at the end of the layer.
+/// All other operations are kept in place and end the current sequence of layers,
+/// the circuits of [crate::operations::PragmaConditional] operations are not changed.
+/// No noise is inserted for qubits without or with vanishing decoherence rates.
+///
+/// # Arguments
+///
+/// * `device` - The device providing the gate times and decoherence rates.
+/// * `circuit` - The circuit the noise is inserted into.
+///
+/// # Returns
+///
+/// * `Ok(Circuit)` - The circuit with the inserted noise PRAGMAs.
+/// * `Err(RoqoqoBackendError::DeviceValidationFailed)` - The circuit cannot be executed on the device (see [validate_circuit]).
+pub fn insert_device_noise(
+    device: &dyn Device,
+    circuit: &Circuit,
+) -> Result<Circuit, RoqoqoBackendError> {
+    let violations = validate_circuit(device, circuit.iter());
+    if !violations.is_empty() {
+        return Err(RoqoqoBackendError::DeviceValidationFailed { violations });
+    }
+    // Qubits of operations acting on all qubits (e.g. PragmaRepeatedMeasurement) are not
+    // included, to avoid adding noise on device qubits the circuit does not use.
+    let mut circuit_qubits: BTreeSet<usize> = BTreeSet::new();
+    for operation in circuit.iter() {
+        if let InvolvedQubits::Set(qubits) = operation.involved_qubits() {
+            circuit_qubits.extend(qubits);
+        }
+    }
+    let mut noise_inserter = NoiseInserter {
+        device,
+        circuit_qubits,
+        layers: Vec::new(),
+        next_layer: HashMap::new(),
+        noisy_circuit: Circuit::new(),
+    };
+    for operation in circuit.iter() {
+        match gate_time(device, operation) {
+            Some((qubits, time)) => noise_inserter.add_gate(operation, qubits, time),
+            None => {
+                noise_inserter.flush();
+                noise_inserter.noisy_circuit += operation.clone();
+            }
+        }
+    }
+    noise_inserter.flush();
+    Ok(noise_inserter.noisy_circuit)
+}
+
+/// Estimates the duration of one execution of a circuit on a device.
+///
+/// The gates are scheduled in layers in the same way as in [insert_device_noise]:
+/// every gate is placed in the first layer after the last gate acting on one of its qubits,
+/// the duration of a layer is the longest gate time in the layer and all other operations
+/// end the current sequence of layers. Non-gate operations do not take time.
+///
+/// # Arguments
+///
+/// * `device` - The device providing the gate times.
+/// * `operations` - The iterator over operations (corresponds to a circuit).
+///
+/// # Returns
+///
+/// * `Ok(f64)` - The sum of the durations of all layers in seconds.
+/// * `Err(RoqoqoBackendError::DeviceValidationFailed)` - The circuit cannot be executed on the device (see [validate_circuit]).
+pub fn circuit_duration<'a>(
+    device: &dyn Device,
+    operations: impl Iterator<Item = &'a Operation>,
+) -> Result<f64, RoqoqoBackendError> {
+    let operations: Vec<&Operation> = operations.collect();
+    let violations = validate_circuit(device, operations.iter().copied());
+    if !violations.is_empty() {
+        return Err(RoqoqoBackendError::DeviceValidationFailed { violations });
+    }
+    let mut duration = 0.0;
+    let mut layer_durations: Vec<f64> = Vec::new();
+    let mut next_layer: HashMap<usize, usize> = HashMap::new();
+    for operation in operations {
+        match gate_time(device, operation) {
+            Some((qubits, time)) => {
+                let layer = qubits
+                    .iter()
+                    .map(|qubit| next_layer.get(qubit).copied().unwrap_or_default())
+                    .max()
+                    .unwrap_or_default();
+                for qubit in qubits {
+                    next_layer.insert(qubit, layer + 1);
+                }
+                if layer_durations.len() <= layer {
+                    layer_durations.resize(layer + 1, 0.0);
+                }
+                layer_durations[layer] = layer_durations[layer].max(time);
+            }
+            None => {
+                duration += layer_durations.drain(..).sum::<f64>();
+                next_layer.clear();
+            }
+        }
+    }
+    Ok(duration + layer_durations.iter().sum::<f64>())
+}
+
+/// Returns the qubits and the gate time on the device of gate operations.
+fn gate_time(device: &dyn Device, operation: &Operation) -> Option<(Vec<usize>, f64)> {
+    if let Ok(gate) = SingleQubitGateOperation::try_from(operation.clone()) {
+        let time = device.single_qubit_gate_time(operation.hqslang(), gate.qubit())?;
+        Some((vec![*gate.qubit()], time))
+    } else if let Ok(gate) = TwoQubitGateOperation::try_from(operation.clone()) {
+        let time =
+            device.two_qubit_gate_time(operation.hqslang(), gate.control(), gate.target())?;
+        Some((vec![*gate.control(), *gate.target()], time))
+    } else if let Ok(gate) = MultiQubitGateOperation::try_from(operation.clone()) {
+        let time = device.multi_qubit_gate_time(operation.hqslang(), gate.qubits())?;
+        Some((gate.qubits().clone(), time))
+    } else {
+        None
+    }
+}
+
+/// Gate of a layer with the qubits it acts on and its gate time.
+type ScheduledGate = (Operation, Vec<usize>, f64);
+
+/// Schedules gates in layers and writes them with the inserted noise to a circuit.
+struct NoiseInserter<'a> {
+    device: &'a dyn Device,
+    circuit_qubits: BTreeSet<usize>,
+    layers: Vec<Vec<ScheduledGate>>,
+    /// First layer in which a qubit is not busy.
+    next_layer: HashMap<usize, usize>,
+    noisy_circuit: Circuit,
+}
+
+impl<'a> NoiseInserter<'a> {
+    /// Adds a gate to the first layer in which all its qubits are free.
+    fn add_gate(&mut self, operation: &Operation, qubits: Vec<usize>, time: f64) {
+        let layer = qubits
+            .iter()
+            .map(|qubit| self.next_layer.get(qubit).copied().unwrap_or_default())
+            .max()
+            .unwrap_or_default();
+        for qubit in qubits.iter() {
+            self.next_layer.insert(*qubit, layer + 1);
+        }
+        if self.layers.len() <= layer {
+            self.layers.resize_with(layer + 1, Vec::new);
+        }
+        self.layers[layer].push((operation.clone(), qubits, time));
+    }
+
+    /// Writes all scheduled layers with the inserted noise to the noisy circuit.
+    fn flush(&mut self) {
+        for layer in std::mem::take(&mut self.layers) {
+            let duration = layer.iter().map(|(_, _, time)| *time).fold(0.0, f64::max);
+            let mut busy_times: HashMap<usize, f64> = HashMap::new();
+            for (operation, qubits, time) in layer {
+                self.noisy_circuit += operation;
+                for qubit in qubits {
+                    self.add_noise(qubit, time);
+                    busy_times.insert(qubit, time);
+                }
+            }
+            for qubit in self.circuit_qubits.clone() {
+                let busy_time = busy_times.get(&qubit).copied().unwrap_or_default();
+                self.add_noise(qubit, duration - busy_time);
+            }
+        }
+        self.next_layer.clear();
+    }
+
+    /// Adds the decoherence of a qubit during a time span to the noisy circuit.
+    fn add_noise(&mut self, qubit: usize, time: f64) {
+        if time <= 0.0 {
+            return;
+        }
+        if let Some(rates) = self.device.qubit_decoherence_rates(&qubit) {
+            if rates.iter().any(|rate| *rate != 0.0) {
+                self.noisy_circuit += PragmaGeneralNoise::new(qubit, time.into(), rates);
+            }
+        }
+    }
+}
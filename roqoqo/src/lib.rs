@@ -201,12 +201,26 @@ pub enum RoqoqoError {
         /// Error message.
         msg: String,
     },
+    /// The readout error model is invalid.
+    #[error("Invalid readout error model: {msg}")]
+    InvalidReadoutErrorModel {
+        /// Error message.
+        msg: String,
+    },
     /// Error serializing an internal roqoqo object
     #[error("An error occured serializing a roqoqo object: {msg} ")]
     SerializationError {
         /// Error message
         msg: String,
     },
+    /// Error running a transpiler pass.
+    #[error("Transpiler pass {pass} failed: {msg}")]
+    TranspilerPassError {
+        /// Name of the pass.
+        pass: String,
+        /// Error message.
+        msg: String,
+    },
     /// Generic error that does not fit in other error categories.
     #[error("An error occured in roqoqo: {msg} ")]
     GenericError {
@@ -277,6 +291,15 @@ pub enum RoqoqoBackendError {
         /// Generic error message
         msg: String,
     },
+    /// Error when a circuit cannot be executed on the device of the backend.
+    #[error(
+        "Circuit violates the constraints of the device: {}",
+        devices::join_violations(violations)
+    )]
+    DeviceValidationFailed {
+        /// The violations of the device constraints.
+        violations: Vec<devices::DeviceViolation>,
+    },
     /// Transparent propagation of RoqoqoError.
     #[error(transparent)]
     RoqoqoError(#[from] RoqoqoError),
@@ -287,12 +310,21 @@ pub enum RoqoqoBackendError {
 
 #[doc(hidden)]
 mod circuit;
+mod circuit_dag;
 pub mod operations;
 pub mod prelude;
 pub use circuit::*;
+pub use circuit_dag::CircuitDag;
 pub mod backends;
 pub mod devices;
 pub mod measurements;
+pub mod passes;
 mod quantum_program;
+#[cfg(feature = "serialize")]
+mod quantum_program_checkpoint;
 pub mod registers;
+#[cfg(feature = "server")]
+pub mod server;
 pub use quantum_program::QuantumProgram;
+#[cfg(feature = "serialize")]
+pub use quantum_program_checkpoint::MeasurementCheckpoint;
@@ -86,10 +86,12 @@ impl Measure for BasisRotation {
 }
 
 impl MeasureExpectationValues for BasisRotation {
-    // TODO add optional device later for use with flipped measurement
     #[allow(unused_variables)]
     /// Executes the basis rotation measurement.
     ///
+    /// When the input contains a readout error model, the readout errors of the
+    /// Pauli products are mitigated with the inverse confusion matrices of the model.
+    ///
     /// # Arguments
     ///
     /// * `bit_registers` - The classical bit registers as a HashMap with the register name as key.
@@ -108,33 +110,13 @@ impl MeasureExpectationValues for BasisRotation {
         float_registers: HashMap<String, FloatOutputRegister>,
         complex_registers: HashMap<String, ComplexOutputRegister>,
     ) -> Result<Option<HashMap<String, f64>>, RoqoqoError> {
-        // todo replace with actual input
-        let measurement_fidelities = vec![1.0; self.input.number_qubits];
-
-        // Setting up measurement correction factors for flipped measurement
-        let mut measurement_correction_factors: HashMap<String, Vec<f64>> = HashMap::new();
-        let flipped_and_extension: Vec<(bool, &'static str)>;
-        if self.input.use_flipped_measurement {
-            // helper vector to iterate over when evaluating the pauli products
-            flipped_and_extension = vec![(false, ""), (true, "_flipped")];
-            for (name, pauli_product_mask) in self.input.pauli_product_qubit_masks.iter() {
-                let mut measurement_correction_factor: Vec<f64> =
-                    (0..self.input.number_pauli_products)
-                        .into_iter()
-                        .map(|_| 1.0)
-                        .collect();
-                for (pp_index, indices) in pauli_product_mask.iter() {
-                    if !indices.is_empty() {
-                        for i in indices {
-                            measurement_correction_factor[*pp_index] *= measurement_fidelities[*i]
-                        }
-                    }
-                }
-                measurement_correction_factors.insert(name.clone(), measurement_correction_factor);
-            }
+        // helper vector to iterate over when evaluating the pauli products
+        let flipped_and_extension: Vec<(bool, &'static str)> = if self.input.use_flipped_measurement
+        {
+            vec![(false, ""), (true, "_flipped")]
         } else {
-            flipped_and_extension = vec![(false, "")];
-        }
+            vec![(false, "")]
+        };
         let mut pauli_product_dict: HashMap<String, Array1<f64>> = HashMap::new();
         for (register_name, mask) in self.input.pauli_product_qubit_masks.iter() {
             for (flip_measurement, extension) in flipped_and_extension.iter() {
@@ -153,6 +135,12 @@ impl MeasureExpectationValues for BasisRotation {
                     if mask_val.is_empty() {
                         single_shot_pauli_products.column_mut(*index).fill(1.0);
                     } else {
+                        // Estimator correcting readout errors with the inverse confusion matrices
+                        let estimator = self
+                            .input
+                            .readout_error_model
+                            .as_ref()
+                            .map(|model| model.parity_estimator(mask_val));
                         // Accessing column of single_shot_pauli_products that corresponds to pauli product designated by index
                         let mut column = single_shot_pauli_products.column_mut(*index);
                         // Iterate over all single shot readouts for all qubits and construct Pauli Product
@@ -167,9 +155,20 @@ impl MeasureExpectationValues for BasisRotation {
                                     parity = !parity
                                 }
                             } // Map even parity measurement result to 1 and odd parity result to -1
-                            column[row_index] = match parity {
-                                false => 1.0,
-                                true => -1.0,
+                            column[row_index] = match &estimator {
+                                None => match parity {
+                                    false => 1.0,
+                                    true => -1.0,
+                                },
+                                // Readout errors act on the physically measured (possibly flipped) bits,
+                                // the flip of all qubits changes the sign for an odd number of qubits
+                                Some(estimator) => {
+                                    let sign = match *flip_measurement && mask_val.len() % 2 == 1 {
+                                        false => 1.0,
+                                        true => -1.0,
+                                    };
+                                    sign * estimator.estimate(values)
+                                }
                             };
                         }
                     }
@@ -10,6 +10,7 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::measurements::ReadoutErrorModel;
 use crate::CalculatorFloat;
 use crate::RoqoqoError;
 use num_complex::Complex64;
@@ -61,6 +62,12 @@ pub struct BasisRotationInput {
     ///
     /// Measurement errors are symmetrized by repeating measurement with final flip of all qubits.
     pub use_flipped_measurement: bool,
+    /// Readout error model used to mitigate readout errors of the measured Pauli products.
+    ///
+    /// When set, the Pauli products are corrected by inverting the confusion matrices of the model.
+    /// The model of a device is provided by [crate::devices::Device::readout_error_model].
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub readout_error_model: Option<ReadoutErrorModel>,
 }
 
 impl BasisRotationInput {
@@ -83,9 +90,19 @@ impl BasisRotationInput {
             number_pauli_products: 0,
             measured_exp_vals: HashMap::new(),
             use_flipped_measurement,
+            readout_error_model: None,
         }
     }
 
+    /// Sets the readout error model used to mitigate readout errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `readout_error_model` - The readout error model (None for no mitigation).
+    pub fn set_readout_error_model(&mut self, readout_error_model: Option<ReadoutErrorModel>) {
+        self.readout_error_model = readout_error_model;
+    }
+
     /// Adds measured Pauli product to BasisRotationInput and returns index of Pauli product.
     ///
     /// When the pauli product is already in the measurement input the function only returns
@@ -38,6 +38,9 @@ pub use cheated_measurement::*;
 #[doc(hidden)]
 mod classical_register_measurement;
 pub use classical_register_measurement::*;
+#[doc(hidden)]
+mod readout_error_model;
+pub use readout_error_model::ReadoutErrorModel;
 
 use crate::registers::BitOutputRegister;
 use crate::{
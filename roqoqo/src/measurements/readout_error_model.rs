@@ -0,0 +1,401 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::registers::BitOutputRegister;
+use crate::RoqoqoError;
+use nalgebra::DMatrix;
+use ndarray::{array, Array2};
+use rand::Rng;
+
+/// Tolerance used when checking that the columns of a confusion matrix are normalized.
+const NORMALIZATION_TOLERANCE: f64 = 1e-10;
+
+/// Readout error model for the qubits of a quantum computer.
+///
+/// The model consists of independent factors. Each factor describes the readout errors of a single qubit
+/// or of a group of qubits with correlated readout errors by a confusion matrix.
+/// The entry `[measured, prepared]` of the confusion matrix is the probability to read out the basis state `measured`
+/// when the qubits are in the basis state `prepared`.
+/// In the basis states of a factor, the j-th qubit of the factor corresponds to the j-th bit of the state index.
+/// Qubits that are not part of a factor are read out without errors.
+///
+/// The qubit indices of the model are the indices of the measured bits in a readout register.
+/// For a [crate::operations::PragmaRepeatedMeasurement] without a qubit mapping these coincide with the qubit indices.
+///
+/// # Example
+///
+/// ```
+/// use roqoqo::measurements::ReadoutErrorModel;
+///
+/// let mut model = ReadoutErrorModel::new();
+/// // Qubit 0 reads out 1 instead of 0 with probability 0.02 and 0 instead of 1 with probability 0.05
+/// model.set_single_qubit_error(0, 0.02, 0.05).unwrap();
+/// assert_eq!(model.qubits(), vec![0]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serialize",
+    serde(try_from = "ReadoutErrorModelSerializable")
+)]
+#[cfg_attr(feature = "serialize", serde(into = "ReadoutErrorModelSerializable"))]
+pub struct ReadoutErrorModel {
+    /// The independent factors of the readout error model.
+    factors: Vec<ReadoutErrorFactor>,
+}
+
+/// Unvalidated serialized form of a [ReadoutErrorModel].
+#[cfg(feature = "serialize")]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct ReadoutErrorModelSerializable {
+    /// The independent factors of the readout error model.
+    factors: Vec<ReadoutErrorFactor>,
+}
+
+#[cfg(feature = "serialize")]
+impl TryFrom<ReadoutErrorModelSerializable> for ReadoutErrorModel {
+    type Error = RoqoqoError;
+
+    fn try_from(value: ReadoutErrorModelSerializable) -> Result<Self, Self::Error> {
+        let mut model = ReadoutErrorModel::new();
+        for factor in value.factors {
+            if model.confusion_matrix(&factor.qubits).is_some() {
+                return Err(invalid(format!(
+                    "Readout error of qubits {:?} appears twice",
+                    factor.qubits
+                )));
+            }
+            model.set_correlated_error(factor.qubits, factor.confusion_matrix)?;
+        }
+        Ok(model)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl From<ReadoutErrorModel> for ReadoutErrorModelSerializable {
+    fn from(value: ReadoutErrorModel) -> Self {
+        ReadoutErrorModelSerializable {
+            factors: value.factors,
+        }
+    }
+}
+
+/// Readout errors of a group of qubits described by a confusion matrix.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+struct ReadoutErrorFactor {
+    /// The qubits of the factor, the j-th qubit corresponds to the j-th bit of the basis state index.
+    qubits: Vec<usize>,
+    /// The confusion matrix with entries `[measured, prepared]`.
+    confusion_matrix: Array2<f64>,
+}
+
+impl ReadoutErrorModel {
+    /// Creates a new ReadoutErrorModel without readout errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the uncorrelated readout error of a single qubit.
+    ///
+    /// # Arguments
+    ///
+    /// * `qubit` - The qubit the readout error applies to.
+    /// * `prob_0_to_1` - The probability to read out 1 when the qubit is in state 0.
+    /// * `prob_1_to_0` - The probability to read out 0 when the qubit is in state 1.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The readout error was set.
+    /// * `Err(RoqoqoError::InvalidReadoutErrorModel)` - The probabilities are invalid, the readout error cannot be mitigated or the qubit is part of a group of correlated qubits.
+    pub fn set_single_qubit_error(
+        &mut self,
+        qubit: usize,
+        prob_0_to_1: f64,
+        prob_1_to_0: f64,
+    ) -> Result<(), RoqoqoError> {
+        let confusion_matrix = array![
+            [1.0 - prob_0_to_1, prob_1_to_0],
+            [prob_0_to_1, 1.0 - prob_1_to_0]
+        ];
+        self.set_correlated_error(vec![qubit], confusion_matrix)
+    }
+
+    /// Sets the correlated readout error of a group of qubits.
+    ///
+    /// A previously set readout error for exactly the same qubits is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `qubits` - The qubits of the group, the j-th qubit corresponds to the j-th bit of the basis state index.
+    /// * `confusion_matrix` - The confusion matrix with entries `[measured, prepared]`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The readout error was set.
+    /// * `Err(RoqoqoError::InvalidReadoutErrorModel)` - The qubits or the confusion matrix are invalid, the confusion matrix is not invertible or a qubit is already part of another group.
+    pub fn set_correlated_error(
+        &mut self,
+        qubits: Vec<usize>,
+        confusion_matrix: Array2<f64>,
+    ) -> Result<(), RoqoqoError> {
+        if qubits.is_empty() {
+            return Err(invalid(
+                "A readout error needs at least one qubit".to_string(),
+            ));
+        }
+        if let Some((index, qubit)) = qubits
+            .iter()
+            .enumerate()
+            .find(|(index, qubit)| qubits[..*index].contains(qubit))
+        {
+            return Err(invalid(format!(
+                "Qubit {} appears twice in the qubits of the readout error (position {})",
+                qubit, index
+            )));
+        }
+        if qubits.len() >= usize::BITS as usize {
+            return Err(invalid(format!(
+                "A readout error of {} qubits cannot be represented by a confusion matrix",
+                qubits.len()
+            )));
+        }
+        let dimension = 1_usize << qubits.len();
+        if confusion_matrix.dim() != (dimension, dimension) {
+            return Err(invalid(format!(
+                "Confusion matrix of {} qubits needs shape ({}, {}) but has shape {:?}",
+                qubits.len(),
+                dimension,
+                dimension,
+                confusion_matrix.dim()
+            )));
+        }
+        if confusion_matrix.iter().any(|x| !(0.0..=1.0).contains(x)) {
+            return Err(invalid(
+                "Entries of the confusion matrix must be probabilities between 0 and 1".to_string(),
+            ));
+        }
+        for (prepared, column) in confusion_matrix.columns().into_iter().enumerate() {
+            if (column.sum() - 1.0).abs() > NORMALIZATION_TOLERANCE {
+                return Err(invalid(format!(
+                    "Column {} of the confusion matrix does not sum to 1",
+                    prepared
+                )));
+            }
+        }
+        if inverse(&confusion_matrix).is_none() {
+            return Err(invalid(
+                "Confusion matrix is not invertible, readout errors cannot be mitigated"
+                    .to_string(),
+            ));
+        }
+        self.factors.retain(|factor| factor.qubits != qubits);
+        if let Some(qubit) = qubits
+            .iter()
+            .find(|qubit| self.factors.iter().any(|f| f.qubits.contains(qubit)))
+        {
+            return Err(invalid(format!(
+                "Qubit {} is already part of another readout error",
+                qubit
+            )));
+        }
+        self.factors.push(ReadoutErrorFactor {
+            qubits,
+            confusion_matrix,
+        });
+        Ok(())
+    }
+
+    /// Returns the confusion matrix of a group of qubits.
+    ///
+    /// # Arguments
+    ///
+    /// * `qubits` - The qubits of the group in the order used when the readout error was set.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Array2<f64>)` - The confusion matrix of the group.
+    /// * `None` - No readout error was set for exactly these qubits.
+    pub fn confusion_matrix(&self, qubits: &[usize]) -> Option<&Array2<f64>> {
+        self.factors
+            .iter()
+            .find(|factor| factor.qubits == qubits)
+            .map(|factor| &factor.confusion_matrix)
+    }
+
+    /// Returns the sorted qubits with readout errors.
+    pub fn qubits(&self) -> Vec<usize> {
+        let mut qubits: Vec<usize> = self
+            .factors
+            .iter()
+            .flat_map(|factor| factor.qubits.iter().copied())
+            .collect();
+        qubits.sort_unstable();
+        qubits
+    }
+
+    /// Returns true if the model contains no readout errors.
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    /// Applies the readout errors to the measured bits of a register.
+    ///
+    /// Each readout of the register is replaced by a readout sampled from the confusion matrices.
+    /// Readout errors of groups containing qubits beyond the length of a readout are not applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - The register containing the error-free readouts.
+    /// * `rng` - The random number generator used for sampling the readout errors.
+    pub fn apply_to_register<R: Rng>(&self, register: &mut BitOutputRegister, rng: &mut R) {
+        for readout in register.iter_mut() {
+            for factor in self.factors.iter() {
+                if factor.qubits.iter().any(|qubit| *qubit >= readout.len()) {
+                    continue;
+                }
+                let prepared = basis_state_index(&factor.qubits, readout);
+                let random: f64 = rng.gen();
+                let mut cumulative = 0.0;
+                let mut measured = 0;
+                for (state, probability) in
+                    factor.confusion_matrix.column(prepared).iter().enumerate()
+                {
+                    cumulative += probability;
+                    measured = state;
+                    if random < cumulative {
+                        break;
+                    }
+                }
+                for (bit, qubit) in factor.qubits.iter().enumerate() {
+                    readout[*qubit] = (measured >> bit) & 1 == 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the estimator of a Pauli product mitigating the readout errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `pauli_product_mask` - The qubits involved in the Pauli product.
+    ///
+    /// # Returns
+    ///
+    /// * `ParityEstimator` - The estimator of the Pauli product for single readouts.
+    pub(crate) fn parity_estimator(&self, pauli_product_mask: &[usize]) -> ParityEstimator {
+        let mut factors = Vec::new();
+        for factor in self.factors.iter() {
+            let mask_bits: usize = factor
+                .qubits
+                .iter()
+                .enumerate()
+                .filter(|(_, qubit)| pauli_product_mask.contains(qubit))
+                .map(|(bit, _)| 1 << bit)
+                .sum();
+            if mask_bits == 0 {
+                continue;
+            }
+            // Validated when the factor was added or deserialized
+            let inverse = inverse(&factor.confusion_matrix)
+                .expect("Internal error: confusion matrix is not invertible");
+            let weights = (0..inverse.ncols())
+                .map(|measured| {
+                    (0..inverse.nrows())
+                        .map(|prepared| {
+                            let sign = match (prepared & mask_bits).count_ones() % 2 {
+                                0 => 1.0,
+                                _ => -1.0,
+                            };
+                            sign * inverse[(prepared, measured)]
+                        })
+                        .sum()
+                })
+                .collect();
+            factors.push((factor.qubits.clone(), mask_bits, weights));
+        }
+        let uncorrected_qubits = pauli_product_mask
+            .iter()
+            .filter(|qubit| !self.factors.iter().any(|f| f.qubits.contains(qubit)))
+            .copied()
+            .collect();
+        ParityEstimator {
+            factors,
+            uncorrected_qubits,
+        }
+    }
+}
+
+/// Unbiased single-readout estimator of a Pauli product under readout errors.
+///
+/// The estimator is the product of the weights of the factors acting on the Pauli product
+/// (obtained from the inverse confusion matrices) and the parity of the error-free qubits.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ParityEstimator {
+    /// Qubits of the factors acting on the Pauli product, the bits of the qubits in the Pauli product
+    /// and the weights of their measured basis states.
+    factors: Vec<(Vec<usize>, usize, Vec<f64>)>,
+    /// Qubits of the Pauli product without readout errors.
+    uncorrected_qubits: Vec<usize>,
+}
+
+impl ParityEstimator {
+    /// Returns the estimate of the Pauli product for a single readout.
+    ///
+    /// Like in [ReadoutErrorModel::apply_to_register], groups containing qubits beyond the length
+    /// of the readout are treated as free of readout errors.
+    pub(crate) fn estimate(&self, readout: &[bool]) -> f64 {
+        let mut odd = self
+            .uncorrected_qubits
+            .iter()
+            .filter(|qubit| readout.get(**qubit) == Some(&true))
+            .count()
+            % 2
+            == 1;
+        let mut value = 1.0;
+        for (qubits, mask_bits, weights) in self.factors.iter() {
+            let index = basis_state_index(qubits, readout);
+            if qubits.iter().any(|qubit| *qubit >= readout.len()) {
+                odd ^= (index & mask_bits).count_ones() % 2 == 1;
+            } else {
+                value *= weights[index];
+            }
+        }
+        match odd {
+            false => value,
+            true => -value,
+        }
+    }
+}
+
+/// Returns the index of the basis state of the qubits in a readout.
+///
+/// Qubits beyond the length of the readout are counted as measured in the 0 state.
+fn basis_state_index(qubits: &[usize], readout: &[bool]) -> usize {
+    qubits
+        .iter()
+        .enumerate()
+        .filter(|(_, qubit)| readout.get(**qubit) == Some(&true))
+        .map(|(bit, _)| 1 << bit)
+        .sum()
+}
+
+/// Returns the inverse of a confusion matrix if it exists.
+fn inverse(confusion_matrix: &Array2<f64>) -> Option<DMatrix<f64>> {
+    let (rows, columns) = confusion_matrix.dim();
+    DMatrix::from_fn(rows, columns, |row, column| confusion_matrix[[row, column]]).try_inverse()
+}
+
+/// Creates an InvalidReadoutErrorModel error.
+fn invalid(msg: String) -> RoqoqoError {
+    RoqoqoError::InvalidReadoutErrorModel { msg }
+}
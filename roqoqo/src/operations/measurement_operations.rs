@@ -283,6 +283,85 @@ impl InvolveQubits for PragmaGetPauliProduct {
     }
 }
 
+/// This PRAGMA measurement operation returns the amplitudes of selected computational basis states.
+///
+/// Each bitstring selects one basis state, where entry `i` of the bitstring is the value of qubit `i`.
+/// Qubits beyond the length of a bitstring are assumed to be in the `0` state.
+/// The amplitudes are written into the complex readout register in the order of the bitstrings.
+///
+#[derive(Debug, Clone, PartialEq, roqoqo_derive::Operate, roqoqo_derive::OperatePragma)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PragmaGetAmplitudes {
+    /// The name of the classical readout register.
+    readout: String,
+    /// The bitstrings of the basis states for which the amplitudes are returned.
+    bitstrings: Vec<Vec<bool>>,
+    /// The measurement preparation Circuit, applied on a copy of the register before measurement (None if not defined, Some(Circuit) otherwise).
+    circuit: Option<Circuit>,
+}
+
+#[allow(non_upper_case_globals)]
+const TAGS_PragmaGetAmplitudes: &[&str; 4] = &[
+    "Operation",
+    "Measurement",
+    "PragmaOperation",
+    "PragmaGetAmplitudes",
+];
+
+/// Implements [Substitute] trait allowing to replace symbolic parameters and to perform qubit mappings.
+impl Substitute for PragmaGetAmplitudes {
+    /// Remaps qubits in operations in clone of the operation.
+    fn remap_qubits(&self, mapping: &HashMap<usize, usize>) -> Result<Self, RoqoqoError> {
+        let new_circuit = match self.circuit.as_ref() {
+            Some(x) => Some(x.remap_qubits(mapping)?),
+            _ => None,
+        };
+        let new_bitstrings = self
+            .bitstrings
+            .iter()
+            .map(|bitstring| {
+                let length = bitstring
+                    .iter()
+                    .enumerate()
+                    .map(|(qubit, _)| mapping.get(&qubit).unwrap_or(&qubit) + 1)
+                    .max()
+                    .unwrap_or(0);
+                let mut new_bitstring = vec![false; length];
+                for (qubit, value) in bitstring.iter().enumerate() {
+                    new_bitstring[*mapping.get(&qubit).unwrap_or(&qubit)] = *value;
+                }
+                new_bitstring
+            })
+            .collect();
+        Ok(PragmaGetAmplitudes::new(
+            self.readout.clone(),
+            new_bitstrings,
+            new_circuit,
+        ))
+    }
+
+    /// Substitutes symbolic parameters in clone of the operation.
+    fn substitute_parameters(&self, calculator: &mut Calculator) -> Result<Self, RoqoqoError> {
+        let new_circuit = match self.circuit.as_ref() {
+            Some(x) => Some(x.substitute_parameters(calculator)?),
+            _ => None,
+        };
+        Ok(PragmaGetAmplitudes::new(
+            self.readout.clone(),
+            self.bitstrings.clone(),
+            new_circuit,
+        ))
+    }
+}
+
+// Implements the InvolveQubits trait for PragmaGetAmplitudes.
+impl InvolveQubits for PragmaGetAmplitudes {
+    /// Lists all involved qubits (here, all).
+    fn involved_qubits(&self) -> InvolvedQubits {
+        InvolvedQubits::All
+    }
+}
+
 /// This PRAGMA measurement operation returns a measurement record for $N$ repeated measurements.
 ///
 #[derive(Debug, Clone, PartialEq, roqoqo_derive::Operate, roqoqo_derive::OperatePragma)]
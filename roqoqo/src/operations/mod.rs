@@ -444,7 +444,7 @@ pub trait Define: Operate + InvolveQubits + Substitute + Clone + PartialEq {
 pub trait OperateConstantGate:
     OperateGate + Operate + InvolveQubits + Substitute + Clone + PartialEq
 {
-    /// Returns true when unitary operation U is self inverse U*U = I.
+    /// Returns the inverse U^-1 of the unitary operation U.
     fn inverse(&self) -> GateOperation;
 }
 
@@ -612,7 +612,7 @@ pub trait OperateTwoQubitGate:
 /// circuit += Hadamard::new(2);
 /// circuit += CNOT::new(0, 1);
 /// circuit += CNOT::new(1, 2);
-/// circuit += RotateZ::new(2, CalculatorFloat::from(0.5));
+/// circuit += RotateZ::new(2, CalculatorFloat::from(1.0));
 /// circuit += CNOT::new(1, 2);
 /// circuit += CNOT::new(0, 1);
 /// circuit += Hadamard::new(0);
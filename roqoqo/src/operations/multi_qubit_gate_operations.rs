@@ -70,20 +70,13 @@ impl OperateGate for MultiQubitMS {
 }
 
 impl OperateMultiQubitGate for MultiQubitMS {
-    // Todo fill out circuit
+    /// Returns a decomposition into Hadamard gates and the decomposition of [MultiQubitZZ].
     fn circuit(&self) -> Circuit {
-        let dim = self.qubits.len();
         let mut circuit = Circuit::new();
         for q in self.qubits.iter() {
             circuit += operations::Hadamard::new(*q);
         }
-        for q in self.qubits[1..].iter() {
-            circuit += operations::CNOT::new(*q - 1, *q);
-        }
-        circuit += operations::RotateZ::new(dim - 1, self.theta.clone() / 2);
-        for q in self.qubits[1..].iter() {
-            circuit += operations::CNOT::new(dim - *q - 1, dim - *q);
-        }
+        circuit += zz_circuit(&self.qubits, &self.theta);
         for q in self.qubits.iter() {
             circuit += operations::Hadamard::new(*q);
         }
@@ -144,17 +137,26 @@ impl OperateGate for MultiQubitZZ {
 }
 
 impl OperateMultiQubitGate for MultiQubitZZ {
-    // Todo fill out circuit
+    /// Returns a decomposition into a CNOT ladder and a RotateZ on the last qubit.
     fn circuit(&self) -> Circuit {
-        let dim = self.qubits.len();
-        let mut circuit = Circuit::new();
-        for q in self.qubits[1..].iter() {
-            circuit += operations::CNOT::new(*q - 1, *q);
-        }
-        circuit += operations::RotateZ::new(dim - 1, self.theta.clone() / 2);
-        for q in self.qubits[1..].iter() {
-            circuit += operations::CNOT::new(dim - *q - 1, dim - *q);
-        }
-        circuit
+        zz_circuit(&self.qubits, &self.theta)
+    }
+}
+
+/// Returns the decomposition of exp(-i * theta/2 * Z_i0 * Z_i1 * ... * Z_in).
+///
+/// A ladder of CNOT gates computes the parity of all qubits on the last qubit,
+/// which is rotated with RotateZ(theta) before the ladder is undone.
+fn zz_circuit(qubits: &[usize], theta: &CalculatorFloat) -> Circuit {
+    let mut circuit = Circuit::new();
+    for pair in qubits.windows(2) {
+        circuit += operations::CNOT::new(pair[0], pair[1]);
+    }
+    if let Some(last) = qubits.last() {
+        circuit += operations::RotateZ::new(*last, theta.clone());
+    }
+    for pair in qubits.windows(2).rev() {
+        circuit += operations::CNOT::new(pair[0], pair[1]);
     }
+    circuit
 }
@@ -17,8 +17,8 @@ use std::convert::TryFrom;
 use std::f64::consts::PI;
 
 use crate::operations::{
-    InvolveQubits, InvolvedQubits, Operate, OperateGate, OperateSingleQubit,
-    OperateSingleQubitGate, Rotate, Substitute,
+    GateOperation, InvolveQubits, InvolvedQubits, Operate, OperateConstantGate, OperateGate,
+    OperateSingleQubit, OperateSingleQubitGate, Rotate, Substitute,
 };
 use crate::RoqoqoError;
 #[cfg(feature = "overrotate")]
@@ -490,6 +490,14 @@ pub struct PauliX {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for PauliX {
+    /// Returns the inverse of the gate, PauliX is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_PauliX: &[&str; 4] = &[
     "Operation",
@@ -582,6 +590,14 @@ pub struct PauliY {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for PauliY {
+    /// Returns the inverse of the gate, PauliY is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_PauliY: &[&str; 4] = &[
     "Operation",
@@ -674,6 +690,14 @@ pub struct PauliZ {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for PauliZ {
+    /// Returns the inverse of the gate, PauliZ is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_PauliZ: &[&str; 4] = &[
     "Operation",
@@ -766,6 +790,14 @@ pub struct SqrtPauliX {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for SqrtPauliX {
+    /// Returns the inverse of the gate, InvSqrtPauliX.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(InvSqrtPauliX::new(self.qubit))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_SqrtPauliX: &[&str; 4] = &[
     "Operation",
@@ -861,6 +893,14 @@ pub struct InvSqrtPauliX {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for InvSqrtPauliX {
+    /// Returns the inverse of the gate, SqrtPauliX.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(SqrtPauliX::new(self.qubit))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_InvSqrtPauliX: &[&str; 4] = &[
     "Operation",
@@ -956,6 +996,14 @@ pub struct Hadamard {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for Hadamard {
+    /// Returns the inverse of the gate, Hadamard is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_Hadamard: &[&str; 4] = &[
     "Operation",
@@ -1049,6 +1097,17 @@ pub struct SGate {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for SGate {
+    /// Returns the inverse of the gate, PhaseShiftState1 with angle -π/2.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(PhaseShiftState1::new(
+            self.qubit,
+            CalculatorFloat::from(-PI / 2.0),
+        ))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_SGate: &[&str; 4] = &[
     "Operation",
@@ -1141,6 +1200,17 @@ pub struct TGate {
     qubit: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for TGate {
+    /// Returns the inverse of the gate, PhaseShiftState1 with angle -π/4.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(PhaseShiftState1::new(
+            self.qubit,
+            CalculatorFloat::from(-PI / 4.0),
+        ))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_TGate: &[&str; 4] = &[
     "Operation",
@@ -11,6 +11,7 @@
 // limitations under the License.
 
 use crate::operations::single_qubit_gate_operations::*;
+use crate::operations::GateOperation;
 use crate::prelude::*;
 use crate::Circuit;
 use crate::RoqoqoError;
@@ -73,6 +74,14 @@ pub struct CNOT {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for CNOT {
+    /// Returns the inverse of the gate, CNOT is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_CNOT: &[&str; 4] = &[
     "Operation",
@@ -177,6 +186,14 @@ pub struct SWAP {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for SWAP {
+    /// Returns the inverse of the gate, SWAP is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_SWAP: &[&str; 4] = &[
     "Operation",
@@ -273,6 +290,18 @@ pub struct ISwap {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for ISwap {
+    /// Returns the inverse of the gate, XY with angle -π.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(XY::new(
+            self.control,
+            self.target,
+            CalculatorFloat::from(-PI),
+        ))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_ISwap: &[&str; 4] = &[
     "Operation",
@@ -369,6 +398,14 @@ pub struct FSwap {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for FSwap {
+    /// Returns the inverse of the gate, FSwap is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_FSwap: &[&str; 4] = &[
     "Operation",
@@ -469,6 +506,14 @@ pub struct SqrtISwap {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for SqrtISwap {
+    /// Returns the inverse of the gate, InvSqrtISwap.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(InvSqrtISwap::new(self.control, self.target))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_SqrtISwap: &[&str; 4] = &[
     "Operation",
@@ -566,6 +611,14 @@ pub struct InvSqrtISwap {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for InvSqrtISwap {
+    /// Returns the inverse of the gate, SqrtISwap.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(SqrtISwap::new(self.control, self.target))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_InvSqrtISwap: &[&str; 4] = &[
     "Operation",
@@ -872,6 +925,14 @@ pub struct ControlledPauliY {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for ControlledPauliY {
+    /// Returns the inverse of the gate, ControlledPauliY is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_ControlledPauliY: &[&str; 4] = &[
     "Operation",
@@ -976,6 +1037,14 @@ pub struct ControlledPauliZ {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for ControlledPauliZ {
+    /// Returns the inverse of the gate, ControlledPauliZ is self-inverse.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(self.clone())
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_ControlledPauliZ: &[&str; 4] = &[
     "Operation",
@@ -1078,6 +1147,18 @@ pub struct MolmerSorensenXX {
     target: usize,
 }
 
+/// Trait for unitary operations without any free parameters.
+impl OperateConstantGate for MolmerSorensenXX {
+    /// Returns the inverse of the gate, VariableMSXX with angle -π/2.
+    fn inverse(&self) -> GateOperation {
+        GateOperation::from(VariableMSXX::new(
+            self.control,
+            self.target,
+            CalculatorFloat::from(-PI / 2.0),
+        ))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 const TAGS_MolmerSorensenXX: &[&str; 4] = &[
     "Operation",
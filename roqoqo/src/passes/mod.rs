@@ -0,0 +1,320 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transpiler passes transforming roqoqo circuits.
+//!
+//! A [Pass] takes a [Circuit] and returns a transformed circuit, optionally using the
+//! properties of a [Device] (e.g. its connectivity or its available gates).
+//! The [PassManager] chains passes into a pipeline, runs them one after another and records
+//! [PassStatistics] with the [GateCounts] of the circuit before and after every pass.
+//!
+//! The passes implemented in roqoqo are collected in the [TranspilerPass] enum.
+//! A PassManager of TranspilerPasses can be serialized (with the `serialize` feature)
+//! to store or exchange the description of a pipeline.
+//! Custom passes implement [Pass] and can be run directly or collected in a PassManager
+//! of a custom pass type.
+//!
+//! Passes implemented in roqoqo:
+//! * [NativeGateDecompositionPass] - Decomposes gates into the native gates of a device.
+//! * [PeepholeOptimisationPass] - Cancels inverse gates and merges rotations.
+//! * [RemapQubitsPass] - Remaps the qubits of a circuit (see [Circuit::remap_qubits]).
+//! * [SingleQubitGateFusionPass] - Fuses consecutive single-qubit gates into one gate.
+//! * `OverrotatePass` - Applies the PragmaOverrotation operations of a circuit
+//!   (see `Circuit::overrotate`, requires the `overrotate` feature).
+
+use crate::devices::Device;
+use crate::operations::{
+    GateOperation, MultiQubitGateOperation, Operate, SingleQubitGateOperation,
+    TwoQubitGateOperation,
+};
+use crate::{Circuit, RoqoqoError};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+#[doc(hidden)]
+#[cfg(feature = "overrotate")]
+mod overrotate_pass;
+#[cfg(feature = "overrotate")]
+pub use overrotate_pass::*;
+#[doc(hidden)]
+mod native_gate_decomposition_pass;
+pub use native_gate_decomposition_pass::*;
+#[doc(hidden)]
+mod peephole_optimisation_pass;
+pub use peephole_optimisation_pass::*;
+#[doc(hidden)]
+mod remap_qubits_pass;
+pub use remap_qubits_pass::*;
+#[doc(hidden)]
+mod single_qubit_gate_fusion_pass;
+pub use single_qubit_gate_fusion_pass::*;
+
+/// Transformation of a circuit.
+pub trait Pass {
+    /// Returns the name of the pass used in the [PassStatistics].
+    fn name(&self) -> &'static str;
+
+    /// Runs the pass on a circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit that is transformed.
+    /// * `device` - The device the circuit is transformed for, `None` if the pass is run without a device.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Circuit)` - The transformed circuit.
+    /// * `Err(RoqoqoError)` - The pass failed.
+    fn run(&self, circuit: &Circuit, device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError>;
+}
+
+/// The passes implemented in roqoqo.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TranspilerPass {
+    /// Decomposes gates into the native gates of a device.
+    NativeGateDecomposition(NativeGateDecompositionPass),
+    /// Cancels inverse gates and merges rotations.
+    PeepholeOptimisation(PeepholeOptimisationPass),
+    /// Remaps the qubits of a circuit.
+    RemapQubits(RemapQubitsPass),
+    /// Fuses consecutive single-qubit gates into one gate.
+    SingleQubitGateFusion(SingleQubitGateFusionPass),
+    /// Applies the PragmaOverrotation operations of a circuit.
+    #[cfg(feature = "overrotate")]
+    Overrotate(OverrotatePass),
+}
+
+impl Pass for TranspilerPass {
+    fn name(&self) -> &'static str {
+        match self {
+            TranspilerPass::NativeGateDecomposition(pass) => pass.name(),
+            TranspilerPass::PeepholeOptimisation(pass) => pass.name(),
+            TranspilerPass::RemapQubits(pass) => pass.name(),
+            TranspilerPass::SingleQubitGateFusion(pass) => pass.name(),
+            #[cfg(feature = "overrotate")]
+            TranspilerPass::Overrotate(pass) => pass.name(),
+        }
+    }
+
+    fn run(&self, circuit: &Circuit, device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        match self {
+            TranspilerPass::NativeGateDecomposition(pass) => pass.run(circuit, device),
+            TranspilerPass::PeepholeOptimisation(pass) => pass.run(circuit, device),
+            TranspilerPass::RemapQubits(pass) => pass.run(circuit, device),
+            TranspilerPass::SingleQubitGateFusion(pass) => pass.run(circuit, device),
+            #[cfg(feature = "overrotate")]
+            TranspilerPass::Overrotate(pass) => pass.run(circuit, device),
+        }
+    }
+}
+
+impl From<NativeGateDecompositionPass> for TranspilerPass {
+    fn from(pass: NativeGateDecompositionPass) -> Self {
+        TranspilerPass::NativeGateDecomposition(pass)
+    }
+}
+
+impl From<PeepholeOptimisationPass> for TranspilerPass {
+    fn from(pass: PeepholeOptimisationPass) -> Self {
+        TranspilerPass::PeepholeOptimisation(pass)
+    }
+}
+
+impl From<RemapQubitsPass> for TranspilerPass {
+    fn from(pass: RemapQubitsPass) -> Self {
+        TranspilerPass::RemapQubits(pass)
+    }
+}
+
+impl From<SingleQubitGateFusionPass> for TranspilerPass {
+    fn from(pass: SingleQubitGateFusionPass) -> Self {
+        TranspilerPass::SingleQubitGateFusion(pass)
+    }
+}
+
+#[cfg(feature = "overrotate")]
+impl From<OverrotatePass> for TranspilerPass {
+    fn from(pass: OverrotatePass) -> Self {
+        TranspilerPass::Overrotate(pass)
+    }
+}
+
+/// Numbers of operations and gates in a circuit.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GateCounts {
+    /// Number of operations (not including definitions).
+    pub number_operations: usize,
+    /// Number of gate operations.
+    pub number_gates: usize,
+    /// Number of single-qubit gates.
+    pub number_single_qubit_gates: usize,
+    /// Number of two-qubit gates.
+    pub number_two_qubit_gates: usize,
+    /// Number of multi-qubit gates.
+    pub number_multi_qubit_gates: usize,
+    /// Number of operations by their hqslang name.
+    pub operation_counts: HashMap<String, usize>,
+}
+
+impl GateCounts {
+    /// Counts the operations and gates of a circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit whose operations are counted.
+    pub fn from_circuit(circuit: &Circuit) -> Self {
+        let mut counts = GateCounts::default();
+        for operation in circuit.operations() {
+            counts.number_operations += 1;
+            *counts
+                .operation_counts
+                .entry(operation.hqslang().to_string())
+                .or_insert(0) += 1;
+            if GateOperation::try_from(operation.clone()).is_err() {
+                continue;
+            }
+            counts.number_gates += 1;
+            if SingleQubitGateOperation::try_from(operation.clone()).is_ok() {
+                counts.number_single_qubit_gates += 1;
+            } else if TwoQubitGateOperation::try_from(operation.clone()).is_ok() {
+                counts.number_two_qubit_gates += 1;
+            } else if MultiQubitGateOperation::try_from(operation.clone()).is_ok() {
+                counts.number_multi_qubit_gates += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// Statistics of a pass run by a [PassManager].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassStatistics {
+    /// Name of the pass.
+    pub name: String,
+    /// Gate counts of the circuit before the pass.
+    pub before: GateCounts,
+    /// Gate counts of the circuit after the pass.
+    pub after: GateCounts,
+}
+
+/// Pipeline of passes that are run one after another.
+///
+/// # Example
+///
+/// ```
+/// use roqoqo::Circuit;
+/// use roqoqo::operations::CNOT;
+/// use roqoqo::passes::{PassManager, RemapQubitsPass};
+/// use std::collections::HashMap;
+///
+/// let mut circuit = Circuit::new();
+/// circuit += CNOT::new(0, 1);
+/// let mut mapping: HashMap<usize, usize> = HashMap::new();
+/// mapping.insert(0, 1);
+/// mapping.insert(1, 0);
+/// let mut pass_manager: PassManager = PassManager::new();
+/// pass_manager.add_pass(RemapQubitsPass::new(mapping));
+/// let (transformed, statistics) = pass_manager.run(&circuit, None).unwrap();
+/// assert_eq!(transformed, Circuit::new() + CNOT::new(1, 0));
+/// assert_eq!(statistics[0].after.number_two_qubit_gates, 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassManager<P = TranspilerPass>
+where
+    P: Pass,
+{
+    /// The passes in the order they are run.
+    passes: Vec<P>,
+}
+
+impl<P> Default for PassManager<P>
+where
+    P: Pass,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> PassManager<P>
+where
+    P: Pass,
+{
+    /// Creates a new PassManager without passes.
+    pub fn new() -> Self {
+        Self { passes: Vec::new() }
+    }
+
+    /// Adds a pass to the end of the pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `pass` - The pass that is added.
+    pub fn add_pass<T>(&mut self, pass: T)
+    where
+        T: Into<P>,
+    {
+        self.passes.push(pass.into());
+    }
+
+    /// Returns the passes in the order they are run.
+    pub fn passes(&self) -> &[P] {
+        &self.passes
+    }
+
+    /// Runs all passes on a circuit and records their statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit that is transformed.
+    /// * `device` - The device passed to every pass, `None` if the passes are run without a device.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Circuit, Vec<PassStatistics>))` - The transformed circuit and the statistics of every pass.
+    /// * `Err(RoqoqoError)` - The first error of a failing pass.
+    pub fn run(
+        &self,
+        circuit: &Circuit,
+        device: Option<&dyn Device>,
+    ) -> Result<(Circuit, Vec<PassStatistics>), RoqoqoError> {
+        let mut transformed = circuit.clone();
+        let mut before = GateCounts::from_circuit(circuit);
+        let mut statistics: Vec<PassStatistics> = Vec::with_capacity(self.passes.len());
+        for pass in self.passes.iter() {
+            transformed = pass.run(&transformed, device)?;
+            let after = GateCounts::from_circuit(&transformed);
+            statistics.push(PassStatistics {
+                name: pass.name().to_string(),
+                before,
+                after: after.clone(),
+            });
+            before = after;
+        }
+        Ok((transformed, statistics))
+    }
+}
+
+impl<P> Pass for PassManager<P>
+where
+    P: Pass,
+{
+    fn name(&self) -> &'static str {
+        "PassManager"
+    }
+
+    fn run(&self, circuit: &Circuit, device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        PassManager::run(self, circuit, device).map(|(transformed, _)| transformed)
+    }
+}
@@ -0,0 +1,488 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::devices::Device;
+use crate::operations::*;
+use crate::passes::Pass;
+use crate::{Circuit, RoqoqoError};
+use ndarray::Array2;
+use num_complex::Complex64;
+use qoqo_calculator::CalculatorFloat;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// Tolerance below which angles and matrix elements are treated as zero.
+const TOLERANCE: f64 = 1e-10;
+
+/// Pass decomposing the gates of a circuit into the native gates of a device.
+///
+/// A gate is native on the device if the device returns a gate time for the hqslang name of the
+/// gate on its qubits ([Device::single_qubit_gate_time], [Device::two_qubit_gate_time] or
+/// [Device::multi_qubit_gate_time]). Native gates and operations that are not gates are not changed,
+/// all other gates are rewritten:
+///
+/// * Single-qubit gates are decomposed into RotateZ and one of the native bases
+///   {RotateY}, {RotateX} or {SqrtPauliX} (Euler angle decomposition).
+/// * Two-qubit gates are decomposed with their [KakDecomposition] into single-qubit gates and
+///   CNOT gates. The CNOT gates are replaced by a native CNOT (in either direction),
+///   ControlledPauliZ or MolmerSorensenXX gate, consecutive single-qubit gates are
+///   fused and decomposed as above.
+/// * Multi-qubit gates are decomposed recursively with [OperateMultiQubitGate::circuit].
+/// * The circuits of PragmaConditional operations are decomposed recursively.
+///
+/// The target basis is inferred from the device for every gate, so e.g. {RotateZ, RotateX, CNOT},
+/// {RotateZ, SqrtPauliX, ControlledPauliZ} and {RotateZ, RotateX, MolmerSorensenXX} are supported.
+/// The global phase of every decomposed gate is preserved by a [PragmaGlobalPhase]
+/// (unless it is zero).
+///
+/// The pass requires a device. It fails if a gate with symbolic parameters has to be decomposed
+/// or if the device does not provide the gates needed for the decomposition
+/// (e.g. no two-qubit gate between the qubits of a two-qubit gate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NativeGateDecompositionPass;
+
+impl NativeGateDecompositionPass {
+    /// Creates a new NativeGateDecompositionPass.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Pass for NativeGateDecompositionPass {
+    fn name(&self) -> &'static str {
+        "NativeGateDecomposition"
+    }
+
+    fn run(&self, circuit: &Circuit, device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        let device = device.ok_or_else(|| RoqoqoError::TranspilerPassError {
+            pass: self.name().to_string(),
+            msg: "The pass requires a device".to_string(),
+        })?;
+        Decomposer { device }.decompose_circuit(circuit)
+    }
+}
+
+/// Decomposes operations into the native gates of a device.
+struct Decomposer<'a> {
+    /// The device providing the native gates.
+    device: &'a dyn Device,
+}
+
+impl<'a> Decomposer<'a> {
+    /// Decomposes all operations of a circuit, keeping the definitions.
+    fn decompose_circuit(&self, circuit: &Circuit) -> Result<Circuit, RoqoqoError> {
+        let mut decomposed = Circuit::new();
+        for definition in circuit.definitions() {
+            decomposed.add_operation(definition.clone());
+        }
+        for operation in circuit.operations() {
+            for native in self.decompose_operation(operation)? {
+                decomposed.add_operation(native);
+            }
+        }
+        Ok(decomposed)
+    }
+
+    /// Returns the native operations replacing an operation.
+    fn decompose_operation(&self, operation: &Operation) -> Result<Vec<Operation>, RoqoqoError> {
+        if let Operation::PragmaConditional(conditional) = operation {
+            return Ok(vec![PragmaConditional::new(
+                conditional.condition_register().clone(),
+                *conditional.condition_index(),
+                self.decompose_circuit(conditional.circuit())?,
+            )
+            .into()]);
+        }
+        if GateOperation::try_from(operation.clone()).is_err() || self.is_native(operation) {
+            return Ok(vec![operation.clone()]);
+        }
+        if let Ok(gate) = SingleQubitGateOperation::try_from(operation.clone()) {
+            let native = self.decompose_single_qubit_gate(&gate)?;
+            return self.add_global_phase(operation, native, &[*gate.qubit()]);
+        }
+        if let Ok(gate) = TwoQubitGateOperation::try_from(operation.clone()) {
+            let native = self.decompose_two_qubit_gate(&gate)?;
+            return self.add_global_phase(operation, native, &[*gate.control(), *gate.target()]);
+        }
+        if let Ok(gate) = MultiQubitGateOperation::try_from(operation.clone()) {
+            let mut native: Vec<Operation> = Vec::new();
+            for sub_operation in gate.circuit().iter() {
+                native.extend(self.decompose_operation(sub_operation)?);
+            }
+            // The phases of the decomposed sub-gates are replaced by the phase of the whole gate
+            native.retain(|operation| !matches!(operation, Operation::PragmaGlobalPhase(_)));
+            return self.add_global_phase(operation, native, gate.qubits());
+        }
+        Err(self.error(operation, "Gate can not be decomposed"))
+    }
+
+    /// Returns true if the device provides the gate on its qubits.
+    fn is_native(&self, operation: &Operation) -> bool {
+        if let Ok(gate) = SingleQubitGateOperation::try_from(operation.clone()) {
+            return self
+                .device
+                .single_qubit_gate_time(gate.hqslang(), gate.qubit())
+                .is_some();
+        }
+        if let Ok(gate) = TwoQubitGateOperation::try_from(operation.clone()) {
+            return self
+                .device
+                .two_qubit_gate_time(gate.hqslang(), gate.control(), gate.target())
+                .is_some();
+        }
+        if let Ok(gate) = MultiQubitGateOperation::try_from(operation.clone()) {
+            return self
+                .device
+                .multi_qubit_gate_time(gate.hqslang(), gate.qubits())
+                .is_some();
+        }
+        false
+    }
+
+    /// Returns true if the device provides the single-qubit gate with the hqslang name on the qubit.
+    fn has_single_qubit_gate(&self, hqslang: &str, qubit: usize) -> bool {
+        self.device
+            .single_qubit_gate_time(hqslang, &qubit)
+            .is_some()
+    }
+
+    /// Returns true if the device provides the two-qubit gate with the hqslang name on the qubits.
+    fn has_two_qubit_gate(&self, hqslang: &str, control: usize, target: usize) -> bool {
+        self.device
+            .two_qubit_gate_time(hqslang, &control, &target)
+            .is_some()
+    }
+
+    /// Decomposes a single-qubit gate into RotateZ and RotateY, RotateX or SqrtPauliX gates.
+    ///
+    /// The unitary is decomposed as RotateZ(a) RotateY(b) RotateZ(c) up to a global phase.
+    fn decompose_single_qubit_gate(
+        &self,
+        gate: &SingleQubitGateOperation,
+    ) -> Result<Vec<Operation>, RoqoqoError> {
+        let qubit = *gate.qubit();
+        let alpha = Complex64::new(
+            f64::try_from(gate.alpha_r())?,
+            f64::try_from(gate.alpha_i())?,
+        );
+        let beta = Complex64::new(f64::try_from(gate.beta_r())?, f64::try_from(gate.beta_i())?);
+        let b = 2.0 * beta.norm().atan2(alpha.norm());
+        let a = beta.arg() - alpha.arg();
+        let c = -alpha.arg() - beta.arg();
+
+        let mut native: Vec<Operation> = Vec::new();
+        let rotate_z = |theta: f64, native: &mut Vec<Operation>| {
+            let theta = normalise_angle(theta);
+            if theta.abs() > TOLERANCE {
+                native.push(RotateZ::new(qubit, CalculatorFloat::from(theta)).into());
+            }
+        };
+        if b.abs() < TOLERANCE {
+            rotate_z(a + c, &mut native);
+        } else if self.has_single_qubit_gate("RotateY", qubit) {
+            rotate_z(c, &mut native);
+            native.push(RotateY::new(qubit, CalculatorFloat::from(b)).into());
+            rotate_z(a, &mut native);
+        } else if self.has_single_qubit_gate("RotateX", qubit) {
+            rotate_z(c - FRAC_PI_2, &mut native);
+            native.push(RotateX::new(qubit, CalculatorFloat::from(b)).into());
+            rotate_z(a + FRAC_PI_2, &mut native);
+        } else if self.has_single_qubit_gate("SqrtPauliX", qubit) {
+            rotate_z(c, &mut native);
+            native.push(SqrtPauliX::new(qubit).into());
+            rotate_z(b + PI, &mut native);
+            native.push(SqrtPauliX::new(qubit).into());
+            rotate_z(a + PI, &mut native);
+        } else {
+            return Err(self.error(
+                &gate.clone().into(),
+                "Device provides neither RotateY, RotateX nor SqrtPauliX on the qubit",
+            ));
+        }
+        if let Some(operation) = native.iter().find(|operation| !self.is_native(operation)) {
+            return Err(self.error(
+                &gate.clone().into(),
+                &format!(
+                    "Device does not provide {} on the qubit",
+                    operation.hqslang()
+                ),
+            ));
+        }
+        Ok(native)
+    }
+
+    /// Decomposes a two-qubit gate into native single-qubit and two-qubit gates.
+    ///
+    /// The entangling part exp(i (k_x XX + k_y YY + k_z ZZ)) of the KAK decomposition is built from
+    /// exp(i k ZZ) = CNOT RotateZ(-2k) CNOT, rotated into the XX and YY bases by single-qubit gates.
+    /// For k = +-pi/4 a single ControlledPauliZ (built from one CNOT) followed by RotateZ(-+pi/2) on
+    /// both qubits is used instead, so e.g. CNOT and ISwap need one and two native two-qubit gates.
+    fn decompose_two_qubit_gate(
+        &self,
+        gate: &TwoQubitGateOperation,
+    ) -> Result<Vec<Operation>, RoqoqoError> {
+        let (control, target) = (*gate.control(), *gate.target());
+        let kak = gate.kak_decomposition();
+        let mut operations: Vec<Operation> = Vec::new();
+        if let Some(circuit) = kak.circuit_before {
+            operations.extend(circuit.iter().cloned());
+        }
+        let [k_x, k_y, k_z] = kak.k_vector;
+        let basis_changes: [(f64, Vec<Operation>); 3] = [
+            (
+                f64::try_from(k_x)?,
+                vec![Hadamard::new(control).into(), Hadamard::new(target).into()],
+            ),
+            (
+                f64::try_from(k_y)?,
+                vec![
+                    RotateX::new(control, CalculatorFloat::FRAC_PI_2).into(),
+                    RotateX::new(target, CalculatorFloat::FRAC_PI_2).into(),
+                ],
+            ),
+            (f64::try_from(k_z)?, Vec::new()),
+        ];
+        for (k, basis_change) in basis_changes {
+            if k.abs() < TOLERANCE {
+                continue;
+            }
+            operations.extend(basis_change.iter().cloned());
+            if (k.abs() - FRAC_PI_4).abs() < TOLERANCE {
+                let theta = CalculatorFloat::from(-k.signum() * FRAC_PI_2);
+                operations.push(Hadamard::new(target).into());
+                operations.extend(self.native_cnot(gate, control, target)?);
+                operations.push(Hadamard::new(target).into());
+                operations.push(RotateZ::new(control, theta.clone()).into());
+                operations.push(RotateZ::new(target, theta).into());
+            } else {
+                operations.extend(self.native_cnot(gate, control, target)?);
+                operations.push(RotateZ::new(target, CalculatorFloat::from(-2.0 * k)).into());
+                operations.extend(self.native_cnot(gate, control, target)?);
+            }
+            operations.extend(basis_change.iter().map(inverse));
+        }
+        if let Some(circuit) = kak.circuit_after {
+            operations.extend(circuit.iter().cloned());
+        }
+
+        // Fuses the single-qubit gates between the two-qubit gates before decomposing them.
+        let mut native: Vec<Operation> = Vec::new();
+        let mut pending: HashMap<usize, SingleQubitGate> = HashMap::new();
+        for operation in operations {
+            match SingleQubitGateOperation::try_from(operation.clone()) {
+                Ok(single) => {
+                    let fused = match pending.remove(single.qubit()) {
+                        Some(previous) => single.mul(&previous)?,
+                        None => SingleQubitGate::new(
+                            *single.qubit(),
+                            single.alpha_r(),
+                            single.alpha_i(),
+                            single.beta_r(),
+                            single.beta_i(),
+                            single.global_phase(),
+                        ),
+                    };
+                    pending.insert(*single.qubit(), fused);
+                }
+                Err(_) => {
+                    for qubit in [control, target] {
+                        if let Some(fused) = pending.remove(&qubit) {
+                            native.extend(self.decompose_single_qubit_gate(&fused.into())?);
+                        }
+                    }
+                    native.push(operation);
+                }
+            }
+        }
+        for qubit in [control, target] {
+            if let Some(fused) = pending.remove(&qubit) {
+                native.extend(self.decompose_single_qubit_gate(&fused.into())?);
+            }
+        }
+        Ok(native)
+    }
+
+    /// Returns a native two-qubit gate surrounded by single-qubit gates implementing CNOT(control, target).
+    ///
+    /// The returned single-qubit gates are not decomposed yet.
+    fn native_cnot(
+        &self,
+        gate: &TwoQubitGateOperation,
+        control: usize,
+        target: usize,
+    ) -> Result<Vec<Operation>, RoqoqoError> {
+        if self.has_two_qubit_gate("CNOT", control, target) {
+            return Ok(vec![CNOT::new(control, target).into()]);
+        }
+        if self.has_two_qubit_gate("CNOT", target, control) {
+            return Ok(vec![
+                Hadamard::new(control).into(),
+                Hadamard::new(target).into(),
+                CNOT::new(target, control).into(),
+                Hadamard::new(control).into(),
+                Hadamard::new(target).into(),
+            ]);
+        }
+        for (first, second) in [(control, target), (target, control)] {
+            if self.has_two_qubit_gate("ControlledPauliZ", first, second) {
+                return Ok(vec![
+                    Hadamard::new(target).into(),
+                    ControlledPauliZ::new(first, second).into(),
+                    Hadamard::new(target).into(),
+                ]);
+            }
+        }
+        // CNOT = H_t CZ H_t and CZ = RotateZ(-pi/2) RotateZ(-pi/2) (H H) MolmerSorensenXX (H H)
+        // up to a global phase.
+        for (first, second) in [(control, target), (target, control)] {
+            if self.has_two_qubit_gate("MolmerSorensenXX", first, second) {
+                return Ok(vec![
+                    Hadamard::new(control).into(),
+                    MolmerSorensenXX::new(first, second).into(),
+                    Hadamard::new(control).into(),
+                    Hadamard::new(target).into(),
+                    RotateZ::new(control, CalculatorFloat::FRAC_PI_2 * (-1.0)).into(),
+                    RotateZ::new(target, CalculatorFloat::FRAC_PI_2 * (-1.0)).into(),
+                    Hadamard::new(target).into(),
+                ]);
+            }
+        }
+        Err(self.error(
+            &gate.clone().into(),
+            "Device provides neither CNOT, ControlledPauliZ nor MolmerSorensenXX between the qubits",
+        ))
+    }
+
+    /// Appends the PragmaGlobalPhase making the native operations equal to the decomposed gate.
+    ///
+    /// The unitary of the native operations is compared with the unitary of the gate,
+    /// a decomposition that is not equal up to a global phase is refused.
+    fn add_global_phase(
+        &self,
+        operation: &Operation,
+        mut native: Vec<Operation>,
+        qubits: &[usize],
+    ) -> Result<Vec<Operation>, RoqoqoError> {
+        let expected = GateOperation::try_from(operation.clone())?.unitary_matrix()?;
+        let product = unitary_matrix(&native, qubits)?;
+        let ((row, column), _) = product
+            .indexed_iter()
+            .max_by(|(_, a), (_, b)| a.norm().partial_cmp(&b.norm()).unwrap_or(Ordering::Equal))
+            .expect("Unitary matrix is not empty");
+        let phase = (expected[(row, column)] / product[(row, column)]).arg();
+        let phase_factor = Complex64::from_polar(1.0, phase);
+        let matches = expected
+            .iter()
+            .zip(product.iter())
+            .all(|(expected, product)| (expected - product * phase_factor).norm() < 1e-6);
+        if !matches {
+            return Err(self.error(operation, "Decomposition does not reproduce the gate"));
+        }
+        if phase.abs() > TOLERANCE {
+            native.push(PragmaGlobalPhase::new(CalculatorFloat::from(phase)).into());
+        }
+        Ok(native)
+    }
+
+    /// Creates the error of the pass for an operation.
+    fn error(&self, operation: &Operation, msg: &str) -> RoqoqoError {
+        RoqoqoError::TranspilerPassError {
+            pass: NativeGateDecompositionPass.name().to_string(),
+            msg: format!("{}: {}", operation.hqslang(), msg),
+        }
+    }
+}
+
+/// Returns the inverse of a basis change gate (RotateX or the self-inverse Hadamard).
+fn inverse(operation: &Operation) -> Operation {
+    match operation {
+        Operation::RotateX(gate) => {
+            RotateX::new(*gate.qubit(), gate.theta().clone() * (-1.0)).into()
+        }
+        _ => operation.clone(),
+    }
+}
+
+/// Maps an angle into the interval [-pi, pi].
+fn normalise_angle(theta: f64) -> f64 {
+    let theta = theta.rem_euclid(2.0 * PI);
+    if theta > PI {
+        theta - 2.0 * PI
+    } else {
+        theta
+    }
+}
+
+/// Returns the unitary matrix of a sequence of gates acting on the qubits.
+///
+/// The first qubit is the most significant qubit of the matrix, like the control qubit
+/// in the unitary matrix of a two-qubit gate.
+fn unitary_matrix(
+    operations: &[Operation],
+    qubits: &[usize],
+) -> Result<Array2<Complex64>, RoqoqoError> {
+    let dimension = 1 << qubits.len();
+    let mut unitary: Array2<Complex64> = Array2::eye(dimension);
+    for operation in operations {
+        let gate = GateOperation::try_from(operation.clone())?;
+        let gate_qubits = if let Ok(gate) = SingleQubitGateOperation::try_from(operation.clone()) {
+            vec![*gate.qubit()]
+        } else if let Ok(gate) = TwoQubitGateOperation::try_from(operation.clone()) {
+            vec![*gate.control(), *gate.target()]
+        } else {
+            MultiQubitGateOperation::try_from(operation.clone())?
+                .qubits()
+                .clone()
+        };
+        // Bits of the gate qubits in the basis state index, the first gate qubit is the most significant
+        let bits = gate_qubits
+            .iter()
+            .rev()
+            .map(|qubit| {
+                qubits
+                    .iter()
+                    .position(|q| q == qubit)
+                    .map(|position| qubits.len() - 1 - position)
+                    .ok_or(RoqoqoError::QubitMappingError { qubit: *qubit })
+            })
+            .collect::<Result<Vec<usize>, RoqoqoError>>()?;
+        let mask: usize = bits.iter().map(|bit| 1 << bit).sum();
+        let indices = |base: usize| -> Vec<usize> {
+            (0..1_usize << bits.len())
+                .map(|sub| {
+                    bits.iter()
+                        .enumerate()
+                        .filter(|(position, _)| (sub >> position) & 1 == 1)
+                        .fold(base, |index, (_, bit)| index | (1 << bit))
+                })
+                .collect()
+        };
+        let matrix = gate.unitary_matrix()?;
+        for base in (0..dimension).filter(|index| index & mask == 0) {
+            let indices = indices(base);
+            for column in 0..dimension {
+                let old: Vec<Complex64> = indices.iter().map(|i| unitary[(*i, column)]).collect();
+                for (row, index) in indices.iter().enumerate() {
+                    unitary[(*index, column)] = old
+                        .iter()
+                        .enumerate()
+                        .map(|(sub, value)| matrix[(row, sub)] * value)
+                        .sum();
+                }
+            }
+        }
+    }
+    Ok(unitary)
+}
@@ -0,0 +1,37 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::devices::Device;
+use crate::passes::Pass;
+use crate::{Circuit, RoqoqoError};
+
+/// Pass applying the PragmaOverrotation operations of a circuit (see [Circuit::overrotate]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverrotatePass;
+
+impl OverrotatePass {
+    /// Creates a new OverrotatePass.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Pass for OverrotatePass {
+    fn name(&self) -> &'static str {
+        "Overrotate"
+    }
+
+    fn run(&self, circuit: &Circuit, _device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        circuit.overrotate()
+    }
+}
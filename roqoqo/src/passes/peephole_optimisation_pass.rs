@@ -0,0 +1,284 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::devices::Device;
+use crate::operations::*;
+use crate::passes::Pass;
+use crate::{Circuit, RoqoqoError};
+use qoqo_calculator::CalculatorFloat;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+/// Pass removing redundant gates from a circuit by local rewrites.
+///
+/// The pass repeatedly applies the following rewrites until the circuit does not change:
+///
+/// * A constant gate directly followed by its inverse ([OperateConstantGate::inverse])
+///   on the same qubits is removed together with the inverse, e.g. two CNOT or Hadamard gates
+///   or SqrtPauliX followed by InvSqrtPauliX. Gates that are symmetric in their two qubits
+///   (e.g. SWAP or ControlledPauliZ) also cancel when their qubits are in opposite order.
+/// * Two rotations about the same axis on the same qubits (e.g. two RotateZ gates) are merged
+///   into one rotation with the sum of the angles, symbolic angles are added symbolically.
+/// * Rotations about a fixed axis with an angle of zero are removed.
+///
+/// Gates are only combined when no other operation acts on their qubits in between, with one
+/// exception: diagonal gates (e.g. RotateZ, PauliZ or PhaseShiftState1) commute with the diagonal
+/// two-qubit gates ControlledPauliZ and ControlledPhaseShift and with each other,
+/// so they are moved past each other to expose more cancellations.
+/// Operations acting on all qubits (e.g. PragmaRepeatedMeasurement) are never moved past.
+///
+/// All rewrites preserve the unitary of the circuit exactly, including the global phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeepholeOptimisationPass;
+
+impl PeepholeOptimisationPass {
+    /// Creates a new PeepholeOptimisationPass.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Pass for PeepholeOptimisationPass {
+    fn name(&self) -> &'static str {
+        "PeepholeOptimisation"
+    }
+
+    fn run(&self, circuit: &Circuit, _device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        let mut operations: Vec<Operation> = circuit.operations().clone();
+        while optimise_sweep(&mut operations) {}
+        let mut optimised = Circuit::new();
+        for definition in circuit.definitions() {
+            optimised.add_operation(definition.clone());
+        }
+        for operation in operations {
+            optimised.add_operation(operation);
+        }
+        Ok(optimised)
+    }
+}
+
+/// Applies rewrites in one sweep over the operations and returns true if the operations changed.
+///
+/// After a rewrite the sweep continues at the rewritten gate, or after a removal at the previous
+/// operation acting on its qubits, so that nested cancellations are found in the same sweep.
+fn optimise_sweep(operations: &mut Vec<Operation>) -> bool {
+    let length = operations.len();
+    operations.retain(|operation| !is_zero_rotation(operation));
+    let mut changed = operations.len() != length;
+    let mut index = 0;
+    while index < operations.len() {
+        let qubits = match operations[index].involved_qubits() {
+            InvolvedQubits::Set(qubits)
+                if GateOperation::try_from(operations[index].clone()).is_ok() =>
+            {
+                qubits
+            }
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+        let (next, merged) = match next_rewrite(operations, index, &qubits) {
+            Some(rewrite) => rewrite,
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+        changed = true;
+        operations.remove(next);
+        match merged {
+            Some(merged) if !is_zero_rotation(&merged) => operations[index] = merged,
+            _ => {
+                operations.remove(index);
+                index = previous_on_qubits(operations, index, &qubits);
+            }
+        }
+    }
+    changed
+}
+
+/// Returns true if the operation is a rotation about a fixed axis with an angle of zero.
+fn is_zero_rotation(operation: &Operation) -> bool {
+    same_axis_angle(operation)
+        .and_then(|theta| theta.float().ok())
+        .map(|theta| theta.abs() < f64::EPSILON)
+        .unwrap_or(false)
+}
+
+/// Returns the index of the last operation before index acting on the qubits (index if there is none).
+fn previous_on_qubits(operations: &[Operation], index: usize, qubits: &HashSet<usize>) -> usize {
+    operations[..index]
+        .iter()
+        .rposition(|operation| match operation.involved_qubits() {
+            InvolvedQubits::All => true,
+            InvolvedQubits::None => false,
+            InvolvedQubits::Set(previous_qubits) => !previous_qubits.is_disjoint(qubits),
+        })
+        .unwrap_or(index)
+}
+
+/// Finds the next operation acting on the qubits of the gate at index that can be combined with it.
+///
+/// Returns the index of the combined operation and the merged gate (`None` if both gates cancel).
+fn next_rewrite(
+    operations: &[Operation],
+    index: usize,
+    qubits: &HashSet<usize>,
+) -> Option<(usize, Option<Operation>)> {
+    let first = &operations[index];
+    for (next, operation) in operations.iter().enumerate().skip(index + 1) {
+        let next_qubits = match operation.involved_qubits() {
+            InvolvedQubits::All => return None,
+            InvolvedQubits::None => continue,
+            InvolvedQubits::Set(next_qubits) => next_qubits,
+        };
+        if next_qubits.is_disjoint(qubits) {
+            continue;
+        }
+        if next_qubits == *qubits {
+            if cancels(first, operation) {
+                return Some((next, None));
+            }
+            if let Some(merged) = merge_rotations(first, operation) {
+                return Some((next, Some(merged)));
+            }
+        }
+        if !(is_diagonal(first) && is_diagonal(operation)) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Returns true if the second operation is the inverse of the first constant gate.
+fn cancels(first: &Operation, second: &Operation) -> bool {
+    match ConstantGateOperation::try_from(first.clone()) {
+        Ok(gate) => sorted_qubits(Operation::from(gate.inverse())) == sorted_qubits(second.clone()),
+        Err(_) => false,
+    }
+}
+
+/// Orders the qubits of gates that are symmetric in their two qubits, so that the control is the smaller qubit.
+fn sorted_qubits(operation: Operation) -> Operation {
+    let (control, target) = match &operation {
+        Operation::SWAP(op) => (*op.control(), *op.target()),
+        Operation::ISwap(op) => (*op.control(), *op.target()),
+        Operation::FSwap(op) => (*op.control(), *op.target()),
+        Operation::SqrtISwap(op) => (*op.control(), *op.target()),
+        Operation::InvSqrtISwap(op) => (*op.control(), *op.target()),
+        Operation::ControlledPauliZ(op) => (*op.control(), *op.target()),
+        Operation::MolmerSorensenXX(op) => (*op.control(), *op.target()),
+        Operation::ControlledPhaseShift(op) => (*op.control(), *op.target()),
+        Operation::VariableMSXX(op) => (*op.control(), *op.target()),
+        Operation::XY(op) => (*op.control(), *op.target()),
+        _ => return operation,
+    };
+    if control < target {
+        return operation;
+    }
+    match operation {
+        Operation::SWAP(_) => SWAP::new(target, control).into(),
+        Operation::ISwap(_) => ISwap::new(target, control).into(),
+        Operation::FSwap(_) => FSwap::new(target, control).into(),
+        Operation::SqrtISwap(_) => SqrtISwap::new(target, control).into(),
+        Operation::InvSqrtISwap(_) => InvSqrtISwap::new(target, control).into(),
+        Operation::ControlledPauliZ(_) => ControlledPauliZ::new(target, control).into(),
+        Operation::MolmerSorensenXX(_) => MolmerSorensenXX::new(target, control).into(),
+        Operation::ControlledPhaseShift(op) => {
+            ControlledPhaseShift::new(target, control, op.theta().clone()).into()
+        }
+        Operation::VariableMSXX(op) => {
+            VariableMSXX::new(target, control, op.theta().clone()).into()
+        }
+        Operation::XY(op) => XY::new(target, control, op.theta().clone()).into(),
+        _ => operation,
+    }
+}
+
+/// Returns true if the unitary matrix of the operation is diagonal.
+fn is_diagonal(operation: &Operation) -> bool {
+    matches!(
+        operation,
+        Operation::RotateZ(_)
+            | Operation::PauliZ(_)
+            | Operation::SGate(_)
+            | Operation::TGate(_)
+            | Operation::PhaseShiftState0(_)
+            | Operation::PhaseShiftState1(_)
+            | Operation::ControlledPauliZ(_)
+            | Operation::ControlledPhaseShift(_)
+            | Operation::PhaseShiftedControlledZ(_)
+            | Operation::MultiQubitZZ(_)
+    )
+}
+
+/// Returns the angle of a rotation about a fixed axis, which is the identity for an angle of zero.
+fn same_axis_angle(operation: &Operation) -> Option<&CalculatorFloat> {
+    match operation {
+        Operation::RotateX(op) => Some(op.theta()),
+        Operation::RotateY(op) => Some(op.theta()),
+        Operation::RotateZ(op) => Some(op.theta()),
+        Operation::PhaseShiftState0(op) => Some(op.theta()),
+        Operation::PhaseShiftState1(op) => Some(op.theta()),
+        Operation::ControlledPhaseShift(op) => Some(op.theta()),
+        Operation::VariableMSXX(op) => Some(op.theta()),
+        Operation::XY(op) => Some(op.theta()),
+        Operation::MultiQubitMS(op) => Some(op.theta()),
+        Operation::MultiQubitZZ(op) => Some(op.theta()),
+        _ => None,
+    }
+}
+
+/// Merges two rotations about the same axis acting on the same qubits.
+fn merge_rotations(first: &Operation, second: &Operation) -> Option<Operation> {
+    let sum = |a: &CalculatorFloat, b: &CalculatorFloat| a.clone() + b.clone();
+    match (first, second) {
+        (Operation::RotateX(a), Operation::RotateX(b)) if a.qubit() == b.qubit() => {
+            Some(RotateX::new(*a.qubit(), sum(a.theta(), b.theta())).into())
+        }
+        (Operation::RotateY(a), Operation::RotateY(b)) if a.qubit() == b.qubit() => {
+            Some(RotateY::new(*a.qubit(), sum(a.theta(), b.theta())).into())
+        }
+        (Operation::RotateZ(a), Operation::RotateZ(b)) if a.qubit() == b.qubit() => {
+            Some(RotateZ::new(*a.qubit(), sum(a.theta(), b.theta())).into())
+        }
+        (Operation::PhaseShiftState0(a), Operation::PhaseShiftState0(b))
+            if a.qubit() == b.qubit() =>
+        {
+            Some(PhaseShiftState0::new(*a.qubit(), sum(a.theta(), b.theta())).into())
+        }
+        (Operation::PhaseShiftState1(a), Operation::PhaseShiftState1(b))
+            if a.qubit() == b.qubit() =>
+        {
+            Some(PhaseShiftState1::new(*a.qubit(), sum(a.theta(), b.theta())).into())
+        }
+        // ControlledPhaseShift, VariableMSXX and XY are symmetric in the two qubits.
+        (Operation::ControlledPhaseShift(a), Operation::ControlledPhaseShift(b)) => Some(
+            ControlledPhaseShift::new(*a.control(), *a.target(), sum(a.theta(), b.theta())).into(),
+        ),
+        (Operation::VariableMSXX(a), Operation::VariableMSXX(b)) => {
+            Some(VariableMSXX::new(*a.control(), *a.target(), sum(a.theta(), b.theta())).into())
+        }
+        (Operation::XY(a), Operation::XY(b)) => {
+            Some(XY::new(*a.control(), *a.target(), sum(a.theta(), b.theta())).into())
+        }
+        (Operation::MultiQubitMS(a), Operation::MultiQubitMS(b)) => {
+            Some(MultiQubitMS::new(a.qubits().clone(), sum(a.theta(), b.theta())).into())
+        }
+        (Operation::MultiQubitZZ(a), Operation::MultiQubitZZ(b)) => {
+            Some(MultiQubitZZ::new(a.qubits().clone(), sum(a.theta(), b.theta())).into())
+        }
+        _ => None,
+    }
+}
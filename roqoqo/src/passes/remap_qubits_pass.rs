@@ -0,0 +1,52 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::devices::Device;
+use crate::passes::Pass;
+use crate::{Circuit, RoqoqoError};
+use std::collections::HashMap;
+
+/// Pass remapping the qubits of a circuit (see [Circuit::remap_qubits]).
+///
+/// Qubits that are not keys of the mapping are not changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemapQubitsPass {
+    /// The mapping from the qubits of the circuit to the new qubits.
+    mapping: HashMap<usize, usize>,
+}
+
+impl RemapQubitsPass {
+    /// Creates a new RemapQubitsPass.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - The mapping from the qubits of the circuit to the new qubits.
+    pub fn new(mapping: HashMap<usize, usize>) -> Self {
+        Self { mapping }
+    }
+
+    /// Returns the mapping from the qubits of the circuit to the new qubits.
+    pub fn mapping(&self) -> &HashMap<usize, usize> {
+        &self.mapping
+    }
+}
+
+impl Pass for RemapQubitsPass {
+    fn name(&self) -> &'static str {
+        "RemapQubits"
+    }
+
+    fn run(&self, circuit: &Circuit, _device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        circuit.remap_qubits(&self.mapping)
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::devices::Device;
+use crate::operations::{
+    InvolveQubits, InvolvedQubits, OperateSingleQubit, OperateSingleQubitGate, Operation,
+    PragmaGlobalPhase, SingleQubitGate, SingleQubitGateOperation,
+};
+use crate::passes::Pass;
+use crate::{Circuit, RoqoqoError};
+use qoqo_calculator::CalculatorFloat;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Pass fusing consecutive single-qubit gates into one SingleQubitGate.
+///
+/// Every maximal run of single-qubit gates acting on the same qubit, without another operation
+/// acting on the qubit in between, is multiplied into one [SingleQubitGate]
+/// (see [OperateSingleQubitGate::mul]). Symbolic gate parameters result in symbolic
+/// parameters of the fused gate. Runs of a single gate are not changed.
+///
+/// The fused SingleQubitGate has a global phase of zero, the global phase of the run is
+/// preserved by a [PragmaGlobalPhase] directly after the fused gate (unless it is zero).
+/// Operations acting on all qubits end the runs of all qubits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SingleQubitGateFusionPass;
+
+impl SingleQubitGateFusionPass {
+    /// Creates a new SingleQubitGateFusionPass.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Pass for SingleQubitGateFusionPass {
+    fn name(&self) -> &'static str {
+        "SingleQubitGateFusion"
+    }
+
+    fn run(&self, circuit: &Circuit, _device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        let mut fusion = GateFusion {
+            slots: Vec::with_capacity(circuit.operations().len()),
+            runs: HashMap::new(),
+        };
+        for operation in circuit.operations() {
+            fusion.add_operation(operation)?;
+        }
+        let qubits: Vec<usize> = fusion.runs.keys().copied().collect();
+        for qubit in qubits {
+            fusion.flush(qubit)?;
+        }
+
+        let mut fused = Circuit::new();
+        for definition in circuit.definitions() {
+            fused.add_operation(definition.clone());
+        }
+        for operation in fusion.slots.into_iter().flatten() {
+            fused.add_operation(operation);
+        }
+        Ok(fused)
+    }
+}
+
+/// Collects the runs of single-qubit gates of a circuit.
+#[derive(Debug)]
+struct GateFusion {
+    /// The operations replacing each operation of the circuit.
+    slots: Vec<Vec<Operation>>,
+    /// The gates and slot indices of the unfinished run on each qubit.
+    runs: HashMap<usize, Vec<(usize, SingleQubitGateOperation)>>,
+}
+
+impl GateFusion {
+    /// Adds the next operation of the circuit.
+    fn add_operation(&mut self, operation: &Operation) -> Result<(), RoqoqoError> {
+        let slot = self.slots.len();
+        self.slots.push(Vec::new());
+        if let Ok(gate) = SingleQubitGateOperation::try_from(operation.clone()) {
+            self.runs
+                .entry(*gate.qubit())
+                .or_default()
+                .push((slot, gate));
+            return Ok(());
+        }
+        match operation.involved_qubits() {
+            InvolvedQubits::All => {
+                let qubits: Vec<usize> = self.runs.keys().copied().collect();
+                for qubit in qubits {
+                    self.flush(qubit)?;
+                }
+            }
+            InvolvedQubits::Set(qubits) => {
+                for qubit in qubits {
+                    self.flush(qubit)?;
+                }
+            }
+            InvolvedQubits::None => (),
+        }
+        self.slots[slot].push(operation.clone());
+        Ok(())
+    }
+
+    /// Replaces the run of gates on a qubit by the fused gate in the slot of the last gate of the run.
+    fn flush(&mut self, qubit: usize) -> Result<(), RoqoqoError> {
+        let mut run = match self.runs.remove(&qubit) {
+            Some(run) => run.into_iter(),
+            None => return Ok(()),
+        };
+        let (mut slot, first) = match run.next() {
+            Some(first) => first,
+            None => return Ok(()),
+        };
+        let mut fused: Option<SingleQubitGate> = None;
+        for (next_slot, gate) in run {
+            fused = Some(match fused {
+                None => gate.mul(&first)?,
+                Some(fused) => gate.mul(&fused)?,
+            });
+            slot = next_slot;
+        }
+        match fused {
+            None => self.slots[slot].push(first.into()),
+            Some(fused) => {
+                self.slots[slot].push(
+                    SingleQubitGate::new(
+                        qubit,
+                        fused.alpha_r(),
+                        fused.alpha_i(),
+                        fused.beta_r(),
+                        fused.beta_i(),
+                        CalculatorFloat::ZERO,
+                    )
+                    .into(),
+                );
+                if fused.global_phase() != CalculatorFloat::ZERO {
+                    self.slots[slot].push(PragmaGlobalPhase::new(fused.global_phase()).into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -12,11 +12,12 @@
 
 use std::collections::HashMap;
 
-use crate::backends::{EvaluatingBackend, RegisterResult};
+use crate::backends::{BackendObserver, EvaluatingBackend, ObservedBackend, RegisterResult};
 use crate::measurements;
 use crate::measurements::Measure;
 use crate::RoqoqoBackendError;
 use std::fmt::{Display, Formatter};
+use std::time::Instant;
 /// Represents a quantum program evaluating measurements based on a one or more free float parameters.
 ///
 /// The main use of QuantumProgram is to contain a Measurements implementing [crate::measurements::Measure]
@@ -138,6 +139,60 @@ impl QuantumProgram {
             _ => Err(RoqoqoBackendError::GenericError{msg: "A quantum programm returning expectation values cannot be executed by `run_registers` use `run` instead".to_string()})
         }
     }
+
+    /// Runs the QuantumProgram and returns expectation values, notifying an observer about the run.
+    ///
+    /// The observer is notified before and after the program and its measurement,
+    /// which is run by [EvaluatingBackend::run_measurement_registers] of the backend.
+    ///
+    /// Arguments:
+    ///
+    /// * `backend` - The backend the program is executed on.
+    /// * `parameters` - List of float ([f64]) parameters of the function call in order of `input_parameter_names`
+    /// * `observer` - The observer notified about the run.
+    pub fn run_observed<T, O>(
+        &self,
+        backend: T,
+        parameters: &[f64],
+        observer: &O,
+    ) -> Result<Option<HashMap<String, f64>>, RoqoqoBackendError>
+    where
+        T: EvaluatingBackend,
+        O: BackendObserver,
+    {
+        observer.before_program(self, parameters);
+        let start = Instant::now();
+        let result = self.run(ObservedBackend::new(backend, observer), parameters);
+        observer.after_program(self, start.elapsed(), result.as_ref().err());
+        result
+    }
+
+    /// Runs the QuantumProgram and returns the classical registers, notifying an observer about the run.
+    ///
+    /// The observer is notified before and after the program and its measurement,
+    /// which is run by [EvaluatingBackend::run_measurement_registers] of the backend.
+    ///
+    /// Arguments:
+    ///
+    /// * `backend` - The backend the program is executed on.
+    /// * `parameters` - List of float ([f64]) parameters of the function call in order of `input_parameter_names`
+    /// * `observer` - The observer notified about the run.
+    pub fn run_registers_observed<T, O>(
+        &self,
+        backend: T,
+        parameters: &[f64],
+        observer: &O,
+    ) -> RegisterResult
+    where
+        T: EvaluatingBackend,
+        O: BackendObserver,
+    {
+        observer.before_program(self, parameters);
+        let start = Instant::now();
+        let result = self.run_registers(ObservedBackend::new(backend, observer), parameters);
+        observer.after_program(self, start.elapsed(), result.as_ref().err());
+        result
+    }
 }
 
 /// Implements the Display trait for QuantumProgram.
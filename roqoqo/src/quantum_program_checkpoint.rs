@@ -0,0 +1,368 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::{EvaluatingBackend, OutputRegisters, RegisterResult, Registers};
+use crate::measurements::{Measure, MeasureExpectationValues};
+use crate::operations::Operation;
+use crate::{QuantumProgram, RoqoqoBackendError};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Progress of a measurement of a [QuantumProgram] that is stored in a checkpoint file.
+///
+/// The checkpoint contains the parameters of the run, the circuits of the measurement
+/// with substituted parameters and the output registers of the circuits that have already been executed.
+/// It is written by [QuantumProgram::run_with_checkpoint] and [QuantumProgram::run_registers_with_checkpoint]
+/// after every executed circuit and is used to resume an interrupted run with
+/// [QuantumProgram::resume] and [QuantumProgram::resume_registers].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MeasurementCheckpoint {
+    /// The parameters the quantum program is run with.
+    parameters: Vec<f64>,
+    /// The circuits of the measurement including the constant circuit.
+    circuits: Vec<Vec<Operation>>,
+    /// The output registers of the first executed circuits.
+    registers: Vec<Registers>,
+}
+
+impl MeasurementCheckpoint {
+    /// Loads a checkpoint from a checkpoint file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the checkpoint file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MeasurementCheckpoint)` - The checkpoint stored in the file.
+    /// * `Err(RoqoqoBackendError::GenericError)` - The file could not be read or does not contain a checkpoint.
+    pub fn load(path: &Path) -> Result<Self, RoqoqoBackendError> {
+        let to_error = |error: &dyn Display| RoqoqoBackendError::GenericError {
+            msg: format!(
+                "Could not read checkpoint file {}: {}",
+                path.display(),
+                error
+            ),
+        };
+        let bytes = fs::read(path).map_err(|error| to_error(&error))?;
+        bincode::deserialize(&bytes).map_err(|error| to_error(&error))
+    }
+
+    /// Returns the parameters the quantum program is run with.
+    pub fn parameters(&self) -> &[f64] {
+        &self.parameters
+    }
+
+    /// Returns the number of circuits of the measurement.
+    pub fn number_circuits(&self) -> usize {
+        self.circuits.len()
+    }
+
+    /// Returns the number of circuits whose output registers are stored in the checkpoint.
+    pub fn number_finished_circuits(&self) -> usize {
+        self.registers.len()
+    }
+
+    /// Returns true if all circuits of the measurement have been executed.
+    pub fn is_finished(&self) -> bool {
+        self.registers.len() == self.circuits.len()
+    }
+
+    /// Creates the checkpoint file, an existing file is never overwritten.
+    fn create(&self, path: &Path) -> Result<(), RoqoqoBackendError> {
+        let bytes = self.to_bytes(path)?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::AlreadyExists => RoqoqoBackendError::FileAlreadyExists {
+                    path: path.display().to_string(),
+                },
+                _ => write_error(path, &error),
+            })?;
+        file.write_all(&bytes)
+            .map_err(|error| write_error(path, &error))
+    }
+
+    /// Replaces the content of the checkpoint file.
+    fn update(&self, path: &Path) -> Result<(), RoqoqoBackendError> {
+        let bytes = self.to_bytes(path)?;
+        // Writing to a temporary file first keeps the previous checkpoint when the write is interrupted.
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(".tmp");
+        let temporary_path = PathBuf::from(temporary_path);
+        fs::write(&temporary_path, bytes).map_err(|error| write_error(path, &error))?;
+        fs::rename(&temporary_path, path).map_err(|error| write_error(path, &error))
+    }
+
+    /// Serializes the checkpoint.
+    fn to_bytes(&self, path: &Path) -> Result<Vec<u8>, RoqoqoBackendError> {
+        bincode::serialize(self).map_err(|error| write_error(path, &error))
+    }
+}
+
+/// Creates the error for a failed write of a checkpoint file.
+fn write_error(path: &Path, error: &dyn Display) -> RoqoqoBackendError {
+    RoqoqoBackendError::GenericError {
+        msg: format!(
+            "Could not write checkpoint file {}: {}",
+            path.display(),
+            error
+        ),
+    }
+}
+
+/// Start of a checkpointed run.
+#[derive(Debug, Clone, Copy)]
+enum CheckpointStart<'a> {
+    /// Starts a new run with the parameters, creating a new checkpoint file.
+    New(&'a [f64]),
+    /// Resumes the run stored in an existing checkpoint file.
+    Resume,
+}
+
+/// Runs the circuits of a measurement one by one and stores the progress in a checkpoint file.
+///
+/// Returns the measurement with substituted parameters and the combined output registers.
+fn run_checkpointed<B, M>(
+    backend: &B,
+    measurement: &M,
+    input_parameter_names: &[String],
+    start: CheckpointStart,
+    path: &Path,
+) -> Result<(M, Registers), RoqoqoBackendError>
+where
+    B: EvaluatingBackend,
+    M: Measure,
+{
+    let mut checkpoint = match start {
+        CheckpointStart::New(parameters) => MeasurementCheckpoint {
+            parameters: parameters.to_vec(),
+            circuits: Vec::new(),
+            registers: Vec::new(),
+        },
+        CheckpointStart::Resume => MeasurementCheckpoint::load(path)?,
+    };
+    if checkpoint.parameters.len() != input_parameter_names.len() {
+        return Err(RoqoqoBackendError::GenericError {
+            msg: format!(
+                "Wrong number of parameters {} parameters expected {} parameters given",
+                input_parameter_names.len(),
+                checkpoint.parameters.len()
+            ),
+        });
+    }
+    let substituted_parameters: HashMap<String, f64> = input_parameter_names
+        .iter()
+        .zip(checkpoint.parameters.iter())
+        .map(|(key, value)| (key.clone(), *value))
+        .collect();
+    let substituted_measurement = measurement.substitute_parameters(substituted_parameters)?;
+    let circuits: Vec<Vec<Operation>> = substituted_measurement
+        .circuits()
+        .map(|circuit| match substituted_measurement.constant_circuit() {
+            Some(x) => x.iter().chain(circuit.iter()).cloned().collect(),
+            None => circuit.iter().cloned().collect(),
+        })
+        .collect();
+    match start {
+        CheckpointStart::New(_) => {
+            checkpoint.circuits = circuits;
+            checkpoint.create(path)?;
+        }
+        CheckpointStart::Resume => {
+            if checkpoint.circuits != circuits {
+                return Err(RoqoqoBackendError::GenericError {
+                    msg: format!(
+                        "Checkpoint file {} was not created by this quantum program",
+                        path.display()
+                    ),
+                });
+            }
+        }
+    }
+    while checkpoint.registers.len() < checkpoint.circuits.len() {
+        let circuit = &checkpoint.circuits[checkpoint.registers.len()];
+        let registers = backend.run_circuit_iterator(circuit.iter())?;
+        checkpoint.registers.push(registers);
+        checkpoint.update(path)?;
+    }
+    let registers = OutputRegisters::from_results(checkpoint.registers.into_iter().map(Ok))?;
+    Ok((substituted_measurement, registers))
+}
+
+/// Runs a measurement with a checkpoint file and evaluates the expectation values.
+fn evaluate_checkpointed<B, M>(
+    backend: &B,
+    measurement: &M,
+    input_parameter_names: &[String],
+    start: CheckpointStart,
+    path: &Path,
+) -> Result<Option<HashMap<String, f64>>, RoqoqoBackendError>
+where
+    B: EvaluatingBackend,
+    M: MeasureExpectationValues,
+{
+    let (substituted_measurement, (bit_registers, float_registers, complex_registers)) =
+        run_checkpointed(backend, measurement, input_parameter_names, start, path)?;
+    Ok(substituted_measurement.evaluate(bit_registers, float_registers, complex_registers)?)
+}
+
+impl QuantumProgram {
+    /// Runs the QuantumProgram, storing the progress in a checkpoint file, and returns expectation values.
+    ///
+    /// The circuits of the measurement are executed one by one and the output registers of every
+    /// executed circuit are stored in the checkpoint file (see [MeasurementCheckpoint]).
+    /// When the run is interrupted, it can be continued with [QuantumProgram::resume].
+    /// The checkpoint file is kept after the run has finished.
+    ///
+    /// Arguments:
+    ///
+    /// * `backend` - The backend the program is executed on.
+    /// * `parameters` - List of float ([f64]) parameters of the function call in order of `input_parameter_names`
+    /// * `checkpoint_file` - The path of the checkpoint file that is created, an existing file is never overwritten.
+    ///
+    /// Returns:
+    ///
+    /// * `Ok(Option<HashMap<String, f64>>)` - The evaluated expectation values.
+    /// * `Err(RoqoqoBackendError::FileAlreadyExists)` - The checkpoint file already exists.
+    /// * `Err(RoqoqoBackendError)` - Running the program failed, the finished circuits are kept in the checkpoint file.
+    pub fn run_with_checkpoint<T>(
+        &self,
+        backend: T,
+        parameters: &[f64],
+        checkpoint_file: &Path,
+    ) -> Result<Option<HashMap<String, f64>>, RoqoqoBackendError>
+    where
+        T: EvaluatingBackend,
+    {
+        self.evaluate_with_checkpoint(&backend, CheckpointStart::New(parameters), checkpoint_file)
+    }
+
+    /// Resumes a run of the QuantumProgram from a checkpoint file and returns expectation values.
+    ///
+    /// The parameters of the run and the output registers of the finished circuits are loaded from the
+    /// checkpoint file written by [QuantumProgram::run_with_checkpoint]. Only the remaining circuits are
+    /// executed and the checkpoint file is updated after every executed circuit.
+    ///
+    /// Arguments:
+    ///
+    /// * `backend` - The backend the program is executed on.
+    /// * `checkpoint_file` - The path of the checkpoint file.
+    ///
+    /// Returns:
+    ///
+    /// * `Ok(Option<HashMap<String, f64>>)` - The evaluated expectation values.
+    /// * `Err(RoqoqoBackendError)` - The checkpoint file could not be read or was created by another program, or running the program failed.
+    pub fn resume<T>(
+        &self,
+        backend: T,
+        checkpoint_file: &Path,
+    ) -> Result<Option<HashMap<String, f64>>, RoqoqoBackendError>
+    where
+        T: EvaluatingBackend,
+    {
+        self.evaluate_with_checkpoint(&backend, CheckpointStart::Resume, checkpoint_file)
+    }
+
+    /// Runs the QuantumProgram, storing the progress in a checkpoint file, and returns the classical registers.
+    ///
+    /// Variant of [QuantumProgram::run_with_checkpoint] for quantum programs returning classical registers.
+    /// When the run is interrupted, it can be continued with [QuantumProgram::resume_registers].
+    ///
+    /// Arguments:
+    ///
+    /// * `backend` - The backend the program is executed on.
+    /// * `parameters` - List of float ([f64]) parameters of the function call in order of `input_parameter_names`
+    /// * `checkpoint_file` - The path of the checkpoint file that is created, an existing file is never overwritten.
+    pub fn run_registers_with_checkpoint<T>(
+        &self,
+        backend: T,
+        parameters: &[f64],
+        checkpoint_file: &Path,
+    ) -> RegisterResult
+    where
+        T: EvaluatingBackend,
+    {
+        self.registers_with_checkpoint(&backend, CheckpointStart::New(parameters), checkpoint_file)
+    }
+
+    /// Resumes a run of the QuantumProgram from a checkpoint file and returns the classical registers.
+    ///
+    /// Variant of [QuantumProgram::resume] for quantum programs returning classical registers.
+    ///
+    /// Arguments:
+    ///
+    /// * `backend` - The backend the program is executed on.
+    /// * `checkpoint_file` - The path of the checkpoint file.
+    pub fn resume_registers<T>(&self, backend: T, checkpoint_file: &Path) -> RegisterResult
+    where
+        T: EvaluatingBackend,
+    {
+        self.registers_with_checkpoint(&backend, CheckpointStart::Resume, checkpoint_file)
+    }
+
+    /// Runs or resumes a checkpointed QuantumProgram returning expectation values.
+    fn evaluate_with_checkpoint<T>(
+        &self,
+        backend: &T,
+        start: CheckpointStart,
+        path: &Path,
+    ) -> Result<Option<HashMap<String, f64>>, RoqoqoBackendError>
+    where
+        T: EvaluatingBackend,
+    {
+        match self {
+            QuantumProgram::BasisRotation {
+                measurement,
+                input_parameter_names,
+            } => evaluate_checkpointed(backend, measurement, input_parameter_names, start, path),
+            QuantumProgram::CheatedBasisRotation {
+                measurement,
+                input_parameter_names,
+            } => evaluate_checkpointed(backend, measurement, input_parameter_names, start, path),
+            QuantumProgram::Cheated {
+                measurement,
+                input_parameter_names,
+            } => evaluate_checkpointed(backend, measurement, input_parameter_names, start, path),
+            _ => Err(RoqoqoBackendError::GenericError {
+                msg: "A quantum programm returning classical registeres cannot be executed by `run_with_checkpoint` use `run_registers_with_checkpoint` instead".to_string(),
+            }),
+        }
+    }
+
+    /// Runs or resumes a checkpointed QuantumProgram returning classical registers.
+    fn registers_with_checkpoint<T>(
+        &self,
+        backend: &T,
+        start: CheckpointStart,
+        path: &Path,
+    ) -> RegisterResult
+    where
+        T: EvaluatingBackend,
+    {
+        match self {
+            QuantumProgram::ClassicalRegister {
+                measurement,
+                input_parameter_names,
+            } => run_checkpointed(backend, measurement, input_parameter_names, start, path)
+                .map(|(_, registers)| registers),
+            _ => Err(RoqoqoBackendError::GenericError {
+                msg: "A quantum programm returning expectation values cannot be executed by `run_registers_with_checkpoint` use `run_with_checkpoint` instead".to_string(),
+            }),
+        }
+    }
+}
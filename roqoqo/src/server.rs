@@ -0,0 +1,724 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Simulation server exposing a local [EvaluatingBackend] over HTTP.
+//!
+//! The [SimulationServer] accepts serialized circuits, measurements and quantum programs,
+//! queues them as jobs and runs them on a local backend, for example one of the simulators of
+//! [crate::backends]. Requests and results use JSON and the serde formats of the roqoqo types:
+//!
+//! * `POST /jobs` - Submits a [Circuit] (compatible with the `HttpBackend` of the `http` feature).
+//! * `POST /measurements` - Submits a [MeasurementJob].
+//! * `POST /programs` - Submits a [ProgramJob].
+//! * `GET /jobs/<id>` - Returns the [JobStatus] of a job.
+//! * `GET /jobs/<id>/result` - Returns the [JobOutput] of a completed job.
+//! * `DELETE /jobs/<id>` - Cancels an unfinished job or removes a finished job from the server.
+//!
+//! Submitting a job returns `{"job_id": "<id>"}`. Jobs are run in the order of submission.
+//! Request bodies larger than the maximum body size are refused with status 413, jobs submitted
+//! while the maximum number of jobs is queued are refused with status 503, and only the
+//! most recently finished jobs are kept on the server.
+//! The `roqoqo-server` binary of the `server` feature starts a server with one of the roqoqo simulators.
+
+use crate::backends::{EvaluatingBackend, JobStatus};
+use crate::measurements::{BasisRotation, Cheated, CheatedBasisRotation, ClassicalRegister};
+use crate::registers::{BitOutputRegister, ComplexOutputRegister, FloatOutputRegister};
+use crate::{Circuit, QuantumProgram, RoqoqoBackendError};
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Maximum time to wait for a client to send a request or receive a response.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum number of connections handled at the same time, further connections are closed.
+const MAX_CONNECTIONS: usize = 64;
+
+/// Maximum size in bytes of the request line and headers of a request.
+const MAX_HEADER_SIZE: u64 = 64 * 1024;
+
+/// Maximum number of bytes of a refused request body that are discarded before closing the connection.
+///
+/// Closing a connection with unread data resets it, which can hide the response from the client.
+const MAX_DISCARDED_BODY_SIZE: u64 = 64 * 1024;
+
+/// Default maximum size in bytes of the body of a request.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// Default maximum number of finished jobs kept on the server.
+pub const DEFAULT_MAX_FINISHED_JOBS: usize = 1000;
+
+/// Default maximum number of jobs waiting in the queue.
+pub const DEFAULT_MAX_QUEUED_JOBS: usize = 100;
+
+/// Measurement submitted to the [SimulationServer].
+///
+/// Serialized with the name of the measurement type as key, for example `{"ClassicalRegister": {...}}`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum MeasurementJob {
+    /// Measurement of expectation values by rotating qubits into the Pauli basis.
+    BasisRotation(BasisRotation),
+    /// Measurement of expectation values from the cheated statevector readout.
+    CheatedBasisRotation(CheatedBasisRotation),
+    /// Measurement of expectation values from operators and the cheated statevector or density matrix.
+    Cheated(Cheated),
+    /// Measurement returning the classical registers.
+    ClassicalRegister(ClassicalRegister),
+}
+
+/// Quantum program and the values of its free parameters submitted to the [SimulationServer].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProgramJob {
+    /// The quantum program that is run.
+    pub program: QuantumProgram,
+    /// The parameters of the program in the order of its `input_parameter_names`.
+    pub parameters: Vec<f64>,
+}
+
+/// Output of a job completed by the [SimulationServer].
+///
+/// Circuits, [ClassicalRegister] measurements and programs return the output registers,
+/// all other measurements and programs return expectation values.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum JobOutput {
+    /// Output registers of the circuits of the job.
+    Registers {
+        /// The bit output registers.
+        bit_registers: HashMap<String, BitOutputRegister>,
+        /// The float output registers.
+        float_registers: HashMap<String, FloatOutputRegister>,
+        /// The complex output registers.
+        complex_registers: HashMap<String, ComplexOutputRegister>,
+    },
+    /// Expectation values evaluated from the output registers.
+    ExpectationValues {
+        /// The expectation values by name.
+        expectation_values: HashMap<String, f64>,
+    },
+}
+
+/// Job queued by a [SimulationServer].
+#[derive(Debug, Clone, PartialEq)]
+enum SimulationJob {
+    Circuit(Circuit),
+    Measurement(MeasurementJob),
+    Program(ProgramJob),
+}
+
+/// Server queueing jobs submitted over HTTP and running them on a local [EvaluatingBackend].
+///
+/// Jobs are run in the order of submission by a configurable number of worker threads,
+/// each with its own clone of the backend. A job panicking in the backend fails without stopping its worker.
+/// At most a maximum number of jobs is queued, further jobs are refused until the workers catch up.
+/// Finished jobs stay on the server until they are deleted
+/// or, when more than the maximum number of finished jobs are stored, until they are the oldest finished job.
+/// Every connection is handled on its own thread, so that slow clients do not block other clients.
+/// When an access token is set, requests need to provide it as a bearer token in the
+/// `Authorization` header, requests without the token are refused before their body is read.
+/// The server is stopped when it is dropped.
+#[derive(Debug)]
+pub struct SimulationServer {
+    /// The address the server is listening on.
+    address: SocketAddr,
+    /// Jobs and configuration shared with the server threads.
+    shared: Arc<Shared>,
+    /// Threads accepting connections and running jobs.
+    threads: Vec<JoinHandle<()>>,
+}
+
+/// State of a [SimulationServer] shared between its threads.
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<ServerState>,
+    /// Notifies the workers about queued jobs and stopping the server.
+    job_queued: Condvar,
+    /// Number of connections that are currently handled.
+    connections: AtomicUsize,
+}
+
+/// Jobs and configuration of a [SimulationServer].
+#[derive(Debug, Default)]
+struct ServerState {
+    running: bool,
+    access_token: Option<String>,
+    max_body_size: usize,
+    max_finished_jobs: usize,
+    max_queued_jobs: usize,
+    next_id: u64,
+    queue: VecDeque<u64>,
+    /// IDs of the finished jobs in the order they finished, used to evict the oldest ones.
+    finished: VecDeque<u64>,
+    jobs: HashMap<u64, Job>,
+}
+
+impl ServerState {
+    /// Records that a job has finished and evicts the oldest finished jobs beyond the maximum.
+    fn finish(&mut self, id: u64) {
+        self.finished.push_back(id);
+        self.evict_finished_jobs();
+    }
+
+    /// Removes the oldest finished jobs until at most the maximum number of finished jobs is left.
+    fn evict_finished_jobs(&mut self) {
+        while self.finished.len() > self.max_finished_jobs {
+            if let Some(id) = self.finished.pop_front() {
+                self.jobs.remove(&id);
+            }
+        }
+    }
+}
+
+/// Job of a [SimulationServer] with its status and output.
+#[derive(Debug)]
+struct Job {
+    /// The job waiting to be run, taken by the worker running it.
+    job: Option<SimulationJob>,
+    status: JobStatus,
+    output: Option<JobOutput>,
+}
+
+/// Request received by a [SimulationServer].
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    content_length: usize,
+    body: Vec<u8>,
+}
+
+impl SimulationServer {
+    /// Starts a new SimulationServer.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend running the jobs, cloned for every worker.
+    /// * `address` - The address the server listens on, for example `127.0.0.1:8080` (port 0 selects a free port).
+    /// * `number_workers` - The number of jobs that are run at the same time (at least one).
+    /// * `access_token` - The bearer token required from clients (None to accept unauthenticated requests).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SimulationServer)` - The running server.
+    /// * `Err(io::Error)` - The server could not listen on the address.
+    pub fn start<B, A>(
+        backend: B,
+        address: A,
+        number_workers: usize,
+        access_token: Option<String>,
+    ) -> io::Result<Self>
+    where
+        B: EvaluatingBackend + Clone + Send + 'static,
+        A: ToSocketAddrs,
+    {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Shared::default());
+        {
+            let mut state = lock(&shared.state);
+            state.running = true;
+            state.access_token = access_token;
+            state.max_body_size = DEFAULT_MAX_BODY_SIZE;
+            state.max_finished_jobs = DEFAULT_MAX_FINISHED_JOBS;
+            state.max_queued_jobs = DEFAULT_MAX_QUEUED_JOBS;
+        }
+        let mut threads = Vec::new();
+        for _ in 0..number_workers.max(1) {
+            let backend = backend.clone();
+            let shared = shared.clone();
+            threads.push(thread::spawn(move || run_worker(backend, &shared)));
+        }
+        let listener_shared = shared.clone();
+        threads.push(thread::spawn(move || {
+            for stream in listener.incoming() {
+                if !lock(&listener_shared.state).running {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    spawn_connection(stream, &listener_shared);
+                }
+            }
+        }));
+        Ok(Self {
+            address,
+            shared,
+            threads,
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the URL of the server.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Sets the bearer token that requests need to provide.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_token` - The required token (None to accept unauthenticated requests).
+    pub fn set_access_token(&self, access_token: Option<String>) {
+        lock(&self.shared.state).access_token = access_token;
+    }
+
+    /// Sets the maximum size of request bodies, larger requests are refused with status 413.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_body_size` - The maximum size in bytes (default [DEFAULT_MAX_BODY_SIZE]).
+    pub fn set_max_body_size(&self, max_body_size: usize) {
+        lock(&self.shared.state).max_body_size = max_body_size;
+    }
+
+    /// Sets the maximum number of finished jobs kept on the server.
+    ///
+    /// When a job finishes and more finished jobs are stored, the job that finished first is removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_finished_jobs` - The maximum number of finished jobs (default [DEFAULT_MAX_FINISHED_JOBS]).
+    pub fn set_max_finished_jobs(&self, max_finished_jobs: usize) {
+        let mut state = lock(&self.shared.state);
+        state.max_finished_jobs = max_finished_jobs;
+        state.evict_finished_jobs();
+    }
+
+    /// Sets the maximum number of jobs waiting in the queue.
+    ///
+    /// Jobs submitted while the queue is full are refused with status 503.
+    /// Jobs that are already queued stay in the queue when the maximum is lowered.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_queued_jobs` - The maximum number of queued jobs (default [DEFAULT_MAX_QUEUED_JOBS]).
+    pub fn set_max_queued_jobs(&self, max_queued_jobs: usize) {
+        lock(&self.shared.state).max_queued_jobs = max_queued_jobs;
+    }
+
+    /// Returns the number of jobs waiting in the queue.
+    pub fn number_queued_jobs(&self) -> usize {
+        lock(&self.shared.state).queue.len()
+    }
+
+    /// Returns the number of jobs stored on the server, including finished jobs.
+    pub fn number_jobs(&self) -> usize {
+        lock(&self.shared.state).jobs.len()
+    }
+
+    /// Blocks the current thread while the server is running.
+    pub fn wait(mut self) {
+        if let Some(listener) = self.threads.pop() {
+            let _ = listener.join();
+        }
+    }
+}
+
+impl Drop for SimulationServer {
+    fn drop(&mut self) {
+        lock(&self.shared.state).running = false;
+        self.shared.job_queued.notify_all();
+        // Wakes up the thread waiting for the next connection.
+        let _ = TcpStream::connect(self.address);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Locks the shared state, a panic in another thread does not invalidate the jobs.
+fn lock(state: &Mutex<ServerState>) -> MutexGuard<'_, ServerState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Runs queued jobs until the server is stopped.
+fn run_worker<B>(backend: B, shared: &Shared)
+where
+    B: EvaluatingBackend + Clone,
+{
+    loop {
+        let (id, job) = {
+            let mut state = lock(&shared.state);
+            let id = loop {
+                if !state.running {
+                    return;
+                }
+                if let Some(id) = state.queue.pop_front() {
+                    break id;
+                }
+                state = shared
+                    .job_queued
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            };
+            match state.jobs.get_mut(&id) {
+                Some(job) => {
+                    job.status = JobStatus::Running;
+                    (id, job.job.take())
+                }
+                None => continue,
+            }
+        };
+        let result = match job {
+            // A panicking backend only fails the job, the worker keeps running.
+            Some(job) => panic::catch_unwind(AssertUnwindSafe(|| run_job(&backend, job)))
+                .unwrap_or_else(|payload| {
+                    Err(RoqoqoBackendError::GenericError {
+                        msg: format!("Job panicked: {}", panic_message(payload.as_ref())),
+                    })
+                }),
+            None => continue,
+        };
+        let mut state = lock(&shared.state);
+        if let Some(job) = state.jobs.get_mut(&id) {
+            // Jobs cancelled while running keep their status.
+            if job.status == JobStatus::Running {
+                match result {
+                    Ok(output) => {
+                        job.status = JobStatus::Completed;
+                        job.output = Some(output);
+                    }
+                    Err(error) => {
+                        job.status = JobStatus::Failed {
+                            msg: error.to_string(),
+                        }
+                    }
+                }
+                state.finish(id);
+            }
+        }
+    }
+}
+
+/// Returns the message of a panic.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Runs a job on the backend.
+fn run_job<B>(backend: &B, job: SimulationJob) -> Result<JobOutput, RoqoqoBackendError>
+where
+    B: EvaluatingBackend + Clone,
+{
+    match job {
+        SimulationJob::Circuit(circuit) => backend.run_circuit(&circuit).map(JobOutput::from),
+        SimulationJob::Measurement(measurement) => match measurement {
+            MeasurementJob::BasisRotation(measurement) => {
+                expectation_values(backend.run_measurement(&measurement)?)
+            }
+            MeasurementJob::CheatedBasisRotation(measurement) => {
+                expectation_values(backend.run_measurement(&measurement)?)
+            }
+            MeasurementJob::Cheated(measurement) => {
+                expectation_values(backend.run_measurement(&measurement)?)
+            }
+            MeasurementJob::ClassicalRegister(measurement) => backend
+                .run_measurement_registers(&measurement)
+                .map(JobOutput::from),
+        },
+        SimulationJob::Program(ProgramJob {
+            program,
+            parameters,
+        }) => match program {
+            QuantumProgram::ClassicalRegister { .. } => program
+                .run_registers(backend.clone(), &parameters)
+                .map(JobOutput::from),
+            _ => expectation_values(program.run(backend.clone(), &parameters)?),
+        },
+    }
+}
+
+/// Returns the output of a measurement evaluating expectation values.
+fn expectation_values(
+    expectation_values: Option<HashMap<String, f64>>,
+) -> Result<JobOutput, RoqoqoBackendError> {
+    expectation_values
+        .map(|expectation_values| JobOutput::ExpectationValues { expectation_values })
+        .ok_or_else(|| RoqoqoBackendError::GenericError {
+            msg: "Measurement did not return expectation values".to_string(),
+        })
+}
+
+impl
+    From<(
+        HashMap<String, BitOutputRegister>,
+        HashMap<String, FloatOutputRegister>,
+        HashMap<String, ComplexOutputRegister>,
+    )> for JobOutput
+{
+    fn from(
+        (bit_registers, float_registers, complex_registers): (
+            HashMap<String, BitOutputRegister>,
+            HashMap<String, FloatOutputRegister>,
+            HashMap<String, ComplexOutputRegister>,
+        ),
+    ) -> Self {
+        JobOutput::Registers {
+            bit_registers,
+            float_registers,
+            complex_registers,
+        }
+    }
+}
+
+/// Handles a connection on a new thread, closing it when too many connections are handled.
+fn spawn_connection(stream: TcpStream, shared: &Arc<Shared>) {
+    if shared.connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+        shared.connections.fetch_sub(1, Ordering::SeqCst);
+        return;
+    }
+    let connection_shared = shared.clone();
+    let spawned = thread::Builder::new().spawn(move || {
+        // A failed connection only affects the client that opened it.
+        let _ = handle_connection(stream, &connection_shared);
+        connection_shared.connections.fetch_sub(1, Ordering::SeqCst);
+    });
+    if spawned.is_err() {
+        shared.connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Reads a request from a connection, handles it and writes the response.
+///
+/// The body of a request is only read when the request is authorized and not too large.
+fn handle_connection(mut stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = read_head(&mut reader)?;
+    let refusal = refuse(&request, shared);
+    let refused = refusal.is_some();
+    let (status, body) = match refusal {
+        Some(refusal) => refusal,
+        None => {
+            request.body = vec![0; request.content_length];
+            reader.read_exact(&mut request.body)?;
+            respond(&request, shared)
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    if refused {
+        stream.shutdown(Shutdown::Write)?;
+        io::copy(&mut reader.take(MAX_DISCARDED_BODY_SIZE), &mut io::sink())?;
+    }
+    Ok(())
+}
+
+/// Reads the request line and the relevant headers of a request, the body is left unread.
+fn read_head(reader: &mut BufReader<TcpStream>) -> io::Result<Request> {
+    let mut reader = reader.take(MAX_HEADER_SIZE);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let mut authorization = None;
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_lowercase().as_str() {
+                "authorization" => authorization = Some(value.trim().to_string()),
+                "content-length" => content_length = value.trim().parse().unwrap_or_default(),
+                _ => (),
+            }
+        }
+    }
+    Ok(Request {
+        method,
+        path,
+        authorization,
+        content_length,
+        body: Vec::new(),
+    })
+}
+
+/// Returns the response refusing a request without the access token, with a too large body
+/// or submitting a job while the queue is full.
+fn refuse(request: &Request, shared: &Shared) -> Option<(u16, String)> {
+    let state = lock(&shared.state);
+    if let Some(token) = state.access_token.as_ref() {
+        if request.authorization.as_deref() != Some(&format!("Bearer {}", token)) {
+            return Some((401, json_string("Missing or invalid access token")));
+        }
+    }
+    if request.content_length > state.max_body_size {
+        return Some((
+            413,
+            json_string(&format!(
+                "Request body of {} bytes exceeds the maximum of {} bytes",
+                request.content_length, state.max_body_size
+            )),
+        ));
+    }
+    if request.method == "POST" {
+        return queue_full(&state);
+    }
+    None
+}
+
+/// Returns the response refusing a new job when the maximum number of jobs is queued.
+fn queue_full(state: &ServerState) -> Option<(u16, String)> {
+    (state.queue.len() >= state.max_queued_jobs).then(|| {
+        (
+            503,
+            json_string(&format!(
+                "Job queue is full with {} jobs, retry later",
+                state.queue.len()
+            )),
+        )
+    })
+}
+
+/// Handles an authorized request and returns the status code and the JSON body of the response.
+fn respond(request: &Request, shared: &Shared) -> (u16, String) {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["jobs"]) => submit(shared, &request.body, SimulationJob::Circuit),
+        ("POST", ["measurements"]) => submit(shared, &request.body, SimulationJob::Measurement),
+        ("POST", ["programs"]) => submit(shared, &request.body, SimulationJob::Program),
+        ("GET", ["jobs", id]) => with_job(shared, id, |job| to_json(&job.status)),
+        ("GET", ["jobs", id, "result"]) => with_job(shared, id, |job| match &job.output {
+            Some(output) => to_json(output),
+            None => (
+                409,
+                json_string(&format!("Job has no results, status {:?}", job.status)),
+            ),
+        }),
+        ("DELETE", ["jobs", id]) => {
+            let mut state = lock(&shared.state);
+            let id = match id.parse::<u64>() {
+                Ok(id) if state.jobs.contains_key(&id) => id,
+                _ => return (404, json_string("Unknown job")),
+            };
+            let finished = state.jobs[&id].status.is_finished();
+            if finished {
+                state.jobs.remove(&id);
+                state.finished.retain(|finished| *finished != id);
+            } else {
+                state.queue.retain(|queued| *queued != id);
+                if let Some(job) = state.jobs.get_mut(&id) {
+                    job.status = JobStatus::Cancelled;
+                    job.job = None;
+                }
+                state.finish(id);
+            }
+            (200, "null".to_string())
+        }
+        _ => (404, json_string("Unknown route")),
+    }
+}
+
+/// Deserializes a job from the body of a request and queues it.
+fn submit<T>(shared: &Shared, body: &[u8], job: fn(T) -> SimulationJob) -> (u16, String)
+where
+    T: serde::de::DeserializeOwned,
+{
+    let job = match serde_json::from_slice(body) {
+        Ok(deserialized) => job(deserialized),
+        Err(error) => return (400, json_string(&format!("Invalid job: {}", error))),
+    };
+    let id = {
+        let mut state = lock(&shared.state);
+        // Checked again, other connections may have queued jobs while the body was read.
+        if let Some(refusal) = queue_full(&state) {
+            return refusal;
+        }
+        let id = state.next_id;
+        state.next_id += 1;
+        state.jobs.insert(
+            id,
+            Job {
+                job: Some(job),
+                status: JobStatus::Queued,
+                output: None,
+            },
+        );
+        state.queue.push_back(id);
+        id
+    };
+    shared.job_queued.notify_one();
+    (
+        200,
+        serde_json::json!({ "job_id": id.to_string() }).to_string(),
+    )
+}
+
+/// Applies a function to the job with an ID from a request path.
+fn with_job(shared: &Shared, id: &str, f: impl FnOnce(&Job) -> (u16, String)) -> (u16, String) {
+    let state = lock(&shared.state);
+    match id.parse::<u64>().ok().and_then(|id| state.jobs.get(&id)) {
+        Some(job) => f(job),
+        None => (404, json_string("Unknown job")),
+    }
+}
+
+/// Serializes a value as the body of a successful response.
+fn to_json<T>(value: &T) -> (u16, String)
+where
+    T: serde::Serialize,
+{
+    match serde_json::to_string(value) {
+        Ok(body) => (200, body),
+        Err(error) => (
+            500,
+            json_string(&format!("Serialization failed: {}", error)),
+        ),
+    }
+}
+
+/// Serializes a message as a JSON string.
+fn json_string(msg: &str) -> String {
+    serde_json::Value::String(msg.to_string()).to_string()
+}
+
+/// Returns the reason phrase of the status codes used by the server.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
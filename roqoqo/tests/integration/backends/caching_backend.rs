@@ -0,0 +1,159 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    CachingBackend, DensityMatrixSimulator, EvaluatingBackend, StatevectorSimulator,
+};
+use roqoqo::measurements::ClassicalRegister;
+use roqoqo::operations::*;
+use roqoqo::Circuit;
+
+/// Returns a measurement whose circuits all start from the same entangled constant circuit
+fn measurement(constant_circuit: Circuit) -> ClassicalRegister {
+    let mut circuits = Vec::new();
+    for (index, angle) in [0.0, 0.4, 1.3].iter().enumerate() {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new(format!("ro_{}", index), 3, true);
+        circuit += DefinitionComplex::new(format!("state_{}", index), 8, true);
+        circuit += RotateX::new(2, (*angle).into());
+        circuit += PragmaGetStateVector::new(format!("state_{}", index), None);
+        circuit += PragmaRepeatedMeasurement::new(format!("ro_{}", index), 50, None);
+        circuits.push(circuit);
+    }
+    ClassicalRegister {
+        constant_circuit: Some(constant_circuit),
+        circuits,
+    }
+}
+
+fn deterministic_constant_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("unused".to_string(), 1, false);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += RotateY::new(2, 0.7.into());
+    circuit
+}
+
+fn measuring_constant_circuit() -> Circuit {
+    let mut circuit = deterministic_constant_circuit();
+    circuit += DefinitionBit::new("constant_ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "constant_ro".to_string(), 0);
+    circuit
+}
+
+/// Test that the StatevectorSimulator returns the same registers with and without caching
+#[test]
+fn test_statevector_cached_equals_uncached() {
+    let mut backend = StatevectorSimulator::new(3);
+    backend.set_random_seed(Some(11));
+    let measurement = measurement(deterministic_constant_circuit());
+    let cached_state = backend
+        .prepare_constant_circuit(measurement.constant_circuit.as_ref())
+        .unwrap();
+    assert!(cached_state.is_prepared());
+    assert_eq!(
+        backend.run_measurement_registers_cached(&measurement),
+        backend.run_measurement_registers(&measurement)
+    );
+}
+
+/// Test that the DensityMatrixSimulator returns the same registers with and without caching
+#[test]
+fn test_density_matrix_cached_equals_uncached() {
+    let mut backend = DensityMatrixSimulator::new(3);
+    backend.set_random_seed(Some(5));
+    let mut constant_circuit = deterministic_constant_circuit();
+    constant_circuit += PragmaDamping::new(1, 0.1.into(), 0.2.into());
+    let mut measurement = measurement(constant_circuit);
+    for circuit in measurement.circuits.iter_mut() {
+        *circuit = circuit
+            .iter()
+            .filter(|operation| !matches!(operation, Operation::PragmaGetStateVector(_)))
+            .cloned()
+            .collect();
+    }
+    assert_eq!(
+        backend.run_measurement_registers_cached(&measurement),
+        backend.run_measurement_registers(&measurement)
+    );
+}
+
+/// Test that constant circuits with measurements are executed again for every circuit
+#[test]
+fn test_cached_fallback_with_measurement() {
+    let mut backend = StatevectorSimulator::new(3);
+    backend.set_random_seed(Some(2));
+    let measurement = measurement(measuring_constant_circuit());
+    let cached_state = backend
+        .prepare_constant_circuit(measurement.constant_circuit.as_ref())
+        .unwrap();
+    assert!(!cached_state.is_prepared());
+    let (bit_registers, _, _) = backend
+        .run_measurement_registers_cached(&measurement)
+        .unwrap();
+    assert_eq!(bit_registers["constant_ro"].len(), 3);
+    assert_eq!(
+        backend.run_measurement_registers_cached(&measurement),
+        backend.run_measurement_registers(&measurement)
+    );
+}
+
+/// Test that errors in the constant circuit are returned before any circuit is run
+#[test]
+fn test_cached_error_in_constant_circuit() {
+    let backend = StatevectorSimulator::new(2);
+    let measurement = measurement(deterministic_constant_circuit());
+    assert!(backend
+        .prepare_constant_circuit(measurement.constant_circuit.as_ref())
+        .is_err());
+    assert!(backend
+        .run_measurement_registers_cached(&measurement)
+        .is_err());
+}
+
+/// Test that the parallel variants merge the registers in circuit order
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_equals_sequential() {
+    let mut backend = StatevectorSimulator::new(3);
+    backend.set_random_seed(Some(7));
+    for constant_circuit in [
+        deterministic_constant_circuit(),
+        measuring_constant_circuit(),
+    ] {
+        let measurement = measurement(constant_circuit);
+        let sequential = backend.run_measurement_registers(&measurement);
+        assert_eq!(
+            backend.run_measurement_registers_parallel(&measurement),
+            sequential
+        );
+        assert_eq!(
+            backend.run_measurement_registers_cached_parallel(&measurement),
+            sequential
+        );
+    }
+}
+
+/// Test that the parallel variant returns errors
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_error() {
+    let backend = StatevectorSimulator::new(2);
+    let measurement = measurement(deterministic_constant_circuit());
+    assert!(backend
+        .run_measurement_registers_parallel(&measurement)
+        .is_err());
+    assert!(backend
+        .run_measurement_registers_cached_parallel(&measurement)
+        .is_err());
+}
@@ -0,0 +1,89 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ndarray::Array2;
+use roqoqo::devices::Device;
+use std::collections::HashMap;
+
+/// Linear chain device used by the backend tests.
+///
+/// Two-qubit gates are available between neighbouring qubits in both directions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainDevice {
+    pub number_qubits: usize,
+    pub single_qubit_gates: HashMap<String, f64>,
+    pub two_qubit_gates: HashMap<String, f64>,
+    pub multi_qubit_gates: HashMap<String, f64>,
+    pub rates: Array2<f64>,
+}
+
+impl ChainDevice {
+    /// Creates a chain with RotateX, RotateZ, PauliX and CNOT gates and no decoherence.
+    pub fn new(number_qubits: usize) -> Self {
+        let single_qubit_gates = [("RotateX", 1.0), ("RotateZ", 0.5), ("PauliX", 1.0)]
+            .iter()
+            .map(|(name, time)| (name.to_string(), *time))
+            .collect();
+        let two_qubit_gates = [("CNOT".to_string(), 2.0)].into_iter().collect();
+        ChainDevice {
+            number_qubits,
+            single_qubit_gates,
+            two_qubit_gates,
+            multi_qubit_gates: HashMap::new(),
+            rates: Array2::zeros((3, 3)),
+        }
+    }
+
+    fn contains(&self, qubit: &usize) -> bool {
+        *qubit < self.number_qubits
+    }
+}
+
+impl Device for ChainDevice {
+    fn single_qubit_gate_time(&self, hqslang: &str, qubit: &usize) -> Option<f64> {
+        if !self.contains(qubit) {
+            return None;
+        }
+        self.single_qubit_gates.get(hqslang).copied()
+    }
+
+    fn two_qubit_gate_time(&self, hqslang: &str, control: &usize, target: &usize) -> Option<f64> {
+        if !self.contains(control)
+            || !self.contains(target)
+            || (*control + 1 != *target && *target + 1 != *control)
+        {
+            return None;
+        }
+        self.two_qubit_gates.get(hqslang).copied()
+    }
+
+    fn multi_qubit_gate_time(&self, hqslang: &str, qubits: &[usize]) -> Option<f64> {
+        if !qubits.iter().all(|qubit| self.contains(qubit)) {
+            return None;
+        }
+        self.multi_qubit_gates.get(hqslang).copied()
+    }
+
+    fn qubit_decoherence_rates(&self, qubit: &usize) -> Option<Array2<f64>> {
+        self.contains(qubit).then(|| self.rates.clone())
+    }
+
+    fn number_qubits(&self) -> usize {
+        self.number_qubits
+    }
+
+    fn two_qubit_edges(&self) -> Vec<(usize, usize)> {
+        (1..self.number_qubits)
+            .map(|qubit| (qubit - 1, qubit))
+            .collect()
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ndarray::array;
+use num_complex::Complex64;
+use roqoqo::backends::{DensityMatrixSimulator, EvaluatingBackend, StatevectorSimulator};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use std::collections::HashMap;
+use test_case::test_case;
+
+fn occupation_probabilities(circuit: &Circuit, number_qubits: usize) -> Vec<f64> {
+    let mut full_circuit = Circuit::new();
+    full_circuit += DefinitionFloat::new("probabilities".to_string(), 1 << number_qubits, true);
+    full_circuit += circuit.clone();
+    full_circuit += PragmaGetOccupationProbability::new("probabilities".to_string(), None);
+    let backend = DensityMatrixSimulator::new(number_qubits);
+    let (_, float_registers, _) = backend.run_circuit(&full_circuit).unwrap();
+    float_registers["probabilities"][0].clone()
+}
+
+fn density_matrix(circuit: &Circuit, number_qubits: usize) -> Vec<Complex64> {
+    let mut full_circuit = Circuit::new();
+    full_circuit += DefinitionComplex::new("rho".to_string(), 1 << (2 * number_qubits), true);
+    full_circuit += circuit.clone();
+    full_circuit += PragmaGetDensityMatrix::new("rho".to_string(), None);
+    let backend = DensityMatrixSimulator::new(number_qubits);
+    let (_, _, complex_registers) = backend.run_circuit(&full_circuit).unwrap();
+    complex_registers["rho"][0].clone()
+}
+
+/// Test that the getters and setters of the DensityMatrixSimulator work
+#[test]
+fn test_simulator_new() {
+    let mut backend = DensityMatrixSimulator::new(2);
+    assert_eq!(backend.number_qubits(), 2);
+    assert_eq!(backend.random_seed(), None);
+    backend.set_random_seed(Some(3));
+    assert_eq!(backend.random_seed(), Some(3));
+}
+
+/// Test that without noise the density matrix is the projector onto the simulated state vector
+#[test]
+fn test_agrees_with_statevector_simulator() {
+    let mut circuit = Circuit::new();
+    circuit += RotateX::new(0, 0.3.into());
+    circuit += RotateY::new(1, 1.2.into());
+    circuit += CNOT::new(0, 1);
+    circuit += ControlledPhaseShift::new(1, 0, 0.7.into());
+    circuit += RotateZ::new(1, 0.4.into());
+
+    let mut statevector_circuit = Circuit::new();
+    statevector_circuit += DefinitionComplex::new("state".to_string(), 4, true);
+    statevector_circuit += circuit.clone();
+    statevector_circuit += PragmaGetStateVector::new("state".to_string(), None);
+    let (_, _, complex_registers) = StatevectorSimulator::new(2)
+        .run_circuit(&statevector_circuit)
+        .unwrap();
+    let state = &complex_registers["state"][0];
+
+    let rho = density_matrix(&circuit, 2);
+    for row in 0..4 {
+        for column in 0..4 {
+            let expected = state[row] * state[column].conj();
+            assert!((rho[row * 4 + column] - expected).norm() < 1e-10);
+        }
+    }
+}
+
+/// Test that damping relaxes the excited state with the expected rate
+#[test]
+fn test_damping() {
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(0);
+    circuit += PragmaDamping::new(0, 1.0.into(), 0.5.into());
+    let probabilities = occupation_probabilities(&circuit, 1);
+    assert!((probabilities[1] - (-0.5_f64).exp()).abs() < 1e-10);
+    assert!((probabilities[0] + probabilities[1] - 1.0).abs() < 1e-10);
+}
+
+/// Test that PragmaBoostNoise multiplies the gate time of the following noise PRAGMAs
+#[test]
+fn test_boost_noise() {
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(0);
+    circuit += PragmaBoostNoise::new(3.0.into());
+    circuit += PragmaDamping::new(0, 1.0.into(), 0.5.into());
+    let probabilities = occupation_probabilities(&circuit, 1);
+    assert!((probabilities[1] - (-1.5_f64).exp()).abs() < 1e-10);
+}
+
+/// Test that dephasing reduces the coherences of a superposition
+#[test]
+fn test_dephasing() {
+    let mut circuit = Circuit::new();
+    circuit += Hadamard::new(0);
+    circuit += PragmaDephasing::new(0, 1.0.into(), 0.25.into());
+    let rho = density_matrix(&circuit, 1);
+    let expected = 0.5 * (-0.5_f64).exp();
+    assert!((rho[0] - Complex64::new(0.5, 0.0)).norm() < 1e-10);
+    assert!((rho[1] - Complex64::new(expected, 0.0)).norm() < 1e-10);
+    assert!((rho[2] - Complex64::new(expected, 0.0)).norm() < 1e-10);
+}
+
+/// Test that the noise PRAGMAs act only on their qubit and preserve the trace
+#[test_case(PragmaDamping::new(1, 0.5.into(), 1.0.into()).into(); "damping")]
+#[test_case(PragmaDepolarising::new(1, 0.5.into(), 1.0.into()).into(); "depolarising")]
+#[test_case(PragmaDephasing::new(1, 0.5.into(), 1.0.into()).into(); "dephasing")]
+#[test_case(PragmaRandomNoise::new(1, 0.5.into(), 1.0.into(), 0.5.into()).into(); "random_noise")]
+#[test_case(PragmaGeneralNoise::new(1, 0.5.into(), array![[0.1, 0.0, 0.0], [0.0, 0.2, 0.0], [0.0, 0.0, 0.0]]).into(); "general_noise")]
+fn test_noise_preserves_trace(noise: Operation) {
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(0);
+    circuit += Hadamard::new(1);
+    circuit += noise;
+    let probabilities = occupation_probabilities(&circuit, 2);
+    assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-10);
+    // Qubit 0 stays in the excited state
+    assert!((probabilities[1] + probabilities[3] - 1.0).abs() < 1e-10);
+}
+
+/// Test setting a mixed density matrix and sampling from it
+#[test]
+fn test_set_density_matrix_and_repeated_measurement() {
+    let zero = Complex64::new(0.0, 0.0);
+    let half = Complex64::new(0.5, 0.0);
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += DefinitionFloat::new("z".to_string(), 1, true);
+    circuit += PragmaSetDensityMatrix::new(array![[half, zero], [zero, half]]);
+    circuit += PragmaGetPauliProduct::new(HashMap::from([(0, 3)]), "z".to_string(), Circuit::new());
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 200, None);
+    let mut backend = DensityMatrixSimulator::new(1);
+    backend.set_random_seed(Some(1));
+    let (bit_registers, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    assert!(float_registers["z"][0][0].abs() < 1e-10);
+    assert_eq!(bit_registers["ro"].len(), 200);
+    assert!(bit_registers["ro"].iter().any(|row| row[0]));
+    assert!(bit_registers["ro"].iter().any(|row| !row[0]));
+
+    let mut circuit = Circuit::new();
+    circuit += PragmaSetDensityMatrix::new(array![[half, zero], [zero, half]]);
+    assert!(DensityMatrixSimulator::new(2)
+        .run_circuit(&circuit)
+        .is_err());
+}
+
+/// Test that single qubit measurements collapse the density matrix
+#[test]
+fn test_measure_qubit() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaDephasing::new(0, 1.0.into(), 1.0.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+    circuit += PragmaSetNumberOfMeasurements::new(50, "ro".to_string());
+    let backend = DensityMatrixSimulator::new(2);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"].len(), 50);
+    assert!(bit_registers["ro"].iter().all(|row| row[0] == row[1]));
+}
+
+/// Test that an active reset returns a partially decayed qubit to the ground state
+#[test]
+fn test_active_reset() {
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(0);
+    circuit += PragmaDamping::new(0, 1.0.into(), 0.5.into());
+    circuit += PragmaActiveReset::new(0);
+    let probabilities = occupation_probabilities(&circuit, 1);
+    assert!((probabilities[0] - 1.0).abs() < 1e-10);
+}
+
+/// Test that operations that can not be simulated return an error
+#[test]
+fn test_errors() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("state".to_string(), 2, true);
+    circuit += PragmaGetStateVector::new("state".to_string(), None);
+    assert_eq!(
+        DensityMatrixSimulator::new(1).run_circuit(&circuit),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "DensityMatrixSimulator",
+            hqslang: "PragmaGetStateVector"
+        })
+    );
+
+    let mut circuit = Circuit::new();
+    circuit += PragmaDamping::new(1, 1.0.into(), 1.0.into());
+    assert!(DensityMatrixSimulator::new(1)
+        .run_circuit(&circuit)
+        .is_err());
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    let mut mapping = HashMap::new();
+    mapping.insert(1, 0);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, Some(mapping));
+    assert!(DensityMatrixSimulator::new(1)
+        .run_circuit(&circuit)
+        .is_err());
+}
+
+/// Test that density matrices of too many qubits are refused instead of overflowing
+#[test]
+fn test_too_many_qubits() {
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(0);
+    for number_qubits in [40, 64] {
+        assert!(matches!(
+            DensityMatrixSimulator::new(number_qubits).run_circuit(&circuit),
+            Err(RoqoqoBackendError::GenericError { .. })
+        ));
+    }
+}
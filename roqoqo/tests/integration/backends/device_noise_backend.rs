@@ -0,0 +1,133 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::chain_device::ChainDevice;
+use ndarray::{array, Array2};
+use roqoqo::backends::{DensityMatrixSimulator, DeviceNoiseBackend, EvaluatingBackend};
+use roqoqo::devices::insert_device_noise;
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+
+fn rates() -> Array2<f64> {
+    array![[0.0, 0.0, 0.0], [0.0, 0.01, 0.0], [0.0, 0.0, 0.02]]
+}
+
+fn noisy_device() -> ChainDevice {
+    let mut device = ChainDevice::new(3);
+    device.rates = rates();
+    device
+}
+
+fn noise(qubit: usize, time: f64) -> PragmaGeneralNoise {
+    PragmaGeneralNoise::new(qubit, time.into(), rates())
+}
+
+/// Test that noise is inserted after gates and for idle qubits in each layer
+#[test]
+fn test_insert_noise_layers() {
+    let mut circuit = Circuit::new();
+    circuit += RotateX::new(0, 0.5.into());
+    circuit += CNOT::new(0, 1);
+    circuit += RotateZ::new(2, 0.5.into());
+
+    let mut expected = Circuit::new();
+    expected += RotateX::new(0, 0.5.into());
+    expected += noise(0, 1.0);
+    expected += RotateZ::new(2, 0.5.into());
+    expected += noise(2, 0.5);
+    expected += noise(1, 1.0);
+    expected += noise(2, 0.5);
+    expected += CNOT::new(0, 1);
+    expected += noise(0, 2.0);
+    expected += noise(1, 2.0);
+    expected += noise(2, 2.0);
+
+    assert_eq!(
+        insert_device_noise(&noisy_device(), &circuit).unwrap(),
+        expected
+    );
+}
+
+/// Test that non-gate operations are kept in place and end the layers
+#[test]
+fn test_insert_noise_non_gate_operations() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PauliX::new(0);
+    circuit += PragmaDamping::new(1, 1.0.into(), 0.1.into());
+    circuit += PauliX::new(1);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let mut expected = Circuit::new();
+    expected += DefinitionBit::new("ro".to_string(), 2, true);
+    expected += PauliX::new(0);
+    expected += noise(0, 1.0);
+    expected += noise(1, 1.0);
+    expected += PragmaDamping::new(1, 1.0.into(), 0.1.into());
+    expected += PauliX::new(1);
+    expected += noise(1, 1.0);
+    expected += noise(0, 1.0);
+    expected += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    assert_eq!(
+        insert_device_noise(&noisy_device(), &circuit).unwrap(),
+        expected
+    );
+}
+
+/// Test that no noise is inserted for a device without decoherence
+#[test]
+fn test_insert_noise_noiseless_device() {
+    let mut circuit = Circuit::new();
+    circuit += RotateX::new(0, 0.5.into());
+    circuit += CNOT::new(0, 1);
+    assert_eq!(
+        insert_device_noise(&ChainDevice::new(3), &circuit).unwrap(),
+        circuit
+    );
+}
+
+/// Test that circuits that cannot be executed on the device are refused
+#[test]
+fn test_insert_noise_invalid_circuit() {
+    let mut circuit = Circuit::new();
+    circuit += CNOT::new(0, 2);
+    match insert_device_noise(&noisy_device(), &circuit) {
+        Err(RoqoqoBackendError::DeviceValidationFailed { violations }) => {
+            assert_eq!(violations.len(), 1)
+        }
+        _ => panic!("Invalid circuit was not refused"),
+    }
+}
+
+/// Test that the wrapper runs the noisy circuit on the wrapped backend
+#[test]
+fn test_backend() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("rho".to_string(), 16, true);
+    circuit += RotateX::new(0, 1.0.into());
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaGetDensityMatrix::new("rho".to_string(), None);
+
+    let simulator = DensityMatrixSimulator::new(2);
+    let backend = DeviceNoiseBackend::new(simulator.clone(), noisy_device());
+    assert_eq!(backend.device(), &noisy_device());
+    assert_eq!(backend.backend(), &simulator);
+    let noisy_circuit = backend.noisy_circuit(&circuit).unwrap();
+    assert!(noisy_circuit.len() > circuit.len());
+
+    let (_, _, complex_registers) = backend.run_circuit(&circuit).unwrap();
+    let (_, _, expected_registers) = simulator.run_circuit(&noisy_circuit).unwrap();
+    let (_, _, noiseless_registers) = simulator.run_circuit(&circuit).unwrap();
+    assert_eq!(complex_registers, expected_registers);
+    assert_ne!(complex_registers, noiseless_registers);
+}
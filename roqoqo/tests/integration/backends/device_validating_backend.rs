@@ -0,0 +1,188 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::chain_device::ChainDevice;
+use roqoqo::backends::{DeviceValidatingBackend, EvaluatingBackend, StatevectorSimulator};
+use roqoqo::devices::{validate_circuit, DeviceViolation, DeviceViolationKind};
+use roqoqo::measurements::ClassicalRegister;
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+
+fn valid_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 3, true);
+    circuit += RotateX::new(0, 0.5.into());
+    circuit += CNOT::new(0, 1);
+    circuit += CNOT::new(2, 1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    circuit
+}
+
+fn violation(index: usize, hqslang: &str, kind: DeviceViolationKind) -> DeviceViolation {
+    DeviceViolation {
+        index,
+        hqslang: hqslang.to_string(),
+        kind,
+    }
+}
+
+/// Test that a valid circuit has no violations
+#[test]
+fn test_valid_circuit() {
+    let device = ChainDevice::new(3);
+    assert!(validate_circuit(&device, valid_circuit().iter()).is_empty());
+}
+
+/// Test that all violations of a circuit are reported
+#[test]
+fn test_violations() {
+    let mut device = ChainDevice::new(3);
+    device
+        .multi_qubit_gates
+        .insert("MultiQubitMS".to_string(), 3.0);
+    let mut circuit = Circuit::new();
+    circuit += RotateX::new(0, 0.5.into());
+    circuit += Hadamard::new(1);
+    circuit += CNOT::new(0, 2);
+    circuit += ControlledPauliZ::new(1, 2);
+    circuit += RotateZ::new(3, 0.5.into());
+    circuit += CNOT::new(4, 1);
+    circuit += MultiQubitMS::new(vec![0, 1, 2], 0.5.into());
+    circuit += MultiQubitZZ::new(vec![0, 1, 2], 0.5.into());
+    circuit += MeasureQubit::new(5, "ro".to_string(), 0);
+    let mut conditional_circuit = Circuit::new();
+    conditional_circuit += PauliY::new(0);
+    circuit += PragmaConditional::new("ro".to_string(), 0, conditional_circuit);
+
+    let violations = validate_circuit(&device, circuit.iter());
+    assert_eq!(
+        violations,
+        vec![
+            violation(
+                1,
+                "Hadamard",
+                DeviceViolationKind::GateNotAvailable { qubits: vec![1] }
+            ),
+            violation(
+                2,
+                "CNOT",
+                DeviceViolationKind::QubitsNotConnected {
+                    control: 0,
+                    target: 2
+                }
+            ),
+            violation(
+                3,
+                "ControlledPauliZ",
+                DeviceViolationKind::GateNotAvailable { qubits: vec![1, 2] }
+            ),
+            violation(
+                4,
+                "RotateZ",
+                DeviceViolationKind::QubitOutOfRange {
+                    qubit: 3,
+                    number_qubits: 3
+                }
+            ),
+            violation(
+                5,
+                "CNOT",
+                DeviceViolationKind::QubitOutOfRange {
+                    qubit: 4,
+                    number_qubits: 3
+                }
+            ),
+            violation(
+                7,
+                "MultiQubitZZ",
+                DeviceViolationKind::GateNotAvailable {
+                    qubits: vec![0, 1, 2]
+                }
+            ),
+            violation(
+                8,
+                "MeasureQubit",
+                DeviceViolationKind::QubitOutOfRange {
+                    qubit: 5,
+                    number_qubits: 3
+                }
+            ),
+            violation(
+                9,
+                "PauliY",
+                DeviceViolationKind::GateNotAvailable { qubits: vec![0] }
+            ),
+        ]
+    );
+    assert_eq!(
+        violations[1].to_string(),
+        "Operation 2 (CNOT): qubits 0 and 2 are not connected in the device"
+    );
+}
+
+/// Test that the wrapper runs valid circuits and refuses invalid ones
+#[test]
+fn test_backend() {
+    let mut simulator = StatevectorSimulator::new(3);
+    simulator.set_random_seed(Some(1));
+    let backend = DeviceValidatingBackend::new(simulator.clone(), ChainDevice::new(3));
+    assert_eq!(backend.device(), &ChainDevice::new(3));
+    assert_eq!(backend.backend(), &simulator);
+    assert_eq!(
+        backend.run_circuit(&valid_circuit()),
+        simulator.run_circuit(&valid_circuit())
+    );
+
+    let mut circuit = valid_circuit();
+    circuit += Hadamard::new(2);
+    assert_eq!(
+        backend.run_circuit(&circuit),
+        Err(RoqoqoBackendError::DeviceValidationFailed {
+            violations: vec![violation(
+                5,
+                "Hadamard",
+                DeviceViolationKind::GateNotAvailable { qubits: vec![2] }
+            )]
+        })
+    );
+    assert_eq!(backend.validate(circuit.iter()).len(), 1);
+
+    circuit += CNOT::new(0, 2);
+    assert_eq!(
+        backend.run_circuit(&circuit).unwrap_err().to_string(),
+        "Circuit violates the constraints of the device: \
+         Operation 5 (Hadamard): gate is not available on qubits [2]; \
+         Operation 6 (CNOT): qubits 0 and 2 are not connected in the device"
+    );
+}
+
+/// Test that measurements are only run if all circuits are valid
+#[test]
+fn test_measurement() {
+    let backend = DeviceValidatingBackend::new(StatevectorSimulator::new(3), ChainDevice::new(3));
+    let mut invalid_circuit = Circuit::new();
+    invalid_circuit += CNOT::new(0, 2);
+    let mut measurement = ClassicalRegister {
+        constant_circuit: Some(valid_circuit()),
+        circuits: vec![Circuit::new(), invalid_circuit],
+    };
+    match backend.run_measurement_registers(&measurement) {
+        Err(RoqoqoBackendError::DeviceValidationFailed { violations }) => {
+            assert_eq!(violations.len(), 1);
+            assert_eq!(violations[0].index, 5);
+        }
+        _ => panic!("Invalid measurement was not refused"),
+    }
+    measurement.circuits.pop();
+    let (bit_registers, _, _) = backend.run_measurement_registers(&measurement).unwrap();
+    assert_eq!(bit_registers["ro"].len(), 10);
+}
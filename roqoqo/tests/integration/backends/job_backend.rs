@@ -0,0 +1,202 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    BlockingJobBackend, EvaluatingBackend, JobBackend, JobStatus, LocalJobBackend,
+    StatevectorSimulator,
+};
+use roqoqo::measurements::ClassicalRegister;
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use std::time::Duration;
+
+fn bell_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 20, None);
+    circuit
+}
+
+fn measurement() -> ClassicalRegister {
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += Hadamard::new(0);
+    let mut circuits = Vec::new();
+    for qubit in 0..3 {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new(format!("ro_{}", qubit), 3, true);
+        circuit += PauliX::new(qubit);
+        circuit += PragmaRepeatedMeasurement::new(format!("ro_{}", qubit), 10, None);
+        circuits.push(circuit);
+    }
+    ClassicalRegister {
+        constant_circuit: Some(constant_circuit),
+        circuits,
+    }
+}
+
+fn simulator() -> StatevectorSimulator {
+    let mut simulator = StatevectorSimulator::new(3);
+    simulator.set_random_seed(Some(4));
+    simulator
+}
+
+/// Test the status of a job of the LocalJobBackend while it is polled
+#[test]
+fn test_local_job_lifecycle() {
+    let mut backend = LocalJobBackend::new(simulator());
+    backend.set_polls_until_completed(3);
+    assert_eq!(backend.polls_until_completed(), 3);
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(backend.number_of_jobs(), 1);
+    assert_eq!(backend.job_status(&job), Ok(JobStatus::Queued));
+    assert!(backend.fetch_result(&job).is_err());
+    assert_eq!(backend.job_status(&job), Ok(JobStatus::Queued));
+    assert_eq!(backend.job_status(&job), Ok(JobStatus::Completed));
+    assert_eq!(backend.job_status(&job), Ok(JobStatus::Completed));
+    assert_eq!(
+        backend.fetch_result(&job),
+        simulator().run_circuit(&bell_circuit())
+    );
+    assert!(backend.fetch_result(&job).is_err());
+}
+
+/// Test that jobs are executed on submission without polls
+#[test]
+fn test_local_job_immediate() {
+    let backend = LocalJobBackend::new(simulator());
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_eq!(
+        backend.fetch_result(&job),
+        simulator().run_circuit(&bell_circuit())
+    );
+}
+
+/// Test cancelling jobs of the LocalJobBackend
+#[test]
+fn test_local_job_cancel() {
+    let mut backend = LocalJobBackend::new(simulator());
+    let finished = backend.submit_circuit(&bell_circuit()).unwrap();
+    backend.set_polls_until_completed(2);
+    let queued = backend.submit_circuit(&bell_circuit()).unwrap();
+    assert_ne!(finished, queued);
+    backend.cancel_job(&queued).unwrap();
+    backend.cancel_job(&finished).unwrap();
+    assert_eq!(backend.job_status(&queued), Ok(JobStatus::Cancelled));
+    assert_eq!(backend.job_status(&queued), Ok(JobStatus::Cancelled));
+    assert!(backend.fetch_result(&queued).is_err());
+    assert_eq!(backend.job_status(&finished), Ok(JobStatus::Completed));
+    assert!(backend.fetch_result(&finished).is_ok());
+}
+
+/// Test that failing jobs report their error
+#[test]
+fn test_local_job_failed() {
+    let backend = LocalJobBackend::new(StatevectorSimulator::new(1));
+    let job = backend.submit_circuit(&bell_circuit()).unwrap();
+    let error = StatevectorSimulator::new(1)
+        .run_circuit(&bell_circuit())
+        .unwrap_err();
+    assert_eq!(
+        backend.job_status(&job),
+        Ok(JobStatus::Failed {
+            msg: error.to_string()
+        })
+    );
+    assert_eq!(backend.fetch_result(&job), Err(error));
+    assert!(backend.job_status(&1).is_err());
+    assert!(backend.fetch_result(&1).is_err());
+    assert!(backend.cancel_job(&1).is_err());
+}
+
+/// Test that all jobs of a measurement are submitted before any of them completes
+#[test]
+fn test_submit_measurement() {
+    let mut backend = LocalJobBackend::new(simulator());
+    backend.set_polls_until_completed(2);
+    let jobs = backend.submit_measurement(&measurement()).unwrap();
+    assert_eq!(jobs, vec![0, 1, 2]);
+    for job in jobs.iter() {
+        assert_eq!(backend.job_status(job), Ok(JobStatus::Queued));
+    }
+    assert!(backend.fetch_measurement_registers(&jobs).is_err());
+    for job in jobs.iter() {
+        assert_eq!(backend.job_status(job), Ok(JobStatus::Completed));
+    }
+    assert_eq!(
+        backend.fetch_measurement_registers(&jobs),
+        simulator().run_measurement_registers(&measurement())
+    );
+}
+
+/// Test that the BlockingJobBackend returns the same results as the wrapped simulator
+#[test]
+fn test_blocking_job_backend() {
+    let mut local_backend = LocalJobBackend::new(simulator());
+    local_backend.set_polls_until_completed(3);
+    let mut backend = BlockingJobBackend::new(local_backend);
+    assert_eq!(backend.poll_interval(), Duration::from_millis(100));
+    assert_eq!(backend.timeout(), None);
+    backend.set_poll_interval(Duration::from_millis(1));
+    backend.set_timeout(Some(Duration::from_secs(10)));
+    assert_eq!(backend.poll_interval(), Duration::from_millis(1));
+    assert_eq!(backend.timeout(), Some(Duration::from_secs(10)));
+
+    assert_eq!(
+        backend.run_circuit(&bell_circuit()),
+        simulator().run_circuit(&bell_circuit())
+    );
+    assert_eq!(
+        backend.run_measurement_registers(&measurement()),
+        simulator().run_measurement_registers(&measurement())
+    );
+    assert_eq!(backend.backend().number_of_jobs(), 4);
+}
+
+/// Test that the BlockingJobBackend cancels unfinished jobs on timeout
+#[test]
+fn test_blocking_job_backend_timeout() {
+    let mut local_backend = LocalJobBackend::new(simulator());
+    local_backend.set_polls_until_completed(usize::MAX);
+    let mut backend = BlockingJobBackend::new(local_backend);
+    backend.set_poll_interval(Duration::from_millis(1));
+    backend.set_timeout(Some(Duration::from_millis(20)));
+    let result = backend.run_measurement_registers(&measurement());
+    assert!(matches!(result, Err(RoqoqoBackendError::Timeout { .. })));
+    for job in 0..3 {
+        assert_eq!(backend.backend().job_status(&job), Ok(JobStatus::Cancelled));
+    }
+}
+
+/// Test that the BlockingJobBackend returns errors of failed jobs
+#[test]
+fn test_blocking_job_backend_failed() {
+    let backend = BlockingJobBackend::new(LocalJobBackend::new(StatevectorSimulator::new(1)));
+    assert_eq!(
+        backend.run_circuit(&bell_circuit()),
+        StatevectorSimulator::new(1).run_circuit(&bell_circuit())
+    );
+}
+
+/// Test the helper functions of JobStatus
+#[test]
+fn test_job_status() {
+    assert!(!JobStatus::Queued.is_finished());
+    assert!(!JobStatus::Running.is_finished());
+    assert!(JobStatus::Completed.is_finished());
+    assert!(JobStatus::Cancelled.is_finished());
+    assert!(JobStatus::Failed {
+        msg: "error".to_string()
+    }
+    .is_finished());
+}
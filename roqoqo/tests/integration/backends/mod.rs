@@ -0,0 +1,29 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod caching_backend;
+pub(crate) mod chain_device;
+mod density_matrix_simulator;
+mod device_noise_backend;
+mod device_validating_backend;
+mod job_backend;
+mod mps_simulator;
+mod observed_backend;
+mod readout_error_backend;
+mod resource_estimation_backend;
+#[cfg(feature = "serialize")]
+mod result_caching_backend;
+mod retry_backend;
+mod stabilizer_simulator;
+mod statevector_simulator;
+mod tensor_network_simulator;
+mod trajectory_simulator;
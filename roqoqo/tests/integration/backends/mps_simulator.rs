@@ -0,0 +1,227 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    EvaluatingBackend, MpsSimulator, StatevectorSimulator, MPS_TRUNCATION_ERROR_REGISTER,
+};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use std::collections::HashMap;
+
+fn ghz_circuit(number_qubits: usize) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += Hadamard::new(0);
+    for qubit in 1..number_qubits {
+        circuit += CNOT::new(qubit - 1, qubit);
+    }
+    circuit
+}
+
+/// Test that the getters and setters of the MpsSimulator work
+#[test]
+fn test_simulator_new() {
+    let mut backend = MpsSimulator::new(4);
+    assert_eq!(backend.number_qubits(), 4);
+    assert_eq!(backend.max_bond_dimension(), 64);
+    assert_eq!(backend.truncation_threshold(), 1e-12);
+    assert_eq!(backend.random_seed(), None);
+    backend.set_max_bond_dimension(8);
+    backend.set_truncation_threshold(1e-6);
+    backend.set_random_seed(Some(1));
+    assert_eq!(backend.max_bond_dimension(), 8);
+    assert_eq!(backend.truncation_threshold(), 1e-6);
+    assert_eq!(backend.random_seed(), Some(1));
+    backend.set_max_bond_dimension(0);
+    assert_eq!(backend.max_bond_dimension(), 1);
+}
+
+/// Test that the MpsSimulator reproduces the statevector simulator without truncation
+#[test]
+fn test_agrees_with_statevector_simulator() {
+    let number_qubits = 5;
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("state".to_string(), 1 << number_qubits, true);
+    for qubit in 0..number_qubits {
+        circuit += RotateY::new(qubit, (0.3 + 0.2 * qubit as f64).into());
+        circuit += RotateZ::new(qubit, (0.1 * qubit as f64).into());
+    }
+    circuit += CNOT::new(0, 1);
+    circuit += CNOT::new(3, 1);
+    circuit += ControlledPhaseShift::new(4, 0, 0.7.into());
+    circuit += ISwap::new(1, 4);
+    circuit += MolmerSorensenXX::new(2, 0);
+    circuit += GivensRotation::new(3, 2, 0.4.into(), 0.2.into());
+    circuit += RotateX::new(2, 1.1.into());
+    circuit += PragmaGetStateVector::new("state".to_string(), None);
+
+    let (_, _, expected) = StatevectorSimulator::new(number_qubits)
+        .run_circuit(&circuit)
+        .unwrap();
+    let (_, float_registers, result) = MpsSimulator::new(number_qubits)
+        .run_circuit(&circuit)
+        .unwrap();
+    for (a, b) in expected["state"][0].iter().zip(result["state"][0].iter()) {
+        assert!((a - b).norm() < 1e-8);
+    }
+    assert!(float_registers[MPS_TRUNCATION_ERROR_REGISTER][0][0] < 1e-10);
+}
+
+/// Test that the truncation error is reported when the bond dimension is limited
+#[test]
+fn test_truncation_error() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("probabilities".to_string(), 4, true);
+    circuit += ghz_circuit(2);
+    circuit += PragmaGetOccupationProbability::new("probabilities".to_string(), None);
+    let mut backend = MpsSimulator::new(2);
+    backend.set_max_bond_dimension(1);
+    let (_, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    assert!((float_registers[MPS_TRUNCATION_ERROR_REGISTER][0][0] - 0.5).abs() < 1e-10);
+    // The truncated state is renormalized
+    let probabilities = &float_registers["probabilities"][0];
+    assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-10);
+
+    backend.set_max_bond_dimension(2);
+    let (_, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    assert!(float_registers[MPS_TRUNCATION_ERROR_REGISTER][0][0] < 1e-10);
+}
+
+/// Test Pauli products and repeated measurements on a long chain
+#[test]
+fn test_long_chain() {
+    let number_qubits = 60;
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), number_qubits, true);
+    circuit += DefinitionFloat::new("zz".to_string(), 1, true);
+    circuit += DefinitionFloat::new("z".to_string(), 1, true);
+    circuit += DefinitionFloat::new("xx".to_string(), 1, true);
+    circuit += ghz_circuit(number_qubits);
+    circuit += PragmaGetPauliProduct::new(
+        HashMap::from([(0, 3), (number_qubits - 1, 3)]),
+        "zz".to_string(),
+        Circuit::new(),
+    );
+    circuit +=
+        PragmaGetPauliProduct::new(HashMap::from([(10, 3)]), "z".to_string(), Circuit::new());
+    let all_x: HashMap<usize, usize> = (0..number_qubits).map(|qubit| (qubit, 1)).collect();
+    circuit += PragmaGetPauliProduct::new(all_x, "xx".to_string(), Circuit::new());
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 20, None);
+    let mut backend = MpsSimulator::new(number_qubits);
+    backend.set_random_seed(Some(3));
+    let (bit_registers, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    assert!((float_registers["zz"][0][0] - 1.0).abs() < 1e-8);
+    assert!(float_registers["z"][0][0].abs() < 1e-8);
+    assert!((float_registers["xx"][0][0] - 1.0).abs() < 1e-8);
+    let rows = &bit_registers["ro"];
+    assert_eq!(rows.len(), 20);
+    for row in rows {
+        assert!(row.iter().all(|bit| *bit == row[0]));
+    }
+    assert!(rows.iter().any(|row| row[0]));
+    assert!(rows.iter().any(|row| !row[0]));
+}
+
+/// Test repeated measurements of qubits beyond the 64 bits of a basis state index
+#[test]
+fn test_repeated_measurement_100_qubits() {
+    let number_qubits = 100;
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), number_qubits, true);
+    circuit += DefinitionBit::new("mapped".to_string(), 2, true);
+    circuit += PauliX::new(66);
+    circuit += PauliX::new(99);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 5, None);
+    let mapping = HashMap::from([(99, 0), (2, 1)]);
+    circuit += PragmaRepeatedMeasurement::new("mapped".to_string(), 5, Some(mapping));
+    let backend = MpsSimulator::new(number_qubits);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    let expected: Vec<bool> = (0..number_qubits)
+        .map(|qubit| qubit == 66 || qubit == 99)
+        .collect();
+    assert_eq!(bit_registers["ro"], vec![expected; 5]);
+    assert_eq!(bit_registers["mapped"], vec![vec![true, false]; 5]);
+}
+
+/// Test that dense outputs of too many qubits return an error instead of overflowing
+#[test]
+fn test_dense_outputs_too_large() {
+    let operations: Vec<Operation> = vec![
+        PragmaGetStateVector::new("complex".to_string(), None).into(),
+        PragmaGetDensityMatrix::new("complex".to_string(), None).into(),
+        PragmaGetOccupationProbability::new("float".to_string(), None).into(),
+    ];
+    for operation in operations {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionComplex::new("complex".to_string(), 1, true);
+        circuit += DefinitionFloat::new("float".to_string(), 1, true);
+        circuit.add_operation(operation);
+        assert!(matches!(
+            MpsSimulator::new(70).run_circuit(&circuit),
+            Err(RoqoqoBackendError::GenericError { .. })
+        ));
+    }
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("complex".to_string(), 1, true);
+    circuit += PragmaGetDensityMatrix::new("complex".to_string(), None);
+    assert!(MpsSimulator::new(13).run_circuit(&circuit).is_err());
+}
+
+/// Test that a circuit register clashing with the truncation error register is refused
+#[test]
+fn test_register_name_clash() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new(MPS_TRUNCATION_ERROR_REGISTER.to_string(), 1, true);
+    assert!(matches!(
+        MpsSimulator::new(1).run_circuit(&circuit),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
+
+/// Test that single qubit measurements collapse the matrix product state
+#[test]
+fn test_measure_qubit_and_active_reset() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 4, true);
+    circuit += ghz_circuit(3);
+    circuit += PauliX::new(3);
+    circuit += PragmaActiveReset::new(3);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(2, "ro".to_string(), 2);
+    circuit += MeasureQubit::new(3, "ro".to_string(), 3);
+    circuit += PragmaSetNumberOfMeasurements::new(30, "ro".to_string());
+    let backend = MpsSimulator::new(4);
+    let (bit_registers, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    let rows = &bit_registers["ro"];
+    assert_eq!(rows.len(), 30);
+    assert!(rows
+        .iter()
+        .all(|row| row[0] == row[1] && row[1] == row[2] && !row[3]));
+    assert_eq!(float_registers[MPS_TRUNCATION_ERROR_REGISTER].len(), 30);
+}
+
+/// Test that multi-qubit gates and noise PRAGMAs are rejected
+#[test]
+fn test_not_in_backend() {
+    let mut circuit = Circuit::new();
+    circuit += MultiQubitMS::new(vec![0, 1, 2], 1.0.into());
+    assert_eq!(
+        MpsSimulator::new(3).run_circuit(&circuit),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "MpsSimulator",
+            hqslang: "MultiQubitMS"
+        })
+    );
+    let mut circuit = Circuit::new();
+    circuit += PragmaDephasing::new(0, 1.0.into(), 1.0.into());
+    assert!(MpsSimulator::new(3).run_circuit(&circuit).is_err());
+}
@@ -0,0 +1,292 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    BackendObserver, CircuitStatistics, EvaluatingBackend, LoggingObserver, ObservedBackend,
+    RegisterResult, StatevectorSimulator, SummaryObserver,
+};
+use roqoqo::measurements::{ClassicalRegister, Measure};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, QuantumProgram, RoqoqoBackendError};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn circuit(qubit: usize, number_measurements: usize) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new(format!("ro_{}", qubit), 3, true);
+    circuit += PauliX::new(qubit);
+    circuit += PragmaRepeatedMeasurement::new(format!("ro_{}", qubit), number_measurements, None);
+    circuit
+}
+
+fn simulator(number_qubits: usize) -> StatevectorSimulator {
+    let mut simulator = StatevectorSimulator::new(number_qubits);
+    simulator.set_random_seed(Some(3));
+    simulator
+}
+
+fn measurement() -> ClassicalRegister {
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += Hadamard::new(0);
+    ClassicalRegister {
+        constant_circuit: Some(constant_circuit),
+        circuits: vec![circuit(1, 10), circuit(2, 20)],
+    }
+}
+
+/// Observer recording the names of the events.
+#[derive(Debug, Default)]
+struct RecordingObserver {
+    events: Mutex<Vec<String>>,
+}
+
+impl BackendObserver for RecordingObserver {
+    fn before_circuit(&self, statistics: &CircuitStatistics) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("before_circuit {}", statistics.number_shots));
+    }
+
+    fn after_circuit(
+        &self,
+        _statistics: &CircuitStatistics,
+        _duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("after_circuit {}", error.is_some()));
+    }
+
+    fn before_measurement(&self, circuits: &[CircuitStatistics]) {
+        let shots: Vec<usize> = circuits
+            .iter()
+            .map(|statistics| statistics.number_shots)
+            .collect();
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("before_measurement {:?}", shots));
+    }
+
+    fn after_measurement(
+        &self,
+        circuits: &[CircuitStatistics],
+        _duration: Duration,
+        error: Option<&RoqoqoBackendError>,
+    ) {
+        self.events.lock().unwrap().push(format!(
+            "after_measurement {} {}",
+            circuits.len(),
+            error.is_some()
+        ));
+    }
+}
+
+/// Backend counting the measurements passed to it as a whole, clones share the count.
+#[derive(Debug, Clone, Default)]
+struct MeasurementCountingBackend {
+    measurements: Arc<AtomicUsize>,
+}
+
+impl EvaluatingBackend for MeasurementCountingBackend {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        simulator(3).run_circuit_iterator(circuit)
+    }
+
+    fn run_measurement_registers<T>(&self, measurement: &T) -> RegisterResult
+    where
+        T: Measure,
+    {
+        self.measurements.fetch_add(1, Ordering::SeqCst);
+        simulator(3).run_measurement_registers(measurement)
+    }
+}
+
+/// Shared buffer the LoggingObserver writes to.
+#[derive(Debug, Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Test the operation counts and shots of CircuitStatistics
+#[test]
+fn test_circuit_statistics() {
+    let mut test_circuit = circuit(0, 100);
+    test_circuit += PauliX::new(1);
+    test_circuit += PragmaSetNumberOfMeasurements::new(200, "ro_0".to_string());
+    let statistics = CircuitStatistics::from_operations(test_circuit.iter());
+    assert_eq!(statistics.number_operations, 5);
+    assert_eq!(statistics.operation_counts["PauliX"], 2);
+    assert_eq!(statistics.operation_counts["DefinitionBit"], 1);
+    assert_eq!(statistics.number_shots, 200);
+
+    let mut no_measurement = Circuit::new();
+    no_measurement += PauliX::new(0);
+    assert_eq!(
+        CircuitStatistics::from_operations(no_measurement.iter()).number_shots,
+        1
+    );
+}
+
+/// Test that the observer is notified about measurements with the statistics of their circuits
+#[test]
+fn test_measurement_events() {
+    let observer = RecordingObserver::default();
+    let backend = ObservedBackend::new(simulator(3), &observer);
+    assert_eq!(backend.backend(), &simulator(3));
+    assert_eq!(
+        backend.run_measurement_registers(&measurement()).unwrap(),
+        simulator(3)
+            .run_measurement_registers(&measurement())
+            .unwrap()
+    );
+    assert_eq!(
+        *observer.events.lock().unwrap(),
+        vec!["before_measurement [10, 20]", "after_measurement 2 false"]
+    );
+
+    observer.events.lock().unwrap().clear();
+    let backend = ObservedBackend::new(StatevectorSimulator::new(2), &observer);
+    assert!(backend.run_measurement_registers(&measurement()).is_err());
+    assert_eq!(
+        *observer.events.lock().unwrap(),
+        vec!["before_measurement [10, 20]", "after_measurement 2 true"]
+    );
+
+    observer.events.lock().unwrap().clear();
+    let backend = ObservedBackend::new(StatevectorSimulator::new(2), &observer);
+    assert!(backend.run_circuit(&circuit(2, 5)).is_err());
+    assert_eq!(
+        *observer.events.lock().unwrap(),
+        vec!["before_circuit 5", "after_circuit true"]
+    );
+}
+
+/// Test that measurements are run as a whole by the wrapped backend
+#[test]
+fn test_measurement_delegated() {
+    let observer = RecordingObserver::default();
+    let backend = ObservedBackend::new(MeasurementCountingBackend::default(), &observer);
+    assert!(backend.run_measurement_registers(&measurement()).is_ok());
+    assert_eq!(backend.backend().measurements.load(Ordering::SeqCst), 1);
+
+    let program = QuantumProgram::ClassicalRegister {
+        measurement: measurement(),
+        input_parameter_names: vec![],
+    };
+    let backend = MeasurementCountingBackend::default();
+    assert!(program
+        .run_registers_observed(backend.clone(), &[], &observer)
+        .is_ok());
+    assert_eq!(backend.measurements.load(Ordering::SeqCst), 1);
+}
+
+/// Test the summary of circuits, measurements and programs
+#[test]
+fn test_summary_observer() {
+    let observer = SummaryObserver::new();
+    let backend = ObservedBackend::new(StatevectorSimulator::new(2), observer.clone());
+    backend.run_circuit(&circuit(0, 5)).unwrap();
+    assert!(backend.run_circuit(&circuit(2, 5)).is_err());
+    let summary = observer.summary();
+    assert_eq!(summary.number_circuits, 2);
+    assert_eq!(summary.failed_circuits, 1);
+    assert_eq!(summary.number_measurements, 0);
+    assert_eq!(summary.number_operations, 6);
+    assert_eq!(summary.operation_counts["PauliX"], 2);
+    assert_eq!(summary.number_shots, 10);
+    assert!(summary.wall_time >= summary.circuit_time);
+
+    observer.reset();
+    let program = QuantumProgram::ClassicalRegister {
+        measurement: measurement(),
+        input_parameter_names: vec![],
+    };
+    let registers = program
+        .run_registers_observed(StatevectorSimulator::new(3), &[], &observer)
+        .unwrap();
+    assert_eq!(registers.0["ro_2"].len(), 20);
+    let summary = observer.summary();
+    assert_eq!(summary.number_programs, 1);
+    assert_eq!(summary.number_measurements, 1);
+    assert_eq!(summary.number_circuits, 2);
+    assert_eq!(summary.number_shots, 30);
+    assert_eq!(summary.operation_counts["Hadamard"], 2);
+    assert!(summary.wall_time >= summary.circuit_time);
+
+    assert!(program
+        .run_observed(StatevectorSimulator::new(3), &[], &observer)
+        .is_err());
+    assert_eq!(observer.summary().number_programs, 2);
+
+    assert!(program
+        .run_registers_observed(StatevectorSimulator::new(2), &[], &observer)
+        .is_err());
+    let summary = observer.summary();
+    assert_eq!(summary.number_measurements, 2);
+    assert_eq!(summary.failed_measurements, 1);
+    assert_eq!(summary.failed_circuits, 0);
+}
+
+/// Test the log lines of the LoggingObserver
+#[test]
+fn test_logging_observer() {
+    let buffer = SharedBuffer::default();
+    let observer = LoggingObserver::new(buffer.clone());
+    let backend = ObservedBackend::new(StatevectorSimulator::new(2), &observer);
+    backend.run_circuit(&circuit(0, 5)).unwrap();
+    assert!(backend.run_circuit(&circuit(2, 5)).is_err());
+    let program = QuantumProgram::ClassicalRegister {
+        measurement: measurement(),
+        input_parameter_names: vec![],
+    };
+    assert!(program
+        .run_registers_observed(StatevectorSimulator::new(3), &[], &observer)
+        .is_ok());
+
+    let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[0], "event=circuit_started operations=3 shots=5");
+    assert!(lines[1].starts_with("event=circuit_finished operations=3 shots=5 duration_s="));
+    assert!(lines[1].ends_with("status=ok"));
+    assert!(lines[3].contains("status=error error=\""));
+    assert_eq!(
+        lines[4],
+        "event=program_started program=QuantumProgram::ClassicalRegister parameters=[]"
+    );
+    assert_eq!(lines[5], "event=measurement_started circuits=2");
+    assert_eq!(
+        lines[6],
+        "event=measurement_circuit index=0 operations=4 shots=10"
+    );
+    assert!(lines[8].starts_with("event=measurement_finished circuits=2 duration_s="));
+    assert!(
+        lines[9].starts_with("event=program_finished program=QuantumProgram::ClassicalRegister")
+    );
+}
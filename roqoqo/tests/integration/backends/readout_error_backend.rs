@@ -0,0 +1,89 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::chain_device::ChainDevice;
+use roqoqo::backends::{EvaluatingBackend, ReadoutErrorBackend, StatevectorSimulator};
+use roqoqo::devices::Device;
+use roqoqo::measurements::{BasisRotation, BasisRotationInput, ReadoutErrorModel};
+use roqoqo::operations::*;
+use roqoqo::Circuit;
+use std::collections::HashMap;
+
+fn circuit(number_measurements: usize) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PauliX::new(0);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), number_measurements, None);
+    circuit
+}
+
+/// Test that the readout errors are applied to the bit registers
+#[test]
+fn test_readout_errors_applied() {
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(0, 1.0, 1.0).unwrap();
+    let simulator = StatevectorSimulator::new(2);
+    let backend = ReadoutErrorBackend::new(simulator.clone(), model.clone());
+    assert_eq!(backend.backend(), &simulator);
+    assert_eq!(backend.readout_error_model(), &model);
+
+    let (bit_registers, _, _) = backend.run_circuit(&circuit(10)).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![false, false]; 10]);
+}
+
+/// Test that a fixed seed reproduces the readout errors
+#[test]
+fn test_random_seed() {
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(0, 0.3, 0.3).unwrap();
+    model.set_single_qubit_error(1, 0.3, 0.3).unwrap();
+    let mut backend = ReadoutErrorBackend::new(StatevectorSimulator::new(2), model);
+    assert_eq!(backend.random_seed(), None);
+    backend.set_random_seed(Some(5));
+    assert_eq!(backend.random_seed(), Some(5));
+    let first = backend.run_circuit(&circuit(100)).unwrap();
+    assert_eq!(backend.run_circuit(&circuit(100)).unwrap(), first);
+    assert_ne!(first.0["ro"], vec![vec![true, false]; 100]);
+}
+
+/// Test that simulated readout errors are mitigated by the BasisRotation measurement
+#[test]
+fn test_mitigation() {
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(0, 0.05, 0.2).unwrap();
+    let mut backend = ReadoutErrorBackend::new(StatevectorSimulator::new(2), model.clone());
+    backend.set_random_seed(Some(2));
+
+    let mut input = BasisRotationInput::new(2, false);
+    let index = input.add_pauli_product("ro".to_string(), vec![0]).unwrap();
+    let linear_map: HashMap<usize, f64> = [(index, 1.0)].into_iter().collect();
+    input
+        .add_linear_exp_val("z0".to_string(), linear_map)
+        .unwrap();
+    let mut measurement = BasisRotation {
+        constant_circuit: None,
+        circuits: vec![circuit(4000)],
+        input,
+    };
+    let unmitigated = backend.run_measurement(&measurement).unwrap().unwrap()["z0"];
+    assert!((unmitigated + 0.6).abs() < 0.05, "{}", unmitigated);
+
+    measurement.input.set_readout_error_model(Some(model));
+    let mitigated = backend.run_measurement(&measurement).unwrap().unwrap()["z0"];
+    assert!((mitigated + 1.0).abs() < 0.05, "{}", mitigated);
+}
+
+/// Test that devices have no readout error model by default
+#[test]
+fn test_device_default() {
+    assert_eq!(ChainDevice::new(2).readout_error_model(), None);
+}
@@ -0,0 +1,127 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::chain_device::ChainDevice;
+use num_complex::Complex64;
+use roqoqo::backends::{EvaluatingBackend, ResourceEstimate, ResourceEstimationBackend};
+use roqoqo::devices::circuit_duration;
+use roqoqo::measurements::{BasisRotation, BasisRotationInput};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, QuantumProgram, RoqoqoBackendError};
+use std::collections::HashMap;
+
+fn circuit(number_measurements: usize) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 3, true);
+    circuit += DefinitionFloat::new("float".to_string(), 2, true);
+    circuit += DefinitionComplex::new("complex".to_string(), 4, true);
+    circuit += DefinitionBit::new("internal".to_string(), 1, false);
+    circuit += RotateX::new(0, 1.0.into());
+    circuit += RotateZ::new(1, 1.0.into());
+    circuit += PauliX::new(2);
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), number_measurements, None);
+    circuit
+}
+
+/// Test that the duration of a circuit is the sum of the durations of its gate layers
+#[test]
+fn test_circuit_duration() {
+    let device = ChainDevice::new(3);
+    assert_eq!(circuit_duration(&device, circuit(1).iter()), Ok(3.0));
+
+    let mut interrupted = Circuit::new();
+    interrupted += RotateX::new(0, 1.0.into());
+    interrupted += MeasureQubit::new(1, "ro".to_string(), 0);
+    interrupted += RotateZ::new(1, 1.0.into());
+    assert_eq!(circuit_duration(&device, interrupted.iter()), Ok(1.5));
+
+    let mut invalid = Circuit::new();
+    invalid += CNOT::new(0, 2);
+    assert!(matches!(
+        circuit_duration(&device, invalid.iter()),
+        Err(RoqoqoBackendError::DeviceValidationFailed { .. })
+    ));
+}
+
+/// Test that zero-filled registers of the defined shape are returned and resources are accumulated
+#[test]
+fn test_registers_and_estimate() {
+    let backend = ResourceEstimationBackend::new(ChainDevice::new(3));
+    assert_eq!(backend.device(), &ChainDevice::new(3));
+    let (bit_registers, float_registers, complex_registers) =
+        backend.run_circuit(&circuit(100)).unwrap();
+    assert_eq!(bit_registers.len(), 1);
+    assert_eq!(bit_registers["ro"], vec![vec![false; 3]; 100]);
+    assert_eq!(float_registers["float"], vec![vec![0.0; 2]]);
+    assert_eq!(
+        complex_registers["complex"],
+        vec![vec![Complex64::new(0.0, 0.0); 4]]
+    );
+
+    backend.run_circuit(&circuit(10)).unwrap();
+    assert_eq!(
+        backend.estimate(),
+        ResourceEstimate {
+            number_circuits: 2,
+            number_shots: 110,
+            number_operations: 18,
+            number_single_qubit_gates: 6,
+            number_two_qubit_gates: 2,
+            number_multi_qubit_gates: 0,
+            estimated_time: 330.0,
+        }
+    );
+    backend.reset_estimate();
+    assert_eq!(backend.estimate(), ResourceEstimate::default());
+}
+
+/// Test that circuits that cannot run on the device are refused without changing the estimate
+#[test]
+fn test_invalid_circuit() {
+    let backend = ResourceEstimationBackend::new(ChainDevice::new(2));
+    assert!(matches!(
+        backend.run_circuit(&circuit(10)),
+        Err(RoqoqoBackendError::DeviceValidationFailed { .. })
+    ));
+    assert_eq!(backend.estimate(), ResourceEstimate::default());
+}
+
+/// Test that a quantum program can be evaluated and its resources are estimated
+#[test]
+fn test_quantum_program() {
+    let mut input = BasisRotationInput::new(3, false);
+    let index = input.add_pauli_product("ro".to_string(), vec![0]).unwrap();
+    let mut linear = HashMap::new();
+    linear.insert(index, 1.0);
+    input.add_linear_exp_val("z_0".to_string(), linear).unwrap();
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += RotateX::new(0, "theta".into());
+    let program = QuantumProgram::BasisRotation {
+        measurement: BasisRotation {
+            constant_circuit: Some(constant_circuit),
+            circuits: vec![circuit(50), circuit(150)],
+            input,
+        },
+        input_parameter_names: vec!["theta".to_string()],
+    };
+
+    let backend = ResourceEstimationBackend::new(ChainDevice::new(3));
+    let result = program.run(backend.clone(), &[0.5]).unwrap().unwrap();
+    assert_eq!(result["z_0"], 1.0);
+    let estimate = backend.estimate();
+    assert_eq!(estimate.number_circuits, 2);
+    assert_eq!(estimate.number_shots, 200);
+    assert_eq!(estimate.number_single_qubit_gates, 8);
+    assert_eq!(estimate.number_two_qubit_gates, 2);
+    assert_eq!(estimate.estimated_time, 800.0);
+}
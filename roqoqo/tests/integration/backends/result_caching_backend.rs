@@ -0,0 +1,175 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    content_hash, CacheStatistics, EvaluatingBackend, ResultCachingBackend, StatevectorSimulator,
+};
+use roqoqo::measurements::ClassicalRegister;
+use roqoqo::operations::*;
+use roqoqo::{Circuit, QuantumProgram};
+use std::collections::HashMap;
+use std::fs;
+
+fn circuit(angle: f64) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateX::new(0, angle.into());
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, None);
+    circuit
+}
+
+/// Returns a simulator without fixed seed, so that repeated runs return different registers
+fn backend() -> ResultCachingBackend<StatevectorSimulator> {
+    ResultCachingBackend::new(StatevectorSimulator::new(2))
+}
+
+/// Test that the content hash is stable and only depends on the content of the operations
+#[test]
+fn test_content_hash() {
+    let hash = content_hash(circuit(0.5).iter()).unwrap();
+    assert_eq!(hash, content_hash(circuit(0.5).iter()).unwrap());
+    assert_ne!(hash, content_hash(circuit(0.6).iter()).unwrap());
+    assert_ne!(hash, content_hash(circuit(0.5).iter().skip(1)).unwrap());
+    assert_ne!(
+        content_hash([Operation::from(PauliX::new(0))].iter()).unwrap(),
+        content_hash([Operation::from(PauliY::new(0))].iter()).unwrap()
+    );
+
+    let forward: HashMap<usize, usize> = (0..20).map(|qubit| (qubit, 19 - qubit)).collect();
+    let backward: HashMap<usize, usize> = (0..20).rev().map(|qubit| (qubit, 19 - qubit)).collect();
+    let mut other = forward.clone();
+    other.insert(0, 0);
+    let hash_mapping = |mapping: HashMap<usize, usize>| {
+        let operation: Operation =
+            PragmaRepeatedMeasurement::new("ro".to_string(), 10, Some(mapping)).into();
+        content_hash([operation].iter()).unwrap()
+    };
+    assert_eq!(hash_mapping(forward.clone()), hash_mapping(backward));
+    assert_ne!(hash_mapping(forward), hash_mapping(other));
+
+    // The hash must not change between processes and platforms
+    let operations: Vec<Operation> = vec![
+        PauliX::new(0).into(),
+        RotateZ::new(1, 0.25.into()).into(),
+        DefinitionBit::new("ro".to_string(), 2, true).into(),
+    ];
+    assert_eq!(
+        content_hash(operations.iter()).unwrap(),
+        10299013646101518718
+    );
+}
+
+/// Test that repeated circuits are returned from the in-memory cache
+#[test]
+fn test_memory_cache() {
+    let backend = backend();
+    let first = backend.run_circuit(&circuit(0.5)).unwrap();
+    assert_eq!(
+        backend.statistics(),
+        CacheStatistics {
+            hits: 0,
+            disk_hits: 0,
+            misses: 1
+        }
+    );
+    assert_eq!(backend.run_circuit(&circuit(0.5)).unwrap(), first);
+    backend.run_circuit(&circuit(0.7)).unwrap();
+    assert_eq!(
+        backend.statistics(),
+        CacheStatistics {
+            hits: 1,
+            disk_hits: 0,
+            misses: 2
+        }
+    );
+
+    backend.reset_statistics();
+    assert_eq!(backend.statistics(), CacheStatistics::default());
+    backend.clear_memory_cache();
+    backend.run_circuit(&circuit(0.5)).unwrap();
+    assert_eq!(backend.statistics().misses, 1);
+}
+
+/// Test that errors are not cached
+#[test]
+fn test_errors_not_cached() {
+    let backend = ResultCachingBackend::new(StatevectorSimulator::new(1));
+    assert!(backend.run_circuit(&circuit(0.5)).is_err());
+    assert!(backend.run_circuit(&circuit(0.5)).is_err());
+    assert_eq!(backend.statistics().misses, 2);
+    assert_eq!(backend.statistics().hits, 0);
+}
+
+/// Test that results are stored in and loaded from the cache directory
+#[test]
+fn test_disk_cache() {
+    let directory =
+        std::env::temp_dir().join(format!("roqoqo_result_cache_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+
+    let mut backend = backend();
+    assert_eq!(backend.cache_directory(), None);
+    backend.set_cache_directory(Some(directory.clone()));
+    assert_eq!(backend.cache_directory(), Some(directory.as_path()));
+    let first = backend.run_circuit(&circuit(0.5)).unwrap();
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+
+    let mut new_backend = self::backend();
+    new_backend.set_cache_directory(Some(directory.clone()));
+    assert_eq!(new_backend.run_circuit(&circuit(0.5)).unwrap(), first);
+    assert_eq!(new_backend.run_circuit(&circuit(0.5)).unwrap(), first);
+    assert_eq!(
+        new_backend.statistics(),
+        CacheStatistics {
+            hits: 2,
+            disk_hits: 1,
+            misses: 0
+        }
+    );
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+/// Test that repeated runs of a QuantumProgram are served from the cache shared by clones
+#[test]
+fn test_quantum_program() {
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += Hadamard::new(1);
+    let mut parametrized_circuit = Circuit::new();
+    parametrized_circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    parametrized_circuit += RotateX::new(0, "angle".into());
+    parametrized_circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, None);
+    let program = QuantumProgram::ClassicalRegister {
+        measurement: ClassicalRegister {
+            constant_circuit: Some(constant_circuit),
+            circuits: vec![parametrized_circuit, circuit(0.3)],
+        },
+        input_parameter_names: vec!["angle".to_string()],
+    };
+
+    let backend = backend();
+    let first = program.run_registers(backend.clone(), &[0.5]).unwrap();
+    assert_eq!(
+        program.run_registers(backend.clone(), &[0.5]).unwrap(),
+        first
+    );
+    program.run_registers(backend.clone(), &[0.6]).unwrap();
+    assert_eq!(
+        backend.statistics(),
+        CacheStatistics {
+            hits: 3,
+            disk_hits: 0,
+            misses: 3
+        }
+    );
+}
@@ -0,0 +1,228 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{
+    EvaluatingBackend, RegisterResult, RetryBackend, RetryPolicy, RetrySummary,
+    StatevectorSimulator,
+};
+use roqoqo::measurements::ClassicalRegister;
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Local stand-in for a flaky hardware backend.
+///
+/// Each call takes the next scripted error, calls without a scripted error are run on a simulator.
+#[derive(Debug, Clone)]
+struct FlakyBackend {
+    simulator: StatevectorSimulator,
+    errors: Arc<Mutex<VecDeque<Option<RoqoqoBackendError>>>>,
+    calls: Arc<Mutex<usize>>,
+}
+
+impl FlakyBackend {
+    fn new(errors: Vec<Option<RoqoqoBackendError>>) -> Self {
+        let mut simulator = StatevectorSimulator::new(2);
+        simulator.set_random_seed(Some(3));
+        Self {
+            simulator,
+            errors: Arc::new(Mutex::new(errors.into_iter().collect())),
+            calls: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    fn calls(&self) -> usize {
+        *self.calls.lock().unwrap()
+    }
+}
+
+impl EvaluatingBackend for FlakyBackend {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        *self.calls.lock().unwrap() += 1;
+        if let Some(Some(error)) = self.errors.lock().unwrap().pop_front() {
+            return Err(error);
+        }
+        self.simulator.run_circuit_iterator(circuit)
+    }
+}
+
+fn timeout() -> Option<RoqoqoBackendError> {
+    Some(RoqoqoBackendError::Timeout {
+        msg: "no answer".to_string(),
+    })
+}
+
+fn network_error() -> Option<RoqoqoBackendError> {
+    Some(RoqoqoBackendError::NetworkError {
+        msg: "connection reset".to_string(),
+    })
+}
+
+fn policy(max_attempts: usize) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(1),
+        ..RetryPolicy::default()
+    }
+}
+
+fn circuit(qubit: usize) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PauliX::new(qubit);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 5, None);
+    circuit
+}
+
+/// Test the exponential backoff of the retry policy
+#[test]
+fn test_backoff() {
+    let policy = RetryPolicy {
+        max_attempts: 5,
+        initial_backoff: Duration::from_millis(100),
+        backoff_factor: 3.0,
+        max_backoff: Duration::from_secs(1),
+    };
+    assert_eq!(policy.backoff(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff(1), Duration::from_millis(300));
+    assert_eq!(policy.backoff(2), Duration::from_millis(900));
+    assert_eq!(policy.backoff(3), Duration::from_secs(1));
+    assert_eq!(policy.backoff(usize::MAX), Duration::from_secs(1));
+    assert!(policy.is_retryable(&timeout().unwrap()));
+    assert!(policy.is_retryable(&network_error().unwrap()));
+    assert!(
+        !policy.is_retryable(&RoqoqoBackendError::MissingAuthentification {
+            msg: "".to_string()
+        })
+    );
+}
+
+/// Test that transient errors are retried until the circuit succeeds
+#[test]
+fn test_retry_until_success() {
+    let flaky = FlakyBackend::new(vec![timeout(), network_error()]);
+    let backend = RetryBackend::new(flaky.clone(), policy(3));
+    assert_eq!(backend.policy(), &policy(3));
+    let (bit_registers, _, _) = backend.run_circuit(&circuit(0)).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true, false]; 5]);
+    assert_eq!(flaky.calls(), 3);
+    assert_eq!(
+        backend.summary(),
+        RetrySummary {
+            successful_runs: 1,
+            failed_runs: 0,
+            retries: 2,
+            reused_circuits: 0
+        }
+    );
+    backend.reset_summary();
+    assert_eq!(backend.summary(), RetrySummary::default());
+}
+
+/// Test that the last error is returned after the maximum number of attempts
+#[test]
+fn test_max_attempts() {
+    let flaky = FlakyBackend::new(vec![timeout(), timeout(), network_error(), None]);
+    let mut backend = RetryBackend::new(flaky.clone(), policy(1));
+    backend.set_policy(policy(3));
+    assert_eq!(
+        backend.run_circuit(&circuit(0)),
+        Err(network_error().unwrap())
+    );
+    assert_eq!(flaky.calls(), 3);
+    assert_eq!(backend.summary().failed_runs, 1);
+    assert_eq!(backend.summary().retries, 2);
+}
+
+/// Test that errors that are not transient are returned without retry
+#[test]
+fn test_no_retry_for_other_errors() {
+    let error = || RoqoqoBackendError::GenericError {
+        msg: "invalid circuit".to_string(),
+    };
+    let flaky = FlakyBackend::new(vec![Some(error()), None]);
+    let backend = RetryBackend::new(flaky.clone(), policy(3));
+    assert_eq!(backend.run_circuit(&circuit(0)), Err(error()));
+    assert_eq!(flaky.calls(), 1);
+}
+
+/// Test that the registers of finished circuits are kept when a measurement fails
+#[test]
+fn test_measurement_keeps_registers() {
+    let measurement = ClassicalRegister {
+        constant_circuit: None,
+        circuits: vec![circuit(0), circuit(1), circuit(0)],
+    };
+    // The first circuit succeeds after one retry, the second circuit fails twice
+    let flaky = FlakyBackend::new(vec![timeout(), None, timeout(), timeout()]);
+    let backend = RetryBackend::new(flaky.clone(), policy(2));
+    assert_eq!(
+        backend.run_measurement_registers(&measurement),
+        Err(timeout().unwrap())
+    );
+    assert_eq!(backend.number_kept_circuits(), 1);
+    assert_eq!(flaky.calls(), 4);
+
+    // Only the remaining circuits are run again
+    let (bit_registers, _, _) = backend
+        .clone()
+        .run_measurement_registers(&measurement)
+        .unwrap();
+    assert_eq!(flaky.calls(), 6);
+    assert_eq!(bit_registers["ro"].len(), 15);
+    assert_eq!(bit_registers["ro"][0], vec![true, false]);
+    assert_eq!(bit_registers["ro"][5], vec![false, true]);
+    assert_eq!(bit_registers["ro"][10], vec![true, false]);
+    assert_eq!(backend.number_kept_circuits(), 0);
+    assert_eq!(
+        backend.summary(),
+        RetrySummary {
+            successful_runs: 3,
+            failed_runs: 1,
+            retries: 2,
+            reused_circuits: 1
+        }
+    );
+}
+
+/// Test that kept registers are only used for the same measurement
+#[test]
+fn test_kept_registers_other_measurement() {
+    let measurement = ClassicalRegister {
+        constant_circuit: None,
+        circuits: vec![circuit(0), circuit(1)],
+    };
+    let flaky = FlakyBackend::new(vec![None, timeout()]);
+    let backend = RetryBackend::new(flaky.clone(), policy(1));
+    assert!(backend.run_measurement_registers(&measurement).is_err());
+    assert_eq!(backend.number_kept_circuits(), 1);
+
+    let other_measurement = ClassicalRegister {
+        constant_circuit: Some(circuit(1)),
+        circuits: measurement.circuits.clone(),
+    };
+    backend
+        .run_measurement_registers(&other_measurement)
+        .unwrap();
+    assert_eq!(flaky.calls(), 4);
+    assert_eq!(backend.summary().reused_circuits, 0);
+
+    assert!(backend.run_measurement_registers(&measurement).is_ok());
+    assert_eq!(flaky.calls(), 6);
+    backend.clear_kept_registers();
+    assert_eq!(backend.number_kept_circuits(), 0);
+}
@@ -0,0 +1,213 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{EvaluatingBackend, StabilizerSimulator, StatevectorSimulator};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use test_case::test_case;
+
+fn measure_all(
+    circuit: &Circuit,
+    number_qubits: usize,
+    number_measurements: usize,
+) -> Vec<Vec<bool>> {
+    let mut full_circuit = Circuit::new();
+    full_circuit += DefinitionBit::new("ro".to_string(), number_qubits, true);
+    full_circuit += circuit.clone();
+    full_circuit += PragmaRepeatedMeasurement::new("ro".to_string(), number_measurements, None);
+    let mut backend = StabilizerSimulator::new(number_qubits);
+    backend.set_random_seed(Some(7));
+    let (bit_registers, _, _) = backend.run_circuit(&full_circuit).unwrap();
+    bit_registers["ro"].clone()
+}
+
+/// Test that the getters and setters of the StabilizerSimulator work
+#[test]
+fn test_simulator_new() {
+    let mut backend = StabilizerSimulator::new(5);
+    assert_eq!(backend.number_qubits(), 5);
+    assert_eq!(backend.random_seed(), None);
+    backend.set_random_seed(Some(2));
+    assert_eq!(backend.random_seed(), Some(2));
+}
+
+/// Test Clifford gates that deterministically flip qubit 0
+#[test_case(vec![PauliX::new(0).into()]; "pauli_x")]
+#[test_case(vec![PauliY::new(0).into()]; "pauli_y")]
+#[test_case(vec![Hadamard::new(0).into(), SGate::new(0).into(), SGate::new(0).into(), Hadamard::new(0).into()]; "hssh")]
+#[test_case(vec![SqrtPauliX::new(0).into(), SqrtPauliX::new(0).into()]; "sqrt_x_squared")]
+#[test_case(vec![InvSqrtPauliX::new(0).into(), InvSqrtPauliX::new(0).into()]; "inv_sqrt_x_squared")]
+#[test_case(vec![RotateX::new(0, PI.into()).into()]; "rotate_x_pi")]
+#[test_case(vec![RotateY::new(0, (-PI).into()).into()]; "rotate_y_minus_pi")]
+#[test_case(vec![RotateX::new(0, (PI / 2.0).into()).into(), RotateX::new(0, (PI / 2.0).into()).into()]; "rotate_x_half_pi")]
+#[test_case(vec![PauliX::new(1).into(), SWAP::new(0, 1).into()]; "swap")]
+#[test_case(vec![PauliX::new(1).into(), ISwap::new(0, 1).into()]; "iswap")]
+#[test_case(vec![PauliX::new(1).into(), CNOT::new(1, 0).into(), PauliX::new(1).into()]; "cnot")]
+#[test_case(vec![PauliX::new(1).into(), Hadamard::new(0).into(), ControlledPauliZ::new(1, 0).into(), Hadamard::new(0).into(), PauliX::new(1).into()]; "controlled_z")]
+#[test_case(vec![Hadamard::new(0).into(), PauliZ::new(0).into(), Hadamard::new(0).into()]; "hzh")]
+fn test_deterministic_flip(operations: Vec<Operation>) {
+    let mut circuit = Circuit::new();
+    for operation in operations {
+        circuit += operation;
+    }
+    let rows = measure_all(&circuit, 2, 20);
+    assert!(rows.iter().all(|row| row == &vec![true, false]));
+}
+
+/// Test that measurement results agree with the statevector simulator for Clifford circuits
+#[test]
+fn test_agrees_with_statevector_simulator() {
+    let number_qubits = 3;
+    // Simple linear congruential generator for a reproducible pseudo-random gate sequence
+    let mut seed: u64 = 12345;
+    let mut next = move |modulus: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((seed >> 33) % modulus) as usize
+    };
+    for _ in 0..20 {
+        let mut circuit = Circuit::new();
+        for _ in 0..15 {
+            let qubit = next(3);
+            let other = (qubit + 1 + next(2)) % 3;
+            circuit += match next(8) {
+                0 => Operation::from(Hadamard::new(qubit)),
+                1 => SGate::new(qubit).into(),
+                2 => SqrtPauliX::new(qubit).into(),
+                3 => CNOT::new(qubit, other).into(),
+                4 => ControlledPauliZ::new(qubit, other).into(),
+                5 => ControlledPauliY::new(qubit, other).into(),
+                6 => RotateY::new(qubit, (PI / 2.0).into()).into(),
+                _ => ISwap::new(qubit, other).into(),
+            };
+        }
+        let mut probability_circuit = Circuit::new();
+        probability_circuit += DefinitionFloat::new("probabilities".to_string(), 8, true);
+        probability_circuit += circuit.clone();
+        probability_circuit +=
+            PragmaGetOccupationProbability::new("probabilities".to_string(), None);
+        let (_, float_registers, _) = StatevectorSimulator::new(number_qubits)
+            .run_circuit(&probability_circuit)
+            .unwrap();
+        let probabilities = &float_registers["probabilities"][0];
+        for row in measure_all(&circuit, number_qubits, 50) {
+            let basis_state: usize = row
+                .iter()
+                .enumerate()
+                .map(|(qubit, bit)| (*bit as usize) << qubit)
+                .sum();
+            assert!(probabilities[basis_state] > 1e-6);
+        }
+    }
+}
+
+/// Test a GHZ state on a large number of qubits
+#[test]
+fn test_large_ghz_state() {
+    let number_qubits = 500;
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), number_qubits, true);
+    circuit += Hadamard::new(0);
+    for qubit in 1..number_qubits {
+        circuit += CNOT::new(qubit - 1, qubit);
+    }
+    for qubit in 0..number_qubits {
+        circuit += MeasureQubit::new(qubit, "ro".to_string(), qubit);
+    }
+    circuit += PragmaSetNumberOfMeasurements::new(2, "ro".to_string());
+    let backend = StabilizerSimulator::new(number_qubits);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"].len(), 2);
+    for row in bit_registers["ro"].iter() {
+        assert!(row.iter().all(|bit| *bit == row[0]));
+    }
+}
+
+/// Test repeated measurements with qubit mapping and active reset
+#[test]
+fn test_repeated_measurement_and_active_reset() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 3, true);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += PauliX::new(2);
+    circuit += Hadamard::new(2);
+    circuit += PragmaActiveReset::new(2);
+    let mapping = HashMap::from([(0, 2), (1, 1), (2, 0)]);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, Some(mapping));
+    let mut backend = StabilizerSimulator::new(3);
+    backend.set_random_seed(Some(5));
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    let rows = &bit_registers["ro"];
+    assert_eq!(rows.len(), 100);
+    assert!(rows.iter().all(|row| !row[0] && row[1] == row[2]));
+    assert!(rows.iter().any(|row| row[1]));
+    assert!(rows.iter().any(|row| !row[1]));
+}
+
+/// Test that qubit mappings containing qubits the simulator does not have are refused
+#[test]
+fn test_repeated_measurement_invalid_qubit_mapping() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    let mapping = HashMap::from([(50, 0)]);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, Some(mapping));
+    assert!(matches!(
+        StabilizerSimulator::new(10).run_circuit(&circuit),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
+
+/// Test that multi-qubit gates on many qubits are decomposed into Clifford gates
+#[test]
+fn test_multi_qubit_gates() {
+    let number_qubits = 12;
+    let qubits: Vec<usize> = (0..number_qubits).collect();
+    let mut circuit = Circuit::new();
+    // Twice MultiQubitMS(π/2) is X on all qubits up to a global phase
+    circuit += MultiQubitMS::new(qubits.clone(), (PI / 2.0).into());
+    circuit += MultiQubitMS::new(qubits.clone(), (PI / 2.0).into());
+    circuit += MultiQubitZZ::new(qubits.clone(), (PI / 2.0).into());
+    circuit += MultiQubitZZ::new(qubits, (PI / 2.0).into());
+    let rows = measure_all(&circuit, number_qubits, 5);
+    assert!(rows.iter().all(|row| row.iter().all(|bit| *bit)));
+
+    let mut circuit = Circuit::new();
+    circuit += MultiQubitZZ::new(vec![0, 1, 2], 0.3.into());
+    assert_eq!(
+        StabilizerSimulator::new(3).run_circuit(&circuit),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "StabilizerSimulator",
+            hqslang: "MultiQubitZZ"
+        })
+    );
+}
+
+/// Test that non-Clifford and unsupported operations are rejected
+#[test_case(TGate::new(0).into(), "TGate"; "t_gate")]
+#[test_case(RotateX::new(0, 0.3.into()).into(), "RotateX"; "rotate_x")]
+#[test_case(ControlledPhaseShift::new(0, 1, (PI / 4.0).into()).into(), "ControlledPhaseShift"; "controlled_phase")]
+#[test_case(PragmaDamping::new(0, 1.0.into(), 1.0.into()).into(), "PragmaDamping"; "damping")]
+fn test_not_in_backend(operation: Operation, hqslang: &'static str) {
+    let mut circuit = Circuit::new();
+    circuit += operation;
+    assert_eq!(
+        StabilizerSimulator::new(2).run_circuit(&circuit),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "StabilizerSimulator",
+            hqslang
+        })
+    );
+}
@@ -0,0 +1,417 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use num_complex::Complex64;
+use roqoqo::backends::{EvaluatingBackend, StatevectorSimulator};
+use roqoqo::measurements::{
+    BasisRotation, BasisRotationInput, CheatedBasisRotation, CheatedBasisRotationInput,
+};
+use roqoqo::operations::*;
+use roqoqo::prelude::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+fn bell_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit
+}
+
+/// Test that the getters and setters of the StatevectorSimulator work
+#[test]
+fn test_simulator_new() {
+    let mut backend = StatevectorSimulator::new(3);
+    assert_eq!(backend.number_qubits(), 3);
+    assert_eq!(backend.random_seed(), None);
+    backend.set_random_seed(Some(11));
+    assert_eq!(backend.random_seed(), Some(11));
+    assert!(!backend.shot_resolved());
+    backend.set_shot_resolved(true);
+    assert!(backend.shot_resolved());
+    assert_eq!(backend.clone(), backend);
+}
+
+/// Test that the state vector is returned in the index convention of roqoqo
+#[test]
+fn test_get_statevector() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("state".to_string(), 4, true);
+    circuit += PauliX::new(0);
+    circuit += PragmaGetStateVector::new("state".to_string(), None);
+    let backend = StatevectorSimulator::new(2);
+    let (_, _, complex_registers) = backend.run_circuit(&circuit).unwrap();
+    let state = &complex_registers["state"][0];
+    assert_eq!(state.len(), 4);
+    assert!((state[1] - Complex64::new(1.0, 0.0)).norm() < 1e-10);
+    assert!(state[0].norm() < 1e-10);
+    assert!(state[2].norm() < 1e-10);
+    assert!(state[3].norm() < 1e-10);
+}
+
+/// Test that multi qubit gates and global phases are applied correctly
+#[test]
+fn test_multi_qubit_gate_and_global_phase() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("state".to_string(), 8, true);
+    circuit += MultiQubitMS::new(vec![0, 1, 2], PI.into());
+    circuit += PragmaGlobalPhase::new((PI / 2.0).into());
+    circuit += PragmaGetStateVector::new("state".to_string(), None);
+    let backend = StatevectorSimulator::new(3);
+    let (_, _, complex_registers) = backend.run_circuit(&circuit).unwrap();
+    let state = &complex_registers["state"][0];
+    // exp(-i pi/2 XXX)|000> = -i|111>, times the global phase i
+    assert!((state[7] - Complex64::new(1.0, 0.0)).norm() < 1e-10);
+}
+
+/// Test the occupation probability and the density matrix of a Bell state
+#[test]
+fn test_get_occupation_probability_and_density_matrix() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("probabilities".to_string(), 4, true);
+    circuit += DefinitionComplex::new("density_matrix".to_string(), 16, true);
+    circuit += bell_circuit();
+    circuit += PragmaGetOccupationProbability::new("probabilities".to_string(), None);
+    circuit += PragmaGetDensityMatrix::new("density_matrix".to_string(), None);
+    let backend = StatevectorSimulator::new(2);
+    let (_, float_registers, complex_registers) = backend.run_circuit(&circuit).unwrap();
+    let probabilities = &float_registers["probabilities"][0];
+    for (value, expected) in probabilities.iter().zip([0.5, 0.0, 0.0, 0.5]) {
+        assert!((value - expected).abs() < 1e-10);
+    }
+    let density_matrix = &complex_registers["density_matrix"][0];
+    assert_eq!(density_matrix.len(), 16);
+    assert!((density_matrix[0] - Complex64::new(0.5, 0.0)).norm() < 1e-10);
+    assert!((density_matrix[3] - Complex64::new(0.5, 0.0)).norm() < 1e-10);
+    assert!((density_matrix[12] - Complex64::new(0.5, 0.0)).norm() < 1e-10);
+    assert!((density_matrix[15] - Complex64::new(0.5, 0.0)).norm() < 1e-10);
+}
+
+/// Test the expectation values of Pauli products with and without preparation circuit
+#[test]
+fn test_get_pauli_product() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("zz".to_string(), 1, true);
+    circuit += DefinitionFloat::new("z".to_string(), 1, true);
+    circuit += DefinitionFloat::new("xx".to_string(), 1, true);
+    circuit += bell_circuit();
+    let mut zz = HashMap::new();
+    zz.insert(0, 3);
+    zz.insert(1, 3);
+    circuit += PragmaGetPauliProduct::new(zz.clone(), "zz".to_string(), Circuit::new());
+    let mut z = HashMap::new();
+    z.insert(0, 3);
+    circuit += PragmaGetPauliProduct::new(z, "z".to_string(), Circuit::new());
+    let mut rotation = Circuit::new();
+    rotation += Hadamard::new(0);
+    rotation += Hadamard::new(1);
+    circuit += PragmaGetPauliProduct::new(zz, "xx".to_string(), rotation);
+    let backend = StatevectorSimulator::new(2);
+    let (_, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    assert!((float_registers["zz"][0][0] - 1.0).abs() < 1e-10);
+    assert!(float_registers["z"][0][0].abs() < 1e-10);
+    assert!((float_registers["xx"][0][0] - 1.0).abs() < 1e-10);
+}
+
+/// Test that repeated measurements of a Bell state are always correlated
+#[test]
+fn test_repeated_measurement() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += bell_circuit();
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 200, None);
+    let backend = StatevectorSimulator::new(2);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    let rows = &bit_registers["ro"];
+    assert_eq!(rows.len(), 200);
+    assert!(rows.iter().all(|row| row.len() == 2 && row[0] == row[1]));
+    assert!(rows.iter().any(|row| row[0]));
+    assert!(rows.iter().any(|row| !row[0]));
+}
+
+/// Test that the qubit mapping of a repeated measurement is applied
+#[test]
+fn test_repeated_measurement_qubit_mapping() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PauliX::new(0);
+    let mut mapping = HashMap::new();
+    mapping.insert(0, 1);
+    mapping.insert(1, 0);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, Some(mapping));
+    let backend = StatevectorSimulator::new(2);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert!(bit_registers["ro"]
+        .iter()
+        .all(|row| row == &vec![false, true]));
+}
+
+/// Test that qubit mappings containing qubits the simulator does not have are refused
+#[test]
+fn test_repeated_measurement_invalid_qubit_mapping() {
+    let backend = StatevectorSimulator::new(2);
+    for qubit in [2, 70] {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new("ro".to_string(), 2, true);
+        let mut mapping = HashMap::new();
+        mapping.insert(qubit, 0);
+        circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, Some(mapping));
+        assert!(matches!(
+            backend.run_circuit(&circuit),
+            Err(RoqoqoBackendError::GenericError { .. })
+        ));
+    }
+}
+
+/// Test that registers of too many qubits are refused instead of overflowing
+#[test]
+fn test_too_many_qubits() {
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(0);
+    for number_qubits in [64, 100] {
+        assert!(matches!(
+            StatevectorSimulator::new(number_qubits).run_circuit(&circuit),
+            Err(RoqoqoBackendError::GenericError { .. })
+        ));
+    }
+}
+
+/// Test single qubit measurements, active reset and repeated execution of the circuit
+#[test]
+fn test_measure_qubit_and_number_of_measurements() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 3, true);
+    circuit += PauliX::new(0);
+    circuit += PauliX::new(2);
+    circuit += PragmaActiveReset::new(2);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+    circuit += MeasureQubit::new(2, "ro".to_string(), 2);
+    circuit += PragmaSetNumberOfMeasurements::new(5, "ro".to_string());
+    let backend = StatevectorSimulator::new(3);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true, false, false]; 5]);
+}
+
+/// Returns a teleportation circuit moving the state RotateY(theta)|0> from qubit 0 to qubit 2.
+///
+/// The preparation is undone on qubit 2 so that every successful teleportation measures 0.
+fn teleportation_circuit(theta: f64) -> Circuit {
+    let mut correction_x = Circuit::new();
+    correction_x += PauliX::new(2);
+    let mut correction_z = Circuit::new();
+    correction_z += PauliZ::new(2);
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("M1M2".to_string(), 2, true);
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += RotateY::new(0, theta.into());
+    circuit += Hadamard::new(1);
+    circuit += CNOT::new(1, 2);
+    circuit += CNOT::new(0, 1);
+    circuit += Hadamard::new(0);
+    circuit += MeasureQubit::new(0, "M1M2".to_string(), 0);
+    circuit += MeasureQubit::new(1, "M1M2".to_string(), 1);
+    circuit += PragmaConditional::new("M1M2".to_string(), 1, correction_x);
+    circuit += PragmaConditional::new("M1M2".to_string(), 0, correction_z);
+    circuit += RotateY::new(2, (-theta).into());
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 200, Some(HashMap::from([(2, 0)])));
+    circuit
+}
+
+/// Test that PragmaConditional only executes its circuit when the condition bit is set
+#[test]
+fn test_pragma_conditional() {
+    let mut flip = Circuit::new();
+    flip += PauliX::new(1);
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PauliX::new(0);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PragmaConditional::new("ro".to_string(), 0, flip.clone());
+    circuit += PragmaConditional::new("ro".to_string(), 1, flip);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+    let backend = StatevectorSimulator::new(2);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true, true]]);
+
+    let mut circuit = Circuit::new();
+    circuit += PragmaConditional::new("missing".to_string(), 0, Circuit::new());
+    assert!(backend.run_circuit(&circuit).is_err());
+}
+
+/// Test that the shot-resolved mode re-runs the mid-circuit measurement for every shot
+#[test]
+fn test_shot_resolved_rows_belong_to_the_same_shot() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("mid".to_string(), 1, true);
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += Hadamard::new(0);
+    circuit += MeasureQubit::new(0, "mid".to_string(), 0);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, None);
+    let mut backend = StatevectorSimulator::new(1);
+    backend.set_random_seed(Some(3));
+
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["mid"].len(), 1);
+    assert_eq!(
+        bit_registers["ro"],
+        vec![bit_registers["mid"][0].clone(); 100]
+    );
+
+    backend.set_shot_resolved(true);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["mid"].len(), 100);
+    assert_eq!(bit_registers["ro"], bit_registers["mid"]);
+    let number_ones = bit_registers["mid"].iter().filter(|row| row[0]).count();
+    assert!(number_ones > 20 && number_ones < 80);
+}
+
+/// Test that the teleportation protocol succeeds in every shot of a shot-resolved run
+#[test]
+fn test_shot_resolved_teleportation() {
+    let mut backend = StatevectorSimulator::new(3);
+    backend.set_random_seed(Some(7));
+    backend.set_shot_resolved(true);
+    let (bit_registers, _, _) = backend.run_circuit(&teleportation_circuit(1.1)).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![false]; 200]);
+    assert_eq!(bit_registers["M1M2"].len(), 200);
+    for outcome in [
+        vec![false, false],
+        vec![false, true],
+        vec![true, false],
+        vec![true, true],
+    ] {
+        assert!(bit_registers["M1M2"].contains(&outcome));
+    }
+}
+
+/// Test that the number of shots is set by the largest repeated measurement or repetition
+#[test]
+fn test_shot_resolved_number_of_shots() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("a".to_string(), 1, true);
+    circuit += DefinitionBit::new("b".to_string(), 1, true);
+    circuit += PragmaRepeatedMeasurement::new("a".to_string(), 3, None);
+    circuit += PragmaRepeatedMeasurement::new("b".to_string(), 5, None);
+    let mut backend = StatevectorSimulator::new(1);
+    backend.set_shot_resolved(true);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["a"].len(), 3);
+    assert_eq!(bit_registers["b"].len(), 5);
+
+    circuit += DefinitionBit::new("c".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "c".to_string(), 0);
+    circuit += PragmaSetNumberOfMeasurements::new(8, "c".to_string());
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["a"].len(), 3);
+    assert_eq!(bit_registers["b"].len(), 5);
+    assert_eq!(bit_registers["c"].len(), 8);
+}
+
+/// Test that a fixed random seed leads to reproducible results
+#[test]
+fn test_random_seed() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 3, true);
+    circuit += Hadamard::new(0);
+    circuit += Hadamard::new(1);
+    circuit += Hadamard::new(2);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 50, None);
+    let mut backend = StatevectorSimulator::new(3);
+    backend.set_random_seed(Some(42));
+    let (first, _, _) = backend.run_circuit(&circuit).unwrap();
+    let (second, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(first, second);
+}
+
+/// Test that unsupported operations and invalid circuits return errors
+#[test]
+fn test_errors() {
+    let backend = StatevectorSimulator::new(2);
+
+    let mut circuit = Circuit::new();
+    circuit += PragmaDamping::new(0, 1.0.into(), 0.1.into());
+    assert_eq!(
+        backend.run_circuit(&circuit),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "StatevectorSimulator",
+            hqslang: "PragmaDamping"
+        })
+    );
+
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(2);
+    assert!(backend.run_circuit(&circuit).is_err());
+
+    let mut circuit = Circuit::new();
+    circuit += RotateX::new(0, "theta".into());
+    assert!(backend.run_circuit(&circuit).is_err());
+
+    let mut circuit = Circuit::new();
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    assert!(backend.run_circuit(&circuit).is_err());
+}
+
+/// Test that a CheatedBasisRotation measurement can be evaluated with the simulator
+#[test]
+fn test_cheated_basis_rotation_measurement() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("zz".to_string(), 1, true);
+    circuit += RotateX::new(0, "theta".into());
+    circuit += CNOT::new(0, 1);
+    let mut zz = HashMap::new();
+    zz.insert(0, 3);
+    zz.insert(1, 3);
+    circuit += PragmaGetPauliProduct::new(zz, "zz".to_string(), Circuit::new());
+    let mut input = CheatedBasisRotationInput::new();
+    let index = input.add_pauli_product("zz".to_string());
+    input
+        .add_linear_exp_val("result".to_string(), HashMap::from([(index, 2.0)]))
+        .unwrap();
+    let measurement = CheatedBasisRotation {
+        constant_circuit: None,
+        circuits: vec![circuit],
+        input,
+    };
+    let measurement = measurement
+        .substitute_parameters(HashMap::from([("theta".to_string(), PI)]))
+        .unwrap();
+    let backend = StatevectorSimulator::new(2);
+    let result = backend.run_measurement(&measurement).unwrap().unwrap();
+    assert!((result["result"] - 2.0).abs() < 1e-10);
+}
+
+/// Test that a BasisRotation measurement can be evaluated with the simulator
+#[test]
+fn test_basis_rotation_measurement() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += bell_circuit();
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, None);
+    let mut input = BasisRotationInput::new(2, false);
+    let index = input
+        .add_pauli_product("ro".to_string(), vec![0, 1])
+        .unwrap();
+    input
+        .add_linear_exp_val("zz".to_string(), HashMap::from([(index, 1.0)]))
+        .unwrap();
+    let measurement = BasisRotation {
+        constant_circuit: None,
+        circuits: vec![circuit],
+        input,
+    };
+    let backend = StatevectorSimulator::new(2);
+    let result = backend.run_measurement(&measurement).unwrap().unwrap();
+    assert!((result["zz"] - 1.0).abs() < 1e-10);
+}
@@ -0,0 +1,196 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use num_complex::Complex64;
+use roqoqo::backends::{EvaluatingBackend, StatevectorSimulator, TensorNetworkSimulator};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+use std::f64::consts::PI;
+use test_case::test_case;
+
+/// Returns all bitstrings of a number of qubits, ordered by the index of the basis state.
+fn all_bitstrings(number_qubits: usize) -> Vec<Vec<bool>> {
+    (0..1_usize << number_qubits)
+        .map(|index| {
+            (0..number_qubits)
+                .map(|qubit| (index >> qubit) & 1 == 1)
+                .collect()
+        })
+        .collect()
+}
+
+fn mixed_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += Hadamard::new(0);
+    circuit += RotateY::new(1, 0.3.into());
+    circuit += RotateX::new(2, 1.2.into());
+    circuit += CNOT::new(0, 1);
+    circuit += ControlledPhaseShift::new(2, 3, 0.7.into());
+    circuit += ISwap::new(1, 3);
+    circuit += SqrtISwap::new(0, 2);
+    circuit += MultiQubitMS::new(vec![0, 1, 2], 0.4.into());
+    circuit += MultiQubitZZ::new(vec![1, 2, 3], 0.9.into());
+    circuit += MultiQubitMS::new(vec![3, 0], 0.2.into());
+    circuit += RotateZ::new(3, 0.5.into());
+    circuit += SWAP::new(0, 3);
+    circuit += PragmaGlobalPhase::new(0.25.into());
+    circuit
+}
+
+/// Test that the getters and setters of the TensorNetworkSimulator work
+#[test]
+fn test_simulator_new() {
+    let mut backend = TensorNetworkSimulator::new(40);
+    assert_eq!(backend.number_qubits(), 40);
+    assert_eq!(backend.max_tensor_rank(), 28);
+    backend.set_max_tensor_rank(20);
+    assert_eq!(backend.max_tensor_rank(), 20);
+    assert_eq!(backend.clone(), backend);
+}
+
+/// Test that all amplitudes agree with the state vector of the StatevectorSimulator
+#[test_case(None; "without_preparation")]
+#[test_case(Some(mixed_circuit()); "with_preparation")]
+fn test_amplitudes_agree_with_statevector(preparation: Option<Circuit>) {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("amplitudes".to_string(), 16, true);
+    circuit += DefinitionComplex::new("state".to_string(), 16, true);
+    circuit += mixed_circuit();
+    circuit += PragmaGetAmplitudes::new(
+        "amplitudes".to_string(),
+        all_bitstrings(4),
+        preparation.clone(),
+    );
+    circuit += PragmaGetStateVector::new("state".to_string(), preparation);
+
+    let (_, _, statevector_registers) = StatevectorSimulator::new(4).run_circuit(&circuit).unwrap();
+    let mut amplitude_circuit = Circuit::new();
+    for operation in circuit.iter() {
+        if !matches!(operation, Operation::PragmaGetStateVector(_)) {
+            amplitude_circuit += operation.clone();
+        }
+    }
+    let (_, _, tensor_registers) = TensorNetworkSimulator::new(4)
+        .run_circuit(&amplitude_circuit)
+        .unwrap();
+
+    let state = &statevector_registers["state"][0];
+    assert_eq!(&statevector_registers["amplitudes"][0], state);
+    let amplitudes = &tensor_registers["amplitudes"][0];
+    assert_eq!(amplitudes.len(), 16);
+    for (amplitude, expected) in amplitudes.iter().zip(state.iter()) {
+        assert!((amplitude - expected).norm() < 1e-10);
+    }
+}
+
+/// Test the amplitudes of a 50 qubit GHZ state
+#[test]
+fn test_ghz_amplitudes() {
+    let number_qubits = 50;
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("amplitudes".to_string(), 3, true);
+    circuit += Hadamard::new(0);
+    for qubit in 0..number_qubits - 1 {
+        circuit += CNOT::new(qubit, qubit + 1);
+    }
+    let mut mixed = vec![true; number_qubits];
+    mixed[17] = false;
+    circuit += PragmaGetAmplitudes::new(
+        "amplitudes".to_string(),
+        vec![vec![false; number_qubits], vec![true; number_qubits], mixed],
+        None,
+    );
+    let backend = TensorNetworkSimulator::new(number_qubits);
+    let (_, _, complex_registers) = backend.run_circuit(&circuit).unwrap();
+    let amplitudes = &complex_registers["amplitudes"][0];
+    let expected = Complex64::new(0.5_f64.sqrt(), 0.0);
+    assert!((amplitudes[0] - expected).norm() < 1e-10);
+    assert!((amplitudes[1] - expected).norm() < 1e-10);
+    assert!(amplitudes[2].norm() < 1e-10);
+}
+
+/// Test a shallow random-like circuit on 56 qubits where every amplitude has the same magnitude
+#[test]
+fn test_wide_cluster_state() {
+    let number_qubits = 56;
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("amplitudes".to_string(), 2, true);
+    for qubit in 0..number_qubits {
+        circuit += Hadamard::new(qubit);
+    }
+    for layer in 0..2 {
+        for qubit in (layer..number_qubits - 1).step_by(2) {
+            circuit += ControlledPauliZ::new(qubit, qubit + 1);
+        }
+        for qubit in 0..number_qubits {
+            circuit += RotateZ::new(qubit, (PI * (qubit % 5) as f64 / 7.0).into());
+        }
+    }
+    let bitstring: Vec<bool> = (0..number_qubits).map(|qubit| qubit % 3 == 0).collect();
+    circuit += PragmaGetAmplitudes::new(
+        "amplitudes".to_string(),
+        vec![bitstring, vec![true; number_qubits]],
+        None,
+    );
+    let backend = TensorNetworkSimulator::new(number_qubits);
+    let (_, _, complex_registers) = backend.run_circuit(&circuit).unwrap();
+    let expected = 2_f64.powi(-(number_qubits as i32) / 2);
+    for amplitude in complex_registers["amplitudes"][0].iter() {
+        assert!((amplitude.norm() - expected).abs() < 1e-6 * expected);
+    }
+}
+
+/// Test that exceeding the maximum tensor rank returns an error
+#[test]
+fn test_max_tensor_rank() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("amplitudes".to_string(), 1, true);
+    for qubit in 0..6 {
+        circuit += Hadamard::new(qubit);
+    }
+    for first in 0..6 {
+        for second in first + 1..6 {
+            circuit += ControlledPhaseShift::new(first, second, 0.3.into());
+        }
+    }
+    circuit += PragmaGetAmplitudes::new("amplitudes".to_string(), vec![vec![true; 6]], None);
+    let mut backend = TensorNetworkSimulator::new(6);
+    assert!(backend.run_circuit(&circuit).is_ok());
+    backend.set_max_tensor_rank(2);
+    assert!(backend.run_circuit(&circuit).is_err());
+}
+
+/// Test that unsupported operations and invalid circuits return errors
+#[test]
+fn test_errors() {
+    let backend = TensorNetworkSimulator::new(2);
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    assert_eq!(
+        backend.run_circuit(&circuit),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "TensorNetworkSimulator",
+            hqslang: "MeasureQubit"
+        })
+    );
+
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(2);
+    assert!(backend.run_circuit(&circuit).is_err());
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("amplitudes".to_string(), 1, true);
+    circuit += PragmaGetAmplitudes::new("amplitudes".to_string(), vec![vec![false; 3]], None);
+    assert!(backend.run_circuit(&circuit).is_err());
+}
@@ -0,0 +1,199 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ndarray::Array2;
+use roqoqo::backends::{DensityMatrixSimulator, EvaluatingBackend, TrajectorySimulator};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, RoqoqoBackendError};
+
+fn noisy_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("probabilities".to_string(), 4, true);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaDamping::new(0, 1.0.into(), 0.3.into());
+    circuit += PragmaDepolarising::new(1, 1.0.into(), 0.2.into());
+    circuit += RotateX::new(1, 0.4.into());
+    circuit += PragmaDephasing::new(0, 1.0.into(), 0.5.into());
+    circuit += CNOT::new(1, 0);
+    circuit += PragmaGetOccupationProbability::new("probabilities".to_string(), None);
+    circuit
+}
+
+/// Test that the getters and setters of the TrajectorySimulator work
+#[test]
+fn test_simulator_new() {
+    let mut backend = TrajectorySimulator::new(2, 100);
+    assert_eq!(backend.number_qubits(), 2);
+    assert_eq!(backend.number_trajectories(), 100);
+    assert_eq!(backend.random_seed(), None);
+    backend.set_number_trajectories(0);
+    assert_eq!(backend.number_trajectories(), 1);
+    backend.set_random_seed(Some(4));
+    assert_eq!(backend.random_seed(), Some(4));
+}
+
+/// Test that the trajectory average agrees with the density matrix simulation within the error bars
+#[test]
+fn test_agrees_with_density_matrix_simulator() {
+    let circuit = noisy_circuit();
+    let (_, expected, _) = DensityMatrixSimulator::new(2)
+        .run_circuit(&circuit)
+        .unwrap();
+    let mut backend = TrajectorySimulator::new(2, 2000);
+    backend.set_random_seed(Some(21));
+    let (_, result, _) = backend.run_circuit(&circuit).unwrap();
+    let mean = &result["probabilities"];
+    let error = &result["probabilities_error"];
+    assert_eq!(mean.len(), 1);
+    assert_eq!(error.len(), 1);
+    for ((value, sigma), exact) in mean[0]
+        .iter()
+        .zip(error[0].iter())
+        .zip(expected["probabilities"][0].iter())
+    {
+        assert!(*sigma > 0.0);
+        assert!((value - exact).abs() < 5.0 * sigma + 1e-10);
+    }
+    assert!((mean[0].iter().sum::<f64>() - 1.0).abs() < 1e-10);
+}
+
+/// Test that PragmaBoostNoise is applied to the unravelled noise
+#[test]
+fn test_boost_noise() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("probabilities".to_string(), 2, true);
+    circuit += PauliX::new(0);
+    circuit += PragmaBoostNoise::new(2.0.into());
+    circuit += PragmaRandomNoise::new(0, 0.5.into(), 0.3.into(), 0.2.into());
+    circuit += Hadamard::new(0);
+    circuit += PragmaDamping::new(0, 1.0.into(), 0.2.into());
+    circuit += PragmaGetOccupationProbability::new("probabilities".to_string(), None);
+    let (_, expected, _) = DensityMatrixSimulator::new(1)
+        .run_circuit(&circuit)
+        .unwrap();
+    let mut backend = TrajectorySimulator::new(1, 2000);
+    backend.set_random_seed(Some(8));
+    let (_, result, _) = backend.run_circuit(&circuit).unwrap();
+    let sigma = result["probabilities_error"][0][0];
+    assert!((result["probabilities"][0][0] - expected["probabilities"][0][0]).abs() < 5.0 * sigma);
+}
+
+/// Test that repeated measurements are distributed over the trajectories and have error bars
+#[test]
+fn test_sampled_registers() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PauliX::new(0);
+    circuit += PragmaDamping::new(0, 1.0.into(), 0.7.into());
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 1001, None);
+    let mut backend = TrajectorySimulator::new(2, 100);
+    backend.set_random_seed(Some(2));
+    let (bit_registers, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"].len(), 1001);
+    let mean = &float_registers["ro_mean"][0];
+    let error = &float_registers["ro_error"][0];
+    let exact = (-0.7_f64).exp();
+    assert!((mean[0] - exact).abs() < 5.0 * error[0]);
+    assert_eq!(mean[1], 0.0);
+    assert_eq!(error[1], 0.0);
+
+    // Same seed, same result
+    let (second, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers, second);
+}
+
+/// Test that statistics registers clashing with registers of the circuit are refused
+#[test]
+fn test_register_name_clash() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += DefinitionFloat::new("ro_error".to_string(), 1, true);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    assert!(matches!(
+        TrajectorySimulator::new(1, 2).run_circuit(&circuit),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("probabilities".to_string(), 2, true);
+    circuit += DefinitionFloat::new("probabilities_error".to_string(), 2, true);
+    circuit += PragmaGetOccupationProbability::new("probabilities".to_string(), None);
+    assert!(matches!(
+        TrajectorySimulator::new(1, 2).run_circuit(&circuit),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
+
+/// Test that single qubit measurements are recorded once per trajectory
+#[test]
+fn test_measure_qubit() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += PauliX::new(0);
+    circuit += PragmaDepolarising::new(0, 1.0.into(), 0.1.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    let backend = TrajectorySimulator::new(1, 50);
+    let (bit_registers, float_registers, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"].len(), 50);
+    assert!(float_registers.contains_key("ro_mean"));
+
+    circuit += PragmaSetNumberOfMeasurements::new(80, "ro".to_string());
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"].len(), 80);
+}
+
+/// Test that registers of too many qubits are refused instead of overflowing
+#[test]
+fn test_too_many_qubits() {
+    let mut circuit = Circuit::new();
+    circuit += PauliX::new(0);
+    assert!(matches!(
+        TrajectorySimulator::new(64, 1).run_circuit(&circuit),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
+
+/// Test that unsupported operations are reported for the TrajectorySimulator
+#[test]
+fn test_not_in_backend() {
+    let mut circuit = Circuit::new();
+    circuit += PragmaSetDensityMatrix::new(Array2::zeros((2, 2)));
+    assert_eq!(
+        TrajectorySimulator::new(1, 1).run_circuit(&circuit),
+        Err(RoqoqoBackendError::OperationNotInBackend {
+            backend: "TrajectorySimulator",
+            hqslang: "PragmaSetDensityMatrix"
+        })
+    );
+}
+
+/// Test that noise inside a PragmaConditional is applied in the trajectories meeting the condition
+#[test]
+fn test_pragma_conditional() {
+    let mut conditional_circuit = Circuit::new();
+    conditional_circuit += PauliX::new(1);
+    conditional_circuit += PragmaRandomNoise::new(1, 1.0.into(), 0.0.into(), 0.0.into());
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += Hadamard::new(0);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PragmaConditional::new("ro".to_string(), 0, conditional_circuit);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+    let mut backend = TrajectorySimulator::new(2, 100);
+    backend.set_random_seed(Some(5));
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"].len(), 100);
+    for row in bit_registers["ro"].iter() {
+        assert_eq!(row[0], row[1]);
+    }
+}
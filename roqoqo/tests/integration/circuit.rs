@@ -443,3 +443,26 @@ fn test_overrotate() {
     assert_eq!(t.qubit(), &1);
     assert_ne!(t.theta(), &2.0.into());
 }
+
+/// Test that circuits serialized with bincode by roqoqo 0.9.0 still deserialize to the same operations
+#[cfg(feature = "serialize")]
+#[test]
+fn test_deserialize_stored_bincode() {
+    let stored: Vec<u8> = vec![
+        2, 0, 0, 0, 0, 0, 0, 0, 66, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 114, 111, 2, 0, 0, 0, 0, 0, 0,
+        0, 1, 63, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 102, 1, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0,
+        0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 34, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0,
+        0, 0, 0, 0, 0, 62, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 114, 111, 10, 0, 0, 0, 0, 0, 0, 0, 1,
+        1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9, 0,
+        0, 0,
+    ];
+    let mut expected = Circuit::new();
+    expected += DefinitionBit::new("ro".to_string(), 2, true);
+    expected += DefinitionFloat::new("f".to_string(), 1, false);
+    expected += PauliX::new(0);
+    expected += CNOT::new(0, 1);
+    expected += PragmaRepeatedMeasurement::new("ro".to_string(), 10, Some(HashMap::from([(0, 1)])));
+    let circuit: Circuit = bincode::deserialize(&stored).unwrap();
+    assert_eq!(circuit, expected);
+    assert_eq!(bincode::serialize(&expected).unwrap(), stored);
+}
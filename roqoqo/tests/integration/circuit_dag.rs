@@ -0,0 +1,109 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use qoqo_calculator::CalculatorFloat;
+use roqoqo::operations::*;
+use roqoqo::{Circuit, CircuitDag};
+
+fn circuit() -> Circuit {
+    let mut conditional_circuit = Circuit::new();
+    conditional_circuit += PauliX::new(2);
+
+    let mut circuit = Circuit::new();
+    circuit += Hadamard::new(0);
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += PauliX::new(1);
+    circuit += CNOT::new(0, 1);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PragmaConditional::new("ro".to_string(), 0, conditional_circuit);
+    circuit += RotateZ::new(3, CalculatorFloat::from("theta"));
+    circuit += PragmaRepeatedMeasurement::new("ro_all".to_string(), 10, None);
+    circuit += PauliX::new(1);
+    circuit += PragmaGlobalPhase::new(CalculatorFloat::from(1.0));
+    circuit += DefinitionFloat::new("float".to_string(), 1, false);
+    circuit
+}
+
+/// Test the dependencies of qubit wires, classical registers and barriers
+#[test]
+fn test_dependencies() {
+    let dag = CircuitDag::from(&circuit());
+    assert_eq!(dag.len(), 9);
+    assert!(!dag.is_empty());
+    assert_eq!(dag.definitions().len(), 2);
+    assert_eq!(dag.get(2), Some(&Operation::from(CNOT::new(0, 1))));
+    assert_eq!(dag.get(9), None);
+
+    let predecessors: Vec<&[usize]> = (0..dag.len())
+        .map(|node| dag.predecessors(node).unwrap())
+        .collect();
+    let expected: Vec<&[usize]> = vec![&[], &[], &[0, 1], &[2], &[3], &[], &[4, 5], &[6], &[6]];
+    assert_eq!(predecessors, expected);
+
+    let successors: Vec<&[usize]> = (0..dag.len())
+        .map(|node| dag.successors(node).unwrap())
+        .collect();
+    let expected: Vec<&[usize]> = vec![&[2], &[2], &[3], &[4], &[6], &[6], &[7, 8], &[], &[]];
+    assert_eq!(successors, expected);
+    assert_eq!(dag.predecessors(9), None);
+    assert_eq!(dag.successors(9), None);
+}
+
+/// Test the front layer and the as-soon-as-possible layers
+#[test]
+fn test_layers() {
+    let dag = CircuitDag::from(circuit());
+    assert_eq!(dag.front_layer(), vec![0, 1, 5]);
+    assert_eq!(
+        dag.layers(),
+        vec![
+            vec![0, 1, 5],
+            vec![2],
+            vec![3],
+            vec![4],
+            vec![6],
+            vec![7, 8]
+        ]
+    );
+    assert_eq!(dag.depth(), 6);
+
+    let empty = CircuitDag::from(Circuit::new());
+    assert!(empty.is_empty());
+    assert!(empty.front_layer().is_empty());
+    assert_eq!(empty.depth(), 0);
+}
+
+/// Test that iterating the graph and converting it back to a circuit is lossless
+#[test]
+fn test_to_circuit() {
+    let dag = CircuitDag::from(&circuit());
+    let nodes: Vec<usize> = dag.iter().map(|(node, _)| node).collect();
+    assert_eq!(nodes, (0..9).collect::<Vec<usize>>());
+    for (node, operation) in dag.iter() {
+        for predecessor in dag.predecessors(node).unwrap() {
+            assert!(*predecessor < node);
+        }
+        assert_eq!(Some(operation), dag.get(node));
+    }
+    assert_eq!(dag.to_circuit(), circuit());
+    assert_eq!(Circuit::from(dag), circuit());
+}
+
+/// Test serialization and deserialization of CircuitDag
+#[cfg(feature = "serialize")]
+#[test]
+fn test_serialization() {
+    let dag = CircuitDag::from(&circuit());
+    let serialized = bincode::serialize(&dag).unwrap();
+    let deserialized: CircuitDag = bincode::deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, dag);
+}
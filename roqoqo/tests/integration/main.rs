@@ -19,8 +19,20 @@ mod measurements;
 #[cfg(test)]
 mod circuit;
 
+#[cfg(test)]
+mod circuit_dag;
+
 #[cfg(test)]
 mod devices;
 
 #[cfg(test)]
 mod quantum_program;
+
+#[cfg(all(test, feature = "serialize"))]
+mod quantum_program_checkpoint;
+
+#[cfg(test)]
+mod backends;
+
+#[cfg(test)]
+mod passes;
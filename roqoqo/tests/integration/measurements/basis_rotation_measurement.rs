@@ -14,12 +14,13 @@
 
 use std::collections::HashMap;
 
+use ndarray::array;
 use qoqo_calculator::CalculatorFloat;
 use roqoqo::operations;
 use roqoqo::prelude::*;
 use roqoqo::Circuit;
 use roqoqo::{
-    measurements::{BasisRotation, BasisRotationInput},
+    measurements::{BasisRotation, BasisRotationInput, ReadoutErrorModel},
     registers::BitOutputRegister,
 };
 use test_case::test_case;
@@ -360,3 +361,155 @@ fn test_evaluate_symbolic(register: Vec<Vec<bool>>, constant: f64) {
         .unwrap();
     assert!((result.get("constant").unwrap() - constant).abs() < f64::EPSILON);
 }
+
+/// Returns a register containing each readout the given number of times
+fn register_from_counts(counts: &[(Vec<bool>, usize)]) -> BitOutputRegister {
+    counts
+        .iter()
+        .flat_map(|(readout, count)| std::iter::repeat(readout.clone()).take(*count))
+        .collect()
+}
+
+#[test]
+fn test_evaluate_readout_error_mitigation() {
+    let mut bri = BasisRotationInput::new(3, false);
+    let z0 = bri.add_pauli_product("ro".to_string(), vec![0]).unwrap();
+    let z1 = bri.add_pauli_product("ro".to_string(), vec![1]).unwrap();
+    let z1z2 = bri.add_pauli_product("ro".to_string(), vec![1, 2]).unwrap();
+    let z0z1z2 = bri
+        .add_pauli_product("ro".to_string(), vec![0, 1, 2])
+        .unwrap();
+    for (name, index) in [("z0", z0), ("z1", z1), ("z1z2", z1z2), ("z0z1z2", z0z1z2)] {
+        let mut linear_map: HashMap<usize, f64> = HashMap::new();
+        linear_map.insert(index, 1.0);
+        bri.add_linear_exp_val(name.to_string(), linear_map)
+            .unwrap();
+    }
+    // Qubit 0 is in state 1, qubits 1 and 2 are in state 1 and have correlated readout errors.
+    // The readouts follow the exact distribution of the readout error model.
+    let mut counts: Vec<(Vec<bool>, usize)> = Vec::new();
+    for (qubit_0, count_0) in [(true, 17), (false, 3)] {
+        for (qubit_1, qubit_2, count_12) in [
+            (true, true, 15),
+            (false, false, 1),
+            (true, false, 2),
+            (false, true, 2),
+        ] {
+            counts.push((vec![qubit_0, qubit_1, qubit_2], count_0 * count_12));
+        }
+    }
+    let register = register_from_counts(&counts);
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(0, 0.1, 0.15).unwrap();
+    model
+        .set_correlated_error(
+            vec![1, 2],
+            array![
+                [0.85, 0.05, 0.05, 0.05],
+                [0.05, 0.85, 0.05, 0.1],
+                [0.05, 0.05, 0.85, 0.1],
+                [0.05, 0.05, 0.05, 0.75]
+            ],
+        )
+        .unwrap();
+    let mut br = BasisRotation {
+        constant_circuit: None,
+        circuits: vec![Circuit::new()],
+        input: bri,
+    };
+    let mut measured_registers: HashMap<String, BitOutputRegister> = HashMap::new();
+    measured_registers.insert("ro".to_string(), register);
+
+    let unmitigated = br
+        .evaluate(measured_registers.clone(), HashMap::new(), HashMap::new())
+        .unwrap()
+        .unwrap();
+    assert!((unmitigated["z0"] + 0.7).abs() < 1e-10);
+
+    br.input.set_readout_error_model(Some(model));
+    let result = br
+        .evaluate(measured_registers, HashMap::new(), HashMap::new())
+        .unwrap()
+        .unwrap();
+    for (name, expected) in [("z0", -1.0), ("z1", -1.0), ("z1z2", 1.0), ("z0z1z2", -1.0)] {
+        assert!(
+            (result[name] - expected).abs() < 1e-10,
+            "{}: {}",
+            name,
+            result[name]
+        );
+    }
+}
+
+#[test]
+fn test_evaluate_readout_error_mitigation_flipped() {
+    let mut bri = BasisRotationInput::new(1, true);
+    let z0 = bri.add_pauli_product("ro".to_string(), vec![0]).unwrap();
+    let mut linear_map: HashMap<usize, f64> = HashMap::new();
+    linear_map.insert(z0, 1.0);
+    bri.add_linear_exp_val("z0".to_string(), linear_map)
+        .unwrap();
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(0, 0.1, 0.2).unwrap();
+    bri.set_readout_error_model(Some(model));
+    let br = BasisRotation {
+        constant_circuit: None,
+        circuits: vec![Circuit::new()],
+        input: bri,
+    };
+    // The qubit is in state 1, the flipped measurement reads out the physical state 0
+    let mut measured_registers: HashMap<String, BitOutputRegister> = HashMap::new();
+    measured_registers.insert(
+        "ro".to_string(),
+        register_from_counts(&[(vec![false], 2), (vec![true], 8)]),
+    );
+    measured_registers.insert(
+        "ro_flipped".to_string(),
+        register_from_counts(&[(vec![false], 9), (vec![true], 1)]),
+    );
+    let result = br
+        .evaluate(measured_registers, HashMap::new(), HashMap::new())
+        .unwrap()
+        .unwrap();
+    assert!((result["z0"] + 1.0).abs() < 1e-10);
+}
+
+#[test]
+fn test_evaluate_readout_error_group_beyond_register() {
+    let mut bri = BasisRotationInput::new(2, false);
+    let z0 = bri.add_pauli_product("ro".to_string(), vec![0]).unwrap();
+    let mut linear_map: HashMap<usize, f64> = HashMap::new();
+    linear_map.insert(z0, 1.0);
+    bri.add_linear_exp_val("z0".to_string(), linear_map)
+        .unwrap();
+    // The group also contains qubit 5 which is not part of the readout,
+    // so that its readout errors are not applied and not mitigated
+    let mut model = ReadoutErrorModel::new();
+    model
+        .set_correlated_error(
+            vec![0, 5],
+            array![
+                [0.85, 0.05, 0.05, 0.05],
+                [0.05, 0.85, 0.05, 0.1],
+                [0.05, 0.05, 0.85, 0.1],
+                [0.05, 0.05, 0.05, 0.75]
+            ],
+        )
+        .unwrap();
+    bri.set_readout_error_model(Some(model));
+    let br = BasisRotation {
+        constant_circuit: None,
+        circuits: vec![Circuit::new()],
+        input: bri,
+    };
+    let mut measured_registers: HashMap<String, BitOutputRegister> = HashMap::new();
+    measured_registers.insert(
+        "ro".to_string(),
+        register_from_counts(&[(vec![true, false], 3), (vec![false, false], 1)]),
+    );
+    let result = br
+        .evaluate(measured_registers, HashMap::new(), HashMap::new())
+        .unwrap()
+        .unwrap();
+    assert!((result["z0"] + 0.5).abs() < 1e-10);
+}
@@ -20,3 +20,5 @@ mod cheated_measurement;
 pub use cheated_measurement::*;
 mod classical_register_measurement;
 pub use classical_register_measurement::*;
+mod readout_error_model;
+pub use readout_error_model::*;
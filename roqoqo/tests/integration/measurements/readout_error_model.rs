@@ -0,0 +1,140 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Integration test for public API of the readout error model
+
+use ndarray::{array, Array2};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use roqoqo::measurements::ReadoutErrorModel;
+use roqoqo::registers::BitOutputRegister;
+use roqoqo::RoqoqoError;
+use test_case::test_case;
+
+#[test]
+fn test_set_errors() {
+    let mut model = ReadoutErrorModel::new();
+    assert!(model.is_empty());
+    model.set_single_qubit_error(2, 0.1, 0.2).unwrap();
+    model
+        .set_correlated_error(vec![1, 0], Array2::eye(4))
+        .unwrap();
+    assert!(!model.is_empty());
+    assert_eq!(model.qubits(), vec![0, 1, 2]);
+    assert_eq!(
+        model.confusion_matrix(&[2]),
+        Some(&array![[0.9, 0.2], [0.1, 0.8]])
+    );
+    assert_eq!(model.confusion_matrix(&[1, 0]), Some(&Array2::eye(4)));
+    assert_eq!(model.confusion_matrix(&[0, 1]), None);
+
+    // Setting the error of the same qubits again replaces the previous error
+    model.set_single_qubit_error(2, 0.3, 0.0).unwrap();
+    assert_eq!(
+        model.confusion_matrix(&[2]),
+        Some(&array![[0.7, 0.0], [0.3, 1.0]])
+    );
+    assert_eq!(model.qubits(), vec![0, 1, 2]);
+    assert_eq!(model.clone(), model);
+}
+
+#[test_case(vec![], Array2::eye(1); "no qubits")]
+#[test_case(vec![0, 0], Array2::eye(4); "duplicate qubit")]
+#[test_case(vec![0], Array2::eye(4); "wrong shape")]
+#[test_case(vec![0], array![[1.5, 0.0], [-0.5, 1.0]]; "no probabilities")]
+#[test_case(vec![0], array![[0.9, 0.0], [0.0, 1.0]]; "not normalized")]
+#[test_case(vec![0], array![[0.5, 0.5], [0.5, 0.5]]; "not invertible")]
+#[test_case(vec![1, 2], Array2::eye(4); "overlapping qubits")]
+fn test_invalid_errors(qubits: Vec<usize>, confusion_matrix: Array2<f64>) {
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(1, 0.1, 0.1).unwrap();
+    let result = model.set_correlated_error(qubits, confusion_matrix);
+    assert!(matches!(
+        result,
+        Err(RoqoqoError::InvalidReadoutErrorModel { .. })
+    ));
+    assert_eq!(model.qubits(), vec![1]);
+}
+
+#[test]
+fn test_apply_to_register() {
+    let mut model = ReadoutErrorModel::new();
+    // Qubit 0 is always flipped, qubits 1 and 2 are always swapped
+    model.set_single_qubit_error(0, 1.0, 1.0).unwrap();
+    model
+        .set_correlated_error(
+            vec![1, 2],
+            array![
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0]
+            ],
+        )
+        .unwrap();
+    let mut register: BitOutputRegister = vec![
+        vec![false, true, false],
+        vec![true, true, true],
+        vec![false, true],
+    ];
+    let mut rng = StdRng::seed_from_u64(0);
+    model.apply_to_register(&mut register, &mut rng);
+    assert_eq!(
+        register,
+        vec![
+            vec![true, false, true],
+            vec![false, true, true],
+            vec![true, true]
+        ]
+    );
+}
+
+#[test]
+fn test_apply_to_register_statistics() {
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(0, 0.2, 0.0).unwrap();
+    let mut register: BitOutputRegister = vec![vec![false]; 10000];
+    let mut rng = StdRng::seed_from_u64(1);
+    model.apply_to_register(&mut register, &mut rng);
+    let flipped = register.iter().filter(|readout| readout[0]).count();
+    assert!((1800..2200).contains(&flipped), "{}", flipped);
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_serde() {
+    let mut model = ReadoutErrorModel::new();
+    model.set_single_qubit_error(0, 0.1, 0.2).unwrap();
+    let serialized = bincode::serialize(&model).unwrap();
+    let deserialized: ReadoutErrorModel = bincode::deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, model);
+}
+
+/// Serialized form of a readout error model that is not validated
+#[cfg(feature = "serialize")]
+#[derive(serde::Serialize)]
+struct RawReadoutErrorModel {
+    factors: Vec<(Vec<usize>, Array2<f64>)>,
+}
+
+/// Test that invalid readout error models are refused when deserializing
+#[cfg(feature = "serialize")]
+#[test_case(vec![(vec![0], array![[0.5, 0.5], [0.5, 0.5]])]; "not_invertible")]
+#[test_case(vec![(vec![0, 1], array![[0.9, 0.1], [0.1, 0.9]])]; "wrong_shape")]
+#[test_case(vec![(vec![0], array![[1.1, 0.0], [-0.1, 1.0]])]; "not_probabilities")]
+#[test_case(vec![(vec![], Array2::eye(1))]; "no_qubits")]
+#[test_case(vec![(vec![0], Array2::eye(2)), (vec![0], Array2::eye(2))]; "twice")]
+#[test_case(vec![(vec![0], Array2::eye(2)), (vec![1, 0], Array2::eye(4))]; "overlapping")]
+fn test_serde_invalid(factors: Vec<(Vec<usize>, Array2<f64>)>) {
+    let serialized = bincode::serialize(&RawReadoutErrorModel { factors }).unwrap();
+    assert!(bincode::deserialize::<ReadoutErrorModel>(&serialized).is_err());
+}
@@ -311,6 +311,122 @@ fn pragma_get_statevector_serde_compact() {
     );
 }
 
+/// Test PragmaGetAmplitudes inputs, involved qubits and standard derived traits
+#[test]
+fn pragma_get_amplitudes_inputs_qubits() {
+    let pragma = PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], None);
+    // Test inputs are correct
+    assert_eq!(pragma.readout(), &String::from("ro"));
+    assert_eq!(pragma.bitstrings(), &vec![vec![true, false]]);
+    assert_eq!(pragma.circuit(), &None);
+
+    // Test InvolveQubits trait
+    assert_eq!(pragma.involved_qubits(), InvolvedQubits::All);
+
+    // Test Debug, Clone and PartialEq traits
+    assert_eq!(
+        format!("{:?}", pragma),
+        "PragmaGetAmplitudes { readout: \"ro\", bitstrings: [[true, false]], circuit: None }"
+    );
+    assert_eq!(pragma.clone(), pragma);
+    let pragma_1 = PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true]], None);
+    assert!(pragma_1 != pragma);
+}
+
+/// Test PragmaGetAmplitudes Operate trait
+#[test]
+fn pragma_get_amplitudes_operate_trait() {
+    let pragma = PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], None);
+
+    // (1) Test tags function
+    let tags: &[&str; 4] = &[
+        "Operation",
+        "Measurement",
+        "PragmaOperation",
+        "PragmaGetAmplitudes",
+    ];
+    assert_eq!(pragma.tags(), tags);
+
+    // (2) Test hqslang function
+    assert_eq!(pragma.hqslang(), String::from("PragmaGetAmplitudes"));
+
+    // (3) Test is_parametrized function
+    assert!(!pragma.is_parametrized());
+}
+
+/// Test PragmaGetAmplitudes Substitute trait
+#[test]
+fn pragma_get_amplitudes_substitute_trait() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::from("theta")));
+    let pragma = PragmaGetAmplitudes::new(
+        String::from("ro"),
+        vec![vec![true, false], vec![false, true, true]],
+        Some(circuit),
+    );
+
+    // (1) Substitute parameters function
+    let mut substitution_dict: Calculator = Calculator::new();
+    substitution_dict.set_variable("theta", 0.5);
+    let result = pragma
+        .substitute_parameters(&mut substitution_dict)
+        .unwrap();
+    let mut circuit_substituted = Circuit::new();
+    circuit_substituted.add_operation(RotateX::new(0, CalculatorFloat::from(0.5)));
+    let test_pragma = PragmaGetAmplitudes::new(
+        String::from("ro"),
+        vec![vec![true, false], vec![false, true, true]],
+        Some(circuit_substituted),
+    );
+    assert_eq!(result, test_pragma);
+
+    // (2) Remap qubits function moves the bits of the bitstrings with the qubits
+    let mut qubit_mapping_test: HashMap<usize, usize> = HashMap::new();
+    qubit_mapping_test.insert(0, 3);
+    qubit_mapping_test.insert(3, 0);
+    let result = test_pragma.remap_qubits(&qubit_mapping_test).unwrap();
+    let mut circuit_remapped = Circuit::new();
+    circuit_remapped.add_operation(RotateX::new(3, CalculatorFloat::from(0.5)));
+    let remapped_pragma = PragmaGetAmplitudes::new(
+        String::from("ro"),
+        vec![
+            vec![false, false, false, true],
+            vec![false, true, true, false],
+        ],
+        Some(circuit_remapped),
+    );
+    assert_eq!(result, remapped_pragma);
+}
+
+/// Test PragmaGetAmplitudes Serialization and Deserialization traits (readable)
+#[cfg(feature = "serialize")]
+#[test]
+fn pragma_get_amplitudes_serde_readable() {
+    let pragma_serialization =
+        PragmaGetAmplitudes::new(String::from("ro"), vec![vec![true, false]], None);
+    assert_tokens(
+        &pragma_serialization.readable(),
+        &[
+            Token::Struct {
+                name: "PragmaGetAmplitudes",
+                len: 3,
+            },
+            Token::Str("readout"),
+            Token::Str("ro"),
+            Token::Str("bitstrings"),
+            Token::Seq { len: Some(1) },
+            Token::Seq { len: Some(2) },
+            Token::Bool(true),
+            Token::Bool(false),
+            Token::SeqEnd,
+            Token::SeqEnd,
+            Token::Str("circuit"),
+            Token::None,
+            Token::StructEnd,
+        ],
+    );
+}
+
 /// Test PragmaGetDensityMatrix inputs and involved qubits
 #[test]
 fn pragma_get_density_matrix_inputs_qubits() {
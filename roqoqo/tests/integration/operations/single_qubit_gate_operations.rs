@@ -1849,3 +1849,25 @@ pub fn test_tags(gate: SingleQubitGateOperation, tags: Vec<&str>) {
         assert_eq!(gate.tags()[i], tags[i]);
     }
 }
+
+/// Test that the product of a constant gate and its inverse is the identity
+#[test_case(ConstantGateOperation::from(PauliX::new(0)); "PauliX")]
+#[test_case(ConstantGateOperation::from(PauliY::new(0)); "PauliY")]
+#[test_case(ConstantGateOperation::from(PauliZ::new(0)); "PauliZ")]
+#[test_case(ConstantGateOperation::from(Hadamard::new(0)); "Hadamard")]
+#[test_case(ConstantGateOperation::from(SqrtPauliX::new(0)); "SqrtPauliX")]
+#[test_case(ConstantGateOperation::from(InvSqrtPauliX::new(0)); "InvSqrtPauliX")]
+#[test_case(ConstantGateOperation::from(SGate::new(0)); "SGate")]
+#[test_case(ConstantGateOperation::from(TGate::new(0)); "TGate")]
+fn test_inverse_constant_gate(gate: ConstantGateOperation) {
+    let inverse = gate.inverse();
+    assert_eq!(inverse.involved_qubits(), gate.involved_qubits());
+    let product = gate
+        .unitary_matrix()
+        .unwrap()
+        .dot(&inverse.unitary_matrix().unwrap());
+    let identity: Array2<Complex64> = Array2::eye(2);
+    for (value, expected) in product.iter().zip(identity.iter()) {
+        assert!((value - expected).norm() < 1e-10);
+    }
+}
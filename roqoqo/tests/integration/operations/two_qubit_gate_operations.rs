@@ -1082,3 +1082,26 @@ fn test_kakdecomposition_debug() {
     let message = "KakDecomposition { global_phase: Float(1.0), k_vector: [Float(0.0), Float(0.0), Float(0.0)], circuit_before: None, circuit_after: None }";
     assert_eq!(format!("{:?}", gate), message);
 }
+
+/// Test that the product of a constant gate and its inverse is the identity
+#[test_case(ConstantGateOperation::from(CNOT::new(0, 1)); "CNOT")]
+#[test_case(ConstantGateOperation::from(SWAP::new(0, 1)); "SWAP")]
+#[test_case(ConstantGateOperation::from(FSwap::new(0, 1)); "FSwap")]
+#[test_case(ConstantGateOperation::from(ControlledPauliY::new(0, 1)); "ControlledPauliY")]
+#[test_case(ConstantGateOperation::from(ControlledPauliZ::new(0, 1)); "ControlledPauliZ")]
+#[test_case(ConstantGateOperation::from(ISwap::new(0, 1)); "ISwap")]
+#[test_case(ConstantGateOperation::from(SqrtISwap::new(0, 1)); "SqrtISwap")]
+#[test_case(ConstantGateOperation::from(InvSqrtISwap::new(0, 1)); "InvSqrtISwap")]
+#[test_case(ConstantGateOperation::from(MolmerSorensenXX::new(0, 1)); "MolmerSorensenXX")]
+fn test_inverse_constant_gate(gate: ConstantGateOperation) {
+    let inverse = gate.inverse();
+    assert_eq!(inverse.involved_qubits(), gate.involved_qubits());
+    let product = gate
+        .unitary_matrix()
+        .unwrap()
+        .dot(&inverse.unitary_matrix().unwrap());
+    let identity: Array2<Complex64> = Array2::eye(4);
+    for (value, expected) in product.iter().zip(identity.iter()) {
+        assert!((value - expected).norm() < 1e-10);
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::chain_device::ChainDevice;
+use num_complex::Complex64;
+use qoqo_calculator::CalculatorFloat;
+use roqoqo::backends::{EvaluatingBackend, StatevectorSimulator};
+use roqoqo::devices::Device;
+use roqoqo::operations::*;
+use roqoqo::passes::{NativeGateDecompositionPass, Pass, PassManager};
+use roqoqo::{Circuit, RoqoqoError};
+use std::convert::TryFrom;
+use test_case::test_case;
+
+/// Chain devices with the bases {RotateZ, RotateX, CNOT}, {RotateZ, SqrtPauliX, ControlledPauliZ}
+/// and {RotateZ, RotateX, MolmerSorensenXX}.
+fn devices() -> Vec<ChainDevice> {
+    [
+        (["RotateZ", "RotateX"], "CNOT"),
+        (["RotateZ", "SqrtPauliX"], "ControlledPauliZ"),
+        (["RotateZ", "RotateX"], "MolmerSorensenXX"),
+    ]
+    .iter()
+    .map(|(single_qubit_gates, two_qubit_gate)| {
+        let mut device = ChainDevice::new(3);
+        device.single_qubit_gates = single_qubit_gates
+            .iter()
+            .map(|name| (name.to_string(), 1.0))
+            .collect();
+        device.two_qubit_gates = [(two_qubit_gate.to_string(), 2.0)].into_iter().collect();
+        device
+    })
+    .collect()
+}
+
+fn assert_native(circuit: &Circuit, device: &ChainDevice) {
+    for operation in circuit.iter() {
+        let native = if let Ok(gate) = SingleQubitGateOperation::try_from(operation.clone()) {
+            device
+                .single_qubit_gate_time(gate.hqslang(), gate.qubit())
+                .is_some()
+        } else if let Ok(gate) = TwoQubitGateOperation::try_from(operation.clone()) {
+            device
+                .two_qubit_gate_time(gate.hqslang(), gate.control(), gate.target())
+                .is_some()
+        } else {
+            GateOperation::try_from(operation.clone()).is_err()
+        };
+        assert!(native, "{} is not native", operation.hqslang());
+    }
+}
+
+fn assert_same_state(circuit: &Circuit, decomposed: &Circuit) {
+    let statevector = |circuit: &Circuit| -> Vec<Complex64> {
+        let mut full_circuit = Circuit::new();
+        full_circuit += DefinitionComplex::new("state".to_string(), 8, true);
+        full_circuit += circuit.clone();
+        full_circuit += PragmaGetStateVector::new("state".to_string(), None);
+        let (_, _, complex_registers) = StatevectorSimulator::new(3)
+            .run_circuit(&full_circuit)
+            .unwrap();
+        complex_registers["state"][0].clone()
+    };
+    let expected = statevector(circuit);
+    for (value, expected) in statevector(decomposed).iter().zip(expected.iter()) {
+        assert!((value - expected).norm() < 1e-8);
+    }
+}
+
+/// Returns a circuit preparing a generic state followed by the gate.
+fn circuit_with_gate(gate: Operation) -> Circuit {
+    let mut circuit = Circuit::new();
+    for qubit in 0..3 {
+        circuit += Hadamard::new(qubit);
+        circuit += RotateY::new(qubit, CalculatorFloat::from(0.3 * (qubit as f64 + 1.0)));
+        circuit += RotateZ::new(qubit, CalculatorFloat::from(0.7 + qubit as f64));
+    }
+    circuit += CNOT::new(1, 2);
+    circuit.add_operation(gate);
+    circuit
+}
+
+/// Test that single-qubit gates are decomposed into each basis keeping the state and global phase
+#[test_case(Hadamard::new(0).into(); "Hadamard")]
+#[test_case(PauliX::new(1).into(); "PauliX")]
+#[test_case(PauliY::new(2).into(); "PauliY")]
+#[test_case(PauliZ::new(0).into(); "PauliZ")]
+#[test_case(SGate::new(1).into(); "SGate")]
+#[test_case(TGate::new(2).into(); "TGate")]
+#[test_case(SqrtPauliX::new(0).into(); "SqrtPauliX")]
+#[test_case(InvSqrtPauliX::new(1).into(); "InvSqrtPauliX")]
+#[test_case(RotateX::new(2, 0.4.into()).into(); "RotateX")]
+#[test_case(RotateY::new(0, (-1.3).into()).into(); "RotateY")]
+#[test_case(PhaseShiftState0::new(1, 0.9.into()).into(); "PhaseShiftState0")]
+#[test_case(PhaseShiftState1::new(2, 2.9.into()).into(); "PhaseShiftState1")]
+#[test_case(RotateAroundSphericalAxis::new(0, 0.4.into(), 1.1.into(), 0.2.into()).into(); "RotateAroundSphericalAxis")]
+#[test_case(SingleQubitGate::new(1, 0.6.into(), 0.0.into(), 0.0.into(), 0.8.into(), 0.5.into()).into(); "SingleQubitGate")]
+fn test_single_qubit_gates(gate: Operation) {
+    let circuit = circuit_with_gate(gate);
+    for device in devices() {
+        let decomposed = NativeGateDecompositionPass::new()
+            .run(&circuit, Some(&device))
+            .unwrap();
+        assert_native(&decomposed, &device);
+        assert_same_state(&circuit, &decomposed);
+    }
+}
+
+/// Test that two-qubit gates are decomposed into each basis keeping the state and global phase
+#[test_case(CNOT::new(0, 1).into(); "CNOT")]
+#[test_case(CNOT::new(2, 1).into(); "CNOT_reversed")]
+#[test_case(SWAP::new(0, 1).into(); "SWAP")]
+#[test_case(ISwap::new(1, 2).into(); "ISwap")]
+#[test_case(FSwap::new(1, 0).into(); "FSwap")]
+#[test_case(SqrtISwap::new(0, 1).into(); "SqrtISwap")]
+#[test_case(InvSqrtISwap::new(0, 1).into(); "InvSqrtISwap")]
+#[test_case(XY::new(1, 2, 0.3.into()).into(); "XY")]
+#[test_case(ControlledPhaseShift::new(0, 1, 0.7.into()).into(); "ControlledPhaseShift")]
+#[test_case(ControlledPauliY::new(2, 1).into(); "ControlledPauliY")]
+#[test_case(ControlledPauliZ::new(0, 1).into(); "ControlledPauliZ")]
+#[test_case(MolmerSorensenXX::new(1, 2).into(); "MolmerSorensenXX")]
+#[test_case(VariableMSXX::new(0, 1, 0.8.into()).into(); "VariableMSXX")]
+#[test_case(GivensRotation::new(0, 1, 0.5.into(), 0.2.into()).into(); "GivensRotation")]
+#[test_case(GivensRotationLittleEndian::new(1, 2, 0.5.into(), 0.2.into()).into(); "GivensRotationLittleEndian")]
+#[test_case(Qsim::new(0, 1, 1.0.into(), 1.0.into(), (-1.0).into()).into(); "Qsim")]
+#[test_case(Fsim::new(1, 0, 1.0.into(), 2.0.into(), (-1.0).into()).into(); "Fsim")]
+#[test_case(SpinInteraction::new(0, 1, 1.0.into(), 2.0.into(), (-1.0).into()).into(); "SpinInteraction")]
+#[test_case(PMInteraction::new(1, 2, 0.6.into()).into(); "PMInteraction")]
+#[test_case(ComplexPMInteraction::new(0, 1, 1.0.into(), (-1.0).into()).into(); "ComplexPMInteraction")]
+#[test_case(Bogoliubov::new(0, 1, 1.0.into(), (-1.0).into()).into(); "Bogoliubov")]
+#[test_case(PhaseShiftedControlledZ::new(0, 1, 0.4.into()).into(); "PhaseShiftedControlledZ")]
+fn test_two_qubit_gates(gate: Operation) {
+    let circuit = circuit_with_gate(gate);
+    for device in devices() {
+        let decomposed = NativeGateDecompositionPass::new()
+            .run(&circuit, Some(&device))
+            .unwrap();
+        assert_native(&decomposed, &device);
+        assert_same_state(&circuit, &decomposed);
+    }
+}
+
+/// Test that multi-qubit gates are decomposed recursively and native gates are kept
+#[test]
+fn test_multi_qubit_gates_and_native_gates() {
+    let mut conditional_circuit = Circuit::new();
+    conditional_circuit += Hadamard::new(1);
+    let mut circuit = circuit_with_gate(MultiQubitMS::new(vec![0, 1, 2], 0.6.into()).into());
+    circuit += MultiQubitZZ::new(vec![0, 1, 2], 1.2.into());
+    circuit += PragmaGlobalPhase::new(0.3.into());
+    for device in devices() {
+        let decomposed = NativeGateDecompositionPass::new()
+            .run(&circuit, Some(&device))
+            .unwrap();
+        assert_native(&decomposed, &device);
+        assert_same_state(&circuit, &decomposed);
+
+        // The global phase of a multi-qubit gate is collected in one PragmaGlobalPhase
+        let mut gate_circuit = Circuit::new();
+        gate_circuit += MultiQubitMS::new(vec![2, 1, 0], 0.6.into());
+        let decomposed = NativeGateDecompositionPass::new()
+            .run(&gate_circuit, Some(&device))
+            .unwrap();
+        assert!(
+            decomposed
+                .iter()
+                .filter(|operation| operation.hqslang() == "PragmaGlobalPhase")
+                .count()
+                <= 1
+        );
+        assert_same_state(&gate_circuit, &decomposed);
+    }
+
+    let mut native = Circuit::new();
+    native += DefinitionBit::new("ro".to_string(), 1, true);
+    native += RotateZ::new(0, "theta".into());
+    native += CNOT::new(0, 1);
+    native += MeasureQubit::new(0, "ro".to_string(), 0);
+    native += PragmaConditional::new("ro".to_string(), 0, conditional_circuit);
+    let decomposed = NativeGateDecompositionPass::new()
+        .run(&native, Some(&devices()[0]))
+        .unwrap();
+    assert_eq!(decomposed.definitions(), native.definitions());
+    assert_eq!(decomposed.operations()[..3], native.operations()[..3]);
+    match &decomposed.operations()[3] {
+        Operation::PragmaConditional(conditional) => {
+            assert_native(conditional.circuit(), &devices()[0])
+        }
+        _ => panic!("Expected PragmaConditional"),
+    }
+}
+
+/// Test the errors for missing devices, symbolic parameters and missing native gates
+#[test]
+fn test_errors() {
+    let pass = NativeGateDecompositionPass::new();
+    let mut circuit = Circuit::new();
+    circuit += Hadamard::new(0);
+    assert!(matches!(
+        pass.run(&circuit, None),
+        Err(RoqoqoError::TranspilerPassError { .. })
+    ));
+
+    let mut symbolic = Circuit::new();
+    symbolic += RotateY::new(0, "theta".into());
+    assert!(matches!(
+        pass.run(&symbolic, Some(&devices()[0])),
+        Err(RoqoqoError::CalculatorError(_))
+    ));
+
+    let mut distant = Circuit::new();
+    distant += SWAP::new(0, 2);
+    assert!(matches!(
+        pass.run(&distant, Some(&devices()[0])),
+        Err(RoqoqoError::TranspilerPassError { .. })
+    ));
+}
+
+/// Test the pass in a PassManager
+#[test]
+fn test_pass_manager() {
+    let mut manager: PassManager = PassManager::new();
+    manager.add_pass(NativeGateDecompositionPass::new());
+    let device = devices()[2].clone();
+    let circuit = circuit_with_gate(ISwap::new(0, 1).into());
+    let (decomposed, statistics) = manager.run(&circuit, Some(&device)).unwrap();
+    assert_eq!(statistics[0].name, "NativeGateDecomposition");
+    // One MolmerSorensenXX for the CNOT of the state preparation and two for the ISwap
+    assert_eq!(
+        statistics[0].after.operation_counts.get("MolmerSorensenXX"),
+        Some(&3)
+    );
+    assert_native(&decomposed, &device);
+    assert_same_state(&circuit, &decomposed);
+}
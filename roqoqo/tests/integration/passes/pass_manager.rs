@@ -0,0 +1,149 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::backends::chain_device::ChainDevice;
+use roqoqo::devices::Device;
+use roqoqo::operations::*;
+use roqoqo::passes::{GateCounts, Pass, PassManager, RemapQubitsPass, TranspilerPass};
+use roqoqo::{Circuit, RoqoqoError};
+use std::collections::HashMap;
+
+fn circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += Hadamard::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    circuit
+}
+
+fn swap_mapping() -> HashMap<usize, usize> {
+    let mut mapping = HashMap::new();
+    mapping.insert(0, 1);
+    mapping.insert(1, 0);
+    mapping
+}
+
+/// Custom pass letting all qubits of the device sleep at the end of the circuit.
+#[derive(Debug, Clone, PartialEq)]
+struct SleepPass;
+
+impl Pass for SleepPass {
+    fn name(&self) -> &'static str {
+        "Sleep"
+    }
+
+    fn run(&self, circuit: &Circuit, device: Option<&dyn Device>) -> Result<Circuit, RoqoqoError> {
+        let device = device.ok_or_else(|| RoqoqoError::GenericError {
+            msg: "SleepPass requires a device".to_string(),
+        })?;
+        let qubits: Vec<usize> = (0..device.number_qubits()).collect();
+        Ok(circuit.clone() + PragmaSleep::new(qubits, 1.0.into()))
+    }
+}
+
+/// Test the gate counts of a circuit
+#[test]
+fn test_gate_counts() {
+    let mut test_circuit = circuit();
+    test_circuit += MultiQubitMS::new(vec![0, 1, 2], 1.0.into());
+    let counts = GateCounts::from_circuit(&test_circuit);
+    assert_eq!(counts.number_operations, 4);
+    assert_eq!(counts.number_gates, 3);
+    assert_eq!(counts.number_single_qubit_gates, 1);
+    assert_eq!(counts.number_two_qubit_gates, 1);
+    assert_eq!(counts.number_multi_qubit_gates, 1);
+    assert_eq!(counts.operation_counts["PragmaRepeatedMeasurement"], 1);
+    assert!(!counts.operation_counts.contains_key("DefinitionBit"));
+}
+
+/// Test that the RemapQubitsPass is equivalent to Circuit::remap_qubits
+#[test]
+fn test_remap_qubits_pass() {
+    let pass = RemapQubitsPass::new(swap_mapping());
+    assert_eq!(pass.mapping(), &swap_mapping());
+    assert_eq!(pass.name(), "RemapQubits");
+    assert_eq!(
+        pass.run(&circuit(), None),
+        circuit().remap_qubits(&swap_mapping())
+    );
+}
+
+/// Test that the passes are run in order and their statistics are recorded
+#[test]
+fn test_pass_manager() {
+    let mut pass_manager: PassManager = PassManager::new();
+    assert_eq!(pass_manager, PassManager::default());
+    pass_manager.add_pass(RemapQubitsPass::new(swap_mapping()));
+    pass_manager.add_pass(TranspilerPass::RemapQubits(RemapQubitsPass::new(
+        swap_mapping(),
+    )));
+    assert_eq!(pass_manager.passes().len(), 2);
+
+    let (transformed, statistics) = pass_manager.run(&circuit(), None).unwrap();
+    assert_eq!(transformed, circuit());
+    assert_eq!(statistics.len(), 2);
+    assert_eq!(statistics[0].name, "RemapQubits");
+    assert_eq!(statistics[0].before, GateCounts::from_circuit(&circuit()));
+    assert_eq!(statistics[0].after, statistics[1].before);
+    assert_eq!(statistics[1].after.number_gates, 2);
+    assert_eq!(Pass::run(&pass_manager, &circuit(), None), Ok(circuit()));
+}
+
+/// Test a pipeline of custom passes using a device
+#[test]
+fn test_custom_passes_with_device() {
+    let mut pass_manager: PassManager<SleepPass> = PassManager::new();
+    pass_manager.add_pass(SleepPass);
+    pass_manager.add_pass(SleepPass);
+    let device = ChainDevice::new(3);
+    let (transformed, statistics) = pass_manager.run(&circuit(), Some(&device)).unwrap();
+    assert_eq!(transformed.operations().len(), 5);
+    assert_eq!(statistics[1].after.operation_counts["PragmaSleep"], 2);
+    assert_eq!(statistics[1].after.number_gates, 2);
+
+    assert!(matches!(
+        pass_manager.run(&circuit(), None),
+        Err(RoqoqoError::GenericError { .. })
+    ));
+}
+
+/// Test that a pipeline can be serialized and deserialized
+#[cfg(feature = "serialize")]
+#[test]
+fn test_serialization() {
+    let mut pass_manager: PassManager = PassManager::new();
+    pass_manager.add_pass(RemapQubitsPass::new(swap_mapping()));
+    let serialized = bincode::serialize(&pass_manager).unwrap();
+    let deserialized: PassManager = bincode::deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, pass_manager);
+}
+
+/// Test the OverrotatePass
+#[cfg(feature = "overrotate")]
+#[test]
+fn test_overrotate_pass() {
+    use roqoqo::passes::OverrotatePass;
+    let mut test_circuit = Circuit::new();
+    test_circuit += PragmaOverrotation::new("RotateY".to_string(), vec![0], 1.0, 0.0);
+    test_circuit += RotateY::new(0, 1.0.into());
+    let pass_manager: PassManager = {
+        let mut pass_manager = PassManager::new();
+        pass_manager.add_pass(OverrotatePass::new());
+        pass_manager
+    };
+    let (transformed, statistics) = pass_manager.run(&test_circuit, None).unwrap();
+    assert_eq!(transformed, test_circuit.overrotate().unwrap());
+    assert_eq!(statistics[0].name, "Overrotate");
+    assert_eq!(statistics[0].before.number_operations, 2);
+    assert_eq!(statistics[0].after.number_operations, 1);
+}
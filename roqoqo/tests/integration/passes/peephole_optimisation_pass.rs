@@ -0,0 +1,153 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use num_complex::Complex64;
+use qoqo_calculator::CalculatorFloat;
+use roqoqo::backends::{EvaluatingBackend, StatevectorSimulator};
+use roqoqo::operations::*;
+use roqoqo::passes::{Pass, PassManager, PeepholeOptimisationPass};
+use roqoqo::Circuit;
+use test_case::test_case;
+
+fn optimise(circuit: &Circuit) -> Circuit {
+    PeepholeOptimisationPass::new().run(circuit, None).unwrap()
+}
+
+fn circuit_from(operations: Vec<Operation>) -> Circuit {
+    operations.into_iter().collect()
+}
+
+fn statevector(circuit: &Circuit) -> Vec<Complex64> {
+    let mut full_circuit = Circuit::new();
+    full_circuit += DefinitionComplex::new("state".to_string(), 8, true);
+    full_circuit += circuit.clone();
+    full_circuit += PragmaGetStateVector::new("state".to_string(), None);
+    let (_, _, complex_registers) = StatevectorSimulator::new(3)
+        .run_circuit(&full_circuit)
+        .unwrap();
+    complex_registers["state"][0].clone()
+}
+
+#[test_case(vec![CNOT::new(0, 1).into(), CNOT::new(0, 1).into()]; "CNOT")]
+#[test_case(vec![Hadamard::new(0).into(), Hadamard::new(0).into()]; "Hadamard")]
+#[test_case(vec![SqrtPauliX::new(1).into(), InvSqrtPauliX::new(1).into()]; "SqrtPauliX")]
+#[test_case(vec![SGate::new(1).into(), PhaseShiftState1::new(1, (-std::f64::consts::FRAC_PI_2).into()).into()]; "SGate")]
+#[test_case(vec![Hadamard::new(0).into(), PauliX::new(0).into(), PauliX::new(0).into(), Hadamard::new(0).into()]; "nested")]
+#[test_case(vec![RotateX::new(0, 0.5.into()).into(), RotateX::new(0, (-0.5).into()).into()]; "RotateX")]
+#[test_case(vec![RotateZ::new(0, 0.0.into()).into()]; "zero RotateZ")]
+#[test_case(vec![ControlledPauliZ::new(0, 1).into(), RotateZ::new(0, 1.0.into()).into(), ControlledPauliZ::new(0, 1).into(), RotateZ::new(0, (-1.0).into()).into()]; "commuted RotateZ")]
+#[test_case(vec![SWAP::new(0, 1).into(), SWAP::new(1, 0).into()]; "reversed SWAP")]
+#[test_case(vec![ControlledPauliZ::new(2, 0).into(), ControlledPauliZ::new(0, 2).into()]; "reversed ControlledPauliZ")]
+#[test_case(vec![SqrtISwap::new(1, 0).into(), InvSqrtISwap::new(0, 1).into()]; "reversed SqrtISwap")]
+#[test_case(vec![ISwap::new(0, 1).into(), XY::new(1, 0, (-std::f64::consts::PI).into()).into()]; "reversed ISwap")]
+/// Test that inverse gates and rotations adding up to zero are removed
+fn test_cancellation(operations: Vec<Operation>) {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    let optimised = optimise(&(circuit.clone() + circuit_from(operations)));
+    assert_eq!(optimised, circuit);
+}
+
+#[test_case(vec![CNOT::new(0, 1).into(), CNOT::new(1, 0).into()]; "reversed CNOT")]
+#[test_case(vec![CNOT::new(0, 1).into(), PauliX::new(1).into(), CNOT::new(0, 1).into()]; "CNOT with PauliX")]
+#[test_case(vec![Hadamard::new(0).into(), MeasureQubit::new(0, "ro".to_string(), 0).into(), Hadamard::new(0).into()]; "measurement")]
+#[test_case(vec![Hadamard::new(0).into(), PragmaRepeatedMeasurement::new("ro".to_string(), 2, None).into(), Hadamard::new(2).into()]; "barrier")]
+#[test_case(vec![RotateZ::new(0, 1.0.into()).into(), CNOT::new(0, 1).into(), RotateZ::new(0, 1.0.into()).into()]; "RotateZ with CNOT")]
+#[test_case(vec![RotateZ::new(0, 1.0.into()).into(), RotateX::new(0, 1.0.into()).into()]; "different axes")]
+/// Test that gates that cannot be combined are kept
+fn test_no_change(operations: Vec<Operation>) {
+    let circuit = circuit_from(operations);
+    assert_eq!(optimise(&circuit), circuit);
+}
+
+/// Test that deeply nested inverse gates are removed
+#[test]
+fn test_nested_cancellation() {
+    let gates: Vec<Operation> = (0..2000)
+        .map(|index| match index % 3 {
+            0 => Hadamard::new(index % 2).into(),
+            1 => CNOT::new(0, 1).into(),
+            _ => SWAP::new(1, 2).into(),
+        })
+        .collect();
+    let mut operations = gates.clone();
+    operations.extend(gates.into_iter().rev().map(|gate| match gate {
+        Operation::SWAP(_) => SWAP::new(2, 1).into(),
+        gate => gate,
+    }));
+    assert_eq!(optimise(&circuit_from(operations)), Circuit::new());
+}
+
+/// Test that rotations about the same axis are merged symbolically and past diagonal gates
+#[test]
+fn test_merge_rotations() {
+    let circuit = circuit_from(vec![
+        RotateZ::new(0, "theta".into()).into(),
+        ControlledPhaseShift::new(0, 1, 0.5.into()).into(),
+        RotateZ::new(0, 1.0.into()).into(),
+        ControlledPhaseShift::new(1, 0, 0.25.into()).into(),
+    ]);
+    let expected = circuit_from(vec![
+        RotateZ::new(
+            0,
+            CalculatorFloat::from("theta") + CalculatorFloat::from(1.0),
+        )
+        .into(),
+        ControlledPhaseShift::new(0, 1, 0.75.into()).into(),
+    ]);
+    assert_eq!(optimise(&circuit), expected);
+}
+
+/// Test that the optimised circuit produces the same state
+#[test]
+fn test_same_state() {
+    let circuit = circuit_from(vec![
+        Hadamard::new(0).into(),
+        Hadamard::new(1).into(),
+        Hadamard::new(2).into(),
+        TGate::new(0).into(),
+        ControlledPauliZ::new(0, 1).into(),
+        RotateZ::new(0, 0.3.into()).into(),
+        ControlledPhaseShift::new(0, 2, 0.7.into()).into(),
+        PhaseShiftState1::new(0, (-std::f64::consts::FRAC_PI_4).into()).into(),
+        RotateX::new(1, 0.2.into()).into(),
+        RotateX::new(1, 0.4.into()).into(),
+        ISwap::new(1, 2).into(),
+        XY::new(1, 2, (-std::f64::consts::PI).into()).into(),
+        PragmaGlobalPhase::new(0.1.into()).into(),
+        SqrtISwap::new(0, 1).into(),
+        InvSqrtISwap::new(0, 1).into(),
+    ]);
+    let optimised = optimise(&circuit);
+    assert_eq!(optimised.len(), 8);
+    let expected = statevector(&circuit);
+    for (value, expected) in statevector(&optimised).iter().zip(expected.iter()) {
+        assert!((value - expected).norm() < 1e-10);
+    }
+}
+
+/// Test the statistics of the pass in a PassManager
+#[test]
+fn test_pass_manager() {
+    let mut pass_manager: PassManager = PassManager::new();
+    pass_manager.add_pass(PeepholeOptimisationPass::new());
+    let circuit = circuit_from(vec![
+        CNOT::new(0, 1).into(),
+        CNOT::new(0, 1).into(),
+        Hadamard::new(0).into(),
+    ]);
+    let (optimised, statistics) = pass_manager.run(&circuit, None).unwrap();
+    assert_eq!(optimised, circuit_from(vec![Hadamard::new(0).into()]));
+    assert_eq!(statistics[0].name, "PeepholeOptimisation");
+    assert_eq!(statistics[0].before.number_two_qubit_gates, 2);
+    assert_eq!(statistics[0].after.number_two_qubit_gates, 0);
+}
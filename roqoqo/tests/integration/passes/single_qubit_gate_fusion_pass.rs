@@ -0,0 +1,128 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use num_complex::Complex64;
+use qoqo_calculator::Calculator;
+use roqoqo::backends::{EvaluatingBackend, StatevectorSimulator};
+use roqoqo::operations::*;
+use roqoqo::passes::{Pass, SingleQubitGateFusionPass};
+use roqoqo::Circuit;
+
+fn fuse(circuit: &Circuit) -> Circuit {
+    SingleQubitGateFusionPass::new().run(circuit, None).unwrap()
+}
+
+fn hqslangs(circuit: &Circuit) -> Vec<&'static str> {
+    circuit
+        .iter()
+        .map(|operation| operation.hqslang())
+        .collect()
+}
+
+fn assert_same_state(circuit: &Circuit, fused: &Circuit) {
+    let statevector = |circuit: &Circuit| -> Vec<Complex64> {
+        let mut full_circuit = Circuit::new();
+        full_circuit += DefinitionComplex::new("state".to_string(), 8, true);
+        full_circuit += circuit.clone();
+        full_circuit += PragmaGetStateVector::new("state".to_string(), None);
+        let (_, _, complex_registers) = StatevectorSimulator::new(3)
+            .run_circuit(&full_circuit)
+            .unwrap();
+        complex_registers["state"][0].clone()
+    };
+    let expected = statevector(circuit);
+    for (value, expected) in statevector(fused).iter().zip(expected.iter()) {
+        assert!((value - expected).norm() < 1e-10);
+    }
+}
+
+/// Test that runs of single-qubit gates are fused while keeping the state and global phase
+#[test]
+fn test_fusion() {
+    let mut circuit = Circuit::new();
+    circuit += Hadamard::new(0);
+    circuit += RotateZ::new(0, 0.3.into());
+    circuit += RotateX::new(1, 0.7.into());
+    circuit += SGate::new(0);
+    circuit += CNOT::new(0, 1);
+    circuit += TGate::new(0);
+    circuit += RotateAroundSphericalAxis::new(0, 0.4.into(), 1.1.into(), 0.2.into());
+    circuit += Hadamard::new(2);
+    circuit += PauliY::new(1);
+    circuit += SqrtPauliX::new(1);
+    circuit += PragmaGlobalPhase::new(0.5.into());
+
+    let fused = fuse(&circuit);
+    assert_eq!(
+        hqslangs(&fused),
+        vec![
+            "RotateX",
+            "SingleQubitGate",
+            "PragmaGlobalPhase",
+            "CNOT",
+            "SingleQubitGate",
+            "PragmaGlobalPhase",
+            "Hadamard",
+            "SingleQubitGate",
+            "PragmaGlobalPhase",
+            "PragmaGlobalPhase",
+        ]
+    );
+    assert_same_state(&circuit, &fused);
+}
+
+/// Test that runs end at operations acting on the qubit or on all qubits
+#[test]
+fn test_runs_end() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 3, true);
+    circuit += PauliX::new(0);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PauliX::new(0);
+    circuit += PauliX::new(1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    circuit += PauliX::new(1);
+    assert_eq!(fuse(&circuit), circuit);
+
+    let mut circuit = Circuit::new();
+    circuit += RotateX::new(0, 0.5.into());
+    circuit += RotateX::new(0, 0.5.into());
+    circuit += PragmaStopParallelBlock::new(vec![0, 1], 0.0.into());
+    circuit += RotateZ::new(1, 0.5.into());
+    let fused = fuse(&circuit);
+    assert_eq!(
+        hqslangs(&fused),
+        vec!["SingleQubitGate", "PragmaStopParallelBlock", "RotateZ"]
+    );
+    assert_same_state(&circuit, &fused);
+}
+
+/// Test that symbolic parameters are fused symbolically
+#[test]
+fn test_symbolic_fusion() {
+    let mut circuit = Circuit::new();
+    circuit += RotateZ::new(0, "theta".into());
+    circuit += RotateX::new(0, 0.7.into());
+    circuit += RotateZ::new(0, "2 * theta".into());
+    let fused = fuse(&circuit);
+    match &fused[0] {
+        Operation::SingleQubitGate(gate) => assert!(!gate.alpha_r().is_float()),
+        _ => panic!("Gates were not fused"),
+    }
+
+    let mut calculator = Calculator::new();
+    calculator.set_variable("theta", 0.3);
+    let fused = fused.substitute_parameters(&mut calculator).unwrap();
+    let circuit = circuit.substitute_parameters(&mut calculator).unwrap();
+    assert_eq!(hqslangs(&fused), vec!["SingleQubitGate"]);
+    assert_same_state(&circuit, &fused);
+}
@@ -0,0 +1,239 @@
+// Copyright © 2021 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::backends::{EvaluatingBackend, RegisterResult, StatevectorSimulator};
+use roqoqo::measurements::{BasisRotation, BasisRotationInput, ClassicalRegister};
+use roqoqo::operations::*;
+use roqoqo::{Circuit, MeasurementCheckpoint, QuantumProgram, RoqoqoBackendError};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Backend that is interrupted with a network error after a number of circuits.
+#[derive(Debug, Clone)]
+struct InterruptedBackend {
+    simulator: StatevectorSimulator,
+    remaining_circuits: Arc<Mutex<usize>>,
+}
+
+impl InterruptedBackend {
+    fn new(remaining_circuits: usize) -> Self {
+        Self {
+            simulator: simulator(),
+            remaining_circuits: Arc::new(Mutex::new(remaining_circuits)),
+        }
+    }
+}
+
+impl EvaluatingBackend for InterruptedBackend {
+    fn run_circuit_iterator<'a>(
+        &self,
+        circuit: impl Iterator<Item = &'a Operation>,
+    ) -> RegisterResult {
+        let mut remaining_circuits = self.remaining_circuits.lock().unwrap();
+        if *remaining_circuits == 0 {
+            return Err(RoqoqoBackendError::NetworkError {
+                msg: "connection lost".to_string(),
+            });
+        }
+        *remaining_circuits -= 1;
+        self.simulator.run_circuit_iterator(circuit)
+    }
+}
+
+fn simulator() -> StatevectorSimulator {
+    let mut simulator = StatevectorSimulator::new(2);
+    simulator.set_random_seed(Some(7));
+    simulator
+}
+
+/// Returns a path in the temporary directory that does not exist
+fn checkpoint_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "roqoqo_checkpoint_{}_{}.bin",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn measured_circuit(qubit: usize) -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateX::new(qubit, "angle".into());
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 20, None);
+    circuit
+}
+
+fn register_program() -> QuantumProgram {
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += Hadamard::new(1);
+    QuantumProgram::ClassicalRegister {
+        measurement: ClassicalRegister {
+            constant_circuit: Some(constant_circuit),
+            circuits: vec![
+                measured_circuit(0),
+                measured_circuit(1),
+                measured_circuit(0),
+            ],
+        },
+        input_parameter_names: vec!["angle".to_string()],
+    }
+}
+
+fn basis_rotation_program() -> QuantumProgram {
+    let mut input = BasisRotationInput::new(2, false);
+    let index = input.add_pauli_product("ro".to_string(), vec![0]).unwrap();
+    let linear_map: HashMap<usize, f64> = [(index, 1.0)].into_iter().collect();
+    input
+        .add_linear_exp_val("z0".to_string(), linear_map)
+        .unwrap();
+    QuantumProgram::BasisRotation {
+        measurement: BasisRotation {
+            constant_circuit: None,
+            circuits: vec![measured_circuit(0), measured_circuit(1)],
+            input,
+        },
+        input_parameter_names: vec!["angle".to_string()],
+    }
+}
+
+/// Test that a checkpointed run returns the same registers as a normal run
+#[test]
+fn test_run_registers_with_checkpoint() {
+    let path = checkpoint_file("registers");
+    let program = register_program();
+    let registers = program
+        .run_registers_with_checkpoint(simulator(), &[1.0], &path)
+        .unwrap();
+    assert_eq!(
+        registers,
+        program.run_registers(simulator(), &[1.0]).unwrap()
+    );
+
+    let checkpoint = MeasurementCheckpoint::load(&path).unwrap();
+    assert_eq!(checkpoint.parameters(), &[1.0]);
+    assert_eq!(checkpoint.number_circuits(), 3);
+    assert!(checkpoint.is_finished());
+    fs::remove_file(&path).unwrap();
+}
+
+/// Test that existing files are not overwritten
+#[test]
+fn test_file_already_exists() {
+    let path = checkpoint_file("exists");
+    fs::write(&path, "important data").unwrap();
+    assert_eq!(
+        register_program().run_registers_with_checkpoint(simulator(), &[1.0], &path),
+        Err(RoqoqoBackendError::FileAlreadyExists {
+            path: path.display().to_string()
+        })
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "important data");
+    fs::remove_file(&path).unwrap();
+}
+
+/// Test that an interrupted run is resumed from the checkpoint file
+#[test]
+fn test_resume_registers() {
+    let path = checkpoint_file("resume_registers");
+    let program = register_program();
+    assert_eq!(
+        program.run_registers_with_checkpoint(InterruptedBackend::new(1), &[0.5], &path),
+        Err(RoqoqoBackendError::NetworkError {
+            msg: "connection lost".to_string()
+        })
+    );
+    let checkpoint = MeasurementCheckpoint::load(&path).unwrap();
+    assert_eq!(checkpoint.number_finished_circuits(), 1);
+    assert!(!checkpoint.is_finished());
+
+    assert!(program
+        .resume_registers(InterruptedBackend::new(1), &path)
+        .is_err());
+    assert_eq!(
+        MeasurementCheckpoint::load(&path)
+            .unwrap()
+            .number_finished_circuits(),
+        2
+    );
+    let registers = program
+        .resume_registers(InterruptedBackend::new(1), &path)
+        .unwrap();
+    assert_eq!(
+        registers,
+        program.run_registers(simulator(), &[0.5]).unwrap()
+    );
+    assert!(MeasurementCheckpoint::load(&path).unwrap().is_finished());
+
+    // A finished checkpoint does not run any further circuits
+    assert_eq!(
+        program
+            .resume_registers(InterruptedBackend::new(0), &path)
+            .unwrap(),
+        registers
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+/// Test checkpointing of a quantum program returning expectation values
+#[test]
+fn test_run_and_resume_expectation_values() {
+    let path = checkpoint_file("expectation_values");
+    let program = basis_rotation_program();
+    assert!(program
+        .run_with_checkpoint(InterruptedBackend::new(1), &[0.3], &path)
+        .is_err());
+    let result = program
+        .resume(InterruptedBackend::new(1), &path)
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, program.run(simulator(), &[0.3]).unwrap().unwrap());
+    assert!(program
+        .run_registers_with_checkpoint(simulator(), &[0.3], &path)
+        .is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+/// Test that checkpoints of other programs are refused
+#[test]
+fn test_resume_other_program() {
+    let path = checkpoint_file("other_program");
+    basis_rotation_program()
+        .run_with_checkpoint(InterruptedBackend::new(0), &[0.3], &path)
+        .unwrap_err();
+    let other_program = QuantumProgram::BasisRotation {
+        measurement: BasisRotation {
+            constant_circuit: None,
+            circuits: vec![measured_circuit(1)],
+            input: BasisRotationInput::new(2, false),
+        },
+        input_parameter_names: vec!["angle".to_string()],
+    };
+    match other_program.resume(simulator(), &path) {
+        Err(RoqoqoBackendError::GenericError { msg }) => {
+            assert!(msg.contains("was not created by this quantum program"))
+        }
+        _ => panic!("Checkpoint of another program was not refused"),
+    }
+    assert!(register_program()
+        .resume_registers(simulator(), &path)
+        .is_err());
+    fs::remove_file(&path).unwrap();
+
+    assert!(matches!(
+        basis_rotation_program().resume(simulator(), &path),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}